@@ -0,0 +1,165 @@
+//!
+//! Cell annotations, better known as comments.
+//!
+//! ```
+//! use spreadsheet_ods::{Sheet, cm, Length};
+//! use spreadsheet_ods::annotation::Annotation;
+//!
+//! let mut sheet = Sheet::new("1");
+//! let mut annotation = Annotation::new("Check this value.");
+//! annotation.set_author(Some("reviewer".to_string()));
+//! annotation.set_rect(cm!(5), cm!(1), cm!(4), cm!(2));
+//! sheet.set_annotation(0, 0, annotation);
+//!
+//! assert!(sheet.annotation(0, 0).is_some());
+//! ```
+
+use crate::attrmap2::AttrMap2;
+use crate::style::ParseStyleAttr;
+use crate::text::{TextP, TextTag};
+use crate::{Length, OdsError};
+use chrono::NaiveDateTime;
+
+/// An annotation attached to a cell.
+#[derive(Debug, Clone, Default)]
+pub struct Annotation {
+    name: String,
+    display: bool,
+    author: Option<String>,
+    date: Option<NaiveDateTime>,
+    text: Vec<TextTag>,
+    attr: AttrMap2,
+}
+
+impl Annotation {
+    /// Annotation with a simple text. Each line of the text is one
+    /// paragraph.
+    pub fn new<S: AsRef<str>>(text: S) -> Self {
+        let mut annotation = Self::new_empty();
+        for line in text.as_ref().split('\n') {
+            annotation.push_text(TextP::new().text(line).into_xmltag());
+        }
+        annotation
+    }
+
+    /// Annotation without any text.
+    pub fn new_empty() -> Self {
+        Self {
+            name: Default::default(),
+            display: false,
+            author: None,
+            date: None,
+            text: Default::default(),
+            attr: Default::default(),
+        }
+    }
+
+    /// Name of the annotation. Only needed to refer to it from
+    /// elsewhere.
+    pub fn set_name<S: Into<String>>(&mut self, name: S) {
+        self.name = name.into();
+    }
+
+    /// Name of the annotation.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The annotation is always shown, not only when hovering.
+    pub fn set_display(&mut self, display: bool) {
+        self.display = display;
+    }
+
+    /// The annotation is always shown, not only when hovering.
+    pub fn display(&self) -> bool {
+        self.display
+    }
+
+    /// Author.
+    pub fn set_author(&mut self, author: Option<String>) {
+        self.author = author;
+    }
+
+    /// Author.
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    /// Creation date.
+    pub fn set_date(&mut self, date: Option<NaiveDateTime>) {
+        self.date = date;
+    }
+
+    /// Creation date.
+    pub fn date(&self) -> Option<NaiveDateTime> {
+        self.date
+    }
+
+    /// Replaces the text. Each TextTag is a paragraph or a list.
+    pub fn set_text(&mut self, text: Vec<TextTag>) {
+        self.text = text;
+    }
+
+    /// Appends a paragraph or a list.
+    pub fn push_text(&mut self, text: TextTag) {
+        self.text.push(text);
+    }
+
+    /// The text. Each TextTag is a paragraph or a list.
+    pub fn text(&self) -> &[TextTag] {
+        &self.text
+    }
+
+    /// The text as plain string. Paragraphs are separated by a newline.
+    pub fn text_str(&self) -> String {
+        let mut buf = String::new();
+        for (i, t) in self.text.iter().enumerate() {
+            if i > 0 {
+                buf.push('\n');
+            }
+            t.extract_text(&mut buf);
+        }
+        buf
+    }
+
+    /// Position and size of the displayed annotation.
+    pub fn set_rect(&mut self, x: Length, y: Length, width: Length, height: Length) {
+        self.attr.set_attr("svg:x", x.to_string());
+        self.attr.set_attr("svg:y", y.to_string());
+        self.attr.set_attr("svg:width", width.to_string());
+        self.attr.set_attr("svg:height", height.to_string());
+    }
+
+    /// Position and size of the displayed annotation as x, y, width
+    /// and height.
+    pub fn rect(&self) -> Result<Option<(Length, Length, Length, Length)>, OdsError> {
+        let x = Length::parse_attr(self.attr.attr("svg:x"))?;
+        let y = Length::parse_attr(self.attr.attr("svg:y"))?;
+        let width = Length::parse_attr(self.attr.attr("svg:width"))?;
+        let height = Length::parse_attr(self.attr.attr("svg:height"))?;
+        match (x, y, width, height) {
+            (Some(x), Some(y), Some(width), Some(height)) => Ok(Some((x, y, width, height))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Removes position and size.
+    pub fn clear_rect(&mut self) {
+        self.attr.clear_attr("svg:x");
+        self.attr.clear_attr("svg:y");
+        self.attr.clear_attr("svg:width");
+        self.attr.clear_attr("svg:height");
+    }
+
+    /// Any other attributes like draw:style-name or
+    /// draw:caption-point-x.
+    pub fn attrmap(&self) -> &AttrMap2 {
+        &self.attr
+    }
+
+    /// Any other attributes like draw:style-name or
+    /// draw:caption-point-x.
+    pub fn attrmap_mut(&mut self) -> &mut AttrMap2 {
+        &mut self.attr
+    }
+}
@@ -220,6 +220,28 @@ impl Condition {
         Condition { cond: buf }
     }
 
+    /// Range check.
+    pub fn content_is_between<V: Into<Value>>(from: V, to: V) -> Condition {
+        let mut buf = String::new();
+        buf.push_str("cell-content-is-between(");
+        buf.push_str(from.into().to_string().as_str());
+        buf.push_str(", ");
+        buf.push_str(to.into().to_string().as_str());
+        buf.push(')');
+        Condition { cond: buf }
+    }
+
+    /// Range check.
+    pub fn content_is_not_between<V: Into<Value>>(from: V, to: V) -> Condition {
+        let mut buf = String::new();
+        buf.push_str("cell-content-is-not-between(");
+        buf.push_str(from.into().to_string().as_str());
+        buf.push_str(", ");
+        buf.push_str(to.into().to_string().as_str());
+        buf.push(')');
+        Condition { cond: buf }
+    }
+
     /// Compares the content length to a value.
     pub fn content_text_length_eq(len: u32) -> Condition {
         let mut buf = String::new();
@@ -415,6 +437,8 @@ mod tests {
             c.to_string(),
             "cell-content-text-length-is-not-between(5, 7)"
         );
+        let c = Condition::content_is_between(1, 5);
+        assert_eq!(c.to_string(), "cell-content-is-between(1, 5)");
         let c = Condition::content_is_in_list(&[1, 2, 3, 4, 5]);
         assert_eq!(
             c.to_string(),
@@ -0,0 +1,760 @@
+//!
+//! Conditional formats as used by LibreOffice (calcext:conditional-formats).
+//!
+//! A conditional format applies to one or more cell ranges and contains
+//! a list of entries. Each entry is either a condition with a cell style,
+//! a color scale, a data bar, an icon set or a date condition.
+//!
+//! The cell styles used by the conditions must be common styles, otherwise
+//! LibreOffice ignores them.
+//!
+//! ```
+//! use spreadsheet_ods::{CellRange, CellStyle, Sheet, WorkBook};
+//! use spreadsheet_ods::condition::Condition;
+//! use spreadsheet_ods::conditional::{ColorScale, ConditionEntry, ConditionalFormat};
+//! use spreadsheet_ods::style::{StyleOrigin, StyleUse};
+//! use color::Rgb;
+//!
+//! let mut book = WorkBook::new_empty();
+//!
+//! let mut red = CellStyle::new_empty();
+//! red.set_name("red");
+//! red.set_origin(StyleOrigin::Styles);
+//! red.set_styleuse(StyleUse::Named);
+//! red.set_color(Rgb::new(255, 0, 0));
+//! let red = book.add_cellstyle(red);
+//!
+//! let mut sheet = Sheet::new("1");
+//! let mut cf = ConditionalFormat::new(CellRange::remote("1", 0, 0, 9, 0));
+//! cf.push_entry(ConditionEntry::new(Condition::content_lt(0), &red));
+//! sheet.add_conditional_format(cf);
+//!
+//! let mut cf = ConditionalFormat::new(CellRange::remote("1", 0, 1, 9, 1));
+//! cf.push_entry(ColorScale::two_color(Rgb::new(255, 0, 0), Rgb::new(0, 255, 0)));
+//! sheet.add_conditional_format(cf);
+//!
+//! book.push_sheet(sheet);
+//! ```
+
+use crate::condition::{Condition, ValueCondition};
+use crate::xmltree::XmlTag;
+use crate::{CellRange, CellRef, CellStyleRef, OdsError};
+use color::Rgb;
+use std::fmt::{Display, Formatter};
+
+/// A conditional format for some cell ranges.
+#[derive(Debug, Clone)]
+pub struct ConditionalFormat {
+    ranges: Vec<CellRange>,
+    entries: Vec<ConditionalEntry>,
+}
+
+impl ConditionalFormat {
+    /// Conditional format for the given range. The range should contain
+    /// the sheet name.
+    pub fn new(range: CellRange) -> Self {
+        Self {
+            ranges: vec![range],
+            entries: Default::default(),
+        }
+    }
+
+    /// Conditional format for a list of ranges.
+    pub fn new_ranges(ranges: Vec<CellRange>) -> Self {
+        Self {
+            ranges,
+            entries: Default::default(),
+        }
+    }
+
+    /// Adds another range.
+    pub fn add_range(&mut self, range: CellRange) {
+        self.ranges.push(range);
+    }
+
+    /// Target ranges.
+    pub fn set_ranges(&mut self, ranges: Vec<CellRange>) {
+        self.ranges = ranges;
+    }
+
+    /// Target ranges.
+    pub fn ranges(&self) -> &[CellRange] {
+        &self.ranges
+    }
+
+    /// Target ranges.
+    pub fn ranges_mut(&mut self) -> &mut Vec<CellRange> {
+        &mut self.ranges
+    }
+
+    /// Appends an entry. The entries are evaluated in order.
+    pub fn push_entry<E: Into<ConditionalEntry>>(&mut self, entry: E) {
+        self.entries.push(entry.into());
+    }
+
+    /// Entries.
+    pub fn entries(&self) -> &[ConditionalEntry] {
+        &self.entries
+    }
+
+    /// Entries.
+    pub fn entries_mut(&mut self) -> &mut Vec<ConditionalEntry> {
+        &mut self.entries
+    }
+}
+
+/// One entry of a conditional format.
+#[derive(Debug, Clone)]
+pub enum ConditionalEntry {
+    /// calcext:condition
+    Condition(ConditionEntry),
+    /// calcext:color-scale
+    ColorScale(ColorScale),
+    /// calcext:data-bar
+    DataBar(DataBar),
+    /// calcext:icon-set
+    IconSet(IconSet),
+    /// calcext:date-is
+    Date(DateEntry),
+    /// Any entry that could not be read. It is written back unchanged.
+    Xml(XmlTag),
+}
+
+impl From<ConditionEntry> for ConditionalEntry {
+    fn from(v: ConditionEntry) -> Self {
+        ConditionalEntry::Condition(v)
+    }
+}
+
+impl From<ColorScale> for ConditionalEntry {
+    fn from(v: ColorScale) -> Self {
+        ConditionalEntry::ColorScale(v)
+    }
+}
+
+impl From<DataBar> for ConditionalEntry {
+    fn from(v: DataBar) -> Self {
+        ConditionalEntry::DataBar(v)
+    }
+}
+
+impl From<IconSet> for ConditionalEntry {
+    fn from(v: IconSet) -> Self {
+        ConditionalEntry::IconSet(v)
+    }
+}
+
+impl From<DateEntry> for ConditionalEntry {
+    fn from(v: DateEntry) -> Self {
+        ConditionalEntry::Date(v)
+    }
+}
+
+/// Applies a cell style if the condition is true.
+#[derive(Debug, Clone)]
+pub struct ConditionEntry {
+    condition: Condition,
+    style: CellStyleRef,
+    base_cell: Option<CellRef>,
+}
+
+impl ConditionEntry {
+    /// Applies the style if the condition is true.
+    ///
+    /// Supported are the comparisons Condition::content_xx(),
+    /// Condition::content_is_between(), content_is_not_between() and
+    /// Condition::is_true_formula().
+    pub fn new(condition: Condition, style: &CellStyleRef) -> Self {
+        Self {
+            condition,
+            style: style.clone(),
+            base_cell: None,
+        }
+    }
+
+    /// Applies the style if the value condition is true.
+    pub fn new_value(condition: ValueCondition, style: &CellStyleRef) -> Self {
+        Self::new(Condition::new(condition.to_string()), style)
+    }
+
+    /// The value is a duplicate within the range.
+    pub fn duplicate(style: &CellStyleRef) -> Self {
+        Self::new(Condition::new("duplicate"), style)
+    }
+
+    /// The value is unique within the range.
+    pub fn unique(style: &CellStyleRef) -> Self {
+        Self::new(Condition::new("unique"), style)
+    }
+
+    /// The value is one of the n largest.
+    pub fn top_elements(n: u32, style: &CellStyleRef) -> Self {
+        Self::new(Condition::new(format!("top-elements({})", n)), style)
+    }
+
+    /// The value is one of the n smallest.
+    pub fn bottom_elements(n: u32, style: &CellStyleRef) -> Self {
+        Self::new(Condition::new(format!("bottom-elements({})", n)), style)
+    }
+
+    /// The value is above the average of the range.
+    pub fn above_average(style: &CellStyleRef) -> Self {
+        Self::new(Condition::new("above-average"), style)
+    }
+
+    /// The value is below the average of the range.
+    pub fn below_average(style: &CellStyleRef) -> Self {
+        Self::new(Condition::new("below-average"), style)
+    }
+
+    /// The cell contains an error.
+    pub fn is_error(style: &CellStyleRef) -> Self {
+        Self::new(Condition::new("is-error"), style)
+    }
+
+    /// The text contains the value.
+    pub fn contains_text<S: AsRef<str>>(text: S, style: &CellStyleRef) -> Self {
+        Self::new(text_condition("contains-text", text.as_ref()), style)
+    }
+
+    /// The text starts with the value.
+    pub fn begins_with<S: AsRef<str>>(text: S, style: &CellStyleRef) -> Self {
+        Self::new(text_condition("begins-with", text.as_ref()), style)
+    }
+
+    /// The text ends with the value.
+    pub fn ends_with<S: AsRef<str>>(text: S, style: &CellStyleRef) -> Self {
+        Self::new(text_condition("ends-with", text.as_ref()), style)
+    }
+
+    /// Condition.
+    pub fn set_condition(&mut self, condition: Condition) {
+        self.condition = condition;
+    }
+
+    /// Condition.
+    pub fn condition(&self) -> &Condition {
+        &self.condition
+    }
+
+    /// Style that is applied.
+    pub fn set_style(&mut self, style: &CellStyleRef) {
+        self.style = style.clone();
+    }
+
+    /// Style that is applied.
+    pub fn style(&self) -> &CellStyleRef {
+        &self.style
+    }
+
+    /// Relative references in the condition are relative to this cell.
+    pub fn set_base_cell(&mut self, base_cell: Option<CellRef>) {
+        self.base_cell = base_cell;
+    }
+
+    /// Relative references in the condition are relative to this cell.
+    pub fn base_cell(&self) -> Option<&CellRef> {
+        self.base_cell.as_ref()
+    }
+}
+
+fn text_condition(fun: &str, text: &str) -> Condition {
+    Condition::new(format!("{}(\"{}\")", fun, text.replace('"', "\"\"")))
+}
+
+/// Converts the condition to the syntax used by calcext:value.
+pub(crate) fn condition_to_calcext(condition: &Condition) -> String {
+    let cond = condition.to_string();
+    if let Some(v) = cond.strip_prefix("cell-content()") {
+        v.to_string()
+    } else if let Some(v) = cond.strip_prefix("value()") {
+        v.to_string()
+    } else if let Some(v) = cond.strip_prefix("cell-content-is-between(") {
+        format!("between({}", v)
+    } else if let Some(v) = cond.strip_prefix("cell-content-is-not-between(") {
+        format!("not-between({}", v)
+    } else if let Some(v) = cond.strip_prefix("is-true-formula(") {
+        format!("formula-is({}", v)
+    } else {
+        cond
+    }
+}
+
+/// Converts a calcext:value to a condition.
+pub(crate) fn condition_from_calcext(value: &str) -> Condition {
+    if value.starts_with('=')
+        || value.starts_with('<')
+        || value.starts_with('>')
+        || value.starts_with("!=")
+    {
+        Condition::new(format!("cell-content(){}", value))
+    } else if let Some(v) = value.strip_prefix("between(") {
+        Condition::new(format!("cell-content-is-between({}", v))
+    } else if let Some(v) = value.strip_prefix("not-between(") {
+        Condition::new(format!("cell-content-is-not-between({}", v))
+    } else if let Some(v) = value.strip_prefix("formula-is(") {
+        Condition::new(format!("is-true-formula({}", v))
+    } else {
+        Condition::new(value)
+    }
+}
+
+/// How the value of a scale entry is interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleType {
+    /// Minimum of the range.
+    Minimum,
+    /// Maximum of the range.
+    Maximum,
+    /// Minimum of the range or 0 for data bars.
+    AutoMinimum,
+    /// Maximum of the range or 0 for data bars.
+    AutoMaximum,
+    /// Percentile.
+    Percentile,
+    /// Percent of the value range.
+    Percent,
+    /// Fixed value.
+    Number,
+    /// Formula.
+    Formula,
+}
+
+impl Display for ScaleType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScaleType::Minimum => write!(f, "minimum"),
+            ScaleType::Maximum => write!(f, "maximum"),
+            ScaleType::AutoMinimum => write!(f, "auto-minimum"),
+            ScaleType::AutoMaximum => write!(f, "auto-maximum"),
+            ScaleType::Percentile => write!(f, "percentile"),
+            ScaleType::Percent => write!(f, "percent"),
+            ScaleType::Number => write!(f, "number"),
+            ScaleType::Formula => write!(f, "formula"),
+        }
+    }
+}
+
+impl TryFrom<&str> for ScaleType {
+    type Error = OdsError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "minimum" => Ok(ScaleType::Minimum),
+            "maximum" => Ok(ScaleType::Maximum),
+            "auto-minimum" => Ok(ScaleType::AutoMinimum),
+            "auto-maximum" => Ok(ScaleType::AutoMaximum),
+            "percentile" => Ok(ScaleType::Percentile),
+            "percent" => Ok(ScaleType::Percent),
+            "number" => Ok(ScaleType::Number),
+            "formula" => Ok(ScaleType::Formula),
+            _ => Err(OdsError::Parse(
+                "invalid calcext:type",
+                Some(value.to_string()),
+            )),
+        }
+    }
+}
+
+/// A threshold for color scales, data bars and icon sets.
+#[derive(Debug, Clone)]
+pub struct ScaleEntry {
+    scale_type: ScaleType,
+    value: String,
+    color: Option<Rgb<u8>>,
+}
+
+impl ScaleEntry {
+    /// New entry. The value is a number or a formula, and ignored for
+    /// minimum and maximum.
+    pub fn new<S: Into<String>>(scale_type: ScaleType, value: S) -> Self {
+        Self {
+            scale_type,
+            value: value.into(),
+            color: None,
+        }
+    }
+
+    /// New entry with a color for color scales.
+    pub fn new_color<S: Into<String>>(scale_type: ScaleType, value: S, color: Rgb<u8>) -> Self {
+        Self {
+            scale_type,
+            value: value.into(),
+            color: Some(color),
+        }
+    }
+
+    /// How the value is interpreted.
+    pub fn set_scale_type(&mut self, scale_type: ScaleType) {
+        self.scale_type = scale_type;
+    }
+
+    /// How the value is interpreted.
+    pub fn scale_type(&self) -> ScaleType {
+        self.scale_type
+    }
+
+    /// Value.
+    pub fn set_value<S: Into<String>>(&mut self, value: S) {
+        self.value = value.into();
+    }
+
+    /// Value.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Color. Only used for color scales.
+    pub fn set_color(&mut self, color: Option<Rgb<u8>>) {
+        self.color = color;
+    }
+
+    /// Color. Only used for color scales.
+    pub fn color(&self) -> Option<Rgb<u8>> {
+        self.color
+    }
+}
+
+/// Colors the cells along a gradient.
+#[derive(Debug, Clone, Default)]
+pub struct ColorScale {
+    entries: Vec<ScaleEntry>,
+}
+
+impl ColorScale {
+    /// Empty color scale.
+    pub fn new() -> Self {
+        Self {
+            entries: Default::default(),
+        }
+    }
+
+    /// Color scale from the minimum to the maximum.
+    pub fn two_color(min: Rgb<u8>, max: Rgb<u8>) -> Self {
+        let mut scale = Self::new();
+        scale.push_entry(ScaleEntry::new_color(ScaleType::Minimum, "0", min));
+        scale.push_entry(ScaleEntry::new_color(ScaleType::Maximum, "0", max));
+        scale
+    }
+
+    /// Color scale from the minimum via the median to the maximum.
+    pub fn three_color(min: Rgb<u8>, mid: Rgb<u8>, max: Rgb<u8>) -> Self {
+        let mut scale = Self::new();
+        scale.push_entry(ScaleEntry::new_color(ScaleType::Minimum, "0", min));
+        scale.push_entry(ScaleEntry::new_color(ScaleType::Percentile, "50", mid));
+        scale.push_entry(ScaleEntry::new_color(ScaleType::Maximum, "0", max));
+        scale
+    }
+
+    /// Appends an entry. Each entry needs a color.
+    pub fn push_entry(&mut self, entry: ScaleEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Entries.
+    pub fn entries(&self) -> &[ScaleEntry] {
+        &self.entries
+    }
+
+    /// Entries.
+    pub fn entries_mut(&mut self) -> &mut Vec<ScaleEntry> {
+        &mut self.entries
+    }
+}
+
+/// Draws a bar proportional to the value.
+#[derive(Debug, Clone)]
+pub struct DataBar {
+    min: ScaleEntry,
+    max: ScaleEntry,
+    positive_color: Rgb<u8>,
+    negative_color: Option<Rgb<u8>>,
+    axis_color: Option<Rgb<u8>>,
+    gradient: Option<bool>,
+    show_value: Option<bool>,
+    min_length: Option<f64>,
+    max_length: Option<f64>,
+}
+
+impl DataBar {
+    /// Data bar from the automatic minimum to the automatic maximum.
+    pub fn new(positive_color: Rgb<u8>) -> Self {
+        Self {
+            min: ScaleEntry::new(ScaleType::AutoMinimum, "0"),
+            max: ScaleEntry::new(ScaleType::AutoMaximum, "0"),
+            positive_color,
+            negative_color: None,
+            axis_color: None,
+            gradient: None,
+            show_value: None,
+            min_length: None,
+            max_length: None,
+        }
+    }
+
+    /// Value for the shortest bar.
+    pub fn set_min(&mut self, min: ScaleEntry) {
+        self.min = min;
+    }
+
+    /// Value for the shortest bar.
+    pub fn min(&self) -> &ScaleEntry {
+        &self.min
+    }
+
+    /// Value for the longest bar.
+    pub fn set_max(&mut self, max: ScaleEntry) {
+        self.max = max;
+    }
+
+    /// Value for the longest bar.
+    pub fn max(&self) -> &ScaleEntry {
+        &self.max
+    }
+
+    /// Color for positive values.
+    pub fn set_positive_color(&mut self, color: Rgb<u8>) {
+        self.positive_color = color;
+    }
+
+    /// Color for positive values.
+    pub fn positive_color(&self) -> Rgb<u8> {
+        self.positive_color
+    }
+
+    /// Color for negative values.
+    pub fn set_negative_color(&mut self, color: Option<Rgb<u8>>) {
+        self.negative_color = color;
+    }
+
+    /// Color for negative values.
+    pub fn negative_color(&self) -> Option<Rgb<u8>> {
+        self.negative_color
+    }
+
+    /// Color of the axis.
+    pub fn set_axis_color(&mut self, color: Option<Rgb<u8>>) {
+        self.axis_color = color;
+    }
+
+    /// Color of the axis.
+    pub fn axis_color(&self) -> Option<Rgb<u8>> {
+        self.axis_color
+    }
+
+    /// Fill with a gradient.
+    pub fn set_gradient(&mut self, gradient: Option<bool>) {
+        self.gradient = gradient;
+    }
+
+    /// Fill with a gradient.
+    pub fn gradient(&self) -> Option<bool> {
+        self.gradient
+    }
+
+    /// Show the value besides the bar.
+    pub fn set_show_value(&mut self, show_value: Option<bool>) {
+        self.show_value = show_value;
+    }
+
+    /// Show the value besides the bar.
+    pub fn show_value(&self) -> Option<bool> {
+        self.show_value
+    }
+
+    /// Minimum bar length in percent of the cell width.
+    pub fn set_min_length(&mut self, min_length: Option<f64>) {
+        self.min_length = min_length;
+    }
+
+    /// Minimum bar length in percent of the cell width.
+    pub fn min_length(&self) -> Option<f64> {
+        self.min_length
+    }
+
+    /// Maximum bar length in percent of the cell width.
+    pub fn set_max_length(&mut self, max_length: Option<f64>) {
+        self.max_length = max_length;
+    }
+
+    /// Maximum bar length in percent of the cell width.
+    pub fn max_length(&self) -> Option<f64> {
+        self.max_length
+    }
+}
+
+/// Shows an icon depending on the value.
+#[derive(Debug, Clone)]
+pub struct IconSet {
+    icon_set_type: String,
+    show_value: Option<bool>,
+    entries: Vec<ScaleEntry>,
+}
+
+impl IconSet {
+    /// Icon set with one entry per icon. The first entry is the lower
+    /// bound for the first icon and so on.
+    ///
+    /// The type is one of the LibreOffice names like "3Arrows",
+    /// "3ArrowsGray", "3Flags", "3TrafficLights1", "3TrafficLights2",
+    /// "3Signs", "3Symbols", "3Symbols2", "3Smilies", "3Stars",
+    /// "3Triangles", "3ColorSmilies", "4Arrows", "4ArrowsGray",
+    /// "4RedToBlack", "4Rating", "4TrafficLights", "5Arrows",
+    /// "5ArrowsGray", "5Rating", "5Quarters" or "5Boxes".
+    pub fn new<S: Into<String>>(icon_set_type: S) -> Self {
+        Self {
+            icon_set_type: icon_set_type.into(),
+            show_value: None,
+            entries: Default::default(),
+        }
+    }
+
+    /// Icon set with the thresholds evenly spaced in percent.
+    pub fn percent<S: Into<String>>(icon_set_type: S, icons: u32) -> Self {
+        let mut set = Self::new(icon_set_type);
+        for i in 0..icons {
+            set.push_entry(ScaleEntry::new(
+                ScaleType::Percent,
+                (i * 100 / icons).to_string(),
+            ));
+        }
+        set
+    }
+
+    /// Type of the icons.
+    pub fn set_icon_set_type<S: Into<String>>(&mut self, icon_set_type: S) {
+        self.icon_set_type = icon_set_type.into();
+    }
+
+    /// Type of the icons.
+    pub fn icon_set_type(&self) -> &str {
+        &self.icon_set_type
+    }
+
+    /// Show the value besides the icon.
+    pub fn set_show_value(&mut self, show_value: Option<bool>) {
+        self.show_value = show_value;
+    }
+
+    /// Show the value besides the icon.
+    pub fn show_value(&self) -> Option<bool> {
+        self.show_value
+    }
+
+    /// Appends a threshold.
+    pub fn push_entry(&mut self, entry: ScaleEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Thresholds.
+    pub fn entries(&self) -> &[ScaleEntry] {
+        &self.entries
+    }
+
+    /// Thresholds.
+    pub fn entries_mut(&mut self) -> &mut Vec<ScaleEntry> {
+        &mut self.entries
+    }
+}
+
+/// Date ranges relative to today.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum DateCondition {
+    Today,
+    Yesterday,
+    Tomorrow,
+    Last7Days,
+    ThisWeek,
+    LastWeek,
+    NextWeek,
+    ThisMonth,
+    LastMonth,
+    NextMonth,
+    ThisYear,
+    LastYear,
+    NextYear,
+}
+
+impl Display for DateCondition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DateCondition::Today => write!(f, "today"),
+            DateCondition::Yesterday => write!(f, "yesterday"),
+            DateCondition::Tomorrow => write!(f, "tomorrow"),
+            DateCondition::Last7Days => write!(f, "last-7-days"),
+            DateCondition::ThisWeek => write!(f, "this-week"),
+            DateCondition::LastWeek => write!(f, "last-week"),
+            DateCondition::NextWeek => write!(f, "next-week"),
+            DateCondition::ThisMonth => write!(f, "this-month"),
+            DateCondition::LastMonth => write!(f, "last-month"),
+            DateCondition::NextMonth => write!(f, "next-month"),
+            DateCondition::ThisYear => write!(f, "this-year"),
+            DateCondition::LastYear => write!(f, "last-year"),
+            DateCondition::NextYear => write!(f, "next-year"),
+        }
+    }
+}
+
+impl TryFrom<&str> for DateCondition {
+    type Error = OdsError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "today" => Ok(DateCondition::Today),
+            "yesterday" => Ok(DateCondition::Yesterday),
+            "tomorrow" => Ok(DateCondition::Tomorrow),
+            "last-7-days" => Ok(DateCondition::Last7Days),
+            "this-week" => Ok(DateCondition::ThisWeek),
+            "last-week" => Ok(DateCondition::LastWeek),
+            "next-week" => Ok(DateCondition::NextWeek),
+            "this-month" => Ok(DateCondition::ThisMonth),
+            "last-month" => Ok(DateCondition::LastMonth),
+            "next-month" => Ok(DateCondition::NextMonth),
+            "this-year" => Ok(DateCondition::ThisYear),
+            "last-year" => Ok(DateCondition::LastYear),
+            "next-year" => Ok(DateCondition::NextYear),
+            _ => Err(OdsError::Parse(
+                "invalid calcext:date",
+                Some(value.to_string()),
+            )),
+        }
+    }
+}
+
+/// Applies a cell style if the date is within a range relative to today.
+#[derive(Debug, Clone)]
+pub struct DateEntry {
+    date: DateCondition,
+    style: CellStyleRef,
+}
+
+impl DateEntry {
+    /// Applies the style for dates in the given range.
+    pub fn new(date: DateCondition, style: &CellStyleRef) -> Self {
+        Self {
+            date,
+            style: style.clone(),
+        }
+    }
+
+    /// Date range.
+    pub fn set_date(&mut self, date: DateCondition) {
+        self.date = date;
+    }
+
+    /// Date range.
+    pub fn date(&self) -> DateCondition {
+        self.date
+    }
+
+    /// Style that is applied.
+    pub fn set_style(&mut self, style: &CellStyleRef) {
+        self.style = style.clone();
+    }
+
+    /// Style that is applied.
+    pub fn style(&self) -> &CellStyleRef {
+        &self.style
+    }
+}
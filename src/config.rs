@@ -132,6 +132,37 @@ impl ConfigMap {
         }
     }
 
+    /// Removes a ConfigItem.
+    pub(crate) fn remove<S>(&mut self, name: S) -> Option<ConfigItem>
+    where
+        S: AsRef<str>,
+    {
+        let idx = self.key_index.remove(name.as_ref())?;
+        let (_, item) = self.values.remove(idx);
+        for v in self.key_index.values_mut() {
+            if *v > idx {
+                *v -= 1;
+            }
+        }
+        Some(item)
+    }
+
+    /// Changes the key of a ConfigItem. The position stays the same.
+    /// An existing item with the new name is removed.
+    pub(crate) fn rename<S, T>(&mut self, old: S, new: T)
+    where
+        S: AsRef<str>,
+        T: AsRef<str>,
+    {
+        if old.as_ref() == new.as_ref() || !self.key_index.contains_key(old.as_ref()) {
+            return;
+        }
+        self.remove(new.as_ref());
+        let idx = self.key_index.remove(old.as_ref()).expect("key");
+        self.values[idx].0 = new.as_ref().to_string();
+        self.key_index.insert(new.as_ref().to_string(), idx);
+    }
+
     /// Returns a ConfigItem or creates it.
     pub(crate) fn get_or_create<S, F>(&mut self, name: S, default: F) -> &mut ConfigItem
     where
@@ -434,6 +465,31 @@ impl ConfigItem {
         }
     }
 
+    /// Recursive get for any ConfigItem.
+    pub(crate) fn get_rec_mut<S>(&mut self, names: &[S]) -> Option<&mut ConfigItem>
+    where
+        S: AsRef<str>,
+    {
+        if let Some((name, rest)) = names.split_first() {
+            let map = self.as_map_mut()?;
+            let idx = *map.key_index.get(name.as_ref())?;
+            map.values[idx].1.get_rec_mut(rest)
+        } else {
+            Some(self)
+        }
+    }
+
+    /// Changes the key of an item of this map-like ConfigItem.
+    pub(crate) fn rename<S, T>(&mut self, old: S, new: T)
+    where
+        S: AsRef<str>,
+        T: AsRef<str>,
+    {
+        if let Some(m) = self.as_map_mut() {
+            m.rename(old, new);
+        }
+    }
+
     /// Recursive get for only the ConfigValue leaves.
     pub(crate) fn get_value_rec<S>(&self, names: &[S]) -> Option<&ConfigValue>
     where
@@ -501,6 +557,14 @@ impl Config {
         self.config.get_rec(names)
     }
 
+    /// Recursive get.
+    pub(crate) fn get_mut<S>(&mut self, names: &[S]) -> Option<&mut ConfigItem>
+    where
+        S: AsRef<str>,
+    {
+        self.config.get_rec_mut(names)
+    }
+
     /// Recursive get, only for ConfigValue leaves.
     pub(crate) fn get_value<S>(&self, names: &[S]) -> Option<&ConfigValue>
     where
@@ -607,4 +671,27 @@ mod tests {
             ("CodeName", ConfigItemType::Value), // here
         ]);
     }
+
+    #[test]
+    fn test_rename() {
+        let mut config = setup_config();
+        let v = config.create_path(&[
+            ("ooo:configuration-settings", ConfigItemType::Set),
+            ("ScriptConfiguration", ConfigItemType::Map),
+        ]);
+        v.insert("Tabelle0", ConfigItem::new_entry());
+        v.rename("Tabelle1", "Sheet1");
+
+        let v = config
+            .get_mut(&["ooo:configuration-settings", "ScriptConfiguration"])
+            .unwrap();
+        let keys: Vec<_> = v.iter().map(|(k, _)| k.clone()).collect();
+        assert_eq!(keys, vec!["Sheet1".to_string(), "Tabelle0".to_string()]);
+
+        // replaces an existing item.
+        v.rename("Sheet1", "Tabelle0");
+        let keys: Vec<_> = v.iter().map(|(k, _)| k.clone()).collect();
+        assert_eq!(keys, vec!["Tabelle0".to_string()]);
+        assert!(v.get_rec(&["Tabelle0", "CodeName"]).is_some());
+    }
 }
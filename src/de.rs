@@ -0,0 +1,530 @@
+//!
+//! Deserializes the rows of a sheet with serde.
+//!
+//! The header row maps the columns to the fields of a struct by name.
+//! Each following row is one value. Tuples take the header columns in
+//! order.
+//!
+//! The functions datetime() and datetime_opt() can be used with
+//! `#[serde(deserialize_with = "...")]` to read a date cell as
+//! midnight into a NaiveDateTime.
+//!
+//! ```
+//! use chrono::{NaiveDate, NaiveDateTime};
+//! use serde::Deserialize;
+//! use spreadsheet_ods::Sheet;
+//!
+//! #[derive(Deserialize)]
+//! struct Order {
+//!     item: String,
+//!     #[serde(deserialize_with = "spreadsheet_ods::de::datetime")]
+//!     shipped: NaiveDateTime,
+//! }
+//!
+//! let date = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+//! let mut sheet = Sheet::new("orders");
+//! sheet.set_value(0, 0, "item");
+//! sheet.set_value(0, 1, "shipped");
+//! sheet.set_value(1, 0, "pencil");
+//! sheet.set_value(1, 1, date);
+//!
+//! let orders: Vec<Order> = sheet.deserialize_rows(0, ..).unwrap();
+//! assert_eq!(orders[0].shipped, date.and_hms_opt(0, 0, 0).unwrap());
+//! ```
+//!
+
+use crate::refs::CellRef;
+use crate::{OdsError, Sheet, Value};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use rust_decimal::prelude::ToPrimitive;
+use serde::de::{
+    self, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Unexpected, Visitor,
+};
+use serde::{forward_to_deserialize_any, Deserialize, Deserializer};
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+use std::slice;
+
+/// Column and name of a header cell.
+type Header<'a> = (u32, Cow<'a, str>);
+
+/// Reads a date or a date and time into a NaiveDateTime. A date is
+/// midnight. Other deserializers must give the value as a string.
+pub fn datetime<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDateTime, D::Error> {
+    deserializer.deserialize_str(DateTimeVisitor)
+}
+
+/// Like datetime() for an `Option<NaiveDateTime>`. An empty cell is
+/// None.
+pub fn datetime_opt<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<NaiveDateTime>, D::Error> {
+    deserializer.deserialize_option(DateTimeOptVisitor)
+}
+
+struct DateTimeVisitor;
+
+impl<'de> Visitor<'de> for DateTimeVisitor {
+    type Value = NaiveDateTime;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a date or a date and time")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<NaiveDateTime, E> {
+        if let Ok(v) = NaiveDateTime::parse_from_str(v, "%Y-%m-%dT%H:%M:%S%.f") {
+            Ok(v)
+        } else if let Ok(v) = NaiveDate::parse_from_str(v, "%Y-%m-%d") {
+            Ok(v.and_time(NaiveTime::MIN))
+        } else {
+            Err(de::Error::invalid_value(Unexpected::Str(v), &self))
+        }
+    }
+}
+
+struct DateTimeOptVisitor;
+
+impl<'de> Visitor<'de> for DateTimeOptVisitor {
+    type Value = Option<NaiveDateTime>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a date or a date and time")
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        datetime(deserializer).map(Some)
+    }
+}
+
+/// Error while deserializing a row. The column is set when the
+/// conversion of a cell fails, header when a field has no column.
+#[derive(Debug)]
+struct DeError {
+    msg: String,
+    col: Option<u32>,
+    header: bool,
+}
+
+impl DeError {
+    fn at(mut self, col: u32) -> Self {
+        self.col.get_or_insert(col);
+        self
+    }
+}
+
+impl fmt::Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.msg)
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        DeError {
+            msg: msg.to_string(),
+            col: None,
+            header: false,
+        }
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        DeError {
+            msg: format!("missing header `{}`", field),
+            col: None,
+            header: true,
+        }
+    }
+}
+
+/// Deserializes the rows of the sheet. See Sheet::deserialize_rows().
+pub(crate) fn deserialize_rows<'a, T, R>(
+    sheet: &'a Sheet,
+    header_row: u32,
+    rows: R,
+) -> Result<Vec<T>, OdsError>
+where
+    T: Deserialize<'a>,
+    R: RangeBounds<u32>,
+{
+    let header = header(sheet, header_row);
+
+    let from = match rows.start_bound() {
+        Bound::Included(v) => *v,
+        Bound::Excluded(v) => v.saturating_add(1),
+        Bound::Unbounded => header_row.saturating_add(1),
+    };
+    let last = match sheet.data.max_cell() {
+        Some((row, _)) => row,
+        None => return Ok(Vec::new()),
+    };
+    let to = match rows.end_bound() {
+        Bound::Included(v) => (*v).min(last),
+        Bound::Excluded(0) => return Ok(Vec::new()),
+        Bound::Excluded(v) => (*v - 1).min(last),
+        Bound::Unbounded => last,
+    };
+
+    let mut result = Vec::new();
+    if from > to {
+        return Ok(result);
+    }
+    for row in from..=to {
+        // blank lines are skipped.
+        if header
+            .iter()
+            .all(|(col, _)| matches!(sheet.value(row, *col), Value::Empty))
+        {
+            continue;
+        }
+
+        let de = RowDeserializer {
+            sheet,
+            row,
+            header: &header,
+        };
+        match T::deserialize(de) {
+            Ok(v) => result.push(v),
+            Err(e) => {
+                // a missing header is reported behind the last one.
+                let (row, col) = if e.header {
+                    (header_row, header.last().map_or(0, |v| v.0 + 1))
+                } else {
+                    (
+                        row,
+                        e.col.unwrap_or_else(|| header.first().map_or(0, |v| v.0)),
+                    )
+                };
+                return Err(OdsError::Serde(
+                    CellRef::remote(sheet.name().as_str(), row, col),
+                    e.msg,
+                ));
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Columns of the header row with some text.
+fn header(sheet: &Sheet, header_row: u32) -> Vec<Header<'_>> {
+    sheet
+        .data
+        .range((header_row, 0)..=(header_row, u32::MAX))
+        .filter_map(|((_, col), cell)| {
+            let name = match text(&cell.value)? {
+                Cow::Borrowed(v) => Cow::Borrowed(v.trim()),
+                Cow::Owned(v) => Cow::Owned(v.trim().to_string()),
+            };
+            if name.is_empty() {
+                None
+            } else {
+                Some((col, name))
+            }
+        })
+        .collect()
+}
+
+/// Text of the value.
+fn text(value: &Value) -> Option<Cow<'_, str>> {
+    match value {
+        Value::Text(v) => Some(Cow::Borrowed(v.as_str())),
+        Value::TextXml(v) => {
+            let mut buf = String::new();
+            for (i, t) in v.iter().enumerate() {
+                if i > 0 {
+                    buf.push('\n');
+                }
+                t.extract_text(&mut buf);
+            }
+            Some(Cow::Owned(buf))
+        }
+        _ => None,
+    }
+}
+
+/// Deserializes one row as a map of the header names to the cell values.
+struct RowDeserializer<'a, 'h> {
+    sheet: &'a Sheet,
+    row: u32,
+    header: &'h [Header<'a>],
+}
+
+impl<'de> Deserializer<'de> for RowDeserializer<'de, '_> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_map(RowAccess {
+            sheet: self.sheet,
+            row: self.row,
+            header: self.header.iter(),
+            col: 0,
+        })
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_seq(RowAccess {
+            sheet: self.sheet,
+            row: self.row,
+            header: self.header.iter(),
+            col: 0,
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct map struct
+        enum identifier ignored_any
+    }
+}
+
+/// Walks the header columns of a row.
+struct RowAccess<'a, 'h> {
+    sheet: &'a Sheet,
+    row: u32,
+    header: slice::Iter<'h, Header<'a>>,
+    col: u32,
+}
+
+impl<'de> MapAccess<'de> for RowAccess<'de, '_> {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, DeError> {
+        match self.header.next() {
+            Some((col, name)) => {
+                self.col = *col;
+                seed.deserialize(name.as_ref().into_deserializer())
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, DeError> {
+        let value = self.sheet.value(self.row, self.col);
+        seed.deserialize(ValueDeserializer(value))
+            .map_err(|e| e.at(self.col))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.header.len())
+    }
+}
+
+impl<'de> SeqAccess<'de> for RowAccess<'de, '_> {
+    type Error = DeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, DeError> {
+        match self.header.next() {
+            Some((col, _)) => {
+                let value = self.sheet.value(self.row, *col);
+                seed.deserialize(ValueDeserializer(value))
+                    .map(Some)
+                    .map_err(|e| e.at(*col))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.header.len())
+    }
+}
+
+/// Deserializes one cell value.
+///
+/// Numbers are read as f64 or as integers if they have no fraction.
+/// Decimals, dates and times are given as strings in the format
+/// rust_decimal and chrono expect. Durations are fractions of a day
+/// like in a formula.
+struct ValueDeserializer<'a>(&'a Value);
+
+impl<'de> ValueDeserializer<'de> {
+    fn deserialize_integer<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self.0 {
+            Value::Number(v) | Value::Percentage(v) | Value::Currency(v, _)
+                if v.fract() == 0.0 && v.abs() < i64::MAX as f64 =>
+            {
+                visitor.visit_i64(*v as i64)
+            }
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) if v.fract().is_zero() => {
+                match v.to_i64() {
+                    Some(v) => visitor.visit_i64(v),
+                    None => self.deserialize_any(visitor),
+                }
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_float<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self.0 {
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => match v.to_f64() {
+                Some(v) => visitor.visit_f64(v),
+                None => self.deserialize_any(visitor),
+            },
+            _ => self.deserialize_any(visitor),
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self.0 {
+            Value::Empty => Err(de::Error::invalid_type(
+                Unexpected::Other("empty cell"),
+                &visitor,
+            )),
+            Value::Boolean(v) => visitor.visit_bool(*v),
+            Value::Number(v) | Value::Percentage(v) | Value::Currency(v, _) => {
+                visitor.visit_f64(*v)
+            }
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => visitor.visit_string(v.to_string()),
+            Value::Text(v) => visitor.visit_borrowed_str(v.as_str()),
+            Value::TextXml(_) => match text(self.0) {
+                Some(v) => visitor.visit_string(v.into_owned()),
+                None => visitor.visit_str(""),
+            },
+            Value::DateTime(v) => {
+                visitor.visit_string(v.format("%Y-%m-%dT%H:%M:%S%.f").to_string())
+            }
+            Value::Date(v) => visitor.visit_string(v.format("%Y-%m-%d").to_string()),
+            Value::TimeOfDay(v) => visitor.visit_string(v.format("%H:%M:%S%.f").to_string()),
+            Value::TimeDuration(v) => visitor.visit_f64(v.num_milliseconds() as f64 / 86_400_000.0),
+            Value::Error(e) => Err(de::Error::invalid_type(
+                Unexpected::Other(e.as_str()),
+                &visitor,
+            )),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self.0 {
+            Value::Empty => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self.0 {
+            Value::Empty => visitor.visit_unit(),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        match self.0 {
+            Value::Text(v) => visitor.visit_enum(v.as_str().into_deserializer()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_float(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_float(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool char str string bytes byte_buf seq tuple tuple_struct map struct
+        identifier
+    }
+}
@@ -181,6 +181,8 @@ pub fn create_default_styles(book: &mut WorkBook) {
     book.add_def_style(ValueType::Number, &DefaultStyle::number());
     book.add_def_style(ValueType::Percentage, &DefaultStyle::percent());
     book.add_def_style(ValueType::Currency, &DefaultStyle::currency());
-    book.add_def_style(ValueType::DateTime, &DefaultStyle::date());
+    book.add_def_style(ValueType::DateTime, &DefaultStyle::datetime());
+    book.add_def_style(ValueType::Date, &DefaultStyle::date());
+    book.add_def_style(ValueType::TimeOfDay, &DefaultStyle::time_of_day());
     book.add_def_style(ValueType::TimeDuration, &DefaultStyle::time_interval());
 }
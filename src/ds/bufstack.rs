@@ -1,3 +1,4 @@
+#[derive(Debug)]
 pub(crate) struct BufStack {
     n: i32,
     buf: Vec<Vec<u8>>,
@@ -0,0 +1,267 @@
+//!
+//! Storage for the cells of a sheet.
+//!
+//! The cells are kept as dense vectors per row, sorted by column.
+//! Cell style and validation names are Arc<String>s, the cells read
+//! from a file share one string per name.
+//!
+
+use crate::CellData;
+use std::collections::{btree_map, BTreeMap};
+use std::ops::{Bound, RangeBounds};
+use std::slice;
+
+/// Cells of one row, sorted by column.
+type Row = Vec<(u32, CellData)>;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CellStore {
+    rows: BTreeMap<u32, Row>,
+}
+
+impl CellStore {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub(crate) fn get(&self, (row, col): &(u32, u32)) -> Option<&CellData> {
+        let cells = self.rows.get(row)?;
+        match cells.binary_search_by_key(col, |v| v.0) {
+            Ok(idx) => Some(&cells[idx].1),
+            Err(_) => None,
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self, (row, col): &(u32, u32)) -> Option<&mut CellData> {
+        let cells = self.rows.get_mut(row)?;
+        match cells.binary_search_by_key(col, |v| v.0) {
+            Ok(idx) => Some(&mut cells[idx].1),
+            Err(_) => None,
+        }
+    }
+
+    /// Returns the cell, an empty one is created if necessary.
+    pub(crate) fn get_or_default(&mut self, (row, col): (u32, u32)) -> &mut CellData {
+        let cells = self.rows.entry(row).or_default();
+        let idx = match cells.last() {
+            // appending is the common case.
+            Some((last, _)) if *last < col => {
+                cells.push((col, CellData::default()));
+                cells.len() - 1
+            }
+            None => {
+                cells.push((col, CellData::default()));
+                0
+            }
+            _ => match cells.binary_search_by_key(&col, |v| v.0) {
+                Ok(idx) => idx,
+                Err(idx) => {
+                    cells.insert(idx, (col, CellData::default()));
+                    idx
+                }
+            },
+        };
+        &mut cells[idx].1
+    }
+
+    /// Sets the cell and returns the previous one.
+    pub(crate) fn insert(&mut self, (row, col): (u32, u32), cell: CellData) -> Option<CellData> {
+        let cells = self.rows.entry(row).or_default();
+        match cells.last() {
+            Some((last, _)) if *last < col => {
+                cells.push((col, cell));
+                None
+            }
+            None => {
+                cells.push((col, cell));
+                None
+            }
+            _ => match cells.binary_search_by_key(&col, |v| v.0) {
+                Ok(idx) => Some(std::mem::replace(&mut cells[idx].1, cell)),
+                Err(idx) => {
+                    cells.insert(idx, (col, cell));
+                    None
+                }
+            },
+        }
+    }
+
+    pub(crate) fn remove(&mut self, (row, col): &(u32, u32)) -> Option<CellData> {
+        let cells = self.rows.get_mut(row)?;
+        let cell = match cells.binary_search_by_key(col, |v| v.0) {
+            Ok(idx) => cells.remove(idx).1,
+            Err(_) => return None,
+        };
+        if cells.is_empty() {
+            self.rows.remove(row);
+        }
+        Some(cell)
+    }
+
+    /// Removes all cells.
+    pub(crate) fn drain(&mut self) -> impl Iterator<Item = ((u32, u32), CellData)> {
+        std::mem::take(&mut self.rows)
+            .into_iter()
+            .flat_map(|(row, cells)| cells.into_iter().map(move |(col, v)| ((row, col), v)))
+    }
+
+    /// Returns a tuple of (max(row), max(col)).
+    pub(crate) fn max_cell(&self) -> Option<(u32, u32)> {
+        let row = *self.rows.keys().next_back()?;
+        let col = self
+            .rows
+            .values()
+            .filter_map(|v| v.last().map(|v| v.0))
+            .max()?;
+        Some((row, col))
+    }
+
+    pub(crate) fn iter(&self) -> Iter<'_> {
+        Iter {
+            rows: self.rows.iter(),
+            row: 0,
+            cells: [].iter(),
+        }
+    }
+
+    pub(crate) fn values(&self) -> impl Iterator<Item = &CellData> {
+        self.rows.values().flat_map(|v| v.iter().map(|v| &v.1))
+    }
+
+    pub(crate) fn values_mut(&mut self) -> impl Iterator<Item = &mut CellData> {
+        self.rows
+            .values_mut()
+            .flat_map(|v| v.iter_mut().map(|v| &mut v.1))
+    }
+
+    pub(crate) fn range<R>(&self, range: R) -> Range<'_>
+    where
+        R: RangeBounds<(u32, u32)>,
+    {
+        let from = range.start_bound().cloned();
+        let to = range.end_bound().cloned();
+        let row_bound = |b: Bound<(u32, u32)>| match b {
+            Bound::Included((row, _)) | Bound::Excluded((row, _)) => Bound::Included(row),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let rows = self.rows.range((row_bound(from), row_bound(to)));
+        let len = rows
+            .clone()
+            .map(|(row, cells)| slice_row(*row, cells, from, to).len())
+            .sum();
+        Range {
+            rows,
+            from,
+            to,
+            front: (0, [].iter()),
+            back: (0, [].iter()),
+            len,
+        }
+    }
+}
+
+/// The part of the row that is within the bounds.
+fn slice_row(
+    row: u32,
+    cells: &Row,
+    from: Bound<(u32, u32)>,
+    to: Bound<(u32, u32)>,
+) -> &[(u32, CellData)] {
+    let start = match from {
+        Bound::Included((r, c)) if r == row => cells.partition_point(|v| v.0 < c),
+        Bound::Excluded((r, c)) if r == row => cells.partition_point(|v| v.0 <= c),
+        _ => 0,
+    };
+    let end = match to {
+        Bound::Included((r, c)) if r == row => cells.partition_point(|v| v.0 <= c),
+        Bound::Excluded((r, c)) if r == row => cells.partition_point(|v| v.0 < c),
+        _ => cells.len(),
+    };
+    if start < end {
+        &cells[start..end]
+    } else {
+        &[]
+    }
+}
+
+/// Iterates all cells.
+#[derive(Debug, Clone)]
+pub(crate) struct Iter<'a> {
+    rows: btree_map::Iter<'a, u32, Row>,
+    row: u32,
+    cells: slice::Iter<'a, (u32, CellData)>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = ((u32, u32), &'a CellData);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((col, cell)) = self.cells.next() {
+                return Some(((self.row, *col), cell));
+            }
+            let (row, cells) = self.rows.next()?;
+            self.row = *row;
+            self.cells = cells.iter();
+        }
+    }
+}
+
+/// Iterates a range of cells.
+#[derive(Debug, Clone)]
+pub(crate) struct Range<'a> {
+    rows: btree_map::Range<'a, u32, Row>,
+    from: Bound<(u32, u32)>,
+    to: Bound<(u32, u32)>,
+    front: (u32, slice::Iter<'a, (u32, CellData)>),
+    back: (u32, slice::Iter<'a, (u32, CellData)>),
+    len: usize,
+}
+
+impl<'a> Iterator for Range<'a> {
+    type Item = ((u32, u32), &'a CellData);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((col, cell)) = self.front.1.next() {
+                self.len -= 1;
+                return Some(((self.front.0, *col), cell));
+            }
+            match self.rows.next() {
+                Some((row, cells)) => {
+                    self.front = (*row, slice_row(*row, cells, self.from, self.to).iter());
+                }
+                None => {
+                    let (col, cell) = self.back.1.next()?;
+                    self.len -= 1;
+                    return Some(((self.back.0, *col), cell));
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl DoubleEndedIterator for Range<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((col, cell)) = self.back.1.next_back() {
+                self.len -= 1;
+                return Some(((self.back.0, *col), cell));
+            }
+            match self.rows.next_back() {
+                Some((row, cells)) => {
+                    self.back = (*row, slice_row(*row, cells, self.from, self.to).iter());
+                }
+                None => {
+                    let (col, cell) = self.front.1.next_back()?;
+                    self.len -= 1;
+                    return Some(((self.front.0, *col), cell));
+                }
+            }
+        }
+    }
+}
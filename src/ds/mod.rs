@@ -1,2 +1,3 @@
 pub(crate) mod bufstack;
+pub(crate) mod cellstore;
 pub(crate) mod detach;
@@ -27,6 +27,9 @@ pub enum OdsError {
     ParseFloat(std::num::ParseFloatError),
     Chrono(chrono::format::ParseError),
     SystemTime(std::time::SystemTimeError),
+    /// Conversion of a cell failed.
+    #[cfg(feature = "serde")]
+    Serde(crate::CellRef, String),
 }
 
 impl Display for OdsError {
@@ -44,6 +47,8 @@ impl Display for OdsError {
             OdsError::Chrono(e) => write!(f, "Chrono {}", e)?,
             OdsError::SystemTime(e) => write!(f, "SystemTime {}", e)?,
             OdsError::Utf8(e) => write!(f, "UTF8 {}", e)?,
+            #[cfg(feature = "serde")]
+            OdsError::Serde(c, e) => write!(f, "Serde {} {}", c, e)?,
         }
 
         Ok(())
@@ -65,6 +70,8 @@ impl Error for OdsError {
             OdsError::Chrono(e) => Some(e),
             OdsError::SystemTime(e) => Some(e),
             OdsError::Utf8(e) => Some(e),
+            #[cfg(feature = "serde")]
+            OdsError::Serde(_, _) => None,
         }
     }
 }
@@ -1,10 +1,33 @@
 //!
-//! For now defines functions to create cell references for formulas.
+//! Defines functions to create cell references for formulas,
+//! a parser for OpenFormula expressions, an evaluator,
+//! a dependency graph for incremental recalculation and
+//! the translation from and to Excel formulas.
 //!
+//! ```
+//! use spreadsheet_ods::formula::{parse, Expr, InfixOp};
+//!
+//! let expr = parse("of:=SUM([.A1:.B3])*2").unwrap();
+//! match &expr {
+//!     Expr::Infix(InfixOp::Mul, f, _) => {
+//!         assert!(matches!(f.as_ref(), Expr::Function(n, _) if n == "SUM"));
+//!     }
+//!     _ => unreachable!(),
+//! }
+//! assert_eq!(expr.to_formula(), "of:=SUM([.A1:.B3])*2");
+//! ```
+
+use crate::refs::{CellRange, CellRef, ColRange, RowRange};
+use crate::{OdsError, Value, WorkBook};
+use std::fmt::{Display, Formatter};
 
-use crate::refs::{CellRange, CellRef};
+mod deps;
+mod eval;
+mod excel;
+mod parser;
+pub(crate) mod rewrite;
 
-// TODO: more formula stuff. parsing?
+pub use deps::{CellKey, DependencyGraph};
 
 /// Creates a cell-reference for use in formulas.
 pub fn fcellref(row: u32, col: u32) -> String {
@@ -127,3 +150,443 @@ pub fn frangerefa_table<S: Into<String>>(
         .absolute()
         .to_formula()
 }
+
+/// Parses an OpenFormula expression. A leading namespace prefix
+/// like "of:" and the "=" are optional.
+///
+/// Parentheses, function calls and arrays can be nested 64 levels deep.
+pub fn parse(formula: &str) -> Result<Expr, OdsError> {
+    parser::parse_formula(formula)
+}
+
+/// Translates a formula in Excel A1 syntax like "=SUM(A1:B3)" or
+/// "='Sheet 2'!$A$1" to OpenFormula.
+///
+/// References, whole columns and rows, 3D references and external
+/// references get the OpenFormula syntax. The separators "," and ";"
+/// become ";" and "|", a space between two references becomes the
+/// intersection "!", a "," within parentheses that don't belong to a
+/// function becomes the union "~". TRUE and FALSE become functions.
+/// Functions that are known under a different name are renamed.
+///
+/// Structured references to Excel tables, ranges that mix cells,
+/// columns and rows, the implicit intersection "@" and a union
+/// without parentheses are not supported and return an error.
+///
+/// ```
+/// use spreadsheet_ods::formula::from_excel;
+///
+/// let f = from_excel("=SUM(Sheet2!A1:B3,$C$1)").unwrap();
+/// assert_eq!(f, "of:=SUM([Sheet2.A1:.B3];[.$C$1])");
+/// ```
+pub fn from_excel(formula: &str) -> Result<String, OdsError> {
+    excel::from_excel(formula)
+}
+
+/// Translates an OpenFormula formula to Excel A1 syntax.
+/// This is the reverse of [from_excel]. A union gets the parentheses
+/// Excel needs.
+///
+/// ```
+/// use spreadsheet_ods::formula::to_excel;
+///
+/// let f = to_excel("of:=SUM([Sheet2.A1:.B3];[.$C$1])").unwrap();
+/// assert_eq!(f, "=SUM(Sheet2!A1:B3,$C$1)");
+/// ```
+pub fn to_excel(formula: &str) -> Result<String, OdsError> {
+    excel::to_excel(formula)
+}
+
+/// Evaluates the expression as if it was the formula of the given cell.
+/// Referenced formula cells are evaluated too, but their values are
+/// not changed.
+///
+/// Errors are returned as Value::Error.
+pub fn evaluate(book: &WorkBook, sheet: usize, row: u32, col: u32, expr: &Expr) -> Value {
+    let mut eval = eval::Evaluator::new(book);
+    let result = eval.eval(expr, eval::Pos { sheet, row, col });
+    eval::to_value(result, &Value::Empty)
+}
+
+/// Recalculates all formulas of the workbook and stores the results
+/// as cell values. Percentage and currency values keep their type.
+///
+/// Errors are stored as Value::Error. Circular references
+/// result in Err:522. Formulas that can't be parsed are left as is.
+pub fn calculate(book: &mut WorkBook) {
+    // In dependency order each formula finds its references already
+    // calculated. This avoids a deep recursion for long chains.
+    let graph = DependencyGraph::new(book);
+    let all = (0..book.num_sheets())
+        .flat_map(|idx| {
+            book.sheet(idx)
+                .data
+                .iter()
+                .filter(|(_, cell)| cell.formula().is_some())
+                .map(move |((row, col), _)| (idx, row, col))
+        })
+        .collect();
+    let (order, circular) = graph.calc_order(&all);
+
+    let mut results = Vec::new();
+    {
+        let mut eval = eval::Evaluator::new(book);
+        for (idx, row, col) in order.into_iter().chain(circular) {
+            eval.cell(idx, row, col);
+            if let Some(v) = eval.cached(idx, row, col) {
+                results.push((idx, row, col, v.clone()));
+            }
+        }
+    }
+    store_results(book, results);
+}
+
+/// Recalculates only the formulas that depend on the changed cells.
+/// Returns the recalculated cells in the order of calculation.
+///
+/// Formulas that are part of a circular reference or depend on one
+/// get the error Err:522. They are returned last.
+pub fn recalculate(
+    book: &mut WorkBook,
+    graph: &DependencyGraph,
+    changed: &[CellKey],
+) -> Vec<CellKey> {
+    let dirty = graph.dirty(changed);
+    let (mut order, circular) = graph.calc_order(&dirty);
+
+    let mut results = Vec::new();
+    {
+        let mut eval = eval::Evaluator::new(book);
+        eval.set_dirty(&dirty);
+        for (idx, row, col) in &circular {
+            eval.set_cached(
+                *idx,
+                *row,
+                *col,
+                eval::CalcValue::Error(ErrorKind::Circular),
+            );
+        }
+        for (idx, row, col) in order.iter().chain(circular.iter()) {
+            results.push((*idx, *row, *col, eval.cell(*idx, *row, *col)));
+        }
+    }
+    store_results(book, results);
+
+    order.extend(circular);
+    order
+}
+
+fn store_results(book: &mut WorkBook, results: Vec<(usize, u32, u32, eval::CalcValue)>) {
+    for (idx, row, col, v) in results {
+        let sheet = book.sheet_mut(idx);
+        let (rows, cols) = match sheet.data.get(&(row, col)) {
+            Some(cell) => cell.matrix_span().into(),
+            None => continue,
+        };
+        // array formulas fill their whole range.
+        for r in 0..rows {
+            for c in 0..cols {
+                let cell = sheet.data.get_or_default((row + r, col + c));
+                cell.value = eval::to_value(eval::spill(&v, r, c), &cell.value);
+            }
+        }
+    }
+}
+
+/// Standard error values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// #NULL! Intersection of two ranges is empty.
+    Null,
+    /// #DIV/0! Division by zero.
+    Div0,
+    /// #VALUE! Wrong type of argument.
+    Value,
+    /// #REF! Invalid reference.
+    Ref,
+    /// #NAME? Unknown function or name.
+    Name,
+    /// #NUM! Invalid numeric value.
+    Num,
+    /// #N/A Value not available.
+    NA,
+    /// Err:522 Circular reference, as LibreOffice writes it.
+    Circular,
+}
+
+impl ErrorKind {
+    /// Text representation as used in formulas.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::Null => "#NULL!",
+            ErrorKind::Div0 => "#DIV/0!",
+            ErrorKind::Value => "#VALUE!",
+            ErrorKind::Ref => "#REF!",
+            ErrorKind::Name => "#NAME?",
+            ErrorKind::Num => "#NUM!",
+            ErrorKind::NA => "#N/A",
+            ErrorKind::Circular => "Err:522",
+        }
+    }
+
+    /// All error kinds.
+    pub fn all() -> &'static [ErrorKind] {
+        &[
+            ErrorKind::Null,
+            ErrorKind::Div0,
+            ErrorKind::Value,
+            ErrorKind::Ref,
+            ErrorKind::Name,
+            ErrorKind::Num,
+            ErrorKind::NA,
+            ErrorKind::Circular,
+        ]
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl TryFrom<&str> for ErrorKind {
+    type Error = OdsError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        ErrorKind::all()
+            .iter()
+            .find(|v| v.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| OdsError::Parse("unknown error value", Some(s.to_string())))
+    }
+}
+
+/// Prefix operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefixOp {
+    /// +
+    Plus,
+    /// -
+    Minus,
+}
+
+/// Postfix operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostfixOp {
+    /// %
+    Percent,
+}
+
+/// Infix operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfixOp {
+    /// +
+    Add,
+    /// -
+    Sub,
+    /// *
+    Mul,
+    /// /
+    Div,
+    /// ^
+    Pow,
+    /// &
+    Concat,
+    /// =
+    Eq,
+    /// <>
+    Ne,
+    /// <
+    Lt,
+    /// <=
+    Le,
+    /// >
+    Gt,
+    /// >=
+    Ge,
+    /// : Range spanned by two references.
+    Range,
+    /// ! Intersection of two references.
+    Intersect,
+    /// ~ Union of two references.
+    Union,
+}
+
+impl InfixOp {
+    /// Operator as in the formula.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InfixOp::Add => "+",
+            InfixOp::Sub => "-",
+            InfixOp::Mul => "*",
+            InfixOp::Div => "/",
+            InfixOp::Pow => "^",
+            InfixOp::Concat => "&",
+            InfixOp::Eq => "=",
+            InfixOp::Ne => "<>",
+            InfixOp::Lt => "<",
+            InfixOp::Le => "<=",
+            InfixOp::Gt => ">",
+            InfixOp::Ge => ">=",
+            InfixOp::Range => ":",
+            InfixOp::Intersect => "!",
+            InfixOp::Union => "~",
+        }
+    }
+
+    /// Binding strength. Higher binds tighter.
+    pub(crate) fn precedence(&self) -> u8 {
+        match self {
+            InfixOp::Eq | InfixOp::Ne | InfixOp::Lt | InfixOp::Le | InfixOp::Gt | InfixOp::Ge => 1,
+            InfixOp::Concat => 2,
+            InfixOp::Add | InfixOp::Sub => 3,
+            InfixOp::Mul | InfixOp::Div => 4,
+            InfixOp::Pow => 5,
+            InfixOp::Union => 8,
+            InfixOp::Intersect => 9,
+            InfixOp::Range => 10,
+        }
+    }
+}
+
+const PREC_POSTFIX: u8 = 6;
+const PREC_PREFIX: u8 = 7;
+const PREC_PRIMARY: u8 = 11;
+
+/// Parsed formula expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// Number literal.
+    Number(f64),
+    /// String literal.
+    String(String),
+    /// Error value literal.
+    Error(ErrorKind),
+    /// Cell reference.
+    CellRef(CellRef),
+    /// Cell range.
+    CellRange(CellRange),
+    /// Column range.
+    ColRange(ColRange),
+    /// Row range.
+    RowRange(RowRange),
+    /// A reference that couldn't be parsed, for example "[.#REF!]".
+    /// Contains the text between the brackets.
+    RefError(String),
+    /// Named range or named expression.
+    Name(String),
+    /// Function call.
+    Function(String, Vec<Expr>),
+    /// Omitted function argument.
+    Missing,
+    /// Inline array. Rows of columns.
+    Array(Vec<Vec<Expr>>),
+    /// Explicit parentheses.
+    Parentheses(Box<Expr>),
+    /// Prefix operator.
+    Prefix(PrefixOp, Box<Expr>),
+    /// Postfix operator.
+    Postfix(PostfixOp, Box<Expr>),
+    /// Infix operator.
+    Infix(InfixOp, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// Creates an infix expression.
+    pub fn infix(op: InfixOp, left: Expr, right: Expr) -> Self {
+        Expr::Infix(op, Box::new(left), Box::new(right))
+    }
+
+    /// Creates a function call.
+    pub fn function<S: Into<String>>(name: S, args: Vec<Expr>) -> Self {
+        Expr::Function(name.into(), args)
+    }
+
+    /// Returns the formula as used for table:formula including the
+    /// "of:=" prefix.
+    pub fn to_formula(&self) -> String {
+        format!("of:={}", self)
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Prefix(_, _) => PREC_PREFIX,
+            Expr::Postfix(_, _) => PREC_POSTFIX,
+            Expr::Infix(op, _, _) => op.precedence(),
+            _ => PREC_PRIMARY,
+        }
+    }
+}
+
+/// Writes the expression. Parentheses are added where the
+/// precedence of the operators requires it.
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        fn fmt_sub(f: &mut Formatter<'_>, expr: &Expr, parens: bool) -> std::fmt::Result {
+            if parens {
+                write!(f, "({})", expr)
+            } else {
+                write!(f, "{}", expr)
+            }
+        }
+
+        match self {
+            Expr::Number(v) => write!(f, "{}", v),
+            Expr::String(v) => write!(f, "\"{}\"", v.replace('"', "\"\"")),
+            Expr::Error(v) => write!(f, "{}", v),
+            Expr::CellRef(v) => write!(f, "{}", v.to_formula()),
+            Expr::CellRange(v) => write!(f, "{}", v.to_formula()),
+            Expr::ColRange(v) => write!(f, "{}", v.to_formula()),
+            Expr::RowRange(v) => write!(f, "{}", v.to_formula()),
+            Expr::RefError(v) => write!(f, "[{}]", v),
+            Expr::Name(v) => write!(f, "{}", v),
+            Expr::Function(name, args) => {
+                write!(f, "{}(", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ";")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Missing => Ok(()),
+            Expr::Array(rows) => {
+                write!(f, "{{")?;
+                for (i, row) in rows.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "|")?;
+                    }
+                    for (j, col) in row.iter().enumerate() {
+                        if j > 0 {
+                            write!(f, ";")?;
+                        }
+                        write!(f, "{}", col)?;
+                    }
+                }
+                write!(f, "}}")
+            }
+            Expr::Parentheses(v) => write!(f, "({})", v),
+            Expr::Prefix(op, v) => {
+                match op {
+                    PrefixOp::Plus => write!(f, "+")?,
+                    PrefixOp::Minus => write!(f, "-")?,
+                }
+                fmt_sub(f, v, v.precedence() < PREC_PREFIX)
+            }
+            Expr::Postfix(op, v) => {
+                fmt_sub(f, v, v.precedence() < PREC_POSTFIX)?;
+                match op {
+                    PostfixOp::Percent => write!(f, "%"),
+                }
+            }
+            Expr::Infix(op, l, r) => {
+                // all operators are left associative.
+                fmt_sub(f, l, l.precedence() < op.precedence())?;
+                write!(f, "{}", op.as_str())?;
+                fmt_sub(f, r, r.precedence() <= op.precedence())
+            }
+        }
+    }
+}
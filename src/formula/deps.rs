@@ -0,0 +1,660 @@
+//!
+//! Dependencies between formula cells.
+//!
+
+use crate::formula::{parse, Expr, InfixOp};
+use crate::refs::{CellRange, CellRef};
+use crate::WorkBook;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::RangeInclusive;
+
+/// Position of a cell as (sheet index, row, col).
+pub type CellKey = (usize, u32, u32);
+
+/// Maximum nesting of named expressions.
+const MAX_NAME_DEPTH: u32 = 16;
+
+/// Rows of one slot of the area index.
+const SLOT_ROWS: u32 = 128;
+/// Columns of one slot of the area index.
+const SLOT_COLS: u32 = 32;
+/// Areas that cover more slots are indexed by column or row only.
+const MAX_SLOTS: u64 = 64;
+
+/// A rectangular area of one sheet.
+#[derive(Debug, Clone, Copy)]
+struct Area {
+    sheet: usize,
+    row: u32,
+    col: u32,
+    to_row: u32,
+    to_col: u32,
+}
+
+impl Area {
+    fn contains(&self, sheet: usize, row: u32, col: u32) -> bool {
+        self.sheet == sheet
+            && row >= self.row
+            && row <= self.to_row
+            && col >= self.col
+            && col <= self.to_col
+    }
+
+    fn is_cell(&self) -> bool {
+        self.row == self.to_row && self.col == self.to_col
+    }
+}
+
+/// Where an area is found in the AreaIndex.
+enum Slots {
+    /// (sheet, row slot, col slot)
+    Grid(usize, RangeInclusive<u32>, RangeInclusive<u32>),
+    /// (sheet, col slot) for tall areas.
+    Cols(usize, RangeInclusive<u32>),
+    /// (sheet, row slot) for wide areas.
+    Rows(usize, RangeInclusive<u32>),
+    /// All of the sheet.
+    Sheet(usize),
+}
+
+/// Finds the areas that contain a cell. The sheets are divided into
+/// slots of SLOT_ROWS x SLOT_COLS cells and each area is listed with
+/// all the slots it overlaps. Whole columns and rows are listed per
+/// column or row slot.
+#[derive(Debug, Clone, Default)]
+struct AreaIndex {
+    grid: HashMap<(usize, u32, u32), Vec<(Area, CellKey)>>,
+    cols: HashMap<(usize, u32), Vec<(Area, CellKey)>>,
+    rows: HashMap<(usize, u32), Vec<(Area, CellKey)>>,
+    sheets: HashMap<usize, Vec<(Area, CellKey)>>,
+}
+
+impl AreaIndex {
+    fn slots(area: &Area) -> Slots {
+        let rows = area.row / SLOT_ROWS..=area.to_row / SLOT_ROWS;
+        let cols = area.col / SLOT_COLS..=area.to_col / SLOT_COLS;
+        let n_rows = (rows.end() - rows.start()) as u64 + 1;
+        let n_cols = (cols.end() - cols.start()) as u64 + 1;
+        if n_rows * n_cols <= MAX_SLOTS {
+            Slots::Grid(area.sheet, rows, cols)
+        } else if n_cols <= MAX_SLOTS {
+            Slots::Cols(area.sheet, cols)
+        } else if n_rows <= MAX_SLOTS {
+            Slots::Rows(area.sheet, rows)
+        } else {
+            Slots::Sheet(area.sheet)
+        }
+    }
+
+    fn insert(&mut self, area: Area, key: CellKey) {
+        match Self::slots(&area) {
+            Slots::Grid(sheet, rows, cols) => {
+                for r in rows {
+                    for c in cols.clone() {
+                        self.grid
+                            .entry((sheet, r, c))
+                            .or_default()
+                            .push((area, key));
+                    }
+                }
+            }
+            Slots::Cols(sheet, cols) => {
+                for c in cols {
+                    self.cols.entry((sheet, c)).or_default().push((area, key));
+                }
+            }
+            Slots::Rows(sheet, rows) => {
+                for r in rows {
+                    self.rows.entry((sheet, r)).or_default().push((area, key));
+                }
+            }
+            Slots::Sheet(sheet) => {
+                self.sheets.entry(sheet).or_default().push((area, key));
+            }
+        }
+    }
+
+    fn remove(&mut self, area: &Area, key: CellKey) {
+        fn retain<K: Eq + Hash>(map: &mut HashMap<K, Vec<(Area, CellKey)>>, slot: K, key: CellKey) {
+            if let Some(v) = map.get_mut(&slot) {
+                v.retain(|(_, k)| *k != key);
+                if v.is_empty() {
+                    map.remove(&slot);
+                }
+            }
+        }
+
+        match Self::slots(area) {
+            Slots::Grid(sheet, rows, cols) => {
+                for r in rows {
+                    for c in cols.clone() {
+                        retain(&mut self.grid, (sheet, r, c), key);
+                    }
+                }
+            }
+            Slots::Cols(sheet, cols) => {
+                for c in cols {
+                    retain(&mut self.cols, (sheet, c), key);
+                }
+            }
+            Slots::Rows(sheet, rows) => {
+                for r in rows {
+                    retain(&mut self.rows, (sheet, r), key);
+                }
+            }
+            Slots::Sheet(sheet) => retain(&mut self.sheets, sheet, key),
+        }
+    }
+
+    /// Adds the formula cells with an area that contains the cell.
+    fn find(&self, sheet: usize, row: u32, col: u32, result: &mut BTreeSet<CellKey>) {
+        let (r, c) = (row / SLOT_ROWS, col / SLOT_COLS);
+        for v in [
+            self.grid.get(&(sheet, r, c)),
+            self.cols.get(&(sheet, c)),
+            self.rows.get(&(sheet, r)),
+            self.sheets.get(&sheet),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            for (area, key) in v {
+                if area.contains(sheet, row, col) {
+                    result.insert(*key);
+                }
+            }
+        }
+    }
+}
+
+/// Dependency graph of all formula cells of a workbook.
+///
+/// The graph is built from the formulas once. After changing some
+/// values, e.g. with Sheet::set_value(), dirty() gives all formula
+/// cells that need to be recalculated, and formula::recalculate()
+/// recalculates only those.
+///
+/// ```
+/// use spreadsheet_ods::formula::{recalculate, DependencyGraph};
+/// use spreadsheet_ods::{Sheet, WorkBook};
+///
+/// let mut book = WorkBook::new_empty();
+/// let mut sheet = Sheet::new("1");
+/// sheet.set_value(0, 0, 1);
+/// sheet.set_formula(0, 1, "of:=[.A1]*2");
+/// sheet.set_formula(0, 2, "of:=[.B1]+1");
+/// book.push_sheet(sheet);
+///
+/// let graph = DependencyGraph::new(&book);
+///
+/// book.sheet_mut(0).set_value(0, 0, 5);
+/// assert_eq!(graph.dirty(&[(0, 0, 0)]).len(), 2);
+///
+/// recalculate(&mut book, &graph, &[(0, 0, 0)]);
+/// assert_eq!(book.sheet(0).value(0, 2).as_f64_or(0.0), 11.0);
+/// ```
+///
+/// Changing a formula needs an update of the graph with update_cell().
+/// Inserting or removing a sheet needs insert_sheet() or remove_sheet().
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+    /// Referenced areas for each formula cell.
+    precedents: HashMap<CellKey, Vec<Area>>,
+    /// Formula cells referencing a single cell.
+    cell_dependents: HashMap<CellKey, HashSet<CellKey>>,
+    /// Formula cells referencing a larger area.
+    area_dependents: AreaIndex,
+    /// Formula cells that use TODAY(), NOW() or RAND().
+    volatile: BTreeSet<CellKey>,
+    /// Formula cells that reference a sheet that doesn't exist.
+    unresolved: BTreeSet<CellKey>,
+    /// Formula cells that are part of a circular reference or depend
+    /// on one.
+    circular: BTreeSet<CellKey>,
+}
+
+impl DependencyGraph {
+    /// Builds the graph for all formulas of the workbook.
+    pub fn new(book: &WorkBook) -> Self {
+        let mut graph = Self::default();
+        for idx in 0..book.num_sheets() {
+            graph.insert_formulas(book, idx);
+        }
+        graph.update_circular();
+        graph
+    }
+
+    /// Updates the graph after the formula of this cell has changed
+    /// or was removed.
+    pub fn update_cell(&mut self, book: &WorkBook, sheet: usize, row: u32, col: u32) {
+        let key = (sheet, row, col);
+        self.remove(key);
+        if sheet < book.num_sheets() {
+            if let Some(formula) = book
+                .sheet(sheet)
+                .data
+                .get(&(row, col))
+                .and_then(|v| v.formula())
+            {
+                self.insert(book, key, formula);
+            }
+        }
+        self.update_circular_from(key);
+    }
+
+    /// Updates the graph after WorkBook::insert_sheet(). The formulas
+    /// of the new sheet are added and references to its name are
+    /// resolved.
+    pub fn insert_sheet(&mut self, book: &WorkBook, idx: usize) {
+        self.remap(|v| if v >= idx { Some(v + 1) } else { Some(v) });
+        for key in std::mem::take(&mut self.unresolved) {
+            self.update_formula(book, key);
+        }
+        if idx < book.num_sheets() {
+            self.insert_formulas(book, idx);
+        }
+        self.update_circular();
+    }
+
+    /// Updates the graph after WorkBook::remove_sheet(). The formulas
+    /// of the sheet are removed, references to it are dropped.
+    pub fn remove_sheet(&mut self, book: &WorkBook, idx: usize) {
+        // references to the removed sheet.
+        let affected: Vec<CellKey> = self
+            .precedents
+            .iter()
+            .filter(|(_, areas)| areas.iter().any(|v| v.sheet == idx))
+            .map(|(k, _)| *k)
+            .collect();
+        for key in affected {
+            if let Some(areas) = self.precedents.get_mut(&key) {
+                areas.retain(|v| v.sheet != idx);
+            }
+            self.unresolved.insert(key);
+        }
+        self.remap(|v| match v.cmp(&idx) {
+            Ordering::Less => Some(v),
+            Ordering::Equal => None,
+            Ordering::Greater => Some(v - 1),
+        });
+        // names may have changed their meaning.
+        for key in self.unresolved.clone() {
+            self.update_formula(book, key);
+        }
+        self.update_circular();
+    }
+
+    /// Number of formula cells.
+    pub fn len(&self) -> usize {
+        self.precedents.len()
+    }
+
+    /// No formula cells.
+    pub fn is_empty(&self) -> bool {
+        self.precedents.is_empty()
+    }
+
+    /// Formula cells that directly reference the given cell.
+    pub fn dependents(&self, sheet: usize, row: u32, col: u32) -> Vec<CellKey> {
+        let mut result: BTreeSet<CellKey> = BTreeSet::new();
+        if let Some(v) = self.cell_dependents.get(&(sheet, row, col)) {
+            result.extend(v.iter());
+        }
+        self.area_dependents.find(sheet, row, col, &mut result);
+        result.into_iter().collect()
+    }
+
+    /// All formula cells that need to be recalculated after the given
+    /// cells changed. This includes formulas that depend on them
+    /// indirectly, changed cells that contain a formula, all volatile
+    /// formulas and all circular references.
+    pub fn dirty(&self, changed: &[CellKey]) -> BTreeSet<CellKey> {
+        let mut dirty = self.volatile.clone();
+        dirty.extend(self.circular.iter());
+        let mut queue: VecDeque<CellKey> = VecDeque::new();
+        queue.extend(dirty.iter());
+        for key in changed {
+            if self.precedents.contains_key(key) && dirty.insert(*key) {
+                queue.push_back(*key);
+            }
+            for dep in self.dependents(key.0, key.1, key.2) {
+                if dirty.insert(dep) {
+                    queue.push_back(dep);
+                }
+            }
+        }
+        while let Some(key) = queue.pop_front() {
+            for dep in self.dependents(key.0, key.1, key.2) {
+                if dirty.insert(dep) {
+                    queue.push_back(dep);
+                }
+            }
+        }
+        dirty
+    }
+
+    /// Sorts the formula cells in the order they must be calculated.
+    ///
+    /// Returns the ordered cells and the cells that are part of a
+    /// circular reference or depend on one. The latter can't be
+    /// calculated.
+    pub fn calc_order(&self, cells: &BTreeSet<CellKey>) -> (Vec<CellKey>, Vec<CellKey>) {
+        // edges between the given cells only.
+        let mut in_degree: HashMap<CellKey, usize> = cells.iter().map(|v| (*v, 0)).collect();
+        let mut edges: HashMap<CellKey, Vec<CellKey>> = HashMap::new();
+        for key in cells {
+            for dep in self.dependents(key.0, key.1, key.2) {
+                if let Some(n) = in_degree.get_mut(&dep) {
+                    *n += 1;
+                    edges.entry(*key).or_default().push(dep);
+                }
+            }
+        }
+
+        let mut order = Vec::new();
+        let mut queue: VecDeque<CellKey> = cells
+            .iter()
+            .filter(|v| in_degree[v] == 0)
+            .copied()
+            .collect();
+        while let Some(key) = queue.pop_front() {
+            order.push(key);
+            if let Some(deps) = edges.get(&key) {
+                for dep in deps {
+                    let n = in_degree.get_mut(dep).expect("cell");
+                    *n -= 1;
+                    if *n == 0 {
+                        queue.push_back(*dep);
+                    }
+                }
+            }
+        }
+
+        let circular = cells.iter().filter(|v| in_degree[v] > 0).copied().collect();
+        (order, circular)
+    }
+
+    /// All formula cells that are part of a circular reference or
+    /// depend on one.
+    pub fn circular(&self) -> Vec<CellKey> {
+        self.circular.iter().copied().collect()
+    }
+
+    fn update_circular(&mut self) {
+        let all = self.precedents.keys().copied().collect();
+        self.circular = self.calc_order(&all).1.into_iter().collect();
+    }
+
+    /// Only the cell itself and the cells that depend on it can
+    /// change their state.
+    fn update_circular_from(&mut self, key: CellKey) {
+        let mut affected = BTreeSet::from([key]);
+        let mut queue = VecDeque::from([key]);
+        while let Some(key) = queue.pop_front() {
+            for dep in self.dependents(key.0, key.1, key.2) {
+                if affected.insert(dep) {
+                    queue.push_back(dep);
+                }
+            }
+        }
+        self.circular.retain(|v| !affected.contains(v));
+
+        let (_, circular) = self.calc_order(&affected);
+        let mut queue: VecDeque<CellKey> = circular.into_iter().collect();
+        // depends on a circular reference outside.
+        for key in &self.circular {
+            for dep in self.dependents(key.0, key.1, key.2) {
+                if affected.contains(&dep) {
+                    queue.push_back(dep);
+                }
+            }
+        }
+        while let Some(key) = queue.pop_front() {
+            if self.circular.insert(key) {
+                for dep in self.dependents(key.0, key.1, key.2) {
+                    queue.push_back(dep);
+                }
+            }
+        }
+    }
+
+    /// Changes the sheet index of all cells. Cells of a removed sheet
+    /// are dropped.
+    fn remap(&mut self, f: impl Fn(usize) -> Option<usize>) {
+        let precedents = std::mem::take(&mut self.precedents);
+        self.cell_dependents.clear();
+        self.area_dependents = AreaIndex::default();
+        for ((sheet, row, col), areas) in precedents {
+            if let Some(sheet) = f(sheet) {
+                let areas = areas
+                    .into_iter()
+                    .filter_map(|v| f(v.sheet).map(|sheet| Area { sheet, ..v }))
+                    .collect();
+                self.index((sheet, row, col), areas);
+            }
+        }
+        let remap_set = |set: &BTreeSet<CellKey>| {
+            set.iter()
+                .filter_map(|(sheet, row, col)| f(*sheet).map(|v| (v, *row, *col)))
+                .collect()
+        };
+        self.volatile = remap_set(&self.volatile);
+        self.unresolved = remap_set(&self.unresolved);
+        self.circular = remap_set(&self.circular);
+    }
+
+    /// Reads the formula of the cell again.
+    fn update_formula(&mut self, book: &WorkBook, key: CellKey) {
+        self.remove(key);
+        if let Some(formula) = book
+            .sheet(key.0)
+            .data
+            .get(&(key.1, key.2))
+            .and_then(|v| v.formula())
+        {
+            self.insert(book, key, formula);
+        }
+    }
+
+    fn insert_formulas(&mut self, book: &WorkBook, idx: usize) {
+        for ((row, col), cell) in book.sheet(idx).data.iter() {
+            if let Some(formula) = cell.formula() {
+                self.insert(book, (idx, row, col), formula);
+            }
+        }
+    }
+
+    fn remove(&mut self, key: CellKey) {
+        if let Some(areas) = self.precedents.remove(&key) {
+            for area in areas {
+                if area.is_cell() {
+                    let cell = (area.sheet, area.row, area.col);
+                    if let Some(v) = self.cell_dependents.get_mut(&cell) {
+                        v.remove(&key);
+                        if v.is_empty() {
+                            self.cell_dependents.remove(&cell);
+                        }
+                    }
+                } else {
+                    self.area_dependents.remove(&area, key);
+                }
+            }
+        }
+        self.volatile.remove(&key);
+        self.unresolved.remove(&key);
+    }
+
+    fn insert(&mut self, book: &WorkBook, key: CellKey, formula: &str) {
+        let mut refs = Refs::default();
+        if let Ok(expr) = parse(formula) {
+            collect(book, key.0, &expr, &mut refs, 0);
+        }
+        if refs.volatile {
+            self.volatile.insert(key);
+        }
+        if refs.unresolved {
+            self.unresolved.insert(key);
+        }
+        self.index(key, refs.areas);
+    }
+
+    fn index(&mut self, key: CellKey, areas: Vec<Area>) {
+        for area in &areas {
+            if area.is_cell() {
+                self.cell_dependents
+                    .entry((area.sheet, area.row, area.col))
+                    .or_default()
+                    .insert(key);
+            } else {
+                self.area_dependents.insert(*area, key);
+            }
+        }
+        self.precedents.insert(key, areas);
+    }
+}
+
+/// References of one formula.
+#[derive(Debug, Default)]
+struct Refs {
+    areas: Vec<Area>,
+    /// Uses TODAY(), NOW() or RAND().
+    volatile: bool,
+    /// References a sheet that doesn't exist.
+    unresolved: bool,
+}
+
+fn sheet_idx(
+    book: &WorkBook,
+    sheet: usize,
+    table: Option<&String>,
+    refs: &mut Refs,
+) -> Option<usize> {
+    match table {
+        None => Some(sheet),
+        Some(table) => {
+            let idx = book.sheet_idx(table);
+            refs.unresolved |= idx.is_none();
+            idx
+        }
+    }
+}
+
+fn range_area(book: &WorkBook, sheet: usize, range: &CellRange, refs: &mut Refs) -> Option<Area> {
+    if range.iri().is_some() {
+        return None;
+    }
+    Some(Area {
+        sheet: sheet_idx(book, sheet, range.table(), refs)?,
+        row: range.row().min(range.to_row()),
+        col: range.col().min(range.to_col()),
+        to_row: range.row().max(range.to_row()),
+        to_col: range.col().max(range.to_col()),
+    })
+}
+
+fn cell_area(book: &WorkBook, sheet: usize, cell: &CellRef, refs: &mut Refs) -> Option<Area> {
+    if cell.iri().is_some() {
+        return None;
+    }
+    Some(Area {
+        sheet: sheet_idx(book, sheet, cell.table(), refs)?,
+        row: cell.row(),
+        col: cell.col(),
+        to_row: cell.row(),
+        to_col: cell.col(),
+    })
+}
+
+/// Collects all referenced areas.
+fn collect(book: &WorkBook, sheet: usize, expr: &Expr, refs: &mut Refs, depth: u32) {
+    match expr {
+        Expr::CellRef(v) => {
+            let area = cell_area(book, sheet, v, refs);
+            refs.areas.extend(area);
+        }
+        Expr::CellRange(v) => {
+            let area = range_area(book, sheet, v, refs);
+            refs.areas.extend(area);
+        }
+        Expr::ColRange(v) => {
+            if let Some(sheet) = sheet_idx(book, sheet, v.table(), refs) {
+                refs.areas.push(Area {
+                    sheet,
+                    row: 0,
+                    col: v.col().min(v.to_col()),
+                    to_row: u32::MAX,
+                    to_col: v.col().max(v.to_col()),
+                });
+            }
+        }
+        Expr::RowRange(v) => {
+            if let Some(sheet) = sheet_idx(book, sheet, v.table(), refs) {
+                refs.areas.push(Area {
+                    sheet,
+                    row: v.row().min(v.to_row()),
+                    col: 0,
+                    to_row: v.row().max(v.to_row()),
+                    to_col: u32::MAX,
+                });
+            }
+        }
+        Expr::Name(name) => {
+            let sheet_name = book.sheet(sheet).name().as_str();
+            if let Some(range) = book.local_named_range(sheet_name, name) {
+                let area = range_area(book, sheet, range.range(), refs);
+                refs.areas.extend(area);
+            } else if let Some(expr) = book.local_named_expression(sheet_name, name) {
+                if depth < MAX_NAME_DEPTH {
+                    if let Ok(expr) = parse(expr.expression()) {
+                        collect(book, sheet, &expr, refs, depth + 1);
+                    }
+                }
+            }
+        }
+        Expr::Function(name, args) => {
+            if ["TODAY", "NOW", "RAND"]
+                .iter()
+                .any(|v| name.eq_ignore_ascii_case(v))
+            {
+                refs.volatile = true;
+            }
+            for arg in args {
+                collect(book, sheet, arg, refs, depth);
+            }
+        }
+        Expr::Array(rows) => {
+            for v in rows.iter().flatten() {
+                collect(book, sheet, v, refs, depth);
+            }
+        }
+        Expr::Parentheses(v) | Expr::Prefix(_, v) | Expr::Postfix(_, v) => {
+            collect(book, sheet, v, refs, depth)
+        }
+        Expr::Infix(InfixOp::Range, l, r) => match (l.as_ref(), r.as_ref()) {
+            (Expr::CellRef(l), Expr::CellRef(r)) => {
+                let l = cell_area(book, sheet, l, refs);
+                let r = cell_area(book, sheet, r, refs);
+                if let (Some(l), Some(r)) = (l, r) {
+                    refs.areas.push(Area {
+                        sheet: l.sheet,
+                        row: l.row.min(r.row),
+                        col: l.col.min(r.col),
+                        to_row: l.row.max(r.row),
+                        to_col: l.col.max(r.col),
+                    });
+                }
+            }
+            (l, r) => {
+                collect(book, sheet, l, refs, depth);
+                collect(book, sheet, r, refs, depth);
+            }
+        },
+        Expr::Infix(_, l, r) => {
+            collect(book, sheet, l, refs, depth);
+            collect(book, sheet, r, refs, depth);
+        }
+        Expr::Number(_) | Expr::String(_) | Expr::Error(_) | Expr::RefError(_) | Expr::Missing => {}
+    }
+}
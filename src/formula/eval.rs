@@ -0,0 +1,1109 @@
+//!
+//! Evaluates formulas.
+//!
+//! Numbers, dates and times are all plain numbers during the
+//! calculation, dates as days since 1899-12-30. References are
+//! evaluated to a matrix of values.
+//!
+
+use crate::formula::{parse, ErrorKind, Expr, InfixOp, PostfixOp, PrefixOp};
+use crate::refs::{CellRange, CellRef};
+use crate::{Value, WorkBook};
+use chrono::{Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use rust_decimal::prelude::ToPrimitive;
+use std::cmp::{max, Ordering};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Intermediate values.
+#[derive(Debug, Clone)]
+pub(crate) enum CalcValue {
+    Empty,
+    Number(f64),
+    DateTime(NaiveDateTime),
+    Text(String),
+    Boolean(bool),
+    Error(ErrorKind),
+    Matrix(Vec<Vec<CalcValue>>),
+}
+
+type CalcResult<T> = Result<T, ErrorKind>;
+
+/// Cells covered by array formulas as sheet, row and col, with the
+/// row and col of the formula cell.
+type ArrayIndex = HashMap<(usize, u32, u32), (u32, u32)>;
+
+/// Last row of a sheet. Ranges that reach it end at the used area.
+const MAX_ROW: u32 = 1_048_575;
+/// Last column of a sheet. Ranges that reach it end at the used area.
+const MAX_COL: u32 = 16_383;
+
+/// Position of the formula that is currently evaluated.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Pos {
+    pub(crate) sheet: usize,
+    pub(crate) row: u32,
+    pub(crate) col: u32,
+}
+
+/// Evaluates formulas. Formula cells are evaluated once and the result
+/// is cached.
+pub(crate) struct Evaluator<'a> {
+    book: &'a WorkBook,
+    cache: HashMap<(usize, u32, u32), CalcValue>,
+    active: HashSet<(usize, u32, u32)>,
+    /// Only these formula cells are evaluated, all others use
+    /// the current value.
+    dirty: Option<&'a BTreeSet<(usize, u32, u32)>>,
+    /// Array formulas, collected when first needed.
+    arrays: Option<ArrayIndex>,
+    now: NaiveDateTime,
+}
+
+impl<'a> Evaluator<'a> {
+    pub(crate) fn new(book: &'a WorkBook) -> Self {
+        Self {
+            book,
+            cache: Default::default(),
+            active: Default::default(),
+            dirty: None,
+            arrays: None,
+            now: Local::now().naive_local(),
+        }
+    }
+
+    /// Evaluates only these formula cells.
+    pub(crate) fn set_dirty(&mut self, dirty: &'a BTreeSet<(usize, u32, u32)>) {
+        self.dirty = Some(dirty);
+    }
+
+    /// Sets the result of a formula cell.
+    pub(crate) fn set_cached(&mut self, sheet: usize, row: u32, col: u32, value: CalcValue) {
+        self.cache.insert((sheet, row, col), value);
+    }
+
+    /// Cached result of a formula cell.
+    pub(crate) fn cached(&self, sheet: usize, row: u32, col: u32) -> Option<&CalcValue> {
+        self.cache.get(&(sheet, row, col))
+    }
+
+    /// Value of a cell. Formulas are evaluated.
+    pub(crate) fn cell(&mut self, sheet: usize, row: u32, col: u32) -> CalcValue {
+        let key = (sheet, row, col);
+        if let Some(v) = self.cache.get(&key) {
+            return v.clone();
+        }
+
+        let cell = match self.book.sheet(sheet).data.get(&(row, col)) {
+            Some(cell) => cell,
+            None => {
+                return self
+                    .array_value(sheet, row, col)
+                    .unwrap_or(CalcValue::Empty)
+            }
+        };
+        let formula = match cell.formula() {
+            Some(formula) => formula,
+            None => {
+                return self
+                    .array_value(sheet, row, col)
+                    .unwrap_or_else(|| from_value(&cell.value))
+            }
+        };
+        if let Some(dirty) = self.dirty {
+            if !dirty.contains(&key) {
+                return from_value(&cell.value);
+            }
+        }
+        let expr = match parse(formula) {
+            Ok(expr) => expr,
+            // keep whatever was there.
+            Err(_) => return from_value(&cell.value),
+        };
+
+        // circular reference.
+        if !self.active.insert(key) {
+            return CalcValue::Error(ErrorKind::Circular);
+        }
+        let result = self.eval(&expr, Pos { sheet, row, col });
+        self.active.remove(&key);
+
+        self.cache.insert(key, result.clone());
+        result
+    }
+
+    /// Value of a cell that is covered by an array formula.
+    fn array_value(&mut self, sheet: usize, row: u32, col: u32) -> Option<CalcValue> {
+        let book = self.book;
+        let arrays = self.arrays.get_or_insert_with(|| {
+            let mut arrays = HashMap::new();
+            for idx in 0..book.num_sheets() {
+                for ((row, col), cell) in book.sheet(idx).data.iter() {
+                    let (rows, cols) = cell.matrix_span().into();
+                    if cell.formula().is_some() && (rows > 1 || cols > 1) {
+                        for r in row..row.saturating_add(rows) {
+                            for c in col..col.saturating_add(cols) {
+                                if (r, c) != (row, col) {
+                                    arrays.entry((idx, r, c)).or_insert((row, col));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            arrays
+        });
+        let (r, c) = *arrays.get(&(sheet, row, col))?;
+        if let Some(dirty) = self.dirty {
+            if !dirty.contains(&(sheet, r, c)) {
+                return None;
+            }
+        }
+        let value = self.cell(sheet, r, c);
+        Some(spill(&value, row - r, col - c))
+    }
+
+    /// Evaluates the expression.
+    pub(crate) fn eval(&mut self, expr: &Expr, pos: Pos) -> CalcValue {
+        match expr {
+            Expr::Number(v) => CalcValue::Number(*v),
+            Expr::String(v) => CalcValue::Text(v.clone()),
+            Expr::Error(v) => CalcValue::Error(*v),
+            Expr::CellRef(v) => self.range(&cell_range(v), pos),
+            Expr::CellRange(v) => self.range(v, pos),
+            Expr::ColRange(v) => {
+                let mut range = CellRange::local(0, v.col(), MAX_ROW, v.to_col());
+                if let Some(table) = v.table() {
+                    range.set_table(table.as_str());
+                }
+                self.range(&range, pos)
+            }
+            Expr::RowRange(v) => {
+                let mut range = CellRange::local(v.row(), 0, v.to_row(), MAX_COL);
+                if let Some(table) = v.table() {
+                    range.set_table(table.as_str());
+                }
+                self.range(&range, pos)
+            }
+            Expr::RefError(_) => CalcValue::Error(ErrorKind::Ref),
+            Expr::Name(v) => self.name(v, pos),
+            Expr::Function(name, args) => self.function(name, args, pos),
+            Expr::Missing => CalcValue::Empty,
+            Expr::Array(rows) => CalcValue::Matrix(
+                rows.iter()
+                    .map(|row| row.iter().map(|v| scalar(self.eval(v, pos))).collect())
+                    .collect(),
+            ),
+            Expr::Parentheses(v) => self.eval(v, pos),
+            Expr::Prefix(op, v) => {
+                let v = self.eval(v, pos);
+                match (op, number(&v)) {
+                    (_, Err(e)) => CalcValue::Error(e),
+                    (PrefixOp::Plus, Ok(_)) => scalar(v),
+                    (PrefixOp::Minus, Ok(v)) => CalcValue::Number(-v),
+                }
+            }
+            Expr::Postfix(PostfixOp::Percent, v) => match number(&self.eval(v, pos)) {
+                Ok(v) => CalcValue::Number(v / 100.0),
+                Err(e) => CalcValue::Error(e),
+            },
+            Expr::Infix(op, l, r) => self.infix(*op, l, r, pos),
+        }
+    }
+
+    fn sheet_idx(&self, table: Option<&String>, pos: Pos) -> CalcResult<usize> {
+        match table {
+            None => Ok(pos.sheet),
+            Some(table) => self.book.sheet_idx(table).ok_or(ErrorKind::Ref),
+        }
+    }
+
+    fn range(&mut self, range: &CellRange, pos: Pos) -> CalcValue {
+        if range.iri().is_some() {
+            return CalcValue::Error(ErrorKind::Ref);
+        }
+        let sheet = match self.sheet_idx(range.table(), pos) {
+            Ok(v) => v,
+            Err(e) => return CalcValue::Error(e),
+        };
+        let (row, mut to_row) = minmax(range.row(), range.to_row());
+        let (col, mut to_col) = minmax(range.col(), range.to_col());
+        // whole columns or rows.
+        if to_row >= MAX_ROW || to_col >= MAX_COL {
+            let (rows, cols) = self.book.sheet(sheet).used_grid_size();
+            if to_row >= MAX_ROW {
+                to_row = max(row, rows - 1);
+            }
+            if to_col >= MAX_COL {
+                to_col = max(col, cols - 1);
+            }
+        }
+
+        let mut rows = Vec::new();
+        for r in row..=to_row {
+            let mut cols = Vec::new();
+            for c in col..=to_col {
+                cols.push(scalar(self.cell(sheet, r, c)));
+            }
+            rows.push(cols);
+        }
+        CalcValue::Matrix(rows)
+    }
+
+    fn name(&mut self, name: &str, pos: Pos) -> CalcValue {
+        let sheet_name = self.book.sheet(pos.sheet).name().as_str();
+        if let Some(range) = self.book.local_named_range(sheet_name, name) {
+            let range = range.range().clone();
+            self.range(&range, pos)
+        } else if let Some(expr) = self.book.local_named_expression(sheet_name, name) {
+            match parse(expr.expression()) {
+                Ok(expr) => self.eval(&expr, pos),
+                Err(_) => CalcValue::Error(ErrorKind::Name),
+            }
+        } else if name.eq_ignore_ascii_case("TRUE") {
+            CalcValue::Boolean(true)
+        } else if name.eq_ignore_ascii_case("FALSE") {
+            CalcValue::Boolean(false)
+        } else {
+            CalcValue::Error(ErrorKind::Name)
+        }
+    }
+
+    fn infix(&mut self, op: InfixOp, l: &Expr, r: &Expr, pos: Pos) -> CalcValue {
+        if op == InfixOp::Range {
+            return match (l, r) {
+                (Expr::CellRef(l), Expr::CellRef(r)) => {
+                    let mut range = cell_range(l);
+                    range.set_to_row(r.row());
+                    range.set_to_col(r.col());
+                    self.range(&range, pos)
+                }
+                _ => CalcValue::Error(ErrorKind::Value),
+            };
+        }
+
+        let l = scalar(self.eval(l, pos));
+        let r = scalar(self.eval(r, pos));
+        if let CalcValue::Error(e) = l {
+            return CalcValue::Error(e);
+        }
+        if let CalcValue::Error(e) = r {
+            return CalcValue::Error(e);
+        }
+
+        let result = match op {
+            // adding days to a date gives a date.
+            InfixOp::Add => match (&l, &r) {
+                (CalcValue::DateTime(_), CalcValue::DateTime(_)) => arith(&l, &r, |a, b| Ok(a + b)),
+                (CalcValue::DateTime(_), _) | (_, CalcValue::DateTime(_)) => {
+                    date_arith(&l, &r, |a, b| a + b)
+                }
+                _ => arith(&l, &r, |a, b| Ok(a + b)),
+            },
+            InfixOp::Sub => match (&l, &r) {
+                (CalcValue::DateTime(_), CalcValue::DateTime(_)) => arith(&l, &r, |a, b| Ok(a - b)),
+                (CalcValue::DateTime(_), _) => date_arith(&l, &r, |a, b| a - b),
+                _ => arith(&l, &r, |a, b| Ok(a - b)),
+            },
+            InfixOp::Mul => arith(&l, &r, |a, b| Ok(a * b)),
+            InfixOp::Div => arith(&l, &r, |a, b| {
+                if b == 0.0 {
+                    Err(ErrorKind::Div0)
+                } else {
+                    Ok(a / b)
+                }
+            }),
+            InfixOp::Pow => arith(&l, &r, |a, b| Ok(a.powf(b))),
+            InfixOp::Concat => text(&l).and_then(|l| Ok(CalcValue::Text(l + &text(&r)?))),
+            InfixOp::Eq => Ok(CalcValue::Boolean(compare(&l, &r) == Ordering::Equal)),
+            InfixOp::Ne => Ok(CalcValue::Boolean(compare(&l, &r) != Ordering::Equal)),
+            InfixOp::Lt => Ok(CalcValue::Boolean(compare(&l, &r) == Ordering::Less)),
+            InfixOp::Le => Ok(CalcValue::Boolean(compare(&l, &r) != Ordering::Greater)),
+            InfixOp::Gt => Ok(CalcValue::Boolean(compare(&l, &r) == Ordering::Greater)),
+            InfixOp::Ge => Ok(CalcValue::Boolean(compare(&l, &r) != Ordering::Less)),
+            InfixOp::Range | InfixOp::Intersect | InfixOp::Union => Err(ErrorKind::Value),
+        };
+        result.unwrap_or_else(CalcValue::Error)
+    }
+
+    fn function(&mut self, name: &str, args: &[Expr], pos: Pos) -> CalcValue {
+        let name = name.to_ascii_uppercase();
+        match self.function_impl(&name, args, pos) {
+            Ok(v) => v,
+            Err(e) => CalcValue::Error(e),
+        }
+    }
+
+    fn arg(&mut self, args: &[Expr], idx: usize, pos: Pos) -> CalcResult<CalcValue> {
+        match args.get(idx) {
+            Some(v) => Ok(self.eval(v, pos)),
+            None => Err(ErrorKind::Value),
+        }
+    }
+
+    fn arg_number(&mut self, args: &[Expr], idx: usize, pos: Pos) -> CalcResult<f64> {
+        number(&self.arg(args, idx, pos)?)
+    }
+
+    fn arg_number_or(&mut self, args: &[Expr], idx: usize, d: f64, pos: Pos) -> CalcResult<f64> {
+        match args.get(idx) {
+            None | Some(Expr::Missing) => Ok(d),
+            Some(v) => number(&self.eval(v, pos)),
+        }
+    }
+
+    fn arg_text(&mut self, args: &[Expr], idx: usize, pos: Pos) -> CalcResult<String> {
+        text(&self.arg(args, idx, pos)?)
+    }
+
+    fn arg_bool_or(&mut self, args: &[Expr], idx: usize, d: bool, pos: Pos) -> CalcResult<bool> {
+        match args.get(idx) {
+            None | Some(Expr::Missing) => Ok(d),
+            Some(v) => boolean(&self.eval(v, pos)),
+        }
+    }
+
+    fn arg_date(&mut self, args: &[Expr], idx: usize, pos: Pos) -> CalcResult<NaiveDateTime> {
+        from_serial(self.arg_number(args, idx, pos)?)
+    }
+
+    /// All numbers of the arguments. Within references only numbers
+    /// are used, direct arguments are converted.
+    fn numbers(&mut self, args: &[Expr], pos: Pos) -> CalcResult<Vec<f64>> {
+        let mut numbers = Vec::new();
+        for arg in args {
+            match self.eval(arg, pos) {
+                CalcValue::Matrix(m) => {
+                    for v in m.iter().flatten() {
+                        match v {
+                            CalcValue::Number(_) | CalcValue::DateTime(_) => {
+                                numbers.push(number(v)?)
+                            }
+                            CalcValue::Error(e) => return Err(*e),
+                            _ => {}
+                        }
+                    }
+                }
+                CalcValue::Empty => {}
+                v => numbers.push(number(&v)?),
+            }
+        }
+        Ok(numbers)
+    }
+
+    fn function_impl(&mut self, name: &str, args: &[Expr], pos: Pos) -> CalcResult<CalcValue> {
+        let v = match name {
+            // aggregates
+            "SUM" => CalcValue::Number(self.numbers(args, pos)?.iter().sum()),
+            "PRODUCT" => CalcValue::Number(self.numbers(args, pos)?.iter().product()),
+            "AVERAGE" => {
+                let n = self.numbers(args, pos)?;
+                if n.is_empty() {
+                    return Err(ErrorKind::Div0);
+                }
+                CalcValue::Number(n.iter().sum::<f64>() / n.len() as f64)
+            }
+            "MIN" => {
+                let n = self.numbers(args, pos)?;
+                CalcValue::Number(n.into_iter().reduce(f64::min).unwrap_or(0.0))
+            }
+            "MAX" => {
+                let n = self.numbers(args, pos)?;
+                CalcValue::Number(n.into_iter().reduce(f64::max).unwrap_or(0.0))
+            }
+            "COUNT" => {
+                let mut count = 0;
+                for arg in args {
+                    count += match self.eval(arg, pos) {
+                        CalcValue::Matrix(m) => m
+                            .iter()
+                            .flatten()
+                            .filter(|v| matches!(v, CalcValue::Number(_) | CalcValue::DateTime(_)))
+                            .count(),
+                        CalcValue::Empty => 0,
+                        v => number(&v).is_ok() as usize,
+                    };
+                }
+                CalcValue::Number(count as f64)
+            }
+            "COUNTA" => {
+                let mut count = 0;
+                for arg in args {
+                    count += match self.eval(arg, pos) {
+                        CalcValue::Matrix(m) => m
+                            .iter()
+                            .flatten()
+                            .filter(|v| !matches!(v, CalcValue::Empty))
+                            .count(),
+                        CalcValue::Empty => 0,
+                        _ => 1,
+                    };
+                }
+                CalcValue::Number(count as f64)
+            }
+            "COUNTBLANK" => match self.arg(args, 0, pos)? {
+                CalcValue::Matrix(m) => CalcValue::Number(
+                    m.iter()
+                        .flatten()
+                        .filter(|v| matches!(v, CalcValue::Empty))
+                        .count() as f64,
+                ),
+                _ => return Err(ErrorKind::Value),
+            },
+
+            // logic
+            "TRUE" => CalcValue::Boolean(true),
+            "FALSE" => CalcValue::Boolean(false),
+            "IF" => {
+                let cond = boolean(&self.arg(args, 0, pos)?)?;
+                let idx = if cond { 1 } else { 2 };
+                match args.get(idx) {
+                    None => CalcValue::Boolean(cond),
+                    Some(Expr::Missing) => CalcValue::Number(0.0),
+                    Some(v) => self.eval(v, pos),
+                }
+            }
+            "AND" | "OR" | "XOR" => {
+                let mut values = Vec::new();
+                for arg in args {
+                    match self.eval(arg, pos) {
+                        CalcValue::Matrix(m) => {
+                            for v in m.iter().flatten() {
+                                match v {
+                                    CalcValue::Empty | CalcValue::Text(_) => {}
+                                    v => values.push(boolean(v)?),
+                                }
+                            }
+                        }
+                        v => values.push(boolean(&v)?),
+                    }
+                }
+                if values.is_empty() {
+                    return Err(ErrorKind::Value);
+                }
+                CalcValue::Boolean(match name {
+                    "AND" => values.iter().all(|v| *v),
+                    "OR" => values.iter().any(|v| *v),
+                    _ => values.iter().filter(|v| **v).count() % 2 == 1,
+                })
+            }
+            "NOT" => CalcValue::Boolean(!boolean(&self.arg(args, 0, pos)?)?),
+            "IFERROR" | "IFNA" => match scalar(self.arg(args, 0, pos)?) {
+                CalcValue::Error(e) if name == "IFERROR" || e == ErrorKind::NA => {
+                    self.arg(args, 1, pos)?
+                }
+                v => v,
+            },
+            "ISERROR" => CalcValue::Boolean(matches!(
+                scalar(self.arg(args, 0, pos)?),
+                CalcValue::Error(_)
+            )),
+            "ISNA" => CalcValue::Boolean(matches!(
+                scalar(self.arg(args, 0, pos)?),
+                CalcValue::Error(ErrorKind::NA)
+            )),
+            "ISBLANK" => {
+                CalcValue::Boolean(matches!(scalar(self.arg(args, 0, pos)?), CalcValue::Empty))
+            }
+            "ISNUMBER" => CalcValue::Boolean(matches!(
+                scalar(self.arg(args, 0, pos)?),
+                CalcValue::Number(_) | CalcValue::DateTime(_)
+            )),
+            "ISTEXT" => CalcValue::Boolean(matches!(
+                scalar(self.arg(args, 0, pos)?),
+                CalcValue::Text(_)
+            )),
+            "NA" => return Err(ErrorKind::NA),
+
+            // math
+            "ABS" => CalcValue::Number(self.arg_number(args, 0, pos)?.abs()),
+            "INT" => CalcValue::Number(self.arg_number(args, 0, pos)?.floor()),
+            "SIGN" => CalcValue::Number(sign(self.arg_number(args, 0, pos)?)),
+            "PI" => CalcValue::Number(std::f64::consts::PI),
+            "SQRT" => {
+                let v = self.arg_number(args, 0, pos)?;
+                if v < 0.0 {
+                    return Err(ErrorKind::Num);
+                }
+                CalcValue::Number(v.sqrt())
+            }
+            "POWER" => {
+                let a = self.arg_number(args, 0, pos)?;
+                let b = self.arg_number(args, 1, pos)?;
+                checked(a.powf(b))?
+            }
+            "EXP" => checked(self.arg_number(args, 0, pos)?.exp())?,
+            "LN" => {
+                let v = self.arg_number(args, 0, pos)?;
+                if v <= 0.0 {
+                    return Err(ErrorKind::Num);
+                }
+                CalcValue::Number(v.ln())
+            }
+            "LOG10" => {
+                let v = self.arg_number(args, 0, pos)?;
+                if v <= 0.0 {
+                    return Err(ErrorKind::Num);
+                }
+                CalcValue::Number(v.log10())
+            }
+            "MOD" => {
+                let a = self.arg_number(args, 0, pos)?;
+                let b = self.arg_number(args, 1, pos)?;
+                if b == 0.0 {
+                    return Err(ErrorKind::Div0);
+                }
+                CalcValue::Number(a - b * (a / b).floor())
+            }
+            "ROUND" | "ROUNDUP" | "ROUNDDOWN" => {
+                let v = self.arg_number(args, 0, pos)?;
+                let digits = self.arg_number_or(args, 1, 0.0, pos)?.trunc();
+                let f = 10f64.powi(digits.clamp(-308.0, 308.0) as i32);
+                let s = v * f;
+                // more digits than the number has.
+                if !s.is_finite() || s.abs() >= 1e15 {
+                    return Ok(CalcValue::Number(v));
+                }
+                // 1.005 * 100 is 100.49999999999999, only 15 digits count.
+                let s = format!("{:.14e}", s).parse::<f64>().unwrap_or(s);
+                let s = match name {
+                    "ROUND" => s.round(),
+                    "ROUNDUP" => sign(s) * s.abs().ceil(),
+                    _ => s.trunc(),
+                };
+                checked(s / f)?
+            }
+
+            // text
+            "LEN" => CalcValue::Number(self.arg_text(args, 0, pos)?.chars().count() as f64),
+            "LEFT" | "RIGHT" => {
+                let t = self.arg_text(args, 0, pos)?;
+                let n = self.arg_number_or(args, 1, 1.0, pos)?;
+                if n < 0.0 {
+                    return Err(ErrorKind::Value);
+                }
+                let n = n as usize;
+                let len = t.chars().count();
+                CalcValue::Text(if name == "LEFT" {
+                    t.chars().take(n).collect()
+                } else {
+                    t.chars().skip(len.saturating_sub(n)).collect()
+                })
+            }
+            "MID" => {
+                let t = self.arg_text(args, 0, pos)?;
+                let start = self.arg_number(args, 1, pos)?;
+                let n = self.arg_number(args, 2, pos)?;
+                if start < 1.0 || n < 0.0 {
+                    return Err(ErrorKind::Value);
+                }
+                CalcValue::Text(
+                    t.chars()
+                        .skip(start as usize - 1)
+                        .take(n as usize)
+                        .collect(),
+                )
+            }
+            "UPPER" => CalcValue::Text(self.arg_text(args, 0, pos)?.to_uppercase()),
+            "LOWER" => CalcValue::Text(self.arg_text(args, 0, pos)?.to_lowercase()),
+            "TRIM" => CalcValue::Text(
+                self.arg_text(args, 0, pos)?
+                    .split(' ')
+                    .filter(|v| !v.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            "CONCATENATE" | "CONCAT" | "COM.MICROSOFT.CONCAT" => {
+                let mut buf = String::new();
+                for arg in args {
+                    match self.eval(arg, pos) {
+                        CalcValue::Matrix(m) => {
+                            for v in m.iter().flatten() {
+                                buf.push_str(&text(v)?);
+                            }
+                        }
+                        v => buf.push_str(&text(&v)?),
+                    }
+                }
+                CalcValue::Text(buf)
+            }
+            "VALUE" => CalcValue::Number(number(&self.arg(args, 0, pos)?)?),
+
+            // date and time
+            "DATE" => {
+                let y = self.arg_number(args, 0, pos)?.trunc();
+                let m = self.arg_number(args, 1, pos)?.trunc() - 1.0;
+                let d = self.arg_number(args, 2, pos)?.trunc() - 1.0;
+                // month and day may overflow.
+                let y = y + m.div_euclid(12.0);
+                let m = m.rem_euclid(12.0) as u32 + 1;
+                if y.is_nan() || y.abs() > i32::MAX as f64 {
+                    return Err(ErrorKind::Num);
+                }
+                let date = NaiveDate::from_ymd_opt(y as i32, m, 1)
+                    .and_then(|v| v.and_hms_opt(0, 0, 0))
+                    .ok_or(ErrorKind::Num)?;
+                let date = Duration::try_days(d as i64)
+                    .and_then(|v| date.checked_add_signed(v))
+                    .ok_or(ErrorKind::Num)?;
+                CalcValue::DateTime(date)
+            }
+            "TIME" => {
+                let h = self.arg_number(args, 0, pos)?;
+                let m = self.arg_number(args, 1, pos)?;
+                let s = self.arg_number(args, 2, pos)?;
+                CalcValue::Number((h * 3600.0 + m * 60.0 + s) / 86400.0)
+            }
+            "YEAR" => CalcValue::Number(self.arg_date(args, 0, pos)?.year() as f64),
+            "MONTH" => CalcValue::Number(self.arg_date(args, 0, pos)?.month() as f64),
+            "DAY" => CalcValue::Number(self.arg_date(args, 0, pos)?.day() as f64),
+            "HOUR" => CalcValue::Number(self.arg_date(args, 0, pos)?.hour() as f64),
+            "MINUTE" => CalcValue::Number(self.arg_date(args, 0, pos)?.minute() as f64),
+            "SECOND" => CalcValue::Number(self.arg_date(args, 0, pos)?.second() as f64),
+            "WEEKDAY" => {
+                let d = self.arg_date(args, 0, pos)?.weekday();
+                let ty = self.arg_number_or(args, 1, 1.0, pos)? as u32;
+                CalcValue::Number(match ty {
+                    1 => d.number_from_sunday(),
+                    2 => d.number_from_monday(),
+                    3 => d.num_days_from_monday(),
+                    _ => return Err(ErrorKind::Num),
+                } as f64)
+            }
+            "DAYS" => {
+                let end = self.arg_number(args, 0, pos)?;
+                let start = self.arg_number(args, 1, pos)?;
+                CalcValue::Number(end.trunc() - start.trunc())
+            }
+            "EDATE" => {
+                let date = self.arg_date(args, 0, pos)?;
+                let months = self.arg_number(args, 1, pos)? as i32;
+                let date = if months >= 0 {
+                    date.checked_add_months(Months::new(months as u32))
+                } else {
+                    date.checked_sub_months(Months::new(months.unsigned_abs()))
+                };
+                CalcValue::DateTime(date.ok_or(ErrorKind::Num)?)
+            }
+            "TODAY" => CalcValue::DateTime(self.now.date().and_hms_opt(0, 0, 0).expect("time")),
+            "NOW" => CalcValue::DateTime(self.now),
+
+            // lookup
+            "ROW" | "COLUMN" => match args.first() {
+                None => CalcValue::Number(if name == "ROW" {
+                    pos.row as f64 + 1.0
+                } else {
+                    pos.col as f64 + 1.0
+                }),
+                Some(Expr::CellRef(v)) => CalcValue::Number(if name == "ROW" {
+                    v.row() as f64 + 1.0
+                } else {
+                    v.col() as f64 + 1.0
+                }),
+                Some(Expr::CellRange(v)) => CalcValue::Number(if name == "ROW" {
+                    v.row() as f64 + 1.0
+                } else {
+                    v.col() as f64 + 1.0
+                }),
+                _ => return Err(ErrorKind::Value),
+            },
+            "ROWS" | "COLUMNS" => match self.arg(args, 0, pos)? {
+                CalcValue::Matrix(m) => CalcValue::Number(if name == "ROWS" {
+                    m.len() as f64
+                } else {
+                    m.first().map(|v| v.len()).unwrap_or(0) as f64
+                }),
+                CalcValue::Error(e) => return Err(e),
+                _ => CalcValue::Number(1.0),
+            },
+            "TRANSPOSE" => CalcValue::Matrix(transpose(matrix(self.arg(args, 0, pos)?)?)),
+            "MMULT" => {
+                let a = matrix(self.arg(args, 0, pos)?)?;
+                let b = matrix(self.arg(args, 1, pos)?)?;
+                let n = b.len();
+                if a.iter().any(|v| v.len() != n) {
+                    return Err(ErrorKind::Value);
+                }
+                let cols = b.first().map(|v| v.len()).unwrap_or(0);
+                let mut m = Vec::new();
+                for row in &a {
+                    let mut r = Vec::new();
+                    for c in 0..cols {
+                        let mut sum = 0.0;
+                        for (k, v) in row.iter().enumerate() {
+                            let w = b[k].get(c).ok_or(ErrorKind::Value)?;
+                            sum += number(v)? * number(w)?;
+                        }
+                        r.push(CalcValue::Number(sum));
+                    }
+                    m.push(r);
+                }
+                CalcValue::Matrix(m)
+            }
+            "VLOOKUP" | "HLOOKUP" => {
+                let value = scalar(self.arg(args, 0, pos)?);
+                if let CalcValue::Error(e) = value {
+                    return Err(e);
+                }
+                let mut m = matrix(self.arg(args, 1, pos)?)?;
+                if name == "HLOOKUP" {
+                    m = transpose(m);
+                }
+                let idx = self.arg_number(args, 2, pos)? as usize;
+                let sorted = self.arg_bool_or(args, 3, true, pos)?;
+                if idx < 1 {
+                    return Err(ErrorKind::Value);
+                }
+                let keys: Vec<&CalcValue> = m.iter().filter_map(|v| v.first()).collect();
+                let row = lookup(&value, &keys, if sorted { 1 } else { 0 })?;
+                m[row].get(idx - 1).cloned().ok_or(ErrorKind::Ref)?
+            }
+            "MATCH" => {
+                let value = scalar(self.arg(args, 0, pos)?);
+                if let CalcValue::Error(e) = value {
+                    return Err(e);
+                }
+                let m = matrix(self.arg(args, 1, pos)?)?;
+                let ty = self.arg_number_or(args, 2, 1.0, pos)?;
+                let keys: Vec<&CalcValue> = if m.len() == 1 {
+                    m[0].iter().collect()
+                } else if m.iter().all(|v| v.len() == 1) {
+                    m.iter().map(|v| &v[0]).collect()
+                } else {
+                    return Err(ErrorKind::NA);
+                };
+                let idx = lookup(&value, &keys, sign(ty) as i32)?;
+                CalcValue::Number(idx as f64 + 1.0)
+            }
+            "INDEX" => {
+                let m = matrix(self.arg(args, 0, pos)?)?;
+                let row = self.arg_number_or(args, 1, 0.0, pos)? as usize;
+                let col = self.arg_number_or(args, 2, 0.0, pos)? as usize;
+                // a single row or column only needs one index.
+                let (row, col) = if m.len() == 1 && col == 0 {
+                    (1, row)
+                } else {
+                    (row, col)
+                };
+                match (row, col) {
+                    (0, 0) => CalcValue::Matrix(m),
+                    (0, c) => CalcValue::Matrix(
+                        m.iter()
+                            .map(|v| v.get(c - 1).cloned().ok_or(ErrorKind::Ref).map(|v| vec![v]))
+                            .collect::<CalcResult<_>>()?,
+                    ),
+                    (r, 0) => {
+                        CalcValue::Matrix(vec![m.get(r - 1).cloned().ok_or(ErrorKind::Ref)?])
+                    }
+                    (r, c) => m
+                        .get(r - 1)
+                        .and_then(|v| v.get(c - 1))
+                        .cloned()
+                        .ok_or(ErrorKind::Ref)?,
+                }
+            }
+
+            _ => return Err(ErrorKind::Name),
+        };
+        Ok(v)
+    }
+}
+
+/// Range consisting of only one cell.
+fn cell_range(v: &CellRef) -> CellRange {
+    let mut range = CellRange::local(v.row(), v.col(), v.row(), v.col());
+    if let Some(iri) = v.iri() {
+        range.set_iri(iri.as_str());
+    }
+    if let Some(table) = v.table() {
+        range.set_table(table.as_str());
+    }
+    range
+}
+
+fn minmax(a: u32, b: u32) -> (u32, u32) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+fn sign(v: f64) -> f64 {
+    if v > 0.0 {
+        1.0
+    } else if v < 0.0 {
+        -1.0
+    } else {
+        0.0
+    }
+}
+
+fn checked(v: f64) -> CalcResult<CalcValue> {
+    if v.is_finite() {
+        Ok(CalcValue::Number(v))
+    } else {
+        Err(ErrorKind::Num)
+    }
+}
+
+fn arith(
+    l: &CalcValue,
+    r: &CalcValue,
+    f: impl Fn(f64, f64) -> CalcResult<f64>,
+) -> CalcResult<CalcValue> {
+    checked(f(number(l)?, number(r)?)?)
+}
+
+fn date_arith(l: &CalcValue, r: &CalcValue, f: impl Fn(f64, f64) -> f64) -> CalcResult<CalcValue> {
+    Ok(CalcValue::DateTime(from_serial(f(number(l)?, number(r)?))?))
+}
+
+/// Converts a cell value.
+pub(crate) fn from_value(value: &Value) -> CalcValue {
+    match value {
+        Value::Empty => CalcValue::Empty,
+        Value::Boolean(v) => CalcValue::Boolean(*v),
+        Value::Number(v) => CalcValue::Number(*v),
+        Value::Percentage(v) => CalcValue::Number(*v),
+        Value::Currency(v, _) => CalcValue::Number(*v),
+        Value::Decimal(v) | Value::DecimalCurrency(v, _) => {
+            CalcValue::Number(v.to_f64().unwrap_or_default())
+        }
+        Value::Text(v) => CalcValue::Text(v.clone()),
+        Value::TextXml(v) => {
+            let mut buf = String::new();
+            for (i, t) in v.iter().enumerate() {
+                if i > 0 {
+                    buf.push('\n');
+                }
+                t.extract_text(&mut buf);
+            }
+            CalcValue::Text(buf)
+        }
+        Value::DateTime(v) => CalcValue::DateTime(*v),
+        Value::Date(v) => CalcValue::DateTime(v.and_time(NaiveTime::MIN)),
+        Value::TimeOfDay(v) => {
+            CalcValue::Number((*v - NaiveTime::MIN).num_milliseconds() as f64 / 86_400_000.0)
+        }
+        Value::TimeDuration(v) => CalcValue::Number(v.num_milliseconds() as f64 / 86_400_000.0),
+        Value::Error(e) => CalcValue::Error(*e),
+    }
+}
+
+/// Converts the result of a calculation. The type of the old value
+/// is kept for percentages and currencies.
+pub(crate) fn to_value(value: CalcValue, old: &Value) -> Value {
+    match scalar(value) {
+        CalcValue::Empty => to_value(CalcValue::Number(0.0), old),
+        CalcValue::Number(v) => match old {
+            Value::Percentage(_) => Value::Percentage(v),
+            Value::Currency(_, c) => Value::Currency(v, c.clone()),
+            Value::DecimalCurrency(_, c) => Value::Currency(v, c.clone()),
+            _ => Value::Number(v),
+        },
+        CalcValue::DateTime(v) => match old {
+            Value::Date(_) => Value::Date(v.date()),
+            _ => Value::DateTime(v),
+        },
+        CalcValue::Text(v) => Value::Text(v),
+        CalcValue::Boolean(v) => Value::Boolean(v),
+        CalcValue::Error(e) => Value::Error(e),
+        CalcValue::Matrix(_) => unreachable!(),
+    }
+}
+
+/// Reduces a matrix to its first value.
+fn scalar(value: CalcValue) -> CalcValue {
+    match value {
+        CalcValue::Matrix(m) => match m.into_iter().next().and_then(|v| v.into_iter().next()) {
+            Some(v) => scalar(v),
+            None => CalcValue::Error(ErrorKind::Value),
+        },
+        v => v,
+    }
+}
+
+/// Part of the result of an array formula. A single row or column
+/// is repeated, missing values are #N/A.
+pub(crate) fn spill(value: &CalcValue, row: u32, col: u32) -> CalcValue {
+    match value {
+        CalcValue::Matrix(m) => {
+            let row = if m.len() == 1 { 0 } else { row as usize };
+            match m.get(row) {
+                Some(v) => {
+                    let col = if v.len() == 1 { 0 } else { col as usize };
+                    v.get(col)
+                        .cloned()
+                        .unwrap_or(CalcValue::Error(ErrorKind::NA))
+                }
+                None => CalcValue::Error(ErrorKind::NA),
+            }
+        }
+        v => v.clone(),
+    }
+}
+
+fn matrix(value: CalcValue) -> CalcResult<Vec<Vec<CalcValue>>> {
+    match value {
+        CalcValue::Matrix(m) => Ok(m),
+        CalcValue::Error(e) => Err(e),
+        v => Ok(vec![vec![v]]),
+    }
+}
+
+fn transpose(m: Vec<Vec<CalcValue>>) -> Vec<Vec<CalcValue>> {
+    let cols = m.iter().map(|v| v.len()).max().unwrap_or(0);
+    (0..cols)
+        .map(|c| {
+            m.iter()
+                .map(|v| v.get(c).cloned().unwrap_or(CalcValue::Empty))
+                .collect()
+        })
+        .collect()
+}
+
+fn base_date() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(1899, 12, 30)
+        .and_then(|v| v.and_hms_opt(0, 0, 0))
+        .expect("base date")
+}
+
+fn to_serial(v: NaiveDateTime) -> f64 {
+    (v - base_date()).num_milliseconds() as f64 / 86_400_000.0
+}
+
+fn from_serial(v: f64) -> CalcResult<NaiveDateTime> {
+    if !v.is_finite() {
+        return Err(ErrorKind::Num);
+    }
+    Duration::try_milliseconds((v * 86_400_000.0).round() as i64)
+        .and_then(|v| base_date().checked_add_signed(v))
+        .ok_or(ErrorKind::Num)
+}
+
+fn number(value: &CalcValue) -> CalcResult<f64> {
+    match value {
+        CalcValue::Empty => Ok(0.0),
+        CalcValue::Number(v) => Ok(*v),
+        CalcValue::DateTime(v) => Ok(to_serial(*v)),
+        CalcValue::Boolean(v) => Ok(if *v { 1.0 } else { 0.0 }),
+        CalcValue::Text(v) => v.trim().parse::<f64>().map_err(|_| ErrorKind::Value),
+        CalcValue::Error(e) => Err(*e),
+        CalcValue::Matrix(_) => number(&scalar(value.clone())),
+    }
+}
+
+fn text(value: &CalcValue) -> CalcResult<String> {
+    match value {
+        CalcValue::Empty => Ok(String::new()),
+        CalcValue::Number(v) => Ok(number_text(*v)),
+        CalcValue::DateTime(v) => Ok(number_text(to_serial(*v))),
+        CalcValue::Boolean(v) => Ok(if *v { "TRUE" } else { "FALSE" }.to_string()),
+        CalcValue::Text(v) => Ok(v.clone()),
+        CalcValue::Error(e) => Err(*e),
+        CalcValue::Matrix(_) => text(&scalar(value.clone())),
+    }
+}
+
+fn number_text(v: f64) -> String {
+    if v.fract() == 0.0 && v.abs() < 1e15 {
+        format!("{}", v as i64)
+    } else {
+        format!("{}", v)
+    }
+}
+
+fn boolean(value: &CalcValue) -> CalcResult<bool> {
+    match value {
+        CalcValue::Empty => Ok(false),
+        CalcValue::Number(v) => Ok(*v != 0.0),
+        CalcValue::DateTime(v) => Ok(to_serial(*v) != 0.0),
+        CalcValue::Boolean(v) => Ok(*v),
+        CalcValue::Text(v) if v.eq_ignore_ascii_case("TRUE") => Ok(true),
+        CalcValue::Text(v) if v.eq_ignore_ascii_case("FALSE") => Ok(false),
+        CalcValue::Text(_) => Err(ErrorKind::Value),
+        CalcValue::Error(e) => Err(*e),
+        CalcValue::Matrix(_) => boolean(&scalar(value.clone())),
+    }
+}
+
+/// Orders numbers before text before booleans. Text is compared
+/// case-insensitive. Empty is 0, "" or FALSE depending on the other
+/// value.
+fn compare(l: &CalcValue, r: &CalcValue) -> Ordering {
+    fn rank(v: &CalcValue) -> u8 {
+        match v {
+            CalcValue::Number(_) | CalcValue::DateTime(_) => 0,
+            CalcValue::Text(_) => 1,
+            CalcValue::Boolean(_) => 2,
+            _ => 3,
+        }
+    }
+    let l = match (l, r) {
+        (CalcValue::Empty, CalcValue::Text(_)) => CalcValue::Text(String::new()),
+        (CalcValue::Empty, CalcValue::Boolean(_)) => CalcValue::Boolean(false),
+        (CalcValue::Empty, _) => CalcValue::Number(0.0),
+        (l, _) => l.clone(),
+    };
+    let r = match (&l, r) {
+        (CalcValue::Text(_), CalcValue::Empty) => CalcValue::Text(String::new()),
+        (CalcValue::Boolean(_), CalcValue::Empty) => CalcValue::Boolean(false),
+        (_, CalcValue::Empty) => CalcValue::Number(0.0),
+        (_, r) => r.clone(),
+    };
+
+    match (&l, &r) {
+        (CalcValue::Text(a), CalcValue::Text(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+        (CalcValue::Boolean(a), CalcValue::Boolean(b)) => a.cmp(b),
+        _ if rank(&l) == 0 && rank(&r) == 0 => {
+            let a = number(&l).unwrap_or(0.0);
+            let b = number(&r).unwrap_or(0.0);
+            a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+        }
+        _ => rank(&l).cmp(&rank(&r)),
+    }
+}
+
+/// Finds the index of the value. ty 0 is an exact match, ty 1 the
+/// largest value less or equal in ascending keys and ty -1 the
+/// smallest value greater or equal in descending keys.
+fn lookup(value: &CalcValue, keys: &[&CalcValue], ty: i32) -> CalcResult<usize> {
+    let same_type = |v: &CalcValue| {
+        matches!(
+            (value, v),
+            (
+                CalcValue::Number(_) | CalcValue::DateTime(_),
+                CalcValue::Number(_) | CalcValue::DateTime(_)
+            ) | (CalcValue::Text(_), CalcValue::Text(_))
+                | (CalcValue::Boolean(_), CalcValue::Boolean(_))
+        )
+    };
+
+    let mut found = None;
+    for (i, key) in keys.iter().enumerate() {
+        if !same_type(key) {
+            continue;
+        }
+        let ord = compare(key, value);
+        match ty {
+            0 => {
+                if ord == Ordering::Equal {
+                    return Ok(i);
+                }
+            }
+            t if t > 0 => {
+                if ord == Ordering::Greater {
+                    break;
+                }
+                found = Some(i);
+            }
+            _ => {
+                if ord == Ordering::Less {
+                    break;
+                }
+                found = Some(i);
+            }
+        }
+    }
+    found.ok_or(ErrorKind::NA)
+}
@@ -0,0 +1,675 @@
+//!
+//! Translates formulas between the Excel A1 syntax and OpenFormula.
+//!
+//! Only the syntax is translated: references and sheet names, array
+//! constants, separators, the intersection and union operators,
+//! TRUE/FALSE and the functions that are known under a different name.
+//! Everything else is copied as is.
+//!
+
+use crate::formula::rewrite::AnyRef;
+use crate::formula::ErrorKind;
+use crate::refs::{
+    fmt_excel_cell_range, fmt_excel_cell_ref, fmt_excel_col_range, fmt_excel_row_range,
+    parse_cellrange, parse_cellref, parse_colrange, parse_rowrange,
+};
+use crate::OdsError;
+
+/// Functions with different names. Excel name and OpenFormula name.
+const FUNCTIONS: [(&str, &str); 24] = [
+    ("CHIDIST", "LEGACY.CHIDIST"),
+    ("CHIINV", "LEGACY.CHIINV"),
+    ("CHITEST", "LEGACY.CHITEST"),
+    ("FDIST", "LEGACY.FDIST"),
+    ("FINV", "LEGACY.FINV"),
+    ("NORMSDIST", "LEGACY.NORMSDIST"),
+    ("NORMSINV", "LEGACY.NORMSINV"),
+    ("TDIST", "LEGACY.TDIST"),
+    ("CEILING.MATH", "COM.MICROSOFT.CEILING.MATH"),
+    ("CONCAT", "COM.MICROSOFT.CONCAT"),
+    ("FILTER", "COM.MICROSOFT.FILTER"),
+    ("FLOOR.MATH", "COM.MICROSOFT.FLOOR.MATH"),
+    ("IFS", "COM.MICROSOFT.IFS"),
+    ("LET", "COM.MICROSOFT.LET"),
+    ("MAXIFS", "COM.MICROSOFT.MAXIFS"),
+    ("MINIFS", "COM.MICROSOFT.MINIFS"),
+    ("SEQUENCE", "COM.MICROSOFT.SEQUENCE"),
+    ("SORT", "COM.MICROSOFT.SORT"),
+    ("SORTBY", "COM.MICROSOFT.SORTBY"),
+    ("SWITCH", "COM.MICROSOFT.SWITCH"),
+    ("TEXTJOIN", "COM.MICROSOFT.TEXTJOIN"),
+    ("UNIQUE", "COM.MICROSOFT.UNIQUE"),
+    ("XLOOKUP", "COM.MICROSOFT.XLOOKUP"),
+    ("XMATCH", "COM.MICROSOFT.XMATCH"),
+];
+
+/// OpenFormula name for an Excel function. The "_xlfn." prefix of
+/// newer functions is removed.
+fn of_function(name: &str) -> String {
+    let name = name.to_uppercase();
+    let name = name
+        .strip_prefix("_XLFN.")
+        .or_else(|| name.strip_prefix("_XLWS."))
+        .unwrap_or(&name);
+    FUNCTIONS
+        .iter()
+        .find(|(xl, _)| *xl == name)
+        .map(|(_, of)| of.to_string())
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// Excel name for an OpenFormula function.
+fn excel_function(name: &str) -> &str {
+    FUNCTIONS
+        .iter()
+        .find(|(_, of)| of.eq_ignore_ascii_case(name))
+        .map(|(xl, _)| *xl)
+        .unwrap_or(name)
+}
+
+/// Length of the string literal at the start of the text, including
+/// the quotes. Doubled quotes are part of the string.
+fn string_len(text: &str) -> Option<usize> {
+    let mut quotes = 0;
+    for (i, c) in text.char_indices() {
+        if c == '"' {
+            quotes += 1;
+        } else if quotes > 1 && quotes % 2 == 0 {
+            return Some(i);
+        }
+    }
+    if quotes > 1 && quotes % 2 == 0 {
+        Some(text.len())
+    } else {
+        None
+    }
+}
+
+/// Part of an A1 reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    /// "$A$1"
+    Cell,
+    /// "$A"
+    Col,
+    /// "$1"
+    Row,
+}
+
+fn part(text: &str) -> Option<Part> {
+    let text = text.strip_prefix('$').unwrap_or(text);
+    let letters = text.chars().take_while(|c| c.is_ascii_alphabetic()).count();
+    let digits = &text[letters..];
+    let digits = if letters > 0 {
+        digits.strip_prefix('$').unwrap_or(digits)
+    } else {
+        digits
+    };
+    let digits_ok = !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit());
+
+    if letters > 3 {
+        None
+    } else if letters == 0 {
+        digits_ok.then_some(Part::Row)
+    } else if text.len() == letters {
+        Some(Part::Col)
+    } else {
+        digits_ok.then_some(Part::Cell)
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.' || c == '$' || c == '\\'
+}
+
+/// Translates an Excel formula to OpenFormula.
+pub(crate) fn from_excel(formula: &str) -> Result<String, OdsError> {
+    let text = formula.trim();
+    let text = text.strip_prefix('=').unwrap_or(text);
+
+    let mut t = FromExcel {
+        formula,
+        text,
+        pos: 0,
+        buf: "of:=".to_string(),
+        array: 0,
+        ws: String::new(),
+        after_ref: false,
+        parens: Vec::new(),
+        call: false,
+    };
+    t.translate()?;
+    Ok(t.buf)
+}
+
+struct FromExcel<'a> {
+    formula: &'a str,
+    text: &'a str,
+    pos: usize,
+    buf: String,
+    array: u32,
+    /// Whitespace is kept back until we know if it is an intersection.
+    ws: String,
+    after_ref: bool,
+    /// Open parentheses, true for function calls.
+    parens: Vec<bool>,
+    /// The last token was a function name.
+    call: bool,
+}
+
+impl<'a> FromExcel<'a> {
+    fn translate(&mut self) -> Result<(), OdsError> {
+        while let Some(c) = self.peek() {
+            match c {
+                '"' => {
+                    let len = match string_len(&self.text[self.pos..]) {
+                        Some(v) => v,
+                        None => return Err(self.error("unterminated string")),
+                    };
+                    let s = &self.text[self.pos..self.pos + len];
+                    self.pos += len;
+                    self.token(s);
+                }
+                '{' => {
+                    self.pos += 1;
+                    self.array += 1;
+                    self.token("{");
+                }
+                '}' => {
+                    self.pos += 1;
+                    self.array = self.array.saturating_sub(1);
+                    self.token("}");
+                }
+                '(' => {
+                    self.pos += 1;
+                    self.parens.push(self.call);
+                    self.token("(");
+                }
+                ')' => {
+                    self.pos += 1;
+                    self.parens.pop();
+                    self.token(")");
+                }
+                // a comma in parentheses that are no function call is
+                // the union.
+                ',' if self.array == 0 && self.parens.last() == Some(&false) => {
+                    self.pos += 1;
+                    self.token("~");
+                }
+                // Excel doesn't allow a union without parentheses either.
+                ',' if self.array == 0 && self.parens.is_empty() => {
+                    return Err(self.error("union without parentheses"));
+                }
+                ',' => {
+                    self.pos += 1;
+                    self.token(";");
+                }
+                '@' => return Err(self.error("implicit intersection is not supported")),
+                ';' if self.array > 0 => {
+                    self.pos += 1;
+                    self.token("|");
+                }
+                '#' => self.error_value()?,
+                '\'' => self.quoted_prefix()?,
+                '[' => self.external_prefix()?,
+                c if c.is_whitespace() => {
+                    self.pos += c.len_utf8();
+                    self.ws.push(c);
+                }
+                c if c.is_ascii_digit() || c == '.' => self.number_or_rows()?,
+                c if is_word(c) => self.word()?,
+                c => {
+                    self.pos += c.len_utf8();
+                    self.token(c.encode_utf8(&mut [0u8; 4]));
+                }
+            }
+        }
+        self.buf.push_str(&self.ws);
+        Ok(())
+    }
+
+    fn error(&self, msg: &'static str) -> OdsError {
+        OdsError::Parse(msg, Some(self.formula.to_string()))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let rest = &self.text[self.pos..];
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    /// Anything but a reference.
+    fn token(&mut self, s: &str) {
+        self.buf.push_str(&self.ws);
+        self.ws.clear();
+        self.buf.push_str(s);
+        self.after_ref = false;
+        self.call = false;
+    }
+
+    /// Whitespace between two references is the intersection.
+    fn reference(&mut self, s: &str) {
+        if self.after_ref && !self.ws.is_empty() {
+            self.buf.push('!');
+        } else {
+            self.buf.push_str(&self.ws);
+        }
+        self.ws.clear();
+        self.buf.push_str(s);
+        self.after_ref = true;
+    }
+
+    fn error_value(&mut self) -> Result<(), OdsError> {
+        let rest = &self.text[self.pos..];
+        let kind = ErrorKind::all().iter().find(|v| {
+            rest.get(..v.as_str().len())
+                .map(|s| s.eq_ignore_ascii_case(v.as_str()))
+                .unwrap_or(false)
+        });
+        match kind {
+            Some(kind) => {
+                self.pos += kind.as_str().len();
+                self.token(kind.as_str());
+                Ok(())
+            }
+            None => Err(self.error("unknown error value")),
+        }
+    }
+
+    /// 'Sheet 1'!A1 or '[file.xlsx]Sheet 1'!A1 or 'Sheet 1:Sheet 3'!A1
+    fn quoted_prefix(&mut self) -> Result<(), OdsError> {
+        let rest = &self.text[self.pos + 1..];
+        let mut name = String::new();
+        let mut end = None;
+        let mut chars = rest.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if c == '\'' {
+                if let Some((_, '\'')) = chars.peek() {
+                    chars.next();
+                } else {
+                    end = Some(i);
+                    break;
+                }
+            }
+            name.push(c);
+        }
+        let end = match end {
+            Some(v) => self.pos + 1 + v + 1,
+            None => return Err(self.error("unterminated sheet name")),
+        };
+        if !self.text[end..].starts_with('!') {
+            return Err(self.error("expected ! after the sheet name"));
+        }
+        self.pos = end + 1;
+
+        let (iri, name) = match name.strip_prefix('[').and_then(|v| v.split_once(']')) {
+            Some((iri, name)) => (Some(iri.to_string()), name.to_string()),
+            None => (None, name),
+        };
+        match name.split_once(':') {
+            Some((table, to_table)) => self.prefixed(iri, Some(table), Some(to_table)),
+            None => self.prefixed(iri, Some(name.as_str()), None),
+        }
+    }
+
+    /// [file.xlsx]Sheet1!A1
+    fn external_prefix(&mut self) -> Result<(), OdsError> {
+        let rest = &self.text[self.pos + 1..];
+        let iri = match rest.find(']') {
+            Some(v) => &rest[..v],
+            None => return Err(self.error("unterminated external reference")),
+        };
+        self.pos += iri.len() + 2;
+        let table = self.take_while(|c| is_word(c) && c != '$');
+        if table.is_empty() || self.peek() != Some('!') {
+            return Err(self.error("structured references are not supported"));
+        }
+        self.pos += 1;
+        self.prefixed(Some(iri.to_string()), Some(table), None)
+    }
+
+    /// Function names, sheet names, references, TRUE/FALSE and names.
+    fn word(&mut self) -> Result<(), OdsError> {
+        let word = self.take_while(is_word);
+        match self.peek() {
+            Some('(') => {
+                let name = of_function(word);
+                self.token(&name);
+                self.call = true;
+            }
+            Some('!') => {
+                self.pos += 1;
+                self.prefixed(None, Some(word), None)?;
+            }
+            _ => {
+                // 3D reference Sheet1:Sheet3!A1
+                if self.peek() == Some(':') {
+                    let save = self.pos;
+                    self.pos += 1;
+                    let to_table = self.take_while(|c| is_word(c) && c != '$');
+                    if !to_table.is_empty() && self.peek() == Some('!') {
+                        self.pos += 1;
+                        return self.prefixed(None, Some(word), Some(to_table));
+                    }
+                    self.pos = save;
+                }
+
+                if self.range(None, None, None, word)? {
+                    // done
+                } else if word.eq_ignore_ascii_case("TRUE") {
+                    self.token("TRUE()");
+                } else if word.eq_ignore_ascii_case("FALSE") {
+                    self.token("FALSE()");
+                } else {
+                    self.token(word);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The reference after a sheet prefix.
+    fn prefixed(
+        &mut self,
+        iri: Option<String>,
+        table: Option<&str>,
+        to_table: Option<&str>,
+    ) -> Result<(), OdsError> {
+        if self.peek() == Some('#') {
+            return self.error_value();
+        }
+        let first = self.take_while(|c| c.is_ascii_alphanumeric() || c == '$');
+        if self.range(iri, table, to_table, first)? {
+            Ok(())
+        } else {
+            Err(self.error("invalid reference"))
+        }
+    }
+
+    /// Row ranges 1:3 or numbers.
+    fn number_or_rows(&mut self) -> Result<(), OdsError> {
+        let start = self.pos;
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        if self.peek() == Some(':') && self.range(None, None, None, digits)? {
+            return Ok(());
+        }
+        self.pos = start;
+
+        let mut len = self.take_while(|c| c.is_ascii_digit()).len();
+        if self.peek() == Some('.') {
+            self.pos += 1;
+            len += 1 + self.take_while(|c| c.is_ascii_digit()).len();
+        }
+        let rest = &self.text[self.pos..];
+        if rest.starts_with(['e', 'E']) {
+            let exp = rest[1..].strip_prefix(['+', '-']).unwrap_or(&rest[1..]);
+            let exp_digits = exp.chars().take_while(|c| c.is_ascii_digit()).count();
+            if exp_digits > 0 {
+                let exp_len = rest.len() - exp.len() + exp_digits;
+                self.pos += exp_len;
+                len += exp_len;
+            }
+        }
+        let number = &self.text[start..start + len];
+        self.token(number);
+        Ok(())
+    }
+
+    /// Tries to read a reference starting with the given part.
+    /// Leaves the position after the first part if it isn't one.
+    fn range(
+        &mut self,
+        iri: Option<String>,
+        table: Option<&str>,
+        to_table: Option<&str>,
+        first: &str,
+    ) -> Result<bool, OdsError> {
+        let kind = match part(first) {
+            Some(v) => v,
+            None => return Ok(false),
+        };
+
+        let save = self.pos;
+        let mut second = None;
+        if self.peek() == Some(':') {
+            self.pos += 1;
+            let text = self.take_while(|c| c.is_ascii_alphanumeric() || c == '$');
+            match part(text) {
+                Some(v) if v == kind => second = Some(text),
+                // a name can look like a column.
+                Some(Part::Col) if !text.contains('$') => self.pos = save,
+                Some(_) => return Err(self.error("mixed reference range")),
+                None => self.pos = save,
+            }
+        }
+
+        let first = first.to_uppercase();
+        let r = match (kind, second) {
+            (Part::Cell, None) if to_table.is_none() => {
+                let mut r = parse_cellref(&format!(".{}", first))?;
+                if let Some(iri) = iri {
+                    r.set_iri(iri);
+                }
+                if let Some(table) = table {
+                    r.set_table(table);
+                }
+                AnyRef::Cell(r)
+            }
+            (Part::Cell, second) => {
+                let second = second
+                    .map(|v| v.to_uppercase())
+                    .unwrap_or_else(|| first.clone());
+                let mut r = parse_cellrange(&format!(".{}:.{}", first, second))?;
+                if let Some(iri) = iri {
+                    r.set_iri(iri);
+                }
+                if let Some(table) = table {
+                    r.set_table(table);
+                }
+                if let Some(to_table) = to_table {
+                    r.set_to_table(to_table);
+                }
+                AnyRef::Range(r)
+            }
+            (Part::Col, Some(second)) => {
+                let mut r = parse_colrange(&format!(".{}:.{}", first, second.to_uppercase()))?;
+                if let Some(iri) = iri {
+                    r.set_iri(iri);
+                }
+                if let Some(table) = table {
+                    r.set_table(table);
+                }
+                if let Some(to_table) = to_table {
+                    r.set_to_table(to_table);
+                }
+                AnyRef::Col(r)
+            }
+            (Part::Row, Some(second)) => {
+                let mut r = parse_rowrange(&format!(".{}:.{}", first, second))?;
+                if let Some(iri) = iri {
+                    r.set_iri(iri);
+                }
+                if let Some(table) = table {
+                    r.set_table(table);
+                }
+                if let Some(to_table) = to_table {
+                    r.set_to_table(to_table);
+                }
+                AnyRef::Row(r)
+            }
+            (Part::Col, None) | (Part::Row, None) => return Ok(false),
+        };
+        self.reference(&r.to_formula());
+        Ok(true)
+    }
+}
+
+/// Translates an OpenFormula formula to Excel.
+pub(crate) fn to_excel(formula: &str) -> Result<String, OdsError> {
+    let text = formula.trim();
+    // namespace prefix
+    let text = match text.split_once(':') {
+        Some((ns, rest))
+            if rest.starts_with('=') && ns.chars().all(|c| c.is_ascii_alphabetic()) =>
+        {
+            rest
+        }
+        _ => text,
+    };
+    let text = text.strip_prefix('=').unwrap_or(text);
+    let error = |msg: &'static str| OdsError::Parse(msg, Some(formula.to_string()));
+
+    let mut buf = "=".to_string();
+    let mut array = 0u32;
+    let mut pos = 0;
+    // Excel needs parentheses around a union. The operand before a ~
+    // starts at `start`. `unions` has the nesting depth of each open
+    // union and whether a parenthesis was added for it.
+    let mut start = buf.len();
+    let mut ref_op = false;
+    let mut call = false;
+    // operand start, position and function call for each open parenthesis.
+    let mut parens: Vec<(usize, usize, bool)> = Vec::new();
+    let mut unions: Vec<(usize, bool)> = Vec::new();
+    while let Some(c) = text[pos..].chars().next() {
+        if let Some(&(depth, added)) = unions.last() {
+            if depth == parens.len() && ";)|}+-*/&^=<>%".contains(c) {
+                if added {
+                    buf.push(')');
+                }
+                unions.pop();
+            }
+        }
+        let operand = matches!(c, '[' | '(' | '#') || c.is_alphabetic() || c == '_';
+        let call_paren = c == '(' && call;
+        if operand && !ref_op && !call_paren {
+            start = buf.len();
+        }
+        if !c.is_whitespace() {
+            ref_op = matches!(c, ':' | '!' | '~');
+            call = false;
+        }
+        match c {
+            '"' => {
+                let len = match string_len(&text[pos..]) {
+                    Some(v) => v,
+                    None => return Err(error("unterminated string")),
+                };
+                buf.push_str(&text[pos..pos + len]);
+                pos += len;
+            }
+            '[' => {
+                // quoted sheet names can contain a bracket.
+                let mut quoted = false;
+                let mut end = None;
+                for (i, c) in text[pos..].char_indices().skip(1) {
+                    match c {
+                        '\'' => quoted = !quoted,
+                        ']' if !quoted => {
+                            end = Some(pos + i);
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                let end = match end {
+                    Some(v) => v,
+                    None => return Err(error("unterminated reference")),
+                };
+                let r = text[pos + 1..end].trim();
+                if r.ends_with("#REF!") {
+                    buf.push_str("#REF!");
+                } else {
+                    let _ = match AnyRef::parse(r) {
+                        Some(AnyRef::Cell(v)) => fmt_excel_cell_ref(&mut buf, &v),
+                        Some(AnyRef::Range(v)) => fmt_excel_cell_range(&mut buf, &v),
+                        Some(AnyRef::Col(v)) => fmt_excel_col_range(&mut buf, &v),
+                        Some(AnyRef::Row(v)) => fmt_excel_row_range(&mut buf, &v),
+                        None => return Err(error("invalid reference")),
+                    };
+                }
+                pos = end + 1;
+            }
+            '{' => {
+                array += 1;
+                buf.push('{');
+                pos += 1;
+            }
+            '}' => {
+                array = array.saturating_sub(1);
+                buf.push('}');
+                pos += 1;
+            }
+            '(' => {
+                parens.push((start, buf.len(), call_paren));
+                buf.push('(');
+                pos += 1;
+            }
+            ')' => {
+                start = parens.pop().map(|v| v.0).unwrap_or(start);
+                buf.push(')');
+                pos += 1;
+            }
+            '~' => {
+                if unions.last().map(|v| v.0) != Some(parens.len()) {
+                    // already in parentheses.
+                    let grouped =
+                        matches!(parens.last(), Some((_, open, false)) if open + 1 == start);
+                    if !grouped {
+                        buf.insert(start, '(');
+                    }
+                    unions.push((parens.len(), !grouped));
+                }
+                buf.push(',');
+                pos += 1;
+            }
+            ';' => {
+                buf.push(',');
+                pos += 1;
+            }
+            '|' if array > 0 => {
+                buf.push(';');
+                pos += 1;
+            }
+            '!' => {
+                buf.push(' ');
+                pos += 1;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let rest = &text[pos..];
+                let len = rest
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+                    .unwrap_or(rest.len());
+                let word = &rest[..len];
+                pos += len;
+                if text[pos..].starts_with('(') {
+                    let args = text[pos + 1..].trim_start();
+                    if (word.eq_ignore_ascii_case("TRUE") || word.eq_ignore_ascii_case("FALSE"))
+                        && args.starts_with(')')
+                    {
+                        buf.push_str(&word.to_uppercase());
+                        pos = text.len() - args.len() + 1;
+                    } else {
+                        buf.push_str(excel_function(word));
+                        call = true;
+                    }
+                } else {
+                    buf.push_str(word);
+                }
+            }
+            c => {
+                buf.push(c);
+                pos += c.len_utf8();
+            }
+        }
+    }
+    if let Some((_, true)) = unions.last() {
+        buf.push(')');
+    }
+    Ok(buf)
+}
@@ -0,0 +1,401 @@
+//!
+//! Recursive descent parser for OpenFormula expressions.
+//! References are delegated to the refs parser.
+//!
+
+use crate::formula::{ErrorKind, Expr, InfixOp, PostfixOp, PrefixOp};
+use crate::refs::{parse_cellrange, parse_cellref, parse_colrange, parse_rowrange};
+use crate::OdsError;
+
+/// Maximum nesting of parentheses, function calls, arrays and prefix
+/// operators.
+const MAX_DEPTH: u32 = 64;
+
+/// Parses a complete formula.
+pub(crate) fn parse_formula(formula: &str) -> Result<Expr, OdsError> {
+    let mut s = formula.trim_start();
+    // namespace prefix
+    if let Some((ns, rest)) = s.split_once(':') {
+        if !ns.is_empty() && ns.chars().all(|c| c.is_ascii_lowercase()) && rest.starts_with('=') {
+            s = rest;
+        }
+    }
+    let s = s.strip_prefix('=').unwrap_or(s);
+
+    let mut p = Parser {
+        buf: s,
+        pos: 0,
+        depth: 0,
+    };
+    let expr = p.expr()?;
+    p.space();
+    if p.pos < p.buf.len() {
+        return Err(p.error("unexpected input"));
+    }
+    Ok(expr)
+}
+
+struct Parser<'a> {
+    buf: &'a str,
+    pos: usize,
+    depth: u32,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, msg: &'static str) -> OdsError {
+        OdsError::Parse(msg, Some(self.buf[self.pos..].to_string()))
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.buf[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn space(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Skips whitespace and consumes the token if it's next.
+    fn eat(&mut self, token: &str) -> bool {
+        self.space();
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str, msg: &'static str) -> Result<(), OdsError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(msg))
+        }
+    }
+
+    /// One level deeper. Deeply nested input would overflow the stack.
+    fn enter(&mut self) -> Result<(), OdsError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            Err(self.error("formula is nested too deeply"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, OdsError> {
+        self.enter()?;
+        let expr = self.comparison()?;
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, OdsError> {
+        let mut left = self.concat()?;
+        loop {
+            // longest match first.
+            let op = if self.eat("<>") {
+                InfixOp::Ne
+            } else if self.eat("<=") {
+                InfixOp::Le
+            } else if self.eat(">=") {
+                InfixOp::Ge
+            } else if self.eat("<") {
+                InfixOp::Lt
+            } else if self.eat(">") {
+                InfixOp::Gt
+            } else if self.eat("=") {
+                InfixOp::Eq
+            } else {
+                break;
+            };
+            let right = self.concat()?;
+            left = Expr::infix(op, left, right);
+        }
+        Ok(left)
+    }
+
+    fn concat(&mut self) -> Result<Expr, OdsError> {
+        let mut left = self.additive()?;
+        while self.eat("&") {
+            let right = self.additive()?;
+            left = Expr::infix(InfixOp::Concat, left, right);
+        }
+        Ok(left)
+    }
+
+    fn additive(&mut self) -> Result<Expr, OdsError> {
+        let mut left = self.multiplicative()?;
+        loop {
+            let op = if self.eat("+") {
+                InfixOp::Add
+            } else if self.eat("-") {
+                InfixOp::Sub
+            } else {
+                break;
+            };
+            let right = self.multiplicative()?;
+            left = Expr::infix(op, left, right);
+        }
+        Ok(left)
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, OdsError> {
+        let mut left = self.power()?;
+        loop {
+            let op = if self.eat("*") {
+                InfixOp::Mul
+            } else if self.eat("/") {
+                InfixOp::Div
+            } else {
+                break;
+            };
+            let right = self.power()?;
+            left = Expr::infix(op, left, right);
+        }
+        Ok(left)
+    }
+
+    fn power(&mut self) -> Result<Expr, OdsError> {
+        let mut left = self.postfix()?;
+        while self.eat("^") {
+            let right = self.postfix()?;
+            left = Expr::infix(InfixOp::Pow, left, right);
+        }
+        Ok(left)
+    }
+
+    fn postfix(&mut self) -> Result<Expr, OdsError> {
+        let mut expr = self.prefix()?;
+        while self.eat("%") {
+            expr = Expr::Postfix(PostfixOp::Percent, Box::new(expr));
+        }
+        Ok(expr)
+    }
+
+    fn prefix(&mut self) -> Result<Expr, OdsError> {
+        let op = if self.eat("-") {
+            PrefixOp::Minus
+        } else if self.eat("+") {
+            PrefixOp::Plus
+        } else {
+            return self.union();
+        };
+        self.enter()?;
+        let expr = self.prefix()?;
+        self.depth -= 1;
+        Ok(Expr::Prefix(op, Box::new(expr)))
+    }
+
+    fn union(&mut self) -> Result<Expr, OdsError> {
+        let mut left = self.intersect()?;
+        while self.eat("~") {
+            let right = self.intersect()?;
+            left = Expr::infix(InfixOp::Union, left, right);
+        }
+        Ok(left)
+    }
+
+    fn intersect(&mut self) -> Result<Expr, OdsError> {
+        let mut left = self.range()?;
+        while self.eat("!") {
+            let right = self.range()?;
+            left = Expr::infix(InfixOp::Intersect, left, right);
+        }
+        Ok(left)
+    }
+
+    fn range(&mut self) -> Result<Expr, OdsError> {
+        let mut left = self.primary()?;
+        while self.eat(":") {
+            let right = self.primary()?;
+            left = Expr::infix(InfixOp::Range, left, right);
+        }
+        Ok(left)
+    }
+
+    fn primary(&mut self) -> Result<Expr, OdsError> {
+        self.space();
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let expr = self.expr()?;
+                self.expect(")", "missing )")?;
+                Ok(Expr::Parentheses(Box::new(expr)))
+            }
+            Some('"') => self.string(),
+            Some('[') => self.reference(),
+            Some('{') => self.array(),
+            Some('#') => self.error_value(),
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) if c.is_alphabetic() || c == '_' => self.identifier(),
+            _ => Err(self.error("expression expected")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Expr, OdsError> {
+        let rest = self.rest().as_bytes();
+        let mut len = 0;
+        while len < rest.len() && (rest[len].is_ascii_digit() || rest[len] == b'.') {
+            len += 1;
+        }
+        if len < rest.len() && (rest[len] == b'e' || rest[len] == b'E') {
+            let mut exp = len + 1;
+            if exp < rest.len() && (rest[exp] == b'+' || rest[exp] == b'-') {
+                exp += 1;
+            }
+            if exp < rest.len() && rest[exp].is_ascii_digit() {
+                while exp < rest.len() && rest[exp].is_ascii_digit() {
+                    exp += 1;
+                }
+                len = exp;
+            }
+        }
+        let number = &self.rest()[..len];
+        let number = number
+            .parse::<f64>()
+            .map_err(|_| self.error("invalid number"))?;
+        self.pos += len;
+        Ok(Expr::Number(number))
+    }
+
+    fn string(&mut self) -> Result<Expr, OdsError> {
+        // skip the quote
+        let start = self.pos;
+        self.pos += 1;
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                Some('"') => {
+                    self.pos += 1;
+                    if self.peek() == Some('"') {
+                        self.pos += 1;
+                        text.push('"');
+                    } else {
+                        break;
+                    }
+                }
+                Some(c) => {
+                    self.pos += c.len_utf8();
+                    text.push(c);
+                }
+                None => {
+                    self.pos = start;
+                    return Err(self.error("unterminated string"));
+                }
+            }
+        }
+        Ok(Expr::String(text))
+    }
+
+    fn error_value(&mut self) -> Result<Expr, OdsError> {
+        for kind in ErrorKind::all() {
+            let len = kind.as_str().len();
+            if self.rest().len() >= len && self.rest()[..len].eq_ignore_ascii_case(kind.as_str()) {
+                self.pos += len;
+                return Ok(Expr::Error(*kind));
+            }
+        }
+        Err(self.error("unknown error value"))
+    }
+
+    fn reference(&mut self) -> Result<Expr, OdsError> {
+        // find the closing bracket, quoted names may contain one.
+        let rest = self.rest();
+        let mut quoted = false;
+        let mut end = None;
+        for (i, c) in rest.char_indices().skip(1) {
+            match c {
+                '\'' => quoted = !quoted,
+                ']' if !quoted => {
+                    end = Some(i);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let end = match end {
+            Some(end) => end,
+            None => return Err(self.error("missing ]")),
+        };
+        let text = rest[1..end].trim();
+        self.pos += end + 1;
+
+        Ok(if let Ok(v) = parse_cellrange(text) {
+            Expr::CellRange(v)
+        } else if let Ok(v) = parse_cellref(text) {
+            Expr::CellRef(v)
+        } else if let Ok(v) = parse_colrange(text) {
+            Expr::ColRange(v)
+        } else if let Ok(v) = parse_rowrange(text) {
+            Expr::RowRange(v)
+        } else {
+            Expr::RefError(text.to_string())
+        })
+    }
+
+    fn array(&mut self) -> Result<Expr, OdsError> {
+        // skip the brace
+        self.pos += 1;
+        let mut rows = Vec::new();
+        let mut row = Vec::new();
+        if self.eat("}") {
+            return Ok(Expr::Array(rows));
+        }
+        loop {
+            row.push(self.expr()?);
+            if self.eat(";") {
+                continue;
+            } else if self.eat("|") {
+                rows.push(std::mem::take(&mut row));
+            } else if self.eat("}") {
+                rows.push(row);
+                break;
+            } else {
+                return Err(self.error("missing }"));
+            }
+        }
+        Ok(Expr::Array(rows))
+    }
+
+    fn identifier(&mut self) -> Result<Expr, OdsError> {
+        let rest = self.rest();
+        let len = rest
+            .char_indices()
+            .find(|(_, c)| !(c.is_alphanumeric() || *c == '_' || *c == '.'))
+            .map(|(i, _)| i)
+            .unwrap_or(rest.len());
+        let name = &rest[..len];
+        self.pos += len;
+
+        if self.eat("(") {
+            let mut args = Vec::new();
+            if self.eat(")") {
+                return Ok(Expr::Function(name.to_string(), args));
+            }
+            loop {
+                self.space();
+                if matches!(self.peek(), Some(';') | Some(')')) {
+                    args.push(Expr::Missing);
+                } else {
+                    args.push(self.expr()?);
+                }
+                if self.eat(";") {
+                    continue;
+                } else if self.eat(")") {
+                    break;
+                } else {
+                    return Err(self.error("missing )"));
+                }
+            }
+            Ok(Expr::Function(name.to_string(), args))
+        } else {
+            Ok(Expr::Name(name.to_string()))
+        }
+    }
+}
@@ -0,0 +1,589 @@
+//!
+//! Rewrites the references in formulas and everywhere else in a
+//! workbook.
+//!
+//! The formulas are not parsed completely, only the bracketed
+//! references are replaced. Everything else stays as it is.
+//!
+
+use crate::condition::Condition;
+use crate::conditional::{ConditionalEntry, ConditionalFormat};
+use crate::refs::{
+    parse_cellrange, parse_cellref, parse_colrange, parse_rowrange, CellRange, CellRef, ColRange,
+    RowRange,
+};
+use crate::{Sheet, WorkBook};
+
+/// Any reference found in a formula.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum AnyRef {
+    Cell(CellRef),
+    Range(CellRange),
+    Col(ColRange),
+    Row(RowRange),
+}
+
+impl AnyRef {
+    pub(crate) fn parse(text: &str) -> Option<AnyRef> {
+        if let Ok(v) = parse_cellrange(text) {
+            Some(AnyRef::Range(v))
+        } else if let Ok(v) = parse_cellref(text) {
+            Some(AnyRef::Cell(v))
+        } else if let Ok(v) = parse_colrange(text) {
+            Some(AnyRef::Col(v))
+        } else if let Ok(v) = parse_rowrange(text) {
+            Some(AnyRef::Row(v))
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn to_formula(&self) -> String {
+        match self {
+            AnyRef::Cell(v) => v.to_formula(),
+            AnyRef::Range(v) => v.to_formula(),
+            AnyRef::Col(v) => v.to_formula(),
+            AnyRef::Row(v) => v.to_formula(),
+        }
+    }
+
+    /// Sheet of the reference, or the given sheet for local references.
+    pub(crate) fn table<'a>(&'a self, sheet: &'a str) -> &'a str {
+        let table = match self {
+            AnyRef::Cell(v) => v.table(),
+            AnyRef::Range(v) => v.table(),
+            AnyRef::Col(v) => v.table(),
+            AnyRef::Row(v) => v.table(),
+        };
+        table.map(|v| v.as_str()).unwrap_or(sheet)
+    }
+
+    /// Range that spans more than one sheet.
+    pub(crate) fn is_3d(&self, sheet: &str) -> bool {
+        let to_table = match self {
+            AnyRef::Cell(_) => None,
+            AnyRef::Range(v) => v.to_table(),
+            AnyRef::Col(v) => v.to_table(),
+            AnyRef::Row(v) => v.to_table(),
+        };
+        to_table.is_some_and(|v| v != self.table(sheet))
+    }
+
+    /// Reference to another document.
+    pub(crate) fn is_external(&self) -> bool {
+        match self {
+            AnyRef::Cell(v) => v.iri().is_some(),
+            AnyRef::Range(v) => v.iri().is_some(),
+            AnyRef::Col(v) => v.iri().is_some(),
+            AnyRef::Row(v) => v.iri().is_some(),
+        }
+    }
+}
+
+/// Callback for rewriting references. Gets the name of the sheet
+/// that contains the formula and the reference. Returns false if the
+/// reference became invalid.
+pub(crate) type RefFn<'a> = dyn FnMut(&str, &mut AnyRef) -> bool + 'a;
+
+/// Rewrites all references in the formula. References that become
+/// invalid are replaced with #REF!.
+pub(crate) fn rewrite_formula(formula: &str, sheet: &str, f: &mut RefFn<'_>) -> String {
+    let mut buf = String::with_capacity(formula.len());
+    let mut rest = formula;
+    loop {
+        // find the next bracket outside of a string.
+        let mut quoted = false;
+        let mut start = None;
+        for (i, c) in rest.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                '[' if !quoted => {
+                    start = Some(i);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let start = match start {
+            Some(v) => v,
+            None => break,
+        };
+        // quoted sheet names can contain a bracket.
+        let mut quoted = false;
+        let mut end = None;
+        for (i, c) in rest[start..].char_indices().skip(1) {
+            match c {
+                '\'' => quoted = !quoted,
+                ']' if !quoted => {
+                    end = Some(start + i);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let end = match end {
+            Some(v) => v,
+            None => break,
+        };
+
+        buf.push_str(&rest[..start]);
+        let text = &rest[start + 1..end];
+        match AnyRef::parse(text.trim()) {
+            Some(r) => {
+                let mut r2 = r.clone();
+                if !f(sheet, &mut r2) {
+                    buf.push_str("[.#REF!]");
+                } else if r2 != r {
+                    buf.push_str(&r2.to_formula());
+                } else {
+                    buf.push_str(&rest[start..=end]);
+                }
+            }
+            None => buf.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    buf.push_str(rest);
+    buf
+}
+
+/// Rewrites a cell reference outside of a formula. It's left
+/// unchanged if it became invalid.
+pub(crate) fn rewrite_cellref(cellref: &mut CellRef, sheet: &str, f: &mut RefFn<'_>) {
+    let mut r = AnyRef::Cell(cellref.clone());
+    if f(sheet, &mut r) {
+        if let AnyRef::Cell(v) = r {
+            *cellref = v;
+        }
+    }
+}
+
+/// Rewrites a cell range outside of a formula. Returns false if it
+/// became invalid, it's left unchanged then.
+pub(crate) fn rewrite_cellrange(range: &mut CellRange, sheet: &str, f: &mut RefFn<'_>) -> bool {
+    let mut r = AnyRef::Range(range.clone());
+    if f(sheet, &mut r) {
+        if let AnyRef::Range(v) = r {
+            *range = v;
+        }
+        true
+    } else {
+        false
+    }
+}
+
+/// Rewrites the formulas, conditional formats and print ranges
+/// of the sheet.
+pub(crate) fn rewrite_sheet(sheet: &mut Sheet, f: &mut RefFn<'_>) {
+    let name = sheet.name.clone();
+
+    for cell in sheet.data.values_mut() {
+        if let Some(formula) = cell.formula() {
+            let formula = rewrite_formula(formula, &name, f);
+            cell.set_formula(Some(formula));
+        }
+    }
+
+    for cf in sheet.conditional_formats.iter_mut() {
+        rewrite_conditional(cf, &name, f);
+    }
+    sheet.conditional_formats.retain(|v| !v.ranges().is_empty());
+
+    if let Some(print_ranges) = &mut sheet.print_ranges {
+        print_ranges.retain_mut(|v| rewrite_cellrange(v, &name, f));
+    }
+}
+
+fn rewrite_conditional(cf: &mut ConditionalFormat, sheet: &str, f: &mut RefFn<'_>) {
+    cf.ranges_mut()
+        .retain_mut(|v| rewrite_cellrange(v, sheet, f));
+
+    for entry in cf.entries_mut() {
+        match entry {
+            ConditionalEntry::Condition(v) => {
+                let cond = rewrite_formula(&v.condition().to_string(), sheet, f);
+                v.set_condition(Condition::new(cond));
+                if let Some(base_cell) = v.base_cell() {
+                    let mut base_cell = base_cell.clone();
+                    rewrite_cellref(&mut base_cell, sheet, f);
+                    v.set_base_cell(Some(base_cell));
+                }
+            }
+            ConditionalEntry::ColorScale(v) => {
+                for e in v.entries_mut() {
+                    let value = rewrite_formula(e.value(), sheet, f);
+                    e.set_value(value);
+                }
+            }
+            ConditionalEntry::DataBar(v) => {
+                let mut min = v.min().clone();
+                min.set_value(rewrite_formula(min.value(), sheet, f));
+                v.set_min(min);
+                let mut max = v.max().clone();
+                max.set_value(rewrite_formula(max.value(), sheet, f));
+                v.set_max(max);
+            }
+            ConditionalEntry::IconSet(v) => {
+                for e in v.entries_mut() {
+                    let value = rewrite_formula(e.value(), sheet, f);
+                    e.set_value(value);
+                }
+            }
+            ConditionalEntry::Date(_) | ConditionalEntry::Xml(_) => {}
+        }
+    }
+}
+
+/// Rewrites the references of all sheets, validations, named ranges
+/// and named expressions. The sheet with index skip is not changed.
+pub(crate) fn rewrite_book(book: &mut WorkBook, skip: Option<usize>, f: &mut RefFn<'_>) {
+    for (idx, sheet) in book.sheets.iter_mut().enumerate() {
+        if Some(idx) != skip {
+            rewrite_sheet(sheet.as_mut(), f);
+        }
+    }
+
+    for valid in book.validations.values_mut() {
+        let sheet = valid.base_cell().table().cloned().unwrap_or_default();
+        let cond = rewrite_formula(valid.condition(), &sheet, f);
+        valid.set_condition(Condition::new(cond));
+        let mut base_cell = valid.base_cell().clone();
+        rewrite_cellref(&mut base_cell, &sheet, f);
+        valid.set_base_cell(base_cell);
+    }
+
+    for named in book.named_ranges.iter_mut() {
+        let sheet = named.base_cell().table().cloned().unwrap_or_default();
+        rewrite_cellrange(named.range_mut(), &sheet, f);
+        let mut base_cell = named.base_cell().clone();
+        rewrite_cellref(&mut base_cell, &sheet, f);
+        named.set_base_cell(base_cell);
+    }
+
+    for named in book.named_expressions.iter_mut() {
+        let sheet = named
+            .base_cell()
+            .and_then(|v| v.table().cloned())
+            .unwrap_or_default();
+        let expr = rewrite_formula(named.expression(), &sheet, f);
+        named.set_expression(expr);
+        if let Some(base_cell) = named.base_cell() {
+            let mut base_cell = base_cell.clone();
+            rewrite_cellref(&mut base_cell, &sheet, f);
+            named.set_base_cell(Some(base_cell));
+        }
+    }
+}
+
+/// Inserts or deletes rows or columns of one sheet.
+#[derive(Debug, Clone)]
+pub(crate) struct Shift {
+    /// Changed sheet.
+    pub(crate) sheet: String,
+    /// Rows or columns.
+    pub(crate) rows: bool,
+    /// First inserted or deleted row/column.
+    pub(crate) at: u32,
+    /// Count.
+    pub(crate) n: u32,
+    /// Delete instead of insert.
+    pub(crate) delete: bool,
+}
+
+impl Shift {
+    /// New index for a single row/column.
+    pub(crate) fn index(&self, v: u32) -> Option<u32> {
+        if self.delete {
+            if v >= self.at.saturating_add(self.n) {
+                Some(v - self.n)
+            } else if v >= self.at {
+                None
+            } else {
+                Some(v)
+            }
+        } else if v >= self.at {
+            Some(v.saturating_add(self.n))
+        } else {
+            Some(v)
+        }
+    }
+
+    /// New bounds for an inclusive span of rows/columns. Insertions
+    /// within the span enlarge it, deletions shrink it.
+    pub(crate) fn span(&self, from: u32, to: u32) -> Option<(u32, u32)> {
+        if self.delete {
+            let end = self.at.saturating_add(self.n);
+            if from >= self.at && to < end {
+                return None;
+            }
+            let from = if from >= end {
+                from - self.n
+            } else if from >= self.at {
+                self.at
+            } else {
+                from
+            };
+            let to = if to >= end {
+                to - self.n
+            } else if to >= self.at {
+                self.at - 1
+            } else {
+                to
+            };
+            Some((from, to))
+        } else {
+            Some((self.index(from)?, self.index(to)?))
+        }
+    }
+
+    /// Adjusts a reference. Returns false if it was deleted.
+    ///
+    /// Ranges across several sheets are kept, the rows/columns only
+    /// change in one of them.
+    pub(crate) fn apply(&self, sheet: &str, r: &mut AnyRef) -> bool {
+        if r.is_external() || r.table(sheet) != self.sheet || r.is_3d(sheet) {
+            return true;
+        }
+        match r {
+            AnyRef::Cell(v) => {
+                let idx = if self.rows { v.row() } else { v.col() };
+                match self.index(idx) {
+                    Some(idx) if self.rows => v.set_row(idx),
+                    Some(idx) => v.set_col(idx),
+                    None => return false,
+                }
+            }
+            AnyRef::Range(v) => {
+                let (from, to) = if self.rows {
+                    (v.row(), v.to_row())
+                } else {
+                    (v.col(), v.to_col())
+                };
+                match self.span(from, to) {
+                    Some((from, to)) if self.rows => {
+                        v.set_row(from);
+                        v.set_to_row(to);
+                    }
+                    Some((from, to)) => {
+                        v.set_col(from);
+                        v.set_to_col(to);
+                    }
+                    None => return false,
+                }
+            }
+            AnyRef::Col(v) => {
+                if !self.rows {
+                    match self.span(v.col(), v.to_col()) {
+                        Some((from, to)) => {
+                            v.set_col(from);
+                            v.set_to_col(to);
+                        }
+                        None => return false,
+                    }
+                }
+            }
+            AnyRef::Row(v) => {
+                if self.rows {
+                    match self.span(v.row(), v.to_row()) {
+                        Some((from, to)) => {
+                            v.set_row(from);
+                            v.set_to_row(to);
+                        }
+                        None => return false,
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Moves the relative references of a copied formula by an offset.
+/// Absolute references are kept.
+#[derive(Debug, Clone)]
+pub(crate) struct Translate {
+    pub(crate) rows: i64,
+    pub(crate) cols: i64,
+}
+
+impl Translate {
+    fn offset(v: u32, abs: bool, d: i64) -> Option<u32> {
+        if abs {
+            Some(v)
+        } else {
+            u32::try_from(v as i64 + d).ok()
+        }
+    }
+
+    /// Adjusts a reference. Returns false if it moved outside of the
+    /// sheet.
+    pub(crate) fn apply(&self, _sheet: &str, r: &mut AnyRef) -> bool {
+        let (rows, cols) = (self.rows, self.cols);
+        let moved = match r {
+            AnyRef::Cell(v) => Self::offset(v.row(), v.row_abs(), rows)
+                .zip(Self::offset(v.col(), v.col_abs(), cols))
+                .map(|(row, col)| {
+                    v.set_row(row);
+                    v.set_col(col);
+                }),
+            AnyRef::Range(v) => Self::offset(v.row(), v.row_abs(), rows)
+                .zip(Self::offset(v.col(), v.col_abs(), cols))
+                .zip(Self::offset(v.to_row(), v.to_row_abs(), rows))
+                .zip(Self::offset(v.to_col(), v.to_col_abs(), cols))
+                .map(|(((row, col), to_row), to_col)| {
+                    v.set_row(row);
+                    v.set_col(col);
+                    v.set_to_row(to_row);
+                    v.set_to_col(to_col);
+                }),
+            AnyRef::Col(v) => Self::offset(v.col(), v.col_abs(), cols)
+                .zip(Self::offset(v.to_col(), v.to_col_abs(), cols))
+                .map(|(col, to_col)| {
+                    v.set_col(col);
+                    v.set_to_col(to_col);
+                }),
+            AnyRef::Row(v) => Self::offset(v.row(), v.row_abs(), rows)
+                .zip(Self::offset(v.to_row(), v.to_row_abs(), rows))
+                .map(|(row, to_row)| {
+                    v.set_row(row);
+                    v.set_to_row(to_row);
+                }),
+        };
+        moved.is_some()
+    }
+}
+
+/// Follows cells that have been moved. References that lie completely
+/// within the moved area are changed to point to the new position.
+#[derive(Debug, Clone)]
+pub(crate) struct Relocate {
+    /// Sheet and area of the moved cells.
+    pub(crate) sheet: String,
+    pub(crate) row: u32,
+    pub(crate) col: u32,
+    pub(crate) to_row: u32,
+    pub(crate) to_col: u32,
+    /// Destination sheet.
+    pub(crate) dest: String,
+    /// Offset.
+    pub(crate) rows: i64,
+    pub(crate) cols: i64,
+}
+
+impl Relocate {
+    fn contains(&self, row: u32, col: u32) -> bool {
+        row >= self.row && row <= self.to_row && col >= self.col && col <= self.to_col
+    }
+
+    fn offset(v: u32, d: i64) -> u32 {
+        // the destination is always a valid position.
+        (v as i64 + d) as u32
+    }
+
+    /// Adjusts a reference.
+    pub(crate) fn apply(&self, sheet: &str, r: &mut AnyRef) -> bool {
+        if r.is_external() || r.table(sheet) != self.sheet {
+            return true;
+        }
+        // references from other sheets need the sheet name.
+        let table = if self.dest != self.sheet || sheet != self.dest {
+            Some(self.dest.as_str())
+        } else {
+            None
+        };
+        match r {
+            AnyRef::Cell(v) => {
+                if self.contains(v.row(), v.col()) {
+                    v.set_row(Self::offset(v.row(), self.rows));
+                    v.set_col(Self::offset(v.col(), self.cols));
+                    if let Some(table) = table {
+                        v.set_table(table);
+                    }
+                }
+            }
+            AnyRef::Range(v) => {
+                if v.to_table().is_none()
+                    && self.contains(v.row(), v.col())
+                    && self.contains(v.to_row(), v.to_col())
+                {
+                    v.set_row(Self::offset(v.row(), self.rows));
+                    v.set_col(Self::offset(v.col(), self.cols));
+                    v.set_to_row(Self::offset(v.to_row(), self.rows));
+                    v.set_to_col(Self::offset(v.to_col(), self.cols));
+                    if let Some(table) = table {
+                        v.set_table(table);
+                    }
+                }
+            }
+            AnyRef::Col(_) | AnyRef::Row(_) => {}
+        }
+        true
+    }
+}
+
+/// Adds the sheet name to all local references.
+pub(crate) fn make_remote(sheet: &str, r: &mut AnyRef) -> bool {
+    if r.is_external() {
+        return true;
+    }
+    match r {
+        AnyRef::Cell(v) if v.table().is_none() => v.set_table(sheet),
+        AnyRef::Range(v) if v.table().is_none() => v.set_table(sheet),
+        AnyRef::Col(v) if v.table().is_none() => v.set_table(sheet),
+        AnyRef::Row(v) if v.table().is_none() => v.set_table(sheet),
+        _ => {}
+    }
+    true
+}
+
+/// Renames a sheet in all references.
+#[derive(Debug, Clone)]
+pub(crate) struct Rename {
+    pub(crate) old: String,
+    pub(crate) new: String,
+}
+
+impl Rename {
+    fn rename(&self, table: Option<&String>) -> bool {
+        table.map(|v| *v == self.old).unwrap_or(false)
+    }
+
+    /// Adjusts a reference.
+    pub(crate) fn apply(&self, _sheet: &str, r: &mut AnyRef) -> bool {
+        if r.is_external() {
+            return true;
+        }
+        match r {
+            AnyRef::Cell(v) => {
+                if self.rename(v.table()) {
+                    v.set_table(self.new.as_str());
+                }
+            }
+            AnyRef::Range(v) => {
+                if self.rename(v.table()) {
+                    v.set_table(self.new.as_str());
+                }
+                if self.rename(v.to_table()) {
+                    v.set_to_table(self.new.as_str());
+                }
+            }
+            AnyRef::Col(v) => {
+                if self.rename(v.table()) {
+                    v.set_table(self.new.as_str());
+                }
+                if self.rename(v.to_table()) {
+                    v.set_to_table(self.new.as_str());
+                }
+            }
+            AnyRef::Row(v) => {
+                if self.rename(v.table()) {
+                    v.set_table(self.new.as_str());
+                }
+                if self.rename(v.to_table()) {
+                    v.set_to_table(self.new.as_str());
+                }
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,186 @@
+//!
+//! Images anchored to a cell.
+//!
+//! The image data is kept with the image and stored under Pictures/
+//! when writing.
+//!
+//! ```
+//! use spreadsheet_ods::{cm, CellRef, Length, Sheet};
+//!
+//! let png: Vec<u8> = Vec::new(); // some png data.
+//!
+//! let mut sheet = Sheet::new("1");
+//! let image = sheet.add_image(
+//!     CellRef::local(1, 1),
+//!     (cm!(4), cm!(3)),
+//!     (cm!(0.2), cm!(0.2)),
+//!     png,
+//!     "image/png",
+//! );
+//! image.set_desc(Some("Company logo".to_string()));
+//!
+//! assert_eq!(sheet.images().count(), 1);
+//! ```
+
+use crate::attrmap2::AttrMap2;
+use crate::style::GraphicStyleRef;
+use crate::Length;
+
+/// An image embedded in a draw:frame.
+#[derive(Debug, Clone, Default)]
+pub struct Image {
+    name: String,
+    style: Option<GraphicStyleRef>,
+    x: Length,
+    y: Length,
+    width: Length,
+    height: Length,
+    title: Option<String>,
+    desc: Option<String>,
+    href: String,
+    mime: String,
+    data: Vec<u8>,
+    attr: AttrMap2,
+}
+
+impl Image {
+    /// Image with the given media-type and data.
+    pub fn new<S: Into<String>>(mime: S, data: Vec<u8>) -> Self {
+        Self {
+            mime: mime.into(),
+            data,
+            ..Self::new_empty()
+        }
+    }
+
+    /// Image without any data.
+    pub fn new_empty() -> Self {
+        Self {
+            name: Default::default(),
+            style: None,
+            x: Default::default(),
+            y: Default::default(),
+            width: Default::default(),
+            height: Default::default(),
+            title: None,
+            desc: None,
+            href: Default::default(),
+            mime: Default::default(),
+            data: Default::default(),
+            attr: Default::default(),
+        }
+    }
+
+    /// Name of the frame.
+    pub fn set_name<S: Into<String>>(&mut self, name: S) {
+        self.name = name.into();
+    }
+
+    /// Name of the frame.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Graphic style. If no style is set, a default style is
+    /// created when writing.
+    pub fn set_style(&mut self, style: &GraphicStyleRef) {
+        self.style = Some(style.clone());
+    }
+
+    /// Graphic style.
+    pub fn style(&self) -> Option<&GraphicStyleRef> {
+        self.style.as_ref()
+    }
+
+    /// Removes the graphic style.
+    pub fn clear_style(&mut self) {
+        self.style = None;
+    }
+
+    /// Offset relative to the top left corner of the anchor cell.
+    /// For images anchored to the page it is relative to the sheet.
+    pub fn set_offset(&mut self, x: Length, y: Length) {
+        self.x = x;
+        self.y = y;
+    }
+
+    /// Offset relative to the top left corner of the anchor cell.
+    pub fn offset(&self) -> (Length, Length) {
+        (self.x, self.y)
+    }
+
+    /// Width and height.
+    pub fn set_size(&mut self, width: Length, height: Length) {
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Width and height.
+    pub fn size(&self) -> (Length, Length) {
+        (self.width, self.height)
+    }
+
+    /// Short title, used as tooltip.
+    pub fn set_title(&mut self, title: Option<String>) {
+        self.title = title;
+    }
+
+    /// Short title, used as tooltip.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Alternative text.
+    pub fn set_desc(&mut self, desc: Option<String>) {
+        self.desc = desc;
+    }
+
+    /// Alternative text.
+    pub fn desc(&self) -> Option<&str> {
+        self.desc.as_deref()
+    }
+
+    /// Path of the image within the package, e.g. "Pictures/logo.png".
+    /// If this is empty a unique name is assigned when writing.
+    pub fn set_href<S: Into<String>>(&mut self, href: S) {
+        self.href = href.into();
+    }
+
+    /// Path of the image within the package.
+    pub fn href(&self) -> &str {
+        &self.href
+    }
+
+    /// Media-type of the image data, e.g. "image/png".
+    pub fn set_mime<S: Into<String>>(&mut self, mime: S) {
+        self.mime = mime.into();
+    }
+
+    /// Media-type of the image data.
+    pub fn mime(&self) -> &str {
+        &self.mime
+    }
+
+    /// Image data.
+    pub fn set_data(&mut self, data: Vec<u8>) {
+        self.data = data;
+    }
+
+    /// Image data. This is empty if the image was read with
+    /// ReadOptions::skip_filebuf.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Any other attributes of the draw:frame like draw:z-index or
+    /// table:end-cell-address.
+    pub fn attrmap(&self) -> &AttrMap2 {
+        &self.attr
+    }
+
+    /// Any other attributes of the draw:frame like draw:z-index or
+    /// table:end-cell-address.
+    pub fn attrmap_mut(&mut self) -> &mut AttrMap2 {
+        &mut self.attr
+    }
+}
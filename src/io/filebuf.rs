@@ -64,4 +64,67 @@ impl FileBuf {
     pub(crate) fn push_file<S: Into<String>>(&mut self, file: S, data: Vec<u8>) {
         self.buf.push(FileBufEntry::File(file.into(), data));
     }
+
+    pub(crate) fn remove<S: AsRef<str>>(&mut self, name: S) {
+        self.buf.retain(|v| match v {
+            FileBufEntry::Dir(_) => true,
+            FileBufEntry::File(n, _) => n != name.as_ref(),
+        });
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.buf.truncate(len);
+    }
+
+    pub(crate) fn get<S: AsRef<str>>(&self, name: S) -> Option<&[u8]> {
+        for it in &self.buf {
+            if let FileBufEntry::File(n, data) = it {
+                if n == name.as_ref() {
+                    return Some(data.as_slice());
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Guesses the media-type from the file extension.
+pub(crate) fn media_type(name: &str) -> &'static str {
+    let ext = match name.rsplit_once('.') {
+        Some((_, ext)) => ext.to_ascii_lowercase(),
+        None => return "",
+    };
+    match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        "tif" | "tiff" => "image/tiff",
+        "svg" => "image/svg+xml",
+        "wmf" => "image/x-wmf",
+        "emf" => "image/x-emf",
+        "xml" => "text/xml",
+        "rdf" => "application/rdf+xml",
+        _ => "",
+    }
+}
+
+/// Guesses a file extension for the media-type.
+pub(crate) fn media_type_ext(media_type: &str) -> &'static str {
+    match media_type {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/bmp" => "bmp",
+        "image/tiff" => "tif",
+        "image/svg+xml" => "svg",
+        "image/x-wmf" => "wmf",
+        "image/x-emf" => "emf",
+        _ => "bin",
+    }
 }
@@ -4,9 +4,10 @@
 //! For many cases this omits the transformation to a &str
 
 use crate::error::AsStatic;
-use crate::{OdsError, Visibility};
+use crate::{OdsError, Value, Visibility};
 use chrono::Duration;
 use chrono::NaiveDateTime;
+use color::Rgb;
 use kparse::prelude::*;
 use kparse::{TokenizerError, TokenizerResult};
 use nom::character::complete::digit1;
@@ -14,6 +15,7 @@ use nom::combinator::{all_consuming, eof, opt};
 use nom::number::complete::double;
 use nom::sequence::{pair, preceded, terminated, tuple};
 use nom::AsChar;
+use rust_decimal::Decimal;
 use std::fmt::{Display, Formatter};
 use std::str::{from_utf8, from_utf8_unchecked};
 
@@ -70,6 +72,19 @@ pub(crate) fn parse_visibility(input: KSpan<'_>) -> Result<Visibility, OdsError>
     }
 }
 
+/// Parse a color in the form #rrggbb.
+pub(crate) fn parse_color(input: KSpan<'_>) -> Result<Rgb<u8>, OdsError> {
+    match input {
+        [b'#', hex @ ..] if hex.len() == 6 && hex.iter().all(|v| v.is_ascii_hexdigit()) => {
+            Ok(Rgb::from_hex(u32::from_str_radix(from_utf8(hex)?, 16)?))
+        }
+        _ => Err(OdsError::Ods(format!(
+            "Invalid color {}",
+            String::from_utf8_lossy(input)
+        ))),
+    }
+}
+
 /// Parse a attribute value as a currency.
 pub(crate) fn parse_currency(input: KSpan<'_>) -> Result<String, OdsError> {
     Ok(String::from_utf8_lossy(input).to_string())
@@ -105,6 +120,20 @@ pub(crate) fn parse_f64(input: KSpan<'_>) -> Result<f64, OdsError> {
     Ok(token_float(input)?)
 }
 
+/// Parse a decimal. Returns None if the value can't be represented
+/// exactly.
+///
+/// The exponent of the scientific notation is applied, "1E3" becomes
+/// 1000 and "1.5E-3" becomes 0.0015.
+pub(crate) fn parse_decimal(input: KSpan<'_>) -> Option<Decimal> {
+    let input = from_utf8(input).ok()?;
+    if input.contains(['e', 'E']) {
+        Decimal::from_scientific(input).ok()
+    } else {
+        Decimal::from_str_exact(input).ok()
+    }
+}
+
 /// Parse a XML Schema datetime.
 pub(crate) fn parse_datetime(input: KSpan<'_>) -> Result<NaiveDateTime, OdsError> {
     Ok(token_datetime(input)?)
@@ -115,6 +144,17 @@ pub(crate) fn parse_duration(input: KSpan<'_>) -> Result<Duration, OdsError> {
     Ok(token_duration(input)?)
 }
 
+/// Parse an office:date-value. A value without a time part is
+/// a Value::Date, otherwise a Value::DateTime.
+pub(crate) fn parse_date_value(input: KSpan<'_>) -> Result<Value, OdsError> {
+    let v = parse_datetime(input)?;
+    if input.contains(&b'T') {
+        Ok(Value::DateTime(v))
+    } else {
+        Ok(Value::Date(v.date()))
+    }
+}
+
 #[inline(always)]
 fn token_bool(input: KSpan<'_>) -> KTokenResult<'_, bool> {
     match input {
@@ -279,7 +319,8 @@ fn token_datetime(input: KSpan<'_>) -> KTokenResult<'_, NaiveDateTime> {
 
 #[inline(always)]
 fn token_duration(input: KSpan<'_>) -> KTokenResult<'_, Duration> {
-    let (_, (_, day, _, hour, minute, (second, nanos))) = all_consuming(tuple((
+    let (_, (neg, _, day, _, hour, minute, (second, nanos))) = all_consuming(tuple((
+        opt(byte(b'-')),
         byte(b'P'),
         // these do not occur?
         //opt(terminated(token_datepart, byte(b'Y'))),
@@ -301,6 +342,9 @@ fn token_duration(input: KSpan<'_>) -> KTokenResult<'_, Duration> {
     if let Some(nanos) = nanos {
         result = result + Duration::nanoseconds(nanos);
     }
+    if neg.is_some() {
+        result = -result;
+    }
 
     Ok(result)
 }
@@ -383,6 +427,23 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_decimal() {
+        use crate::io::parse::parse_decimal;
+
+        let d = |v: &[u8]| parse_decimal(v).map(|v| v.to_string());
+        assert_eq!(d(b"0.10").as_deref(), Some("0.10"));
+        assert_eq!(
+            d(b"-12345678901234567890.12").as_deref(),
+            Some("-12345678901234567890.12")
+        );
+        // the exponent is applied, the lexical form is lost.
+        assert_eq!(d(b"1E3").as_deref(), Some("1000"));
+        assert_eq!(d(b"1.5E-3").as_deref(), Some("0.0015"));
+        assert_eq!(d(b"1E40"), None);
+        assert_eq!(d(b"abc"), None);
+    }
+
     #[test]
     fn test_datetime() -> Result<(), OdsError> {
         assert_eq!(parse_datetime(b"19999-01-01")?.timestamp(), 568940284800);
@@ -414,6 +475,10 @@ mod tests {
             parse_duration(b"PT12H12M12.223S")?.num_milliseconds(),
             43932223
         );
+        assert_eq!(
+            parse_duration(b"-P0DT3H0M0.000S")?.num_milliseconds(),
+            -10800000
+        );
         Ok(())
     }
 
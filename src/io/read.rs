@@ -1,79 +1,247 @@
 use std::convert::{TryFrom, TryInto};
 use std::fs::File;
-use std::io::{BufReader, Cursor, Read, Seek, Write};
+use std::io::{BufRead, BufReader, Cursor, Read, Seek, Write};
 use std::path::Path;
 
-use chrono::{Duration, NaiveDateTime};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::FixedOffset;
+use color::Rgb;
 use quick_xml::events::{BytesStart, Event};
+use rust_decimal::Decimal;
 use zip::read::ZipFile;
 use zip::ZipArchive;
 
+use crate::annotation::Annotation;
 use crate::attrmap2::AttrMap2;
 use crate::condition::{Condition, ValueCondition};
+use crate::conditional::{
+    condition_from_calcext, ColorScale, ConditionEntry, ConditionalEntry, ConditionalFormat,
+    DataBar, DateEntry, IconSet, ScaleEntry, ScaleType,
+};
 use crate::config::{Config, ConfigItem, ConfigItemType, ConfigValue};
 use crate::ds::bufstack::BufStack;
 use crate::ds::detach::Detach;
 use crate::error::OdsError;
 use crate::format::{FormatPart, FormatPartType, ValueFormatTrait, ValueStyleMap};
+use crate::formula::ErrorKind;
+use crate::image::Image;
+use crate::io::filebuf::{media_type, media_type_ext, FileBuf};
 use crate::io::parse::{
-    parse_bool, parse_currency, parse_datetime, parse_duration, parse_f64, parse_i16, parse_i32,
-    parse_i64, parse_u32, parse_visibility,
+    parse_bool, parse_color, parse_currency, parse_date_value, parse_datetime, parse_decimal,
+    parse_duration, parse_f64, parse_i16, parse_i32, parse_i64, parse_u32, parse_visibility,
 };
 use crate::io::{DUMP_UNUSED, DUMP_XML};
-use crate::refs::{parse_cellranges, parse_cellref};
+use crate::metadata::{MetaDateTime, MetaValue};
+use crate::named::{NameScope, NamedExpression, NamedRange};
+use crate::refs::{parse_cellrange, parse_cellranges, parse_cellref};
 use crate::style::stylemap::StyleMap;
 use crate::style::tabstop::TabStop;
 use crate::style::{
     ColStyle, FontFaceDecl, GraphicStyle, HeaderFooter, MasterPage, PageStyle, ParagraphStyle,
-    RowStyle, StyleOrigin, StyleUse, TableStyle, TextStyle,
+    ParseStyleAttr, RowStyle, StyleOrigin, StyleUse, TableStyle, TextStyle,
 };
 use crate::text::{TextP, TextTag};
 use crate::validation::{MessageType, Validation, ValidationError, ValidationHelp};
-use crate::xmltree::XmlTag;
+use crate::xmltree::{XmlContent, XmlTag};
 use crate::{
-    CellData, CellStyle, ColRange, Length, RowRange, Sheet, SplitMode, Value, ValueFormatBoolean,
-    ValueFormatCurrency, ValueFormatDateTime, ValueFormatNumber, ValueFormatPercentage,
-    ValueFormatText, ValueFormatTimeDuration, ValueType, Visibility, WorkBook,
+    CellContent, CellData, CellRange, CellStyle, ColRange, Length, RowRange, Sheet, SplitMode,
+    Value, ValueFormatBoolean, ValueFormatCurrency, ValueFormatDateTime, ValueFormatNumber,
+    ValueFormatPercentage, ValueFormatText, ValueFormatTimeDuration, ValueType, Visibility,
+    WorkBook,
 };
 use quick_xml::events::attributes::Attribute;
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::{Debug, Formatter};
+use std::iter::FusedIterator;
 use std::str::from_utf8;
+use std::sync::Arc;
+
+/// Options for reading an ODS-file.
+///
+/// By default everything is read. For just extracting some values most
+/// of the work can be skipped.
+///
+/// ```no_run
+/// use spreadsheet_ods::{read_ods_with, OdsError, ReadOptions};
+///
+/// let options = ReadOptions::new().sheet("Orders").values_only();
+/// let book = read_ods_with("tests/orders.ods", &options)?;
+/// # Ok::<(), OdsError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    sheets: Option<Vec<String>>,
+    skip_styles: bool,
+    skip_filebuf: bool,
+    ignore_settings: bool,
+    exact_numbers: bool,
+}
+
+impl ReadOptions {
+    /// Default options, reads everything.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Reads only the sheets with the given names. Can be called
+    /// multiple times to select more than one sheet.
+    pub fn sheet<S: Into<String>>(mut self, name: S) -> Self {
+        self.sheets.get_or_insert_with(Vec::new).push(name.into());
+        self
+    }
+
+    /// Doesn't read any styles, value formats, fonts or page layouts.
+    /// The style names are still set on the cells, but they can't be
+    /// resolved.
+    pub fn skip_styles(mut self) -> Self {
+        self.skip_styles = true;
+        self
+    }
+
+    /// Doesn't copy any other parts of the zip into the FileBuf.
+    /// Pictures, embedded objects, meta.xml etc. are lost when writing
+    /// the WorkBook. Images anchored to cells are read, but without
+    /// their data.
+    pub fn skip_filebuf(mut self) -> Self {
+        self.skip_filebuf = true;
+        self
+    }
+
+    /// Ignores settings.xml and uses the default settings instead.
+    pub fn ignore_settings(mut self) -> Self {
+        self.ignore_settings = true;
+        self
+    }
+
+    /// Reads float and currency values as Value::Decimal and
+    /// Value::DecimalCurrency. They are written back exactly as they
+    /// were read, except for an exponent: "1E3" is written as 1000.
+    /// Values that don't fit in a Decimal are still read as f64.
+    pub fn exact_numbers(mut self) -> Self {
+        self.exact_numbers = true;
+        self
+    }
+
+    /// Only reads the cell values. Shortcut for skip_styles(),
+    /// skip_filebuf() and ignore_settings().
+    pub fn values_only(self) -> Self {
+        self.skip_styles().skip_filebuf().ignore_settings()
+    }
+
+    /// Is this sheet to be read.
+    fn read_sheet(&self, name: &str) -> bool {
+        match &self.sheets {
+            None => true,
+            Some(sheets) => sheets.iter().any(|v| v == name),
+        }
+    }
+}
 
 /// Reads an ODS-file from a buffer
 pub fn read_ods_buf(buf: &[u8]) -> Result<WorkBook, OdsError> {
     let zip = ZipArchive::new(Cursor::new(buf))?;
-    read_ods_impl(zip)
+    read_ods_impl(zip, &ReadOptions::default())
+}
+
+/// Reads an ODS-file from a buffer with the given options.
+pub fn read_ods_buf_with(buf: &[u8], options: &ReadOptions) -> Result<WorkBook, OdsError> {
+    let zip = ZipArchive::new(Cursor::new(buf))?;
+    read_ods_impl(zip, options)
 }
 
 /// Reads an ODS-file from a reader
 pub fn read_ods_from<T: Read + Seek>(ods: T) -> Result<WorkBook, OdsError> {
     let zip = ZipArchive::new(ods)?;
-    read_ods_impl(zip)
+    read_ods_impl(zip, &ReadOptions::default())
 }
 
 /// Reads an ODS-file.
 pub fn read_ods<P: AsRef<Path>>(path: P) -> Result<WorkBook, OdsError> {
     let file = File::open(path.as_ref())?;
     let zip = ZipArchive::new(file)?;
-    read_ods_impl(zip)
+    read_ods_impl(zip, &ReadOptions::default())
+}
+
+/// Reads an ODS-file with the given options.
+pub fn read_ods_with<P: AsRef<Path>>(path: P, options: &ReadOptions) -> Result<WorkBook, OdsError> {
+    let file = File::open(path.as_ref())?;
+    let zip = ZipArchive::new(file)?;
+    read_ods_impl(zip, options)
+}
+
+/// Reads a flat ODS-file (FODS) from a buffer.
+pub fn read_fods_buf(buf: &[u8]) -> Result<WorkBook, OdsError> {
+    read_fods_impl(buf)
+}
+
+/// Reads a flat ODS-file (FODS) from a reader.
+pub fn read_fods_from<T: Read>(fods: T) -> Result<WorkBook, OdsError> {
+    read_fods_impl(fods)
+}
+
+/// Reads a flat ODS-file (FODS).
+pub fn read_fods<P: AsRef<Path>>(path: P) -> Result<WorkBook, OdsError> {
+    let file = File::open(path.as_ref())?;
+    read_fods_impl(file)
+}
+
+/// Reads a flat ODS-file.
+///
+/// Meta, settings, styles and content are all contained in one
+/// office:document. Embedded office:binary-data is moved to the
+/// FileBuf, just as if it had been read from an ODS-file.
+fn read_fods_impl<T: Read>(fods: T) -> Result<WorkBook, OdsError> {
+    let mut book = WorkBook::new_empty();
+    let mut bufstack = BufStack::new();
+
+    read_content(&mut bufstack, &mut book, &ReadOptions::default(), fods)?;
+
+    extract_binary_data(&mut book)?;
+
+    // We do some data duplication here, to make everything easier to use.
+    calc_derived(&mut book)?;
+
+    Ok(book)
 }
 
 /// Reads an ODS-file.
-fn read_ods_impl<R: Read + Seek>(mut zip: ZipArchive<R>) -> Result<WorkBook, OdsError> {
+fn read_ods_impl<R: Read + Seek>(
+    mut zip: ZipArchive<R>,
+    options: &ReadOptions,
+) -> Result<WorkBook, OdsError> {
     let mut book = WorkBook::new_empty();
     let mut bufstack = BufStack::new();
 
-    read_content(&mut bufstack, &mut book, &mut zip.by_name("content.xml")?)?;
-    read_styles(&mut bufstack, &mut book, &mut zip.by_name("styles.xml")?)?;
+    read_content(
+        &mut bufstack,
+        &mut book,
+        options,
+        zip.by_name("content.xml")?,
+    )?;
+    if !options.skip_styles {
+        read_styles(&mut bufstack, &mut book, &mut zip.by_name("styles.xml")?)?;
+    }
+    // may not exist.
+    if let Ok(mut z) = zip.by_name("meta.xml") {
+        read_meta(&mut bufstack, &mut book, &mut z)?;
+    }
     // may not exist.
-    if let Ok(mut z) = zip.by_name("settings.xml") {
+    if options.ignore_settings {
+        book.config = default_settings();
+    } else if let Ok(mut z) = zip.by_name("settings.xml") {
         read_settings(&mut bufstack, &mut book, &mut z)?;
     } else {
         book.config = default_settings();
     }
 
     // read all extras.
-    read_filebuf(&mut book, &mut zip)?;
+    if !options.skip_filebuf {
+        read_filebuf(&mut book, &mut zip)?;
+        read_images(&mut book);
+    }
 
     // We do some data duplication here, to make everything easier to use.
     calc_derived(&mut book)?;
@@ -81,6 +249,331 @@ fn read_ods_impl<R: Read + Seek>(mut zip: ZipArchive<R>) -> Result<WorkBook, Ods
     Ok(book)
 }
 
+/// Reads an ODS-file row by row without loading all the data into
+/// memory.
+///
+/// The styles and formats are read once and can be accessed via book().
+/// The WorkBook doesn't contain any sheets.
+///
+/// ```no_run
+/// use spreadsheet_ods::{OdsError, OdsRowReader};
+///
+/// let mut reader = OdsRowReader::open("tests/orders.ods")?;
+/// for row in reader.rows()? {
+///     let row = row?;
+///     for (col, cell) in row.cells() {
+///         println!("{} {} {} {:?}", row.sheet_name, row.row, col, cell.value);
+///     }
+/// }
+/// # Ok::<(), OdsError>(())
+/// ```
+#[derive(Debug)]
+pub struct OdsRowReader<R: Read + Seek> {
+    zip: ZipArchive<R>,
+    book: WorkBook,
+    bufstack: BufStack,
+    exact_numbers: bool,
+}
+
+impl OdsRowReader<File> {
+    /// Opens an ODS-file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, OdsError> {
+        let file = File::open(path.as_ref())?;
+        Self::new(file)
+    }
+}
+
+impl<R: Read + Seek> OdsRowReader<R> {
+    /// Reads an ODS-file from a reader.
+    pub fn new(ods: R) -> Result<Self, OdsError> {
+        let mut zip = ZipArchive::new(ods)?;
+        let mut book = WorkBook::new_empty();
+        let mut bufstack = BufStack::new();
+
+        read_styles(&mut bufstack, &mut book, &mut zip.by_name("styles.xml")?)?;
+        // may not exist.
+        if let Ok(mut z) = zip.by_name("meta.xml") {
+            read_meta(&mut bufstack, &mut book, &mut z)?;
+        }
+
+        Ok(Self {
+            zip,
+            book,
+            bufstack,
+            exact_numbers: false,
+        })
+    }
+
+    /// Reads float and currency values as Value::Decimal and
+    /// Value::DecimalCurrency. See [ReadOptions::exact_numbers].
+    pub fn exact_numbers(mut self) -> Self {
+        self.exact_numbers = true;
+        self
+    }
+
+    /// Styles and formats. The automatic styles from content.xml are
+    /// added while iterating the rows.
+    pub fn book(&self) -> &WorkBook {
+        &self.book
+    }
+
+    /// Returns the styles and formats.
+    pub fn into_book(self) -> WorkBook {
+        self.book
+    }
+
+    /// Iterates all rows of all sheets.
+    pub fn rows(&mut self) -> Result<OdsRows<'_>, OdsError> {
+        let zip_file = self.zip.by_name("content.xml")?;
+        Ok(OdsRows {
+            xml: quick_xml::Reader::from_reader(BufReader::new(zip_file)),
+            book: &mut self.book,
+            bs: &mut self.bufstack,
+            exact_numbers: self.exact_numbers,
+            sheet_name: Arc::new(String::new()),
+            row: 0,
+            done: false,
+        })
+    }
+}
+
+/// One row as returned by [OdsRows].
+#[derive(Debug, Clone, Default)]
+pub struct RowContent {
+    /// Sheet name. Shared by all rows of the sheet.
+    pub sheet_name: Arc<String>,
+    /// Row index.
+    pub row: u32,
+    /// Number of repeats of this row. The row is returned only once.
+    pub repeat: u32,
+    /// Cells in column order, each with the number of columns it is
+    /// repeated. Empty cells at the end of the row are dropped.
+    pub repeated_cells: Vec<(CellContent, u32)>,
+}
+
+impl RowContent {
+    /// No cells in this row.
+    pub fn is_empty(&self) -> bool {
+        self.repeated_cells.is_empty()
+    }
+
+    /// Number of columns covered by the cells.
+    pub fn len(&self) -> u32 {
+        self.repeated_cells.iter().map(|(_, repeat)| *repeat).sum()
+    }
+
+    /// Cell at the given column.
+    pub fn cell(&self, col: u32) -> Option<&CellContent> {
+        let mut start = 0;
+        for (cell, repeat) in &self.repeated_cells {
+            if col < start + *repeat {
+                return Some(cell);
+            }
+            start += *repeat;
+        }
+        None
+    }
+
+    /// Iterates the cells with their column. Repeated cells are returned
+    /// once for each column.
+    pub fn cells(&self) -> impl Iterator<Item = (u32, &CellContent)> {
+        self.repeated_cells
+            .iter()
+            .flat_map(|(cell, repeat)| (0..*repeat).map(move |_| cell))
+            .enumerate()
+            .map(|(col, cell)| (col as u32, cell))
+    }
+}
+
+/// Iterator over the rows of an ODS-file. See [OdsRowReader].
+pub struct OdsRows<'a> {
+    xml: quick_xml::Reader<BufReader<ZipFile<'a>>>,
+    book: &'a mut WorkBook,
+    bs: &'a mut BufStack,
+    exact_numbers: bool,
+    sheet_name: Arc<String>,
+    row: u32,
+    done: bool,
+}
+
+impl<'a> Debug for OdsRows<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OdsRows")
+            .field("sheet_name", &self.sheet_name)
+            .field("row", &self.row)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl<'a> OdsRows<'a> {
+    /// Styles and formats read so far.
+    pub fn book(&self) -> &WorkBook {
+        self.book
+    }
+
+    fn next_row(&mut self) -> Result<Option<RowContent>, OdsError> {
+        let mut buf = self.bs.get_buf();
+        let result = loop {
+            let evt = self.xml.read_event_into(&mut buf)?;
+            if DUMP_XML {
+                println!(" read_rows {:?}", evt);
+            }
+            match evt {
+                Event::Start(xml_tag) if xml_tag.name().as_ref() == b"office:font-face-decls" => {
+                    read_fonts(self.bs, self.book, StyleOrigin::Content, &mut self.xml)?
+                }
+                Event::Start(xml_tag) if xml_tag.name().as_ref() == b"office:automatic-styles" => {
+                    read_auto_styles(self.bs, self.book, StyleOrigin::Content, &mut self.xml)?
+                }
+
+                Event::Start(xml_tag) if xml_tag.name().as_ref() == b"table:table" => {
+                    let mut sheet = Sheet::new("");
+                    read_table_attr(&mut sheet, xml_tag)?;
+                    self.sheet_name = Arc::new(sheet.name().clone());
+                    self.row = 0;
+                }
+
+                Event::Start(xml_tag) if xml_tag.name().as_ref() == b"table:table-row" => {
+                    let (repeat, _, _, _) = read_table_row_attr(xml_tag)?;
+                    let repeated_cells = self.read_row_cells()?;
+                    let row = RowContent {
+                        sheet_name: Arc::clone(&self.sheet_name),
+                        row: self.row,
+                        repeat,
+                        repeated_cells,
+                    };
+                    self.row += repeat;
+                    break Some(row);
+                }
+
+                // descend into these.
+                Event::Start(xml_tag)
+                    if xml_tag.name().as_ref() == b"office:document-content"
+                        || xml_tag.name().as_ref() == b"office:body"
+                        || xml_tag.name().as_ref() == b"office:spreadsheet"
+                        || xml_tag.name().as_ref() == b"table:table-header-rows"
+                        || xml_tag.name().as_ref() == b"table:table-rows"
+                        || xml_tag.name().as_ref() == b"table:table-row-group" => {}
+
+                // skip anything else.
+                Event::Start(xml_tag) => {
+                    let mut skip_buf = self.bs.get_buf();
+                    self.xml.read_to_end_into(xml_tag.name(), &mut skip_buf)?;
+                    self.bs.push(skip_buf);
+                }
+
+                Event::Eof => {
+                    break None;
+                }
+                _ => {
+                    dump_unused2("read_rows", &evt)?;
+                }
+            }
+            buf.clear();
+        };
+        self.bs.push(buf);
+
+        Ok(result)
+    }
+
+    // Reads the cells of one row with their repeat count. Empty cells
+    // are kept only if they are followed by some data.
+    fn read_row_cells(&mut self) -> Result<Vec<(CellContent, u32)>, OdsError> {
+        let mut cells = Vec::new();
+        let mut pending: Vec<(Option<CellData>, u32)> = Vec::new();
+
+        let mut names = SharedNames::default();
+        let mut buf = self.bs.get_buf();
+        loop {
+            let evt = self.xml.read_event_into(&mut buf)?;
+            if DUMP_XML {
+                println!(" read_row_cells {:?}", evt);
+            }
+            let (cell, repeat) = match evt {
+                Event::Empty(xml_tag)
+                    if xml_tag.name().as_ref() == b"table:table-cell"
+                        || xml_tag.name().as_ref() == b"table:covered-table-cell" =>
+                {
+                    read_empty_table_cell_data(&mut names, xml_tag)?
+                }
+                Event::Start(xml_tag)
+                    if xml_tag.name().as_ref() == b"table:table-cell"
+                        || xml_tag.name().as_ref() == b"table:covered-table-cell" =>
+                {
+                    let (cell, repeat) = read_table_cell_data(
+                        self.bs,
+                        &mut names,
+                        &mut self.xml,
+                        xml_tag,
+                        self.exact_numbers,
+                    )?;
+                    (Some(cell), repeat)
+                }
+                Event::End(xml_tag) if xml_tag.name().as_ref() == b"table:table-row" => {
+                    break;
+                }
+                Event::Eof => {
+                    break;
+                }
+                _ => {
+                    dump_unused2("read_row_cells", &evt)?;
+                    buf.clear();
+                    continue;
+                }
+            };
+
+            let is_empty = match &cell {
+                None => true,
+                Some(cell) => {
+                    matches!(cell.value, Value::Empty)
+                        && cell.formula().is_none()
+                        && cell.annotation().is_none()
+                        && cell.images().is_empty()
+                }
+            };
+            pending.push((cell, repeat));
+            if !is_empty {
+                for (cell, repeat) in pending.drain(..) {
+                    let cell = match cell {
+                        None => CellContent::new(),
+                        Some(cell) => cell.into_content(),
+                    };
+                    cells.push((cell, repeat));
+                }
+            }
+
+            buf.clear();
+        }
+        self.bs.push(buf);
+
+        Ok(cells)
+    }
+}
+
+impl<'a> Iterator for OdsRows<'a> {
+    type Item = Result<RowContent, OdsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.next_row() {
+            Ok(Some(row)) => Some(Ok(row)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl<'a> FusedIterator for OdsRows<'a> {}
+
 // Loads all unprocessed files as byte blobs into a buffer.
 fn read_filebuf<R: Read + Seek>(
     book: &mut WorkBook,
@@ -89,9 +582,12 @@ fn read_filebuf<R: Read + Seek>(
     for idx in 0..zip.len() {
         let mut ze = zip.by_index(idx)?;
 
-        // These three are always interpreted and rewritten from scratch.
+        // These are always interpreted and rewritten from scratch.
         // They have their own mechanism to cope with unknown data.
-        if !matches!(ze.name(), "settings.xml" | "styles.xml" | "content.xml") {
+        if !matches!(
+            ze.name(),
+            "settings.xml" | "styles.xml" | "content.xml" | "meta.xml"
+        ) {
             if ze.is_dir() {
                 book.filebuf.push_dir(ze.name());
             } else if ze.is_file() {
@@ -105,6 +601,67 @@ fn read_filebuf<R: Read + Seek>(
     Ok(())
 }
 
+// Moves all office:binary-data into the filebuf and replaces it with
+// a link.
+fn extract_binary_data(book: &mut WorkBook) -> Result<(), OdsError> {
+    for tag in book.extra.iter_mut() {
+        extract_binary_tag(&mut book.filebuf, tag)?;
+    }
+    for sheet in book.sheets.iter_mut() {
+        for tag in sheet.extra.iter_mut() {
+            extract_binary_tag(&mut book.filebuf, tag)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn extract_binary_tag(filebuf: &mut FileBuf, tag: &mut XmlTag) -> Result<(), OdsError> {
+    if tag.name() == "draw:image" {
+        let pos = tag
+            .content()
+            .iter()
+            .position(|v| matches!(v, XmlContent::Tag(t) if t.name() == "office:binary-data"));
+        if let Some(pos) = pos {
+            if let XmlContent::Tag(data) = tag.content_mut().remove(pos) {
+                let mut text = String::new();
+                data.extract_text(&mut text);
+                text.retain(|c| !c.is_ascii_whitespace());
+                let buf = STANDARD.decode(text).map_err(|e| {
+                    OdsError::Parse("invalid office:binary-data", Some(e.to_string()))
+                })?;
+
+                let ext = match tag.attrmap().attr("loext:mime-type") {
+                    Some(v) => media_type_ext(v),
+                    None => "bin",
+                };
+                let mut idx = 0;
+                let name = loop {
+                    idx += 1;
+                    let name = format!("Pictures/embedded{}.{}", idx, ext);
+                    if !filebuf.contains(&name) {
+                        break name;
+                    }
+                };
+
+                tag.set_attr("xlink:href", name.as_str());
+                tag.set_attr("xlink:type", "simple");
+                tag.set_attr("xlink:show", "embed");
+                tag.set_attr("xlink:actuate", "onLoad");
+                filebuf.push_file(name, buf);
+            }
+        }
+    }
+
+    for content in tag.content_mut() {
+        if let XmlContent::Tag(t) = content {
+            extract_binary_tag(filebuf, t)?;
+        }
+    }
+
+    Ok(())
+}
+
 // Sets some values from the styles on the corresponding data fields.
 fn calc_derived(book: &mut WorkBook) -> Result<(), OdsError> {
     let v = book
@@ -208,19 +765,23 @@ fn calc_derived(book: &mut WorkBook) -> Result<(), OdsError> {
     Ok(())
 }
 
-// Reads the content.xml
-fn read_content(
+// Reads the content.xml or the complete flat xml.
+fn read_content<T: Read>(
     bs: &mut BufStack,
     book: &mut WorkBook,
-    zip_file: &mut ZipFile<'_>,
+    options: &ReadOptions,
+    read: T,
 ) -> Result<(), OdsError> {
     // xml parser
-    let mut xml = quick_xml::Reader::from_reader(BufReader::new(zip_file));
+    let mut xml = quick_xml::Reader::from_reader(BufReader::new(read));
     // Do not trim text data. All text read contains significant whitespace.
     // The rest is ignored anyway.
     //
     // xml.trim_text(true);
 
+    // Common styles, fonts and master-pages go to styles.xml for a flat xml.
+    let mut origin = StyleOrigin::Content;
+
     let mut buf = bs.get_buf();
     loop {
         let evt = xml.read_event_into(&mut buf)?;
@@ -259,13 +820,68 @@ fn read_content(
                 // noop
             }
 
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"office:document" => {
+                origin = StyleOrigin::Styles;
+                for attr in xml_tag.attributes().with_checks(false) {
+                    match attr? {
+                        attr if attr.key.as_ref() == b"office:version" => {
+                            book.set_version(attr.unescape_value()?.to_string());
+                        }
+                        _ => {
+                            // noop
+                        }
+                    }
+                }
+            }
+            Event::End(xml_tag)
+            if xml_tag.name().as_ref() == b"office:document" => {
+                // noop
+            }
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"office:meta" => {
+                read_office_meta(bs, book, &mut xml)?;
+            }
+
+            Event::Start(xml_tag)
+            if options.skip_styles && (
+                xml_tag.name().as_ref() == b"office:font-face-decls" ||
+                    xml_tag.name().as_ref() == b"office:styles" ||
+                    xml_tag.name().as_ref() == b"office:automatic-styles" ||
+                    xml_tag.name().as_ref() == b"office:master-styles") => {
+                let mut skip_buf = bs.get_buf();
+                xml.read_to_end_into(xml_tag.name(), &mut skip_buf)?;
+                bs.push(skip_buf);
+            }
+
+            Event::Start(xml_tag)
+            if options.ignore_settings && xml_tag.name().as_ref() == b"office:settings" => {
+                let mut skip_buf = bs.get_buf();
+                xml.read_to_end_into(xml_tag.name(), &mut skip_buf)?;
+                bs.push(skip_buf);
+            }
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table"
+                && !options.read_sheet(&read_table_name(&xml_tag)?) => {
+                let mut skip_buf = bs.get_buf();
+                xml.read_to_end_into(xml_tag.name(), &mut skip_buf)?;
+                bs.push(skip_buf);
+            }
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"office:settings" => {
+                book.config = Detach::new(read_office_settings(bs, &mut xml)?);
+            }
+
             Event::Start(xml_tag)
             if xml_tag.name().as_ref() == b"office:font-face-decls" =>
-                read_fonts(bs, book, StyleOrigin::Content, &mut xml)?,
+                read_fonts(bs, book, origin, &mut xml)?,
 
             Event::Start(xml_tag)
             if xml_tag.name().as_ref() == b"office:styles" =>
-                read_styles_tag(bs, book, StyleOrigin::Content, &mut xml)?,
+                read_styles_tag(bs, book, origin, &mut xml)?,
 
             Event::Start(xml_tag)
             if xml_tag.name().as_ref() == b"office:automatic-styles" =>
@@ -273,15 +889,35 @@ fn read_content(
 
             Event::Start(xml_tag)
             if xml_tag.name().as_ref() == b"office:master-styles" =>
-                read_master_styles(bs, book, StyleOrigin::Content, &mut xml)?,
+                read_master_styles(bs, book, origin, &mut xml)?,
 
             Event::Start(xml_tag)
             if xml_tag.name().as_ref() == b"table:content-validations" =>
                 read_validations(bs, book, &mut xml)?,
 
             Event::Start(xml_tag)
-            if xml_tag.name().as_ref() == b"table:table" =>
-                book.push_sheet(read_table(bs, &mut xml, xml_tag)?),
+            if xml_tag.name().as_ref() == b"table:table" => {
+                let sheet = read_table(bs, book, &mut xml, xml_tag, options.exact_numbers)?;
+                book.push_sheet(sheet);
+            }
+
+            // Up to 0.16 the conditional formats were written after the
+            // end of the table they belong to.
+            Event::Start(xml_tag) |
+            Event::Empty(xml_tag)
+            if xml_tag.name().as_ref() == b"calcext:conditional-formats"
+                && book.num_sheets() > 0 => {
+                let sheet = book.sheet_mut(book.num_sheets() - 1);
+                read_conditional_formats(bs, sheet, &mut xml, &xml_tag, empty_tag)?;
+            }
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:named-expressions" =>
+                read_named_expressions(bs, book, NameScope::Global, &mut xml)?,
+            Event::Empty(xml_tag)
+            if xml_tag.name().as_ref() == b"table:named-expressions" => {
+                // noop
+            }
 
             Event::Empty(xml_tag) |
             Event::Start(xml_tag)
@@ -295,7 +931,6 @@ fn read_content(
                 xml_tag.name().as_ref() == b"table:calculation-settings" ||
                 xml_tag.name().as_ref() == b"table:label-ranges" ||
                 /* epilogue */
-                xml_tag.name().as_ref() == b"table:named-expressions" ||
                 xml_tag.name().as_ref() == b"table:database-ranges" ||
                 xml_tag.name().as_ref() == b"table:data-pilot-tables" ||
                 xml_tag.name().as_ref() == b"table:consolidation" ||
@@ -315,7 +950,6 @@ fn read_content(
                 xml_tag.name().as_ref() == b"table:calculation-settings" ||
                 xml_tag.name().as_ref() == b"table:label-ranges" ||
                 /* epilogue */
-                xml_tag.name().as_ref() == b"table:named-expressions" ||
                 xml_tag.name().as_ref() == b"table:database-ranges" ||
                 xml_tag.name().as_ref() == b"table:data-pilot-tables" ||
                 xml_tag.name().as_ref() == b"table:consolidation" ||
@@ -339,12 +973,32 @@ fn read_content(
 }
 
 // Reads the table.
-fn read_table(
+/// Shares the style and validation names of the cells while reading
+/// a table. All cells with the same style point to one string.
+#[derive(Debug, Default)]
+struct SharedNames(HashMap<String, Arc<String>>);
+
+impl SharedNames {
+    fn get(&mut self, name: &str) -> Arc<String> {
+        if let Some(v) = self.0.get(name) {
+            v.clone()
+        } else {
+            let v = Arc::new(name.to_string());
+            self.0.insert(name.to_string(), v.clone());
+            v
+        }
+    }
+}
+
+fn read_table<R: BufRead>(
     bs: &mut BufStack,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    book: &mut WorkBook,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: BytesStart<'_>,
+    exact_numbers: bool,
 ) -> Result<Sheet, OdsError> {
     let mut sheet = Sheet::new("");
+    let mut names = SharedNames::default();
 
     read_table_attr(&mut sheet, xml_tag)?;
 
@@ -364,6 +1018,10 @@ fn read_table(
     let mut col_range_from = 0;
     let mut row_range_from = 0;
 
+    // Open groups as (start, display).
+    let mut col_group_from: Vec<(u32, bool)> = Vec::new();
+    let mut row_group_from: Vec<(u32, bool)> = Vec::new();
+
     let mut buf = bs.get_buf();
     loop {
         let evt = xml.read_event_into(&mut buf)?;
@@ -398,14 +1056,33 @@ fn read_table(
                 xml_tag.name().as_ref() == b"table:table-source" ||
                 xml_tag.name().as_ref() == b"office:dde-source" ||
                 xml_tag.name().as_ref() == b"table:scenario" ||
-                xml_tag.name().as_ref() == b"office:forms" ||
-                xml_tag.name().as_ref() == b"table:shapes" ||
-                /* epilogue */
-                xml_tag.name().as_ref() == b"table:named-expressions" ||
-                xml_tag.name().as_ref() == b"calcext:conditional-formats" => {
+                xml_tag.name().as_ref() == b"office:forms" => {
                 sheet.extra.push(read_xml(bs, xml_tag.name().as_ref(), xml, &xml_tag, empty_tag)?);
             }
 
+            Event::Start(xml_tag) |
+            Event::Empty(xml_tag)
+            if xml_tag.name().as_ref() == b"table:shapes" => {
+                let shapes = read_xml(bs, b"table:shapes", xml, &xml_tag, empty_tag)?;
+                read_shapes(&mut sheet, shapes)?;
+            }
+
+            Event::Start(xml_tag) |
+            Event::Empty(xml_tag)
+            if xml_tag.name().as_ref() == b"calcext:conditional-formats" => {
+                read_conditional_formats(bs, &mut sheet, xml, &xml_tag, empty_tag)?;
+            }
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:named-expressions" => {
+                let scope = NameScope::Sheet(sheet.name().clone());
+                read_named_expressions(bs, book, scope, xml)?;
+            }
+            Event::Empty(xml_tag)
+            if xml_tag.name().as_ref() == b"table:named-expressions" => {
+                // noop
+            }
+
             Event::End(xml_tag)
             if /* prelude */ xml_tag.name().as_ref() == b"table:title" ||
                 xml_tag.name().as_ref() == b"table:desc" ||
@@ -413,10 +1090,7 @@ fn read_table(
                 xml_tag.name().as_ref() == b"office:dde-source" ||
                 xml_tag.name().as_ref() == b"table:scenario" ||
                 xml_tag.name().as_ref() == b"office:forms" ||
-                xml_tag.name().as_ref() == b"table:shapes" ||
-                /* epilogue */
-                xml_tag.name().as_ref() == b"table:named-expressions" ||
-                xml_tag.name().as_ref() == b"calcext:conditional-formats" => {}
+                xml_tag.name().as_ref() == b"table:shapes" => {}
 
             Event::Start(xml_tag)
             if xml_tag.name().as_ref() == b"table:table-header-columns" => {
@@ -428,6 +1102,20 @@ fn read_table(
                 sheet.header_cols = Some(ColRange::new(col_range_from, table_col - 1));
             }
 
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table-column-group" => {
+                col_group_from.push((table_col, read_group_display(&xml_tag)?));
+            }
+
+            Event::End(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table-column-group" => {
+                if let Some((from, display)) = col_group_from.pop() {
+                    if table_col > from {
+                        sheet.add_col_group(from, table_col - 1, display)?;
+                    }
+                }
+            }
+
             Event::Empty(xml_tag)
             if xml_tag.name().as_ref() == b"table:table-column" => {
                 table_col = read_table_col_attr(&mut sheet, table_col,  &xml_tag)?;
@@ -443,6 +1131,20 @@ fn read_table(
                 sheet.header_rows = Some(RowRange::new(row_range_from, row - 1));
             }
 
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table-row-group" => {
+                row_group_from.push((row, read_group_display(&xml_tag)?));
+            }
+
+            Event::End(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table-row-group" => {
+                if let Some((from, display)) = row_group_from.pop() {
+                    if row > from {
+                        sheet.add_row_group(from, row - 1, display)?;
+                    }
+                }
+            }
+
             Event::Start(xml_tag)
             if xml_tag.name().as_ref() == b"table:table-row" => {
                 let (repeat, style, cellstyle, visible) = read_table_row_attr( xml_tag)?;
@@ -477,12 +1179,12 @@ fn read_table(
 
             Event::Empty(xml_tag)
             if xml_tag.name().as_ref() == b"table:table-cell" || xml_tag.name().as_ref() == b"table:covered-table-cell" => {
-                col = read_empty_table_cell(&mut sheet, row, col, xml_tag)?;
+                col = read_empty_table_cell(&mut names, &mut sheet, row, col, xml_tag)?;
             }
 
             Event::Start(xml_tag)
             if xml_tag.name().as_ref() == b"table:table-cell" || xml_tag.name().as_ref() == b"table:covered-table-cell" => {
-                col = read_table_cell2(bs, &mut sheet, row, col, xml, xml_tag)?;
+                col = read_table_cell2(bs, &mut names, &mut sheet, row, col, xml, xml_tag, exact_numbers)?;
             }
 
             _ => {
@@ -496,7 +1198,180 @@ fn read_table(
     Ok(sheet)
 }
 
+// Reads calcext:conditional-formats.
+fn read_conditional_formats<R: BufRead>(
+    bs: &mut BufStack,
+    sheet: &mut Sheet,
+    xml: &mut quick_xml::Reader<R>,
+    xml_tag: &BytesStart<'_>,
+    empty_tag: bool,
+) -> Result<(), OdsError> {
+    let tag = read_xml(bs, b"calcext:conditional-formats", xml, xml_tag, empty_tag)?;
+
+    for cf_tag in xml_tags(&tag, "calcext:conditional-format") {
+        let ranges = if let Some(v) = cf_tag.attrmap().attr("calcext:target-range-address") {
+            parse_cellranges(v)?.unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        let mut cf = ConditionalFormat::new_ranges(ranges);
+
+        for content in cf_tag.content() {
+            let entry_tag = match content {
+                XmlContent::Tag(v) => v,
+                XmlContent::Text(_) => continue,
+            };
+            // Anything unknown or incomplete is kept as it is.
+            match read_conditional_entry(entry_tag) {
+                Ok(Some(entry)) => cf.push_entry(entry),
+                Ok(None) | Err(_) => {
+                    if DUMP_UNUSED {
+                        println!(" read_conditional_formats unused {}", entry_tag.name());
+                    }
+                    cf.push_entry(ConditionalEntry::Xml(entry_tag.clone()));
+                }
+            }
+        }
+
+        sheet.add_conditional_format(cf);
+    }
+
+    Ok(())
+}
+
+// Reads one entry of a calcext:conditional-format. Returns None if the
+// entry is unknown or incomplete.
+fn read_conditional_entry(entry_tag: &XmlTag) -> Result<Option<ConditionalEntry>, OdsError> {
+    let attr = entry_tag.attrmap();
+    let entry = match entry_tag.name() {
+        "calcext:condition" => {
+            let value = attr.attr("calcext:value");
+            let style = attr.attr("calcext:apply-style-name");
+            let (value, style) = match (value, style) {
+                (Some(value), Some(style)) => (value, style),
+                _ => return Ok(None),
+            };
+            let mut entry =
+                ConditionEntry::new(condition_from_calcext(value), &style.as_str().into());
+            if let Some(v) = attr.attr("calcext:base-cell-address") {
+                entry.set_base_cell(Some(parse_cellref(v)?));
+            }
+            entry.into()
+        }
+        "calcext:date-is" => {
+            let date = attr.attr("calcext:date");
+            let style = attr.attr("calcext:style");
+            let (date, style) = match (date, style) {
+                (Some(date), Some(style)) => (date, style),
+                _ => return Ok(None),
+            };
+            DateEntry::new(date.as_str().try_into()?, &style.as_str().into()).into()
+        }
+        "calcext:color-scale" => {
+            let mut scale = ColorScale::new();
+            for v in xml_tags(entry_tag, "calcext:color-scale-entry") {
+                scale.push_entry(read_scale_entry(v)?);
+            }
+            scale.into()
+        }
+        "calcext:data-bar" => {
+            let positive_color = match attr.attr("calcext:positive-color") {
+                Some(v) => parse_color(v.as_bytes())?,
+                None => Rgb::new(0, 0, 255),
+            };
+            let mut bar = DataBar::new(positive_color);
+            if let Some(v) = attr.attr("calcext:negative-color") {
+                bar.set_negative_color(Some(parse_color(v.as_bytes())?));
+            }
+            if let Some(v) = attr.attr("calcext:axis-color") {
+                bar.set_axis_color(Some(parse_color(v.as_bytes())?));
+            }
+            if let Some(v) = attr.attr("calcext:gradient") {
+                bar.set_gradient(Some(parse_bool(v.as_bytes())?));
+            }
+            if let Some(v) = attr.attr("calcext:show-value") {
+                bar.set_show_value(Some(parse_bool(v.as_bytes())?));
+            }
+            if let Some(v) = attr.attr("calcext:min-length") {
+                bar.set_min_length(Some(parse_f64(v.as_bytes())?));
+            }
+            if let Some(v) = attr.attr("calcext:max-length") {
+                bar.set_max_length(Some(parse_f64(v.as_bytes())?));
+            }
+            let mut it = xml_tags(entry_tag, "calcext:formatting-entry");
+            if let Some(v) = it.next() {
+                bar.set_min(read_scale_entry(v)?);
+            }
+            if let Some(v) = it.next() {
+                bar.set_max(read_scale_entry(v)?);
+            }
+            bar.into()
+        }
+        "calcext:icon-set" => {
+            let icon_set_type = match attr.attr("calcext:icon-set-type") {
+                Some(v) => v.as_str(),
+                None => return Ok(None),
+            };
+            let mut set = IconSet::new(icon_set_type);
+            if let Some(v) = attr.attr("calcext:show-value") {
+                set.set_show_value(Some(parse_bool(v.as_bytes())?));
+            }
+            for v in xml_tags(entry_tag, "calcext:formatting-entry") {
+                set.push_entry(read_scale_entry(v)?);
+            }
+            set.into()
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(entry))
+}
+
+// Child tags with the given name.
+fn xml_tags<'a>(tag: &'a XmlTag, name: &'a str) -> impl Iterator<Item = &'a XmlTag> {
+    tag.content().iter().filter_map(move |v| match v {
+        XmlContent::Tag(v) if v.name() == name => Some(v),
+        _ => None,
+    })
+}
+
+// Reads calcext:formatting-entry and calcext:color-scale-entry.
+fn read_scale_entry(tag: &XmlTag) -> Result<ScaleEntry, OdsError> {
+    let attr = tag.attrmap();
+    let scale_type = match attr.attr("calcext:type") {
+        Some(v) => ScaleType::try_from(v.as_str())?,
+        None => ScaleType::Number,
+    };
+    let value = attr.attr("calcext:value").cloned().unwrap_or_default();
+    let mut entry = ScaleEntry::new(scale_type, value);
+    if let Some(v) = attr.attr("calcext:color") {
+        entry.set_color(Some(parse_color(v.as_bytes())?));
+    }
+    Ok(entry)
+}
+
+// Reads table:display of a row or column group.
+fn read_group_display(xml_tag: &BytesStart<'_>) -> Result<bool, OdsError> {
+    for attr in xml_tag.attributes().with_checks(false) {
+        let attr = attr?;
+        if attr.key.as_ref() == b"table:display" {
+            return parse_bool(&attr.value);
+        }
+    }
+    Ok(true)
+}
+
 // Reads the table attributes.
+// Only the name of the table.
+fn read_table_name(xml_tag: &BytesStart<'_>) -> Result<String, OdsError> {
+    for attr in xml_tag.attributes().with_checks(false) {
+        let attr = attr?;
+        if attr.key.as_ref() == b"table:name" {
+            return Ok(attr.unescape_value()?.to_string());
+        }
+    }
+    Ok(String::new())
+}
+
 fn read_table_attr(sheet: &mut Sheet, xml_tag: BytesStart<'_>) -> Result<(), OdsError> {
     for attr in xml_tag.attributes().with_checks(false) {
         match attr? {
@@ -616,45 +1491,67 @@ enum TextContent2 {
 #[derive(Debug)]
 struct ReadTableCell2 {
     val_type: ValueType,
-    val_datetime: Option<NaiveDateTime>,
-    val_duration: Option<Duration>,
+    val_date: Option<Value>,
+    val_time: Option<Value>,
     val_float: Option<f64>,
+    val_decimal: Option<Decimal>,
     val_bool: Option<bool>,
     val_string: Option<String>,
     val_currency: Option<String>,
+    val_error: bool,
 
     content: TextContent2,
 }
 
-fn read_table_cell2(
+#[allow(clippy::too_many_arguments)]
+fn read_table_cell2<R: BufRead>(
     bs: &mut BufStack,
+    names: &mut SharedNames,
     sheet: &mut Sheet,
     row: u32,
     mut col: u32,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: BytesStart<'_>,
+    exact_numbers: bool,
 ) -> Result<u32, OdsError> {
+    let (cell, mut cell_repeat) = read_table_cell_data(bs, names, xml, xml_tag, exact_numbers)?;
+
+    while cell_repeat > 1 {
+        sheet.add_cell_data(row, col, cell.clone());
+        col += 1;
+        cell_repeat -= 1;
+    }
+    sheet.add_cell_data(row, col, cell);
+    col += 1;
+
+    Ok(col)
+}
+
+// Reads a table-cell and returns the cell and the repeat count.
+fn read_table_cell_data<R: BufRead>(
+    bs: &mut BufStack,
+    names: &mut SharedNames,
+    xml: &mut quick_xml::Reader<R>,
+    xml_tag: BytesStart<'_>,
+    exact_numbers: bool,
+) -> Result<(CellData, u32), OdsError> {
     // Current cell tag
     let tag_name = xml_tag.name();
 
     let mut cell_repeat: u32 = 1;
 
-    let mut cell = CellData {
-        value: Default::default(),
-        formula: None,
-        style: None,
-        validation_name: None,
-        span: Default::default(),
-    };
+    let mut cell = CellData::new();
 
     let mut tc = ReadTableCell2 {
         val_type: ValueType::Empty,
-        val_datetime: None,
-        val_duration: None,
+        val_date: None,
+        val_time: None,
         val_float: None,
+        val_decimal: None,
         val_bool: None,
         val_string: None,
         val_currency: None,
+        val_error: false,
         content: TextContent2::Empty,
     };
 
@@ -664,16 +1561,23 @@ fn read_table_cell2(
                 cell_repeat = parse_u32(&attr.value)?;
             }
             attr if attr.key.as_ref() == b"table:number-rows-spanned" => {
-                cell.span.row_span = parse_u32(&attr.value)?;
+                cell.extra_mut().span.row_span = parse_u32(&attr.value)?;
             }
             attr if attr.key.as_ref() == b"table:number-columns-spanned" => {
-                cell.span.col_span = parse_u32(&attr.value)?;
+                cell.extra_mut().span.col_span = parse_u32(&attr.value)?;
+            }
+            attr if attr.key.as_ref() == b"table:number-matrix-rows-spanned" => {
+                cell.extra_mut().matrix_span.row_span = parse_u32(&attr.value)?;
+            }
+            attr if attr.key.as_ref() == b"table:number-matrix-columns-spanned" => {
+                cell.extra_mut().matrix_span.col_span = parse_u32(&attr.value)?;
             }
             attr if attr.key.as_ref() == b"table:content-validation-name" => {
-                cell.validation_name = Some(attr.unescape_value()?.to_string());
+                cell.extra_mut().validation_name = Some(names.get(&attr.unescape_value()?));
             }
             attr if attr.key.as_ref() == b"calcext:value-type" => {
-                // not used. office:value-type seems to be good enough.
+                // only needed for errors. office:value-type is good enough otherwise.
+                tc.val_error = attr.value.as_ref() == b"error";
             }
             attr if attr.key.as_ref() == b"office:value-type" => {
                 tc.val_type = match attr.value.as_ref() {
@@ -693,13 +1597,16 @@ fn read_table_cell2(
                 }
             }
             attr if attr.key.as_ref() == b"office:date-value" => {
-                tc.val_datetime = Some(parse_datetime(&attr.value)?);
+                tc.val_date = Some(parse_date_value(&attr.value)?);
             }
             attr if attr.key.as_ref() == b"office:time-value" => {
-                tc.val_duration = Some(parse_duration(&attr.value)?);
+                tc.val_time = Some(Value::TimeDuration(parse_duration(&attr.value)?));
             }
             attr if attr.key.as_ref() == b"office:value" => {
                 tc.val_float = Some(parse_f64(&attr.value)?);
+                if exact_numbers {
+                    tc.val_decimal = parse_decimal(&attr.value);
+                }
             }
             attr if attr.key.as_ref() == b"office:boolean-value" => {
                 tc.val_bool = Some(parse_bool(&attr.value)?);
@@ -711,10 +1618,10 @@ fn read_table_cell2(
                 tc.val_currency = Some(parse_currency(&attr.value)?);
             }
             attr if attr.key.as_ref() == b"table:formula" => {
-                cell.formula = Some(attr.unescape_value()?.to_string());
+                cell.extra_mut().formula = Some(attr.unescape_value()?.to_string());
             }
             attr if attr.key.as_ref() == b"table:style-name" => {
-                cell.style = Some(attr.unescape_value()?.to_string());
+                cell.style = Some(names.get(&attr.unescape_value()?));
             }
             attr => {
                 dump_unused("read_table_cell2", xml_tag.name().as_ref(), &attr)?;
@@ -736,18 +1643,19 @@ fn read_table_cell2(
             Event::Empty(xml_tag) if xml_tag.name().as_ref() == b"text:p" => {
                 // noop
             }
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"office:annotation" => {
+                cell.extra_mut().annotation = Some(read_annotation(bs, xml, &xml_tag)?);
+            }
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"draw:frame" => {
+                let frame = read_xml(bs, b"draw:frame", xml, &xml_tag, false)?;
+                // Other frames (charts, ole-objects) are not kept.
+                if let Some(image) = read_image(frame)? {
+                    cell.extra_mut().images.push(image);
+                }
+            }
 
             Event::End(xml_tag) if xml_tag.name() == tag_name => {
                 parse_value2(tc, &mut cell)?;
-
-                while cell_repeat > 1 {
-                    sheet.add_cell_data(row, col, cell.clone());
-                    col += 1;
-                    cell_repeat -= 1;
-                }
-                sheet.add_cell_data(row, col, cell);
-                col += 1;
-
                 break;
             }
 
@@ -764,7 +1672,200 @@ fn read_table_cell2(
     }
     bs.push(buf);
 
-    Ok(col)
+    Ok((cell, cell_repeat))
+}
+
+// Converts a draw:frame that contains a draw:image.
+fn read_image(frame: XmlTag) -> Result<Option<Image>, OdsError> {
+    let mut image = Image::new_empty();
+    let mut found = false;
+
+    for (k, v) in frame.attrmap().iter() {
+        match k.as_ref() {
+            "draw:name" => image.set_name(v.as_str()),
+            "draw:style-name" => image.set_style(&v.into()),
+            "svg:x" | "svg:y" | "svg:width" | "svg:height" => {}
+            _ => image.attrmap_mut().set_attr(k.as_ref(), v.clone()),
+        }
+    }
+    let attr = frame.attrmap();
+    image.set_offset(
+        Length::parse_attr(attr.attr("svg:x"))?.unwrap_or_default(),
+        Length::parse_attr(attr.attr("svg:y"))?.unwrap_or_default(),
+    );
+    image.set_size(
+        Length::parse_attr(attr.attr("svg:width"))?.unwrap_or_default(),
+        Length::parse_attr(attr.attr("svg:height"))?.unwrap_or_default(),
+    );
+
+    for tag in frame.content().iter().filter_map(|v| match v {
+        XmlContent::Tag(t) => Some(t),
+        XmlContent::Text(_) => None,
+    }) {
+        match tag.name() {
+            "draw:image" if !found => {
+                found = true;
+                if let Some(href) = tag.attrmap().attr("xlink:href") {
+                    image.set_href(href.as_str());
+                }
+                if let Some(mime) = tag
+                    .attrmap()
+                    .attr("loext:mime-type")
+                    .or_else(|| tag.attrmap().attr("draw:mime-type"))
+                {
+                    image.set_mime(mime.as_str());
+                } else {
+                    image.set_mime(media_type(image.href()));
+                }
+                for data in tag.content() {
+                    if let XmlContent::Tag(data) = data {
+                        if data.name() == "office:binary-data" {
+                            let mut text = String::new();
+                            data.extract_text(&mut text);
+                            text.retain(|c| !c.is_ascii_whitespace());
+                            image.set_data(STANDARD.decode(text).map_err(|e| {
+                                OdsError::Parse("invalid office:binary-data", Some(e.to_string()))
+                            })?);
+                        }
+                    }
+                }
+            }
+            "svg:title" => {
+                let mut text = String::new();
+                tag.extract_text(&mut text);
+                image.set_title(Some(text));
+            }
+            "svg:desc" => {
+                let mut text = String::new();
+                tag.extract_text(&mut text);
+                image.set_desc(Some(text));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(if found { Some(image) } else { None })
+}
+
+// Images anchored to the page are converted, all other shapes are
+// kept as they are.
+fn read_shapes(sheet: &mut Sheet, mut shapes: XmlTag) -> Result<(), OdsError> {
+    let mut other = Vec::new();
+    for content in std::mem::take(shapes.content_mut()) {
+        match content {
+            XmlContent::Tag(frame) if frame.name() == "draw:frame" && is_image_frame(&frame) => {
+                if let Some(image) = read_image(frame)? {
+                    sheet.page_images.push(image);
+                }
+            }
+            XmlContent::Tag(tag) => other.push(XmlContent::Tag(tag)),
+            XmlContent::Text(_) => {}
+        }
+    }
+    if !other.is_empty() {
+        *shapes.content_mut() = other;
+        sheet.extra.push(shapes);
+    }
+    Ok(())
+}
+
+fn is_image_frame(frame: &XmlTag) -> bool {
+    frame
+        .content()
+        .iter()
+        .any(|v| matches!(v, XmlContent::Tag(t) if t.name() == "draw:image"))
+}
+
+// Moves the data of all images from the filebuf to the images.
+fn read_images(book: &mut WorkBook) {
+    let mut hrefs = Vec::new();
+    for sheet in book.sheets.iter_mut() {
+        for image in sheet.all_images_mut() {
+            if image.data().is_empty() {
+                if let Some(data) = book.filebuf.get(image.href()) {
+                    image.set_data(data.to_vec());
+                    hrefs.push(image.href().to_string());
+                }
+            }
+        }
+    }
+    for href in hrefs {
+        book.filebuf.remove(href);
+    }
+}
+
+// Reads an office:annotation.
+fn read_annotation<R: BufRead>(
+    bs: &mut BufStack,
+    xml: &mut quick_xml::Reader<R>,
+    xml_tag: &BytesStart<'_>,
+) -> Result<Annotation, OdsError> {
+    let mut annotation = Annotation::new_empty();
+
+    for attr in xml_tag.attributes().with_checks(false) {
+        match attr? {
+            attr if attr.key.as_ref() == b"office:display" => {
+                annotation.set_display(parse_bool(&attr.value)?);
+            }
+            attr if attr.key.as_ref() == b"office:name" => {
+                annotation.set_name(attr.unescape_value()?.to_string());
+            }
+            attr => {
+                let k = xml.decoder().decode(attr.key.as_ref())?;
+                let v = attr.unescape_value()?.to_string();
+                annotation.attrmap_mut().set_attr(&k, v);
+            }
+        }
+    }
+
+    let mut buf = bs.get_buf();
+    loop {
+        let evt = xml.read_event_into(&mut buf)?;
+        let empty_tag = matches!(evt, Event::Empty(_));
+        if DUMP_XML {
+            println!(" read_annotation {:?}", evt);
+        }
+        match evt {
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"dc:creator" => {
+                match read_text_or_tag(bs, b"dc:creator", xml, &xml_tag, false)? {
+                    TextContent2::Text(txt) => annotation.set_author(Some(txt)),
+                    _ => annotation.set_author(None),
+                }
+            }
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"dc:date" => {
+                if let TextContent2::Text(txt) =
+                    read_text_or_tag(bs, b"dc:date", xml, &xml_tag, false)?
+                {
+                    annotation.set_date(Some(parse_datetime(txt.as_bytes())?));
+                }
+            }
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"meta:date-string" => {
+                // only for display, ignored.
+                read_text_or_tag(bs, b"meta:date-string", xml, &xml_tag, false)?;
+            }
+            Event::Start(xml_tag) | Event::Empty(xml_tag)
+                if xml_tag.name().as_ref() == b"text:p"
+                    || xml_tag.name().as_ref() == b"text:list" =>
+            {
+                let name = xml_tag.name();
+                annotation.push_text(read_xml(bs, name.as_ref(), xml, &xml_tag, empty_tag)?);
+            }
+            Event::End(xml_tag) if xml_tag.name().as_ref() == b"office:annotation" => {
+                break;
+            }
+            Event::Eof => {
+                break;
+            }
+            _ => {
+                dump_unused2("read_annotation", &evt)?;
+            }
+        }
+
+        buf.clear();
+    }
+    bs.push(buf);
+
+    Ok(annotation)
 }
 
 fn append_text(new_txt: TextContent2, mut content: TextContent2) -> TextContent2 {
@@ -831,7 +1932,12 @@ fn append_text(new_txt: TextContent2, mut content: TextContent2) -> TextContent2
 }
 
 fn parse_value2(tc: ReadTableCell2, cell: &mut CellData) -> Result<(), OdsError> {
-    match tc.val_type {
+    let val_type = if tc.val_error {
+        ValueType::Error
+    } else {
+        tc.val_type
+    };
+    match val_type {
         ValueType::Empty => {
             // noop
         }
@@ -848,6 +1954,9 @@ fn parse_value2(tc: ReadTableCell2, cell: &mut CellData) -> Result<(), OdsError>
             } else {
                 return Err(OdsError::Parse("no float value", None));
             }
+            if let Some(v) = tc.val_decimal {
+                cell.value = Value::Decimal(v);
+            }
         }
         ValueType::Percentage => {
             if let Some(v) = tc.val_float {
@@ -860,6 +1969,9 @@ fn parse_value2(tc: ReadTableCell2, cell: &mut CellData) -> Result<(), OdsError>
             if let Some(v) = tc.val_float {
                 if let Some(c) = tc.val_currency {
                     cell.value = Value::Currency(v, c);
+                    if let Some(v) = tc.val_decimal {
+                        cell.value = Value::DecimalCurrency(v, cell.value.currency().to_string());
+                    }
                 } else {
                     return Err(OdsError::Parse("no currency value", None));
                 }
@@ -890,20 +2002,29 @@ fn parse_value2(tc: ReadTableCell2, cell: &mut CellData) -> Result<(), OdsError>
         ValueType::TextXml => {
             unreachable!();
         }
-        ValueType::DateTime => {
-            if let Some(v) = tc.val_datetime {
-                cell.value = Value::DateTime(v);
+        ValueType::DateTime | ValueType::Date => {
+            if let Some(v) = tc.val_date {
+                cell.value = v;
             } else {
                 return Err(OdsError::Parse("no datetime value", None));
             }
         }
-        ValueType::TimeDuration => {
-            if let Some(v) = tc.val_duration {
-                cell.value = Value::TimeDuration(v);
+        ValueType::TimeDuration | ValueType::TimeOfDay => {
+            if let Some(v) = tc.val_time {
+                cell.value = v;
             } else {
                 return Err(OdsError::Parse("no duration value", None));
             }
         }
+        ValueType::Error => {
+            // Unknown errors like "Err:502" are kept as text.
+            if let TextContent2::Text(txt) = tc.content {
+                cell.value = match ErrorKind::try_from(txt.as_str()) {
+                    Ok(e) => Value::Error(e),
+                    Err(_) => Value::Text(txt),
+                };
+            }
+        }
     }
 
     Ok(())
@@ -913,11 +2034,35 @@ fn parse_value2(tc: ReadTableCell2, cell: &mut CellData) -> Result<(), OdsError>
 /// There seems to be no data associated, but it can have a style and a formula.
 /// And first of all we need the repeat count for the correct placement.
 fn read_empty_table_cell(
+    names: &mut SharedNames,
     sheet: &mut Sheet,
     row: u32,
     mut col: u32,
     xml_tag: BytesStart<'_>,
 ) -> Result<u32, OdsError> {
+    let (cell, mut cell_repeat) = read_empty_table_cell_data(names, xml_tag)?;
+
+    if let Some(cell) = cell {
+        while cell_repeat > 1 {
+            sheet.add_cell_data(row, col, cell.clone());
+            col += 1;
+            cell_repeat -= 1;
+        }
+        sheet.add_cell_data(row, col, cell);
+        col += 1;
+    } else {
+        col += cell_repeat;
+    }
+
+    Ok(col)
+}
+
+// Reads an empty table-cell and returns the cell, if it contains
+// anything useful, and the repeat count.
+fn read_empty_table_cell_data(
+    names: &mut SharedNames,
+    xml_tag: BytesStart<'_>,
+) -> Result<(Option<CellData>, u32), OdsError> {
     let mut cell = None;
     // Default advance is one column.
     let mut cell_repeat = 1;
@@ -927,22 +2072,41 @@ fn read_empty_table_cell(
                 cell_repeat = parse_u32(&attr.value)?;
             }
             attr if attr.key.as_ref() == b"table:formula" => {
-                cell.get_or_insert_with(CellData::new).formula =
+                cell.get_or_insert_with(CellData::new).extra_mut().formula =
                     Some(attr.unescape_value()?.to_string());
             }
             attr if attr.key.as_ref() == b"table:style-name" => {
                 cell.get_or_insert_with(CellData::new).style =
-                    Some(attr.unescape_value()?.to_string());
+                    Some(names.get(&attr.unescape_value()?));
             }
             attr if attr.key.as_ref() == b"table:number-rows-spanned" => {
-                cell.get_or_insert_with(CellData::new).span.row_span = parse_u32(&attr.value)?;
+                cell.get_or_insert_with(CellData::new)
+                    .extra_mut()
+                    .span
+                    .row_span = parse_u32(&attr.value)?;
             }
             attr if attr.key.as_ref() == b"table:number-columns-spanned" => {
-                cell.get_or_insert_with(CellData::new).span.col_span = parse_u32(&attr.value)?;
+                cell.get_or_insert_with(CellData::new)
+                    .extra_mut()
+                    .span
+                    .col_span = parse_u32(&attr.value)?;
+            }
+            attr if attr.key.as_ref() == b"table:number-matrix-rows-spanned" => {
+                cell.get_or_insert_with(CellData::new)
+                    .extra_mut()
+                    .matrix_span
+                    .row_span = parse_u32(&attr.value)?;
+            }
+            attr if attr.key.as_ref() == b"table:number-matrix-columns-spanned" => {
+                cell.get_or_insert_with(CellData::new)
+                    .extra_mut()
+                    .matrix_span
+                    .col_span = parse_u32(&attr.value)?;
             }
             attr if attr.key.as_ref() == b"table:content-validation-name" => {
-                cell.get_or_insert_with(CellData::new).validation_name =
-                    Some(attr.unescape_value()?.to_string());
+                cell.get_or_insert_with(CellData::new)
+                    .extra_mut()
+                    .validation_name = Some(names.get(&attr.unescape_value()?));
             }
 
             attr => {
@@ -951,27 +2115,15 @@ fn read_empty_table_cell(
         }
     }
 
-    if let Some(cell) = cell {
-        while cell_repeat > 1 {
-            sheet.add_cell_data(row, col, cell.clone());
-            col += 1;
-            cell_repeat -= 1;
-        }
-        sheet.add_cell_data(row, col, cell);
-        col += 1;
-    } else {
-        col += cell_repeat;
-    }
-
-    Ok(col)
+    Ok((cell, cell_repeat))
 }
 
 // reads a font-face
-fn read_fonts(
+fn read_fonts<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // no attributes
 ) -> Result<(), OdsError> {
     let mut font: FontFaceDecl = FontFaceDecl::new_empty();
@@ -1021,10 +2173,10 @@ fn read_fonts(
 }
 
 // reads the page-layout tag
-fn read_page_style(
+fn read_page_style<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
 ) -> Result<(), OdsError> {
     let mut pl = PageStyle::new_empty();
@@ -1099,10 +2251,10 @@ fn read_page_style(
     Ok(())
 }
 
-fn read_validations(
+fn read_validations<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
 ) -> Result<(), OdsError> {
     let mut valid = Validation::new();
 
@@ -1271,12 +2423,119 @@ fn read_validations(
     Ok(())
 }
 
+fn read_named_expressions<R: BufRead>(
+    bs: &mut BufStack,
+    book: &mut WorkBook,
+    scope: NameScope,
+    xml: &mut quick_xml::Reader<R>,
+) -> Result<(), OdsError> {
+    let mut buf = bs.get_buf();
+    loop {
+        let evt = xml.read_event_into(&mut buf)?;
+        if DUMP_XML {
+            println!(" read_named_expressions {:?}", evt);
+        }
+        match evt {
+            Event::Start(ref xml_tag) | Event::Empty(ref xml_tag)
+                if xml_tag.name().as_ref() == b"table:named-range" =>
+            {
+                let mut range = NamedRange::new("", CellRange::new());
+                range.set_scope(scope.clone());
+                for attr in xml_tag.attributes().with_checks(false) {
+                    match attr? {
+                        attr if attr.key.as_ref() == b"table:name" => {
+                            range.set_name(attr.unescape_value()?.to_string());
+                        }
+                        attr if attr.key.as_ref() == b"table:base-cell-address" => {
+                            let v = attr.unescape_value()?;
+                            range.set_base_cell(parse_cellref(&v)?);
+                        }
+                        attr if attr.key.as_ref() == b"table:cell-range-address" => {
+                            let v = attr.unescape_value()?;
+                            range.set_range(parse_range_or_cell(&v)?);
+                        }
+                        attr if attr.key.as_ref() == b"table:range-usable-as" => {
+                            range.set_usable_as(Some(attr.unescape_value()?.to_string()));
+                        }
+                        attr => {
+                            dump_unused("read_named_expressions", xml_tag.name().as_ref(), &attr)?;
+                        }
+                    }
+                }
+                book.add_named_range(range);
+            }
+            Event::Start(ref xml_tag) | Event::Empty(ref xml_tag)
+                if xml_tag.name().as_ref() == b"table:named-expression" =>
+            {
+                let mut expr = NamedExpression::new("", "");
+                expr.set_scope(scope.clone());
+                for attr in xml_tag.attributes().with_checks(false) {
+                    match attr? {
+                        attr if attr.key.as_ref() == b"table:name" => {
+                            expr.set_name(attr.unescape_value()?.to_string());
+                        }
+                        attr if attr.key.as_ref() == b"table:base-cell-address" => {
+                            let v = attr.unescape_value()?;
+                            expr.set_base_cell(Some(parse_cellref(&v)?));
+                        }
+                        attr if attr.key.as_ref() == b"table:expression" => {
+                            expr.set_expression(attr.unescape_value()?.to_string());
+                        }
+                        attr => {
+                            dump_unused("read_named_expressions", xml_tag.name().as_ref(), &attr)?;
+                        }
+                    }
+                }
+                book.add_named_expression(expr);
+            }
+            Event::End(ref xml_tag)
+                if xml_tag.name().as_ref() == b"table:named-range"
+                    || xml_tag.name().as_ref() == b"table:named-expression" => {}
+            Event::End(ref xml_tag) if xml_tag.name().as_ref() == b"table:named-expressions" => {
+                break;
+            }
+            Event::Text(_) => (),
+            Event::Eof => break,
+            _ => {
+                dump_unused2("read_named_expressions", &evt)?;
+            }
+        }
+        buf.clear();
+    }
+    bs.push(buf);
+
+    Ok(())
+}
+
+// A range address might be a single cell too.
+fn parse_range_or_cell(v: &str) -> Result<CellRange, OdsError> {
+    match parse_cellrange(v) {
+        Ok(range) => Ok(range),
+        Err(_) => {
+            let cell = parse_cellref(v)?;
+            Ok(CellRange::new_all(
+                cell.iri().cloned(),
+                cell.table().cloned(),
+                cell.row_abs(),
+                cell.row(),
+                cell.col_abs(),
+                cell.col(),
+                None,
+                cell.row_abs(),
+                cell.row(),
+                cell.col_abs(),
+                cell.col(),
+            ))
+        }
+    }
+}
+
 // read the master-styles tag
-fn read_master_styles(
+fn read_master_styles<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // no attributes
 ) -> Result<(), OdsError> {
     let mut buf = bs.get_buf();
@@ -1316,11 +2575,11 @@ fn read_master_styles(
 }
 
 // read the master-page tag
-fn read_master_page(
+fn read_master_page<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     _origin: StyleOrigin,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
 ) -> Result<(), OdsError> {
     let mut masterpage = MasterPage::new_empty();
@@ -1416,10 +2675,10 @@ fn read_master_page(
 }
 
 // reads any header or footer tags
-fn read_headerfooter(
+fn read_headerfooter<R: BufRead>(
     bs: &mut BufStack,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
 ) -> Result<HeaderFooter, OdsError> {
     let mut hf = HeaderFooter::new();
@@ -1498,11 +2757,11 @@ fn read_headerfooter(
 }
 
 // reads the office-styles tag
-fn read_styles_tag(
+fn read_styles_tag<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // not attributes
 ) -> Result<(), OdsError> {
     let mut buf = bs.get_buf();
@@ -1574,11 +2833,11 @@ fn read_styles_tag(
 }
 
 // read the automatic-styles tag
-fn read_auto_styles(
+fn read_auto_styles<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // no attributes
 ) -> Result<(), OdsError> {
     let mut buf = bs.get_buf();
@@ -1641,12 +2900,12 @@ fn read_auto_styles(
 }
 
 // Reads any of the number:xxx tags
-fn read_value_format(
+fn read_value_format<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
 ) -> Result<(), OdsError> {
     match xml_tag.name().as_ref() {
@@ -1698,12 +2957,12 @@ fn read_value_format(
 }
 
 // Reads any of the number:xxx tags
-fn read_value_format_parts<T: ValueFormatTrait>(
+fn read_value_format_parts<T: ValueFormatTrait, R: BufRead>(
     bs: &mut BufStack,
     origin: StyleOrigin,
     styleuse: StyleUse,
     valuestyle: &mut T,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
 ) -> Result<(), OdsError> {
     valuestyle.set_origin(origin);
@@ -1868,9 +3127,9 @@ fn read_value_format_parts<T: ValueFormatTrait>(
     Ok(())
 }
 
-fn read_part(
+fn read_part<R: BufRead>(
     _bs: &mut BufStack,
-    _xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    _xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     part_type: FormatPartType,
 ) -> Result<FormatPart, OdsError> {
@@ -1929,13 +3188,13 @@ fn read_part(
 
 #[allow(clippy::too_many_arguments)]
 // style:style tag
-fn read_style_style(
+fn read_style_style<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<(), OdsError> {
@@ -1984,13 +3243,13 @@ fn read_style_style(
 // style:style tag
 #[allow(clippy::collapsible_else_if)]
 #[allow(clippy::too_many_arguments)]
-fn read_tablestyle(
+fn read_tablestyle<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<(), OdsError> {
@@ -2044,13 +3303,13 @@ fn read_tablestyle(
 // style:style tag
 #[allow(clippy::collapsible_else_if)]
 #[allow(clippy::too_many_arguments)]
-fn read_rowstyle(
+fn read_rowstyle<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<(), OdsError> {
@@ -2103,13 +3362,13 @@ fn read_rowstyle(
 // style:style tag
 #[allow(clippy::collapsible_else_if)]
 #[allow(clippy::too_many_arguments)]
-fn read_colstyle(
+fn read_colstyle<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<(), OdsError> {
@@ -2163,13 +3422,13 @@ fn read_colstyle(
 // style:style tag
 #[allow(clippy::collapsible_else_if)]
 #[allow(clippy::too_many_arguments)]
-fn read_cellstyle(
+fn read_cellstyle<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<(), OdsError> {
@@ -2238,13 +3497,13 @@ fn read_cellstyle(
 // style:style tag
 #[allow(clippy::collapsible_else_if)]
 #[allow(clippy::too_many_arguments)]
-fn read_paragraphstyle(
+fn read_paragraphstyle<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<(), OdsError> {
@@ -2312,13 +3571,13 @@ fn read_paragraphstyle(
 // style:style tag
 #[allow(clippy::collapsible_else_if)]
 #[allow(clippy::too_many_arguments)]
-fn read_textstyle(
+fn read_textstyle<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<(), OdsError> {
@@ -2371,13 +3630,13 @@ fn read_textstyle(
 // style:style tag
 #[allow(clippy::collapsible_else_if)]
 #[allow(clippy::too_many_arguments)]
-fn read_graphicstyle(
+fn read_graphicstyle<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<(), OdsError> {
@@ -2655,6 +3914,177 @@ pub(crate) fn default_settings() -> Detach<Config> {
     dc
 }
 
+fn read_meta(
+    bs: &mut BufStack,
+    book: &mut WorkBook,
+    zip_file: &mut ZipFile<'_>,
+) -> Result<(), OdsError> {
+    let mut xml = quick_xml::Reader::from_reader(BufReader::new(zip_file));
+
+    let mut buf = bs.get_buf();
+    loop {
+        let evt = xml.read_event_into(&mut buf)?;
+        if DUMP_XML {
+            println!(" read_meta {:?}", evt);
+        }
+
+        match evt {
+            Event::Decl(_) => {}
+
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"office:document-meta" => {
+                // noop
+            }
+            Event::End(xml_tag) if xml_tag.name().as_ref() == b"office:document-meta" => {
+                // noop
+            }
+
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"office:meta" => {
+                read_office_meta(bs, book, &mut xml)?;
+            }
+
+            Event::Eof => {
+                break;
+            }
+            _ => {
+                dump_unused2("read_meta", &evt)?;
+            }
+        }
+
+        buf.clear();
+    }
+    bs.push(buf);
+
+    Ok(())
+}
+
+fn read_office_meta<R: BufRead>(
+    bs: &mut BufStack,
+    book: &mut WorkBook,
+    xml: &mut quick_xml::Reader<R>,
+) -> Result<(), OdsError> {
+    let meta = book.metadata_mut();
+
+    // Text of the current element and the value-type of meta:user-defined.
+    let mut text = String::new();
+    let mut user_name = String::new();
+    let mut user_type = Vec::new();
+
+    let mut buf = bs.get_buf();
+    loop {
+        let evt = xml.read_event_into(&mut buf)?;
+        if DUMP_XML {
+            println!(" read_office_meta {:?}", evt);
+        }
+        let empty_tag = matches!(evt, Event::Empty(_));
+
+        match evt {
+            Event::Start(xml_tag) | Event::Empty(xml_tag)
+                if xml_tag.name().as_ref() == b"meta:document-statistic"
+                    || xml_tag.name().as_ref() == b"meta:template"
+                    || xml_tag.name().as_ref() == b"meta:auto-reload"
+                    || xml_tag.name().as_ref() == b"meta:hyperlink-behaviour" =>
+            {
+                let tag = read_xml(bs, xml_tag.name().as_ref(), xml, &xml_tag, empty_tag)?;
+                meta.push_extra(tag);
+            }
+            Event::Start(xml_tag) => {
+                text.clear();
+                if xml_tag.name().as_ref() == b"meta:user-defined" {
+                    user_name.clear();
+                    user_type.clear();
+                    for attr in xml_tag.attributes().with_checks(false) {
+                        match attr? {
+                            attr if attr.key.as_ref() == b"meta:name" => {
+                                user_name = attr.unescape_value()?.to_string();
+                            }
+                            attr if attr.key.as_ref() == b"meta:value-type" => {
+                                user_type = attr.value.to_vec();
+                            }
+                            attr => {
+                                dump_unused("read_office_meta", xml_tag.name().as_ref(), &attr)?;
+                            }
+                        }
+                    }
+                }
+            }
+            Event::Text(xml_text) => {
+                text.push_str(xml_text.unescape()?.as_ref());
+            }
+            Event::End(xml_tag) => match xml_tag.name().as_ref() {
+                b"office:meta" => break,
+                b"meta:generator" => meta.set_generator(text.as_str()),
+                b"dc:title" => meta.set_title(Some(text.clone())),
+                b"dc:description" => meta.set_description(Some(text.clone())),
+                b"dc:subject" => meta.set_subject(Some(text.clone())),
+                b"meta:keyword" => meta.push_keyword(text.as_str()),
+                b"meta:initial-creator" => meta.set_initial_creator(Some(text.clone())),
+                b"dc:creator" => meta.set_creator(Some(text.clone())),
+                b"meta:printed-by" => meta.set_printed_by(Some(text.clone())),
+                // Invalid values are not fatal here, they are dropped.
+                b"meta:creation-date" => meta.set_creation_date(parse_meta_datetime(&text).ok()),
+                b"dc:date" => meta.set_date(parse_meta_datetime(&text).ok()),
+                b"meta:print-date" => meta.set_print_date(parse_meta_datetime(&text).ok()),
+                b"dc:language" => meta.set_language(Some(text.clone())),
+                b"meta:editing-cycles" => {
+                    if let Ok(v) = parse_u32(text.as_bytes()) {
+                        meta.set_editing_cycles(v);
+                    }
+                }
+                b"meta:editing-duration" => {
+                    meta.set_editing_duration(parse_duration(text.as_bytes()).ok())
+                }
+                b"meta:user-defined" => {
+                    // Invalid values are kept as string.
+                    let value = match user_type.as_slice() {
+                        b"float" => parse_f64(text.as_bytes()).map(MetaValue::Float),
+                        b"date" => parse_meta_datetime(&text).map(MetaValue::Datetime),
+                        b"time" => parse_duration(text.as_bytes()).map(MetaValue::TimeDuration),
+                        b"boolean" => parse_bool(text.as_bytes()).map(MetaValue::Boolean),
+                        _ => Ok(MetaValue::String(text.clone())),
+                    }
+                    .unwrap_or_else(|_| MetaValue::String(text.clone()));
+                    meta.set_user_defined(user_name.as_str(), value);
+                }
+                _ => {
+                    dump_unused2("read_office_meta", &Event::End(xml_tag))?;
+                }
+            },
+            Event::Eof => break,
+            _ => {
+                dump_unused2("read_office_meta", &evt)?;
+            }
+        }
+
+        buf.clear();
+    }
+    bs.push(buf);
+
+    Ok(())
+}
+
+// Dates in meta.xml may carry a timezone.
+fn parse_meta_datetime(text: &str) -> Result<MetaDateTime, OdsError> {
+    let text = text.trim();
+    let (text, offset) = if let Some(v) = text.strip_suffix('Z') {
+        (v, FixedOffset::east_opt(0))
+    } else if text.len() > 19
+        && text.is_ascii()
+        && matches!(text.as_bytes()[text.len() - 6], b'+' | b'-')
+    {
+        let (text, tz) = text.split_at(text.len() - 6);
+        let hours = parse_i32(&tz.as_bytes()[1..3])?;
+        let minutes = parse_i32(&tz.as_bytes()[4..6])?;
+        let secs = (hours * 3600 + minutes * 60) * if tz.starts_with('-') { -1 } else { 1 };
+        match FixedOffset::east_opt(secs) {
+            Some(offset) => (text, Some(offset)),
+            None => return Err(OdsError::Ods(format!("Invalid timezone {}", tz))),
+        }
+    } else {
+        (text, None)
+    };
+    Ok(MetaDateTime::new(parse_datetime(text.as_bytes())?, offset))
+}
+
 fn read_settings(
     bs: &mut BufStack,
     book: &mut WorkBook,
@@ -2703,9 +4133,9 @@ fn read_settings(
 }
 
 // read the automatic-styles tag
-fn read_office_settings(
+fn read_office_settings<R: BufRead>(
     bs: &mut BufStack,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // no attributes
 ) -> Result<Config, OdsError> {
     let mut config = Config::new();
@@ -2738,10 +4168,10 @@ fn read_office_settings(
 }
 
 // read the automatic-styles tag
-fn read_config_item_set(
+fn read_config_item_set<R: BufRead>(
     bs: &mut BufStack,
     xml_tag: &BytesStart<'_>,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // no attributes
 ) -> Result<(String, ConfigItem), OdsError> {
     let mut name = None;
@@ -2808,10 +4238,10 @@ fn read_config_item_set(
 }
 
 // read the automatic-styles tag
-fn read_config_item_map_indexed(
+fn read_config_item_map_indexed<R: BufRead>(
     bs: &mut BufStack,
     xml_tag: &BytesStart<'_>,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // no attributes
 ) -> Result<(String, ConfigItem), OdsError> {
     let mut name = None;
@@ -2873,10 +4303,10 @@ fn read_config_item_map_indexed(
 }
 
 // read the automatic-styles tag
-fn read_config_item_map_named(
+fn read_config_item_map_named<R: BufRead>(
     bs: &mut BufStack,
     xml_tag: &BytesStart<'_>,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // no attributes
 ) -> Result<(String, ConfigItem), OdsError> {
     let mut name = None;
@@ -2940,10 +4370,10 @@ fn read_config_item_map_named(
 }
 
 // read the automatic-styles tag
-fn read_config_item_map_entry(
+fn read_config_item_map_entry<R: BufRead>(
     bs: &mut BufStack,
     xml_tag: &BytesStart<'_>,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // no attributes
 ) -> Result<(Option<String>, ConfigItem), OdsError> {
     let mut name = None;
@@ -3004,10 +4434,10 @@ fn read_config_item_map_entry(
 }
 
 // read the automatic-styles tag
-fn read_config_item(
+fn read_config_item<R: BufRead>(
     bs: &mut BufStack,
     xml_tag: &BytesStart<'_>,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // no attributes
 ) -> Result<(String, ConfigValue), OdsError> {
     #[derive(PartialEq)]
@@ -3137,10 +4567,10 @@ fn read_config_item(
 }
 
 // Reads a part of the XML as XmlTag's.
-fn read_xml(
+fn read_xml<R: BufRead>(
     bs: &mut BufStack,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<XmlTag, OdsError> {
@@ -3218,10 +4648,10 @@ fn read_xml(
     Ok(stack.pop().unwrap())
 }
 
-fn read_text_or_tag(
+fn read_text_or_tag<R: BufRead>(
     bs: &mut BufStack,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<TextContent2, OdsError> {
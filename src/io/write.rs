@@ -1,31 +1,39 @@
+use std::cmp::{max, min};
 use std::collections::HashMap;
 use std::fs::File;
 use std::io;
-use std::io::{Cursor, Seek, Write};
+use std::io::{BufWriter, Cursor, Seek, Write};
 use std::path::Path;
 
-use chrono::NaiveDateTime;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Duration, NaiveDateTime};
 use zip::write::FileOptions;
 
+use crate::annotation::Annotation;
+use crate::conditional::{condition_to_calcext, ConditionalEntry, ScaleEntry};
 use crate::config::{ConfigItem, ConfigItemType, ConfigValue};
 use crate::error::OdsError;
 use crate::format::FormatPartType;
-use crate::io::filebuf::FileBufEntry;
+use crate::image::Image;
+use crate::io::filebuf::{media_type, media_type_ext, FileBuf, FileBufEntry};
 use crate::io::xmlwriter::XmlWriter;
-use crate::io::zip_out::{ZipOut, ZipWrite};
-use crate::refs::{cellranges_string, CellRange};
+use crate::io::zip_out::ZipOut;
+use crate::metadata::{MetaDateTime, MetaValue};
+use crate::named::NameScope;
+use crate::refs::{cellranges_string, CellRange, CellRef};
 use crate::style::{
-    CellStyle, ColStyle, FontFaceDecl, GraphicStyle, HeaderFooter, MasterPage, PageStyle,
-    ParagraphStyle, RowStyle, StyleOrigin, StyleUse, TableStyle, TextStyle,
+    color_string, CellStyle, ColStyle, FontFaceDecl, GraphicStyle, HeaderFooter, MasterPage,
+    PageStyle, ParagraphStyle, RowStyle, StyleOrigin, StyleUse, TableStyle, TextStyle,
 };
 use crate::validation::ValidationDisplay;
 use crate::xmltree::{XmlContent, XmlTag};
 use crate::{
-    CellContentRef, Length, Sheet, Value, ValueFormatTrait, ValueType, Visibility, WorkBook,
+    CellContent, CellContentRef, Length, Sheet, Value, ValueFormatTrait, ValueType, Visibility,
+    WorkBook,
 };
 
 type OdsWriter<W> = ZipOut<W>;
-type XmlOdsWriter<'a, W> = XmlWriter<ZipWrite<'a, W>>;
 
 /// Writes the ODS file into a supplied buffer.
 pub fn write_ods_buf_uncompressed(book: &mut WorkBook, buf: Vec<u8>) -> Result<Vec<u8>, OdsError> {
@@ -68,20 +76,615 @@ fn write_ods_impl<W: Write + Seek>(
 
     store_derived(book)?;
 
+    // images are in the filebuf only for the duration of the write.
+    let filebuf_len = store_images(book);
+    let result = write_ods_parts(book, &mut zip_writer);
+    book.filebuf.truncate(filebuf_len);
+    result?;
+
+    Ok(zip_writer.zip()?)
+}
+
+fn write_ods_parts<W: Write + Seek>(
+    book: &WorkBook,
+    zip_writer: &mut OdsWriter<W>,
+) -> Result<(), OdsError> {
     // copy all buffered data from the original.
-    copy_workbook(book, &mut zip_writer)?;
+    copy_workbook(book, zip_writer)?;
     // write the rest, if necessary.
-    write_mimetype(book, &mut zip_writer)?;
-    write_manifest(book, &mut zip_writer)?;
-    write_manifest_rdf(book, &mut zip_writer)?;
-    write_meta(book, &mut zip_writer)?;
+    write_mimetype(book, zip_writer)?;
+    write_manifest(book, zip_writer)?;
+    write_manifest_rdf(book, zip_writer)?;
+    write_meta(book, zip_writer)?;
     // not in use any more, just ignore
     // write_configurations(&mut zip_writer, &mut file_set)?;
-    write_settings(book, &mut zip_writer)?;
-    write_ods_styles(book, &mut zip_writer)?;
-    write_ods_content(book, &mut zip_writer)?;
+    write_settings(book, zip_writer)?;
+    write_ods_styles(book, zip_writer)?;
+    write_ods_content(book, zip_writer)?;
 
-    Ok(zip_writer.zip()?)
+    Ok(())
+}
+
+/// Writes an ODS-file row by row without keeping all the data in memory.
+///
+/// The styles, formats and sheets are declared up front in the WorkBook.
+/// Sheets that already contain data are written as they are, the rows
+/// of a streamed sheet are pushed with write_row() directly into the
+/// zip-file.
+///
+/// ```no_run
+/// use spreadsheet_ods::{OdsError, OdsRowWriter, Sheet, Value, WorkBook};
+///
+/// let mut book = WorkBook::new_empty();
+/// book.push_sheet(Sheet::new("Data"));
+///
+/// let mut writer = OdsRowWriter::create(book, "test_out/stream.ods")?;
+/// writer.start_sheet(0)?;
+/// for row in 0..100_000 {
+///     writer.write_row_values(row, &[Value::from(row), Value::from("x")])?;
+/// }
+/// writer.finish()?;
+/// # Ok::<(), OdsError>(())
+/// ```
+#[derive(Debug)]
+pub struct OdsRowWriter<W: Write + Seek> {
+    book: WorkBook,
+    xml_out: XmlWriter<ZipOut<W>>,
+    /// Next sheet that has not been written yet.
+    next_sheet: usize,
+    /// Sheet that is currently streamed.
+    sheet: Option<usize>,
+    /// Number of columns of the current sheet.
+    col_count: u32,
+    /// List of current spans.
+    spans: Vec<CellRange>,
+    /// Header rows and row groups.
+    nesting: Nesting,
+    first_row: bool,
+    last_r: u32,
+    last_r_repeat: u32,
+    /// Pictures up to here have already been copied to the zip.
+    copied_files: usize,
+    /// Last index used for a picture of a streamed row.
+    image_idx: u32,
+}
+
+impl OdsRowWriter<File> {
+    /// Creates the ODS-file.
+    pub fn create<P: AsRef<Path>>(book: WorkBook, path: P) -> Result<Self, OdsError> {
+        let zip_writer = ZipOut::<File>::new_file(path.as_ref())?;
+        Self::new_impl(book, zip_writer)
+    }
+}
+
+impl<W: Write + Seek> OdsRowWriter<W> {
+    /// Writes the ODS-file to the given Write.
+    pub fn new(book: WorkBook, ods: W) -> Result<Self, OdsError> {
+        let zip_writer = ZipOut::new_to(ods)?;
+        Self::new_impl(book, zip_writer)
+    }
+
+    fn new_impl(mut book: WorkBook, mut zip_writer: ZipOut<W>) -> Result<Self, OdsError> {
+        sanity_checks(&mut book)?;
+
+        store_derived(&mut book)?;
+
+        // The pictures stay in the filebuf until the manifest is written
+        // after content.xml. The pictures of the streamed rows are added.
+        store_images(&mut book);
+        copy_workbook(&book, &mut zip_writer)?;
+        let copied_files = book.filebuf.len();
+        write_mimetype(&book, &mut zip_writer)?;
+        write_manifest_rdf(&book, &mut zip_writer)?;
+        write_meta(&book, &mut zip_writer)?;
+        write_settings(&book, &mut zip_writer)?;
+        write_ods_styles(&book, &mut zip_writer)?;
+
+        zip_writer.start_file_direct("content.xml", FileOptions::default())?;
+        let mut xml_out = XmlWriter::new(zip_writer);
+        // The images of the streamed rows are not known yet.
+        write_content_start(&book, true, &mut xml_out)?;
+
+        Ok(Self {
+            book,
+            xml_out,
+            next_sheet: 0,
+            sheet: None,
+            col_count: 0,
+            spans: Vec::new(),
+            nesting: Default::default(),
+            first_row: true,
+            last_r: 0,
+            last_r_repeat: 1,
+            copied_files,
+            image_idx: 0,
+        })
+    }
+
+    /// Styles, formats and the declared sheets.
+    pub fn book(&self) -> &WorkBook {
+        &self.book
+    }
+
+    /// Starts streaming the sheet at the given index.
+    ///
+    /// The sheets must be streamed in order. Any sheet before this one
+    /// is written with its current content. The streamed sheet itself
+    /// must not contain any cell data, but the column and row headers
+    /// are used.
+    ///
+    /// The number of columns is taken from the column headers.
+    pub fn start_sheet(&mut self, idx: usize) -> Result<(), OdsError> {
+        if idx < self.next_sheet {
+            return Err(OdsError::Ods(format!(
+                "Sheet {} has already been written.",
+                idx
+            )));
+        }
+        if idx >= self.book.num_sheets() {
+            return Err(OdsError::Ods(format!("Sheet {} doesn't exist.", idx)));
+        }
+        if !self.book.sheet(idx).data.is_empty() {
+            return Err(OdsError::Ods(format!(
+                "Sheet {} already contains data and can't be streamed.",
+                idx
+            )));
+        }
+
+        self.end_sheet()?;
+        self.write_sheets_before(idx)?;
+
+        let sheet = self.book.sheet(idx);
+        self.col_count = sheet
+            .col_header
+            .keys()
+            .next_back()
+            .map(|v| *v + 1)
+            .unwrap_or(0);
+        write_table_start(&self.book, sheet, (0, self.col_count), &mut self.xml_out)?;
+
+        self.sheet = Some(idx);
+        self.next_sheet = idx + 1;
+        self.spans.clear();
+        self.nesting = Nesting::rows(sheet);
+        self.first_row = true;
+        self.last_r = 0;
+        self.last_r_repeat = 1;
+
+        Ok(())
+    }
+
+    /// Writes a row of the current sheet. The cells start at column 0.
+    ///
+    /// The rows must be written in ascending order, any gaps are filled
+    /// with empty rows. If the row header has a repeat count, the next
+    /// row must be at least row + repeat.
+    ///
+    /// The data of images is kept until finish() and stored under
+    /// Pictures/ after the content.
+    pub fn write_row(&mut self, row: u32, cells: &[CellContent]) -> Result<(), OdsError> {
+        let idx = match self.sheet {
+            Some(idx) => idx,
+            None => return Err(OdsError::Ods("No sheet has been started.".to_string())),
+        };
+        if !self.first_row && row < self.last_r + self.last_r_repeat {
+            return Err(OdsError::Ods(format!(
+                "Rows must be written in ascending order. Row {} after row {}.",
+                row, self.last_r
+            )));
+        }
+
+        let images = if cells.iter().any(|v| !v.images.is_empty()) {
+            cells
+                .iter()
+                .map(|v| store_row_images(&mut self.book.filebuf, &mut self.image_idx, &v.images))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let book = &self.book;
+        let sheet = book.sheet(idx);
+        let xml_out = &mut self.xml_out;
+        let max_cell = (0, max(self.col_count, 1));
+
+        // Any empty rows before this one?
+        let next_row = if self.first_row {
+            0
+        } else {
+            self.last_r + self.last_r_repeat
+        };
+        write_empty_rows(sheet, &mut self.nesting, next_row, row, max_cell, xml_out)?;
+
+        self.nesting.enter(row, xml_out)?;
+        write_start_current_row(sheet, row, 0, xml_out)?;
+
+        // Trailing empty cells are filled up to the column count.
+        let cell_count = cells
+            .iter()
+            .rposition(|v| !is_blank(v))
+            .map(|v| v as u32 + 1)
+            .unwrap_or(0);
+        let col_count = max(cell_count, self.col_count);
+
+        let mut empty_count = 0;
+        for col in 0..col_count {
+            remove_outlooped(&mut self.spans, row, col);
+            let (is_hidden, _) = check_hidden(&self.spans, row, col);
+
+            let cell = cells.get(col as usize).filter(|v| !is_blank(v));
+            if cell.is_none() && !is_hidden {
+                empty_count += 1;
+                continue;
+            }
+            write_blank_cells(empty_count, xml_out)?;
+            empty_count = 0;
+
+            if let Some(cell) = cell {
+                let content = CellContentRef {
+                    value: Some(&cell.value),
+                    style: cell.style.as_ref(),
+                    formula: cell.formula.as_ref(),
+                    validation_name: cell.validation_name.as_ref(),
+                    span: Some(&cell.span),
+                    matrix_span: Some(&cell.matrix_span),
+                    annotation: cell.annotation.as_ref(),
+                    images: images.get(col as usize).unwrap_or(&cell.images),
+                };
+                write_cell(book, &content, is_hidden, xml_out)?;
+
+                // only if visible, that nicely eliminates all double hides.
+                if !is_hidden && (cell.span.row_span > 1 || cell.span.col_span > 1) {
+                    self.spans
+                        .push(CellRange::origin_span(row, col, cell.span.into()));
+                }
+            } else {
+                xml_out.empty("table:covered-table-cell")?;
+            }
+        }
+        // A row needs at least one cell.
+        write_blank_cells(max(empty_count, (col_count == 0) as u32), xml_out)?;
+
+        xml_out.end_elem("table:table-row")?;
+
+        self.first_row = false;
+        self.last_r = row;
+        self.last_r_repeat = if let Some(row_header) = sheet.row_header.get(&row) {
+            row_header.repeat
+        } else {
+            1
+        };
+
+        Ok(())
+    }
+
+    /// Writes a row of plain values.
+    pub fn write_row_values<V: Into<Value> + Clone>(
+        &mut self,
+        row: u32,
+        values: &[V],
+    ) -> Result<(), OdsError> {
+        let cells: Vec<CellContent> = values
+            .iter()
+            .map(|v| CellContent {
+                value: v.clone().into(),
+                ..Default::default()
+            })
+            .collect();
+        self.write_row(row, &cells)
+    }
+
+    /// Writes all remaining sheets and finishes the ODS-file.
+    pub fn finish(mut self) -> Result<W, OdsError> {
+        self.end_sheet()?;
+        self.write_sheets_before(self.book.num_sheets())?;
+
+        write_content_end(&self.book, &mut self.xml_out)?;
+        self.xml_out.close()?;
+
+        let mut zip_writer = self.xml_out.into_inner();
+        // Pictures of the streamed rows.
+        for filebuf in self.book.filebuf.iter().skip(self.copied_files) {
+            if let FileBufEntry::File(name, buf) = filebuf {
+                let mut wr = zip_writer.start_file(name, FileOptions::default())?;
+                wr.write_all(buf.as_slice())?;
+            }
+        }
+        write_manifest(&self.book, &mut zip_writer)?;
+
+        Ok(zip_writer.zip()?)
+    }
+
+    /// Closes the current sheet.
+    fn end_sheet(&mut self) -> Result<(), OdsError> {
+        if let Some(idx) = self.sheet.take() {
+            let sheet = self.book.sheet(idx);
+
+            // Groups may extend beyond the last row.
+            let next_row = if self.first_row {
+                0
+            } else {
+                self.last_r + self.last_r_repeat
+            };
+            let max_cell = (0, max(self.col_count, 1));
+            let end = self.nesting.end();
+            write_empty_rows(
+                sheet,
+                &mut self.nesting,
+                next_row,
+                end,
+                max_cell,
+                &mut self.xml_out,
+            )?;
+            self.nesting.close_all(&mut self.xml_out)?;
+
+            write_table_end(&self.book, sheet, &mut self.xml_out)?;
+        }
+        Ok(())
+    }
+
+    /// Writes all sheets up to idx as they are.
+    fn write_sheets_before(&mut self, idx: usize) -> Result<(), OdsError> {
+        while self.next_sheet < idx {
+            write_sheet(
+                &self.book,
+                self.book.sheet(self.next_sheet),
+                &mut self.xml_out,
+            )?;
+            self.next_sheet += 1;
+        }
+        Ok(())
+    }
+}
+
+// Assigns a href to the images of a streamed row and pushes their
+// data to the filebuf. Returns the images with the new hrefs.
+fn store_row_images(filebuf: &mut FileBuf, idx: &mut u32, images: &[Image]) -> Vec<Image> {
+    let mut stored = Vec::new();
+    for image in images {
+        let mut image = image.clone();
+        if image.href().is_empty() {
+            let href = loop {
+                *idx += 1;
+                let href = format!("Pictures/row{}.{}", idx, media_type_ext(image.mime()));
+                if !filebuf.contains(&href) {
+                    break href;
+                }
+            };
+            image.set_href(href.as_str());
+        }
+        if !image.data().is_empty() && !filebuf.contains(image.href()) {
+            filebuf.push_file(image.href(), image.data().to_vec());
+        }
+        stored.push(image);
+    }
+    stored
+}
+
+/// No content at all.
+fn is_blank(cell: &CellContent) -> bool {
+    matches!(cell.value, Value::Empty)
+        && cell.style.is_none()
+        && cell.formula.is_none()
+        && cell.validation_name.is_none()
+        && cell.annotation.is_none()
+        && cell.images.is_empty()
+        && cell.span.row_span <= 1
+        && cell.span.col_span <= 1
+        && cell.matrix_span.row_span <= 1
+        && cell.matrix_span.col_span <= 1
+}
+
+/// Writes a number of empty cells.
+fn write_blank_cells<W: Write>(count: u32, xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
+    if count > 0 {
+        xml_out.empty("table:table-cell")?;
+        if count > 1 {
+            let repeat = count.to_string();
+            xml_out.attr("table:number-columns-repeated", repeat.as_str())?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the FODS file into a supplied buffer.
+pub fn write_fods_buf(book: &mut WorkBook, buf: Vec<u8>) -> Result<Vec<u8>, OdsError> {
+    write_fods_impl(book, buf)
+}
+
+/// Writes the FODS file to the given Write.
+pub fn write_fods_to<T: Write>(book: &mut WorkBook, fods: T) -> Result<(), OdsError> {
+    write_fods_impl(book, fods)?;
+    Ok(())
+}
+
+/// Writes the FODS file.
+pub fn write_fods<P: AsRef<Path>>(book: &mut WorkBook, fods_path: P) -> Result<(), OdsError> {
+    let write = BufWriter::new(File::create(fods_path.as_ref())?);
+    write_fods_impl(book, write)?.flush()?;
+    Ok(())
+}
+
+/// Writes the FODS file.
+///
+/// Pictures held in the FileBuf are embedded as office:binary-data.
+/// Everything else in the FileBuf can't be stored in a flat file
+/// and is dropped.
+fn write_fods_impl<W: Write>(book: &mut WorkBook, write: W) -> Result<W, OdsError> {
+    sanity_checks(book)?;
+
+    store_derived(book)?;
+
+    // embed pictures only for the duration of the write.
+    let hrefs = take_image_hrefs(book);
+    let book_extra = embed_binary_data(&book.extra, &book.filebuf);
+    let book_extra = std::mem::replace(&mut book.extra, book_extra);
+    let mut sheet_extra = Vec::new();
+    for i in 0..book.sheets.len() {
+        let extra = embed_binary_data(&book.sheets[i].extra, &book.filebuf);
+        sheet_extra.push(std::mem::replace(&mut book.sheets[i].extra, extra));
+    }
+
+    let result = write_fods_document(book, write);
+
+    book.extra = book_extra;
+    for (sheet, extra) in book.sheets.iter_mut().zip(sheet_extra) {
+        sheet.extra = extra;
+    }
+    restore_image_hrefs(book, hrefs);
+
+    result
+}
+
+// Images without a href are embedded as office:binary-data.
+// Removes the href of all images with data and returns them in
+// iteration order.
+fn take_image_hrefs(book: &mut WorkBook) -> Vec<String> {
+    let mut hrefs = Vec::new();
+    for sheet in book.sheets.iter_mut() {
+        for image in sheet.all_images_mut() {
+            if !image.data().is_empty() {
+                hrefs.push(image.href().to_string());
+                image.set_href("");
+            }
+        }
+    }
+    hrefs
+}
+
+fn restore_image_hrefs(book: &mut WorkBook, hrefs: Vec<String>) {
+    let mut hrefs = hrefs.into_iter();
+    for sheet in book.sheets.iter_mut() {
+        for image in sheet.all_images_mut() {
+            if !image.data().is_empty() {
+                if let Some(href) = hrefs.next() {
+                    image.set_href(href);
+                }
+            }
+        }
+    }
+}
+
+// Assigns a href to all images that don't have one yet and pushes
+// their data to the filebuf. Returns the previous length of the
+// filebuf.
+fn store_images(book: &mut WorkBook) -> usize {
+    let filebuf_len = book.filebuf.len();
+
+    let mut hrefs = Vec::new();
+    for sheet in book.sheets.iter() {
+        for image in sheet.all_images() {
+            if !image.href().is_empty() {
+                hrefs.push(image.href().to_string());
+            }
+        }
+    }
+
+    let mut idx = 0;
+    for sheet in book.sheets.iter_mut() {
+        for image in sheet.all_images_mut() {
+            if image.href().is_empty() {
+                let href = loop {
+                    idx += 1;
+                    let href = format!("Pictures/image{}.{}", idx, media_type_ext(image.mime()));
+                    if !hrefs.contains(&href) && !book.filebuf.contains(&href) {
+                        break href;
+                    }
+                };
+                image.set_href(href.as_str());
+                hrefs.push(href);
+            }
+            if !image.data().is_empty() && !book.filebuf.contains(image.href()) {
+                book.filebuf.push_file(image.href(), image.data().to_vec());
+            }
+        }
+    }
+
+    filebuf_len
+}
+
+fn embed_binary_data(extra: &[XmlTag], filebuf: &FileBuf) -> Vec<XmlTag> {
+    let mut extra = extra.to_vec();
+    for tag in extra.iter_mut() {
+        embed_binary_tag(tag, filebuf);
+    }
+    extra
+}
+
+fn embed_binary_tag(tag: &mut XmlTag, filebuf: &FileBuf) {
+    if tag.name() == "draw:image" {
+        let data = match tag.attrmap().attr("xlink:href") {
+            Some(href) => filebuf.get(href),
+            None => None,
+        };
+        if let Some(data) = data {
+            let data = XmlTag::new("office:binary-data").text(STANDARD.encode(data));
+            tag.content_mut().insert(0, XmlContent::Tag(data));
+            tag.attrmap_mut().clear_attr("xlink:href");
+            tag.attrmap_mut().clear_attr("xlink:type");
+            tag.attrmap_mut().clear_attr("xlink:show");
+            tag.attrmap_mut().clear_attr("xlink:actuate");
+        }
+    }
+
+    for content in tag.content_mut() {
+        if let XmlContent::Tag(t) = content {
+            embed_binary_tag(t, filebuf);
+        }
+    }
+}
+
+fn write_fods_document<W: Write>(book: &WorkBook, write: W) -> Result<W, OdsError> {
+    let mut xml_out = XmlWriter::new(write);
+
+    xml_out.dtd("UTF-8")?;
+
+    xml_out.elem("office:document")?;
+    write_content_namespaces(&mut xml_out)?;
+    xml_out.attr(
+        "xmlns:config",
+        "urn:oasis:names:tc:opendocument:xmlns:config:1.0",
+    )?;
+    xml_out.attr("office:version", book.version())?;
+    xml_out.attr(
+        "office:mimetype",
+        "application/vnd.oasis.opendocument.spreadsheet",
+    )?;
+
+    write_office_meta(book, &mut xml_out)?;
+    write_office_settings(book, &mut xml_out)?;
+
+    xml_out.empty("office:scripts")?;
+
+    xml_out.elem("office:font-face-decls")?;
+    write_font_decl(&book.fonts, StyleOrigin::Styles, &mut xml_out)?;
+    write_font_decl(&book.fonts, StyleOrigin::Content, &mut xml_out)?;
+    xml_out.end_elem("office:font-face-decls")?;
+
+    xml_out.elem("office:styles")?;
+    write_office_styles(book, &mut xml_out)?;
+    xml_out.end_elem("office:styles")?;
+
+    xml_out.elem("office:automatic-styles")?;
+    write_pagestyles(&book.pagestyles, &mut xml_out)?;
+    write_office_automatic_styles(book, StyleOrigin::Styles, &mut xml_out)?;
+    write_office_automatic_styles(book, StyleOrigin::Content, &mut xml_out)?;
+    if has_default_image_style(book) {
+        write_default_image_style(book, &mut xml_out)?;
+    }
+    xml_out.end_elem("office:automatic-styles")?;
+
+    xml_out.elem("office:master-styles")?;
+    write_masterpage(&book.masterpages, &mut xml_out)?;
+    xml_out.end_elem("office:master-styles")?;
+
+    write_office_body(book, &mut xml_out)?;
+
+    xml_out.end_elem("office:document")?;
+
+    xml_out.close()?;
+
+    Ok(xml_out.into_inner())
 }
 
 fn sanity_checks(book: &mut WorkBook) -> Result<(), OdsError> {
@@ -282,6 +885,25 @@ fn write_manifest<W: Write + Seek>(
         xml_out.attr("manifest:full-path", "settings.xml")?;
         xml_out.attr("manifest:media-type", "text/xml")?;
 
+        for filebuf in book.filebuf.iter() {
+            if let FileBufEntry::File(name, _) = filebuf {
+                if !matches!(
+                    name.as_str(),
+                    "mimetype"
+                        | "manifest.rdf"
+                        | "styles.xml"
+                        | "meta.xml"
+                        | "content.xml"
+                        | "settings.xml"
+                ) && !name.starts_with("META-INF/")
+                {
+                    xml_out.empty("manifest:file-entry")?;
+                    xml_out.attr_esc("manifest:full-path", name)?;
+                    xml_out.attr("manifest:media-type", media_type(name))?;
+                }
+            }
+        }
+
         xml_out.end_elem("manifest:manifest")?;
 
         xml_out.close()?;
@@ -366,26 +988,11 @@ fn write_meta<W: Write + Seek>(
             "xmlns:office",
             "urn:oasis:names:tc:opendocument:xmlns:office:1.0",
         )?;
+        xml_out.attr("xmlns:dc", "http://purl.org/dc/elements/1.1/")?;
+        xml_out.attr("xmlns:xlink", "http://www.w3.org/1999/xlink")?;
         xml_out.attr("office:version", book.version())?;
 
-        xml_out.elem("office:meta")?;
-
-        xml_out.elem_text("meta:generator", "spreadsheet-ods 0.16.0")?;
-        let s = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
-        let d = NaiveDateTime::from_timestamp_opt(s.as_secs() as i64, 0);
-        if let Some(d) = d {
-            xml_out.elem_text(
-                "meta:creation-date",
-                &d.format("%Y-%m-%dT%H:%M:%S%.f").to_string(),
-            )?;
-        }
-        xml_out.elem_text("meta:editing-duration", "P0D")?;
-        xml_out.elem_text("meta:editing-cycles", "1")?;
-        // xml_out.elem_text_esc("meta:initial-creator", &username::get_user_name().unwrap())?;
-
-        // TODO: allow to set this data.
-
-        xml_out.end_elem("office:meta")?;
+        write_office_meta(book, &mut xml_out)?;
 
         xml_out.end_elem("office:document-meta")?;
 
@@ -416,6 +1023,127 @@ fn write_settings<W: Write + Seek>(
         "urn:oasis:names:tc:opendocument:xmlns:config:1.0",
     )?;
     xml_out.attr("office:version", book.version())?;
+    write_office_settings(book, &mut xml_out)?;
+    xml_out.end_elem("office:document-settings")?;
+
+    xml_out.close()?;
+
+    Ok(())
+}
+
+fn write_office_meta<W: Write>(
+    book: &WorkBook,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    let meta = book.metadata();
+
+    xml_out.elem("office:meta")?;
+
+    xml_out.elem_text(
+        "meta:generator",
+        concat!("spreadsheet-ods ", env!("CARGO_PKG_VERSION")),
+    )?;
+    if let Some(title) = meta.title() {
+        xml_out.elem_text_esc("dc:title", title)?;
+    }
+    if let Some(description) = meta.description() {
+        xml_out.elem_text_esc("dc:description", description)?;
+    }
+    if let Some(subject) = meta.subject() {
+        xml_out.elem_text_esc("dc:subject", subject)?;
+    }
+    for keyword in meta.keywords() {
+        xml_out.elem_text_esc("meta:keyword", keyword)?;
+    }
+    if let Some(initial_creator) = meta.initial_creator() {
+        xml_out.elem_text_esc("meta:initial-creator", initial_creator)?;
+    }
+    if let Some(creator) = meta.creator() {
+        xml_out.elem_text_esc("dc:creator", creator)?;
+    }
+    if let Some(printed_by) = meta.printed_by() {
+        xml_out.elem_text_esc("meta:printed-by", printed_by)?;
+    }
+    if let Some(creation_date) = meta.creation_date() {
+        xml_out.elem_text("meta:creation-date", format_meta_datetime(&creation_date))?;
+    } else {
+        let s = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+        let d = DateTime::from_timestamp(s.as_secs() as i64, 0);
+        if let Some(d) = d {
+            xml_out.elem_text("meta:creation-date", format_datetime(&d.naive_utc()))?;
+        }
+    }
+    if let Some(date) = meta.date() {
+        xml_out.elem_text("dc:date", format_meta_datetime(&date))?;
+    }
+    if let Some(print_date) = meta.print_date() {
+        xml_out.elem_text("meta:print-date", format_meta_datetime(&print_date))?;
+    }
+    if let Some(language) = meta.language() {
+        xml_out.elem_text_esc("dc:language", language)?;
+    }
+    if let Some(editing_duration) = meta.editing_duration() {
+        xml_out.elem_text("meta:editing-duration", format_duration(&editing_duration))?;
+    } else {
+        xml_out.elem_text("meta:editing-duration", "P0D")?;
+    }
+    xml_out.elem_text("meta:editing-cycles", meta.editing_cycles().to_string())?;
+
+    for user_defined in meta.user_defined_list() {
+        xml_out.elem("meta:user-defined")?;
+        xml_out.attr_esc("meta:name", user_defined.name())?;
+        match user_defined.value() {
+            MetaValue::Boolean(v) => {
+                xml_out.attr("meta:value-type", "boolean")?;
+                xml_out.text(v.to_string())?;
+            }
+            MetaValue::Datetime(v) => {
+                xml_out.attr("meta:value-type", "date")?;
+                xml_out.text(format_meta_datetime(v))?;
+            }
+            MetaValue::Float(v) => {
+                xml_out.attr("meta:value-type", "float")?;
+                xml_out.text(v.to_string())?;
+            }
+            MetaValue::TimeDuration(v) => {
+                xml_out.attr("meta:value-type", "time")?;
+                xml_out.text(format_duration(v))?;
+            }
+            MetaValue::String(v) => {
+                xml_out.attr("meta:value-type", "string")?;
+                xml_out.text_esc(v)?;
+            }
+        }
+        xml_out.end_elem("meta:user-defined")?;
+    }
+
+    for tag in meta.extra() {
+        write_xmltag(tag, xml_out)?;
+    }
+
+    xml_out.end_elem("office:meta")?;
+
+    Ok(())
+}
+
+fn format_datetime(d: &NaiveDateTime) -> String {
+    d.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+}
+
+fn format_meta_datetime(d: &MetaDateTime) -> String {
+    match d.offset() {
+        None => format_datetime(&d.datetime()),
+        Some(offset) if offset.local_minus_utc() == 0 => {
+            format!("{}Z", format_datetime(&d.datetime()))
+        }
+        Some(offset) => format!("{}{}", format_datetime(&d.datetime()), offset),
+    }
+}
+
+fn write_office_settings<W: Write>(
+    book: &WorkBook,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
     xml_out.elem("office:settings")?;
 
     for (name, item) in book.config.iter() {
@@ -423,7 +1151,7 @@ fn write_settings<W: Write + Seek>(
             ConfigItem::Value(_) => {
                 panic!("office-settings must not contain config-item");
             }
-            ConfigItem::Set(_) => write_config_item_set(name, item, &mut xml_out)?,
+            ConfigItem::Set(_) => write_config_item_set(name, item, xml_out)?,
             ConfigItem::Vec(_) => {
                 panic!("office-settings must not contain config-item-map-index")
             }
@@ -437,17 +1165,14 @@ fn write_settings<W: Write + Seek>(
     }
 
     xml_out.end_elem("office:settings")?;
-    xml_out.end_elem("office:document-settings")?;
-
-    xml_out.close()?;
 
     Ok(())
 }
 
-fn write_config_item_set<W: Write + Seek>(
+fn write_config_item_set<W: Write>(
     name: &str,
     set: &ConfigItem,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     xml_out.elem("config:config-item-set")?;
     xml_out.attr("config:name", name)?;
@@ -469,10 +1194,10 @@ fn write_config_item_set<W: Write + Seek>(
     Ok(())
 }
 
-fn write_config_item_map_indexed<W: Write + Seek>(
+fn write_config_item_map_indexed<W: Write>(
     name: &str,
     vec: &ConfigItem,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     xml_out.elem("config:config-item-map-indexed")?;
     xml_out.attr("config:name", name)?;
@@ -506,10 +1231,10 @@ fn write_config_item_map_indexed<W: Write + Seek>(
     Ok(())
 }
 
-fn write_config_item_map_named<W: Write + Seek>(
+fn write_config_item_map_named<W: Write>(
     name: &str,
     map: &ConfigItem,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     xml_out.elem("config:config-item-map-named")?;
     xml_out.attr("config:name", name)?;
@@ -535,10 +1260,10 @@ fn write_config_item_map_named<W: Write + Seek>(
     Ok(())
 }
 
-fn write_config_item_map_entry<W: Write + Seek>(
+fn write_config_item_map_entry<W: Write>(
     name: Option<&String>,
     map_entry: &ConfigItem,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     xml_out.elem("config:config-item-map-entry")?;
     if let Some(name) = name {
@@ -562,10 +1287,10 @@ fn write_config_item_map_entry<W: Write + Seek>(
     Ok(())
 }
 
-fn write_config_item<W: Write + Seek>(
+fn write_config_item<W: Write>(
     name: &str,
     value: &ConfigValue,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     let is_empty = match value {
         ConfigValue::Base64Binary(t) => t.is_empty(),
@@ -753,163 +1478,208 @@ fn write_ods_styles<W: Write + Seek>(
     xml_out.end_elem("office:font-face-decls")?;
 
     xml_out.elem("office:styles")?;
-    write_styles(book, StyleOrigin::Styles, StyleUse::Default, &mut xml_out)?;
-    write_styles(book, StyleOrigin::Styles, StyleUse::Named, &mut xml_out)?;
+    write_office_styles(book, &mut xml_out)?;
+    xml_out.end_elem("office:styles")?;
+
+    xml_out.elem("office:automatic-styles")?;
+    write_pagestyles(&book.pagestyles, &mut xml_out)?;
+    write_office_automatic_styles(book, StyleOrigin::Styles, &mut xml_out)?;
+    xml_out.end_elem("office:automatic-styles")?;
+
+    xml_out.elem("office:master-styles")?;
+    write_masterpage(&book.masterpages, &mut xml_out)?;
+    xml_out.end_elem("office:master-styles")?;
+
+    xml_out.end_elem("office:document-styles")?;
+
+    xml_out.close()?;
+
+    Ok(())
+}
+
+fn write_ods_content<W: Write + Seek>(
+    book: &WorkBook,
+    zip_out: &mut OdsWriter<W>,
+) -> Result<(), OdsError> {
+    let w = zip_out.start_file("content.xml", FileOptions::default())?;
+    let mut xml_out = XmlWriter::new(w);
+
+    write_content_start(book, has_default_image_style(book), &mut xml_out)?;
+    for sheet in &book.sheets {
+        write_sheet(book, sheet, &mut xml_out)?;
+    }
+    write_content_end(book, &mut xml_out)?;
+
+    xml_out.close()?;
+
+    Ok(())
+}
+
+/// Everything in content.xml up to the first table.
+fn write_content_start<W: Write>(
+    book: &WorkBook,
+    image_style: bool,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.dtd("UTF-8")?;
+
+    xml_out.elem("office:document-content")?;
+    write_content_namespaces(xml_out)?;
+
+    xml_out.attr("office:version", book.version())?;
+
+    xml_out.empty("office:scripts")?;
+
+    xml_out.elem("office:font-face-decls")?;
+    write_font_decl(&book.fonts, StyleOrigin::Content, xml_out)?;
+    xml_out.end_elem("office:font-face-decls")?;
+
+    xml_out.elem("office:automatic-styles")?;
+    write_office_automatic_styles(book, StyleOrigin::Content, xml_out)?;
+    if image_style {
+        write_default_image_style(book, xml_out)?;
+    }
+    xml_out.end_elem("office:automatic-styles")?;
+
+    write_office_body_start(book, xml_out)?;
+
+    Ok(())
+}
+
+/// Everything in content.xml after the last table.
+fn write_content_end<W: Write>(
+    book: &WorkBook,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    write_office_body_end(book, xml_out)?;
+    xml_out.end_elem("office:document-content")?;
+
+    Ok(())
+}
+
+fn write_office_styles<W: Write>(
+    book: &WorkBook,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    write_styles(book, StyleOrigin::Styles, StyleUse::Default, xml_out)?;
+    write_styles(book, StyleOrigin::Styles, StyleUse::Named, xml_out)?;
     write_valuestyles(
         &book.formats_boolean,
         StyleOrigin::Styles,
         StyleUse::Named,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_currency,
         StyleOrigin::Styles,
         StyleUse::Named,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_datetime,
         StyleOrigin::Styles,
         StyleUse::Named,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_number,
         StyleOrigin::Styles,
         StyleUse::Named,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_percentage,
         StyleOrigin::Styles,
         StyleUse::Named,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_text,
         StyleOrigin::Styles,
         StyleUse::Named,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_timeduration,
         StyleOrigin::Styles,
         StyleUse::Named,
-        &mut xml_out,
+        xml_out,
     )?;
 
     write_valuestyles(
         &book.formats_boolean,
         StyleOrigin::Styles,
         StyleUse::Default,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_currency,
         StyleOrigin::Styles,
         StyleUse::Default,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_datetime,
         StyleOrigin::Styles,
         StyleUse::Default,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_number,
         StyleOrigin::Styles,
         StyleUse::Default,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_percentage,
         StyleOrigin::Styles,
         StyleUse::Default,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_text,
         StyleOrigin::Styles,
         StyleUse::Default,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_timeduration,
         StyleOrigin::Styles,
         StyleUse::Default,
-        &mut xml_out,
+        xml_out,
     )?;
-    xml_out.end_elem("office:styles")?;
 
-    xml_out.elem("office:automatic-styles")?;
-    write_pagestyles(&book.pagestyles, &mut xml_out)?;
-    write_styles(book, StyleOrigin::Styles, StyleUse::Automatic, &mut xml_out)?;
-    write_valuestyles(
-        &book.formats_boolean,
-        StyleOrigin::Styles,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_currency,
-        StyleOrigin::Styles,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_datetime,
-        StyleOrigin::Styles,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_number,
-        StyleOrigin::Styles,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
+    Ok(())
+}
+
+fn write_office_automatic_styles<W: Write>(
+    book: &WorkBook,
+    origin: StyleOrigin,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    write_styles(book, origin, StyleUse::Automatic, xml_out)?;
+    write_valuestyles(&book.formats_boolean, origin, StyleUse::Automatic, xml_out)?;
+    write_valuestyles(&book.formats_currency, origin, StyleUse::Automatic, xml_out)?;
+    write_valuestyles(&book.formats_datetime, origin, StyleUse::Automatic, xml_out)?;
+    write_valuestyles(&book.formats_number, origin, StyleUse::Automatic, xml_out)?;
     write_valuestyles(
         &book.formats_percentage,
-        StyleOrigin::Styles,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_text,
-        StyleOrigin::Styles,
+        origin,
         StyleUse::Automatic,
-        &mut xml_out,
+        xml_out,
     )?;
+    write_valuestyles(&book.formats_text, origin, StyleUse::Automatic, xml_out)?;
     write_valuestyles(
         &book.formats_timeduration,
-        StyleOrigin::Styles,
+        origin,
         StyleUse::Automatic,
-        &mut xml_out,
+        xml_out,
     )?;
-    xml_out.end_elem("office:automatic-styles")?;
-
-    xml_out.elem("office:master-styles")?;
-    write_masterpage(&book.masterpages, &mut xml_out)?;
-    xml_out.end_elem("office:master-styles")?;
-
-    xml_out.end_elem("office:document-styles")?;
-
-    xml_out.close()?;
 
     Ok(())
 }
 
-fn write_ods_content<W: Write + Seek>(
-    book: &WorkBook,
-    zip_out: &mut OdsWriter<W>,
-) -> Result<(), OdsError> {
-    let w = zip_out.start_file("content.xml", FileOptions::default())?;
-    let mut xml_out = XmlWriter::new(w);
-
-    xml_out.dtd("UTF-8")?;
-
-    xml_out.elem("office:document-content")?;
+fn write_content_namespaces<W: Write>(xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
     xml_out.attr(
         "xmlns:meta",
         "urn:oasis:names:tc:opendocument:xmlns:meta:1.0",
@@ -1000,65 +1770,26 @@ fn write_ods_content<W: Write + Seek>(
         "urn:oasis:names:tc:opendocument:xmlns:presentation:1.0",
     )?;
 
-    xml_out.attr("office:version", book.version())?;
-
-    xml_out.empty("office:scripts")?;
+    Ok(())
+}
 
-    xml_out.elem("office:font-face-decls")?;
-    write_font_decl(&book.fonts, StyleOrigin::Content, &mut xml_out)?;
-    xml_out.end_elem("office:font-face-decls")?;
+fn write_office_body<W: Write>(
+    book: &WorkBook,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    write_office_body_start(book, xml_out)?;
+    for sheet in &book.sheets {
+        write_sheet(book, sheet, xml_out)?;
+    }
+    write_office_body_end(book, xml_out)?;
 
-    xml_out.elem("office:automatic-styles")?;
-    write_styles(
-        book,
-        StyleOrigin::Content,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_boolean,
-        StyleOrigin::Content,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_currency,
-        StyleOrigin::Content,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_datetime,
-        StyleOrigin::Content,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_number,
-        StyleOrigin::Content,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_percentage,
-        StyleOrigin::Content,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_text,
-        StyleOrigin::Content,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_timeduration,
-        StyleOrigin::Content,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    xml_out.end_elem("office:automatic-styles")?;
+    Ok(())
+}
 
+fn write_office_body_start<W: Write>(
+    book: &WorkBook,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
     xml_out.elem("office:body")?;
     xml_out.elem("office:spreadsheet")?;
 
@@ -1074,40 +1805,41 @@ fn write_ods_content<W: Write + Seek>(
             tag.name() == "table:calculation-settings" ||
             tag.name() == "table:label-ranges"
         {
-            write_xmltag(tag, &mut xml_out)?;
+            write_xmltag(tag, xml_out)?;
         }
     }
 
-    write_content_validations(book, &mut xml_out)?;
+    write_content_validations(book, xml_out)?;
 
-    for sheet in &book.sheets {
-        write_sheet(book, sheet, &mut xml_out)?;
-    }
+    Ok(())
+}
 
+fn write_office_body_end<W: Write>(
+    book: &WorkBook,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
     // extra tags. pass through only
+    write_named_expressions(book, &NameScope::Global, xml_out)?;
+
     for tag in &book.extra {
-        if tag.name() == "table:named-expressions"
-            || tag.name() == "table:database-ranges"
+        if tag.name() == "table:database-ranges"
             || tag.name() == "table:data-pilot-tables"
             || tag.name() == "table:consolidation"
             || tag.name() == "table:dde-links"
         {
-            write_xmltag(tag, &mut xml_out)?;
+            write_xmltag(tag, xml_out)?;
         }
     }
 
     xml_out.end_elem("office:spreadsheet")?;
     xml_out.end_elem("office:body")?;
-    xml_out.end_elem("office:document-content")?;
-
-    xml_out.close()?;
 
     Ok(())
 }
 
-fn write_content_validations<W: Write + Seek>(
+fn write_content_validations<W: Write>(
     book: &WorkBook,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     if !book.validations.is_empty() {
         xml_out.elem("table:content-validations")?;
@@ -1193,10 +1925,12 @@ pub(crate) fn remove_outlooped(ranges: &mut Vec<CellRange>, row: u32, col: u32)
         .collect();
 }
 
-fn write_sheet<W: Write + Seek>(
+/// Start of the table up to and including the column definitions.
+fn write_table_start<W: Write>(
     book: &WorkBook,
     sheet: &Sheet,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    max_cell: (u32, u32),
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     xml_out.elem("table:table")?;
     xml_out.attr_esc("table:name", &*sheet.name)?;
@@ -1213,8 +1947,6 @@ fn write_sheet<W: Write + Seek>(
         xml_out.attr("table:display", "false")?;
     }
 
-    let max_cell = sheet.used_grid_size();
-
     for tag in &sheet.extra {
         if tag.name() == "table:title"
             || tag.name() == "table:desc"
@@ -1222,22 +1954,244 @@ fn write_sheet<W: Write + Seek>(
             || tag.name() == "office:dde-source"
             || tag.name() == "table:scenario"
             || tag.name() == "office:forms"
-            || tag.name() == "table:shapes"
         {
             write_xmltag(tag, xml_out)?;
         }
     }
 
-    write_table_columns(sheet, max_cell, xml_out)?;
+    write_shapes(book, sheet, xml_out)?;
+
+    write_table_columns(sheet, max_cell, xml_out)?;
+
+    Ok(())
+}
+
+/// Images anchored to the page and any other shapes.
+fn write_shapes<W: Write>(
+    book: &WorkBook,
+    sheet: &Sheet,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    let shapes = sheet.extra.iter().filter(|v| v.name() == "table:shapes");
+    if sheet.page_images.is_empty() {
+        for tag in shapes {
+            write_xmltag(tag, xml_out)?;
+        }
+    } else {
+        xml_out.elem("table:shapes")?;
+        for image in &sheet.page_images {
+            write_image(book, image, xml_out)?;
+        }
+        for tag in shapes {
+            for content in tag.content() {
+                if let XmlContent::Tag(t) = content {
+                    write_xmltag(t, xml_out)?;
+                }
+            }
+        }
+        xml_out.end_elem("table:shapes")?;
+    }
+
+    Ok(())
+}
+
+/// End of the table including the trailing elements.
+fn write_table_end<W: Write>(
+    book: &WorkBook,
+    sheet: &Sheet,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    write_named_expressions(book, &NameScope::Sheet(sheet.name().clone()), xml_out)?;
+
+    write_conditional_formats(sheet, xml_out)?;
+
+    xml_out.end_elem("table:table")?;
+
+    Ok(())
+}
+
+fn write_conditional_formats<W: Write>(
+    sheet: &Sheet,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    if sheet.conditional_formats().is_empty() {
+        return Ok(());
+    }
+
+    xml_out.elem("calcext:conditional-formats")?;
+    for cf in sheet.conditional_formats() {
+        xml_out.elem("calcext:conditional-format")?;
+        xml_out.attr_esc(
+            "calcext:target-range-address",
+            cellranges_string(cf.ranges()),
+        )?;
+
+        for entry in cf.entries() {
+            match entry {
+                ConditionalEntry::Condition(v) => {
+                    xml_out.empty("calcext:condition")?;
+                    xml_out.attr_esc("calcext:apply-style-name", v.style().as_str())?;
+                    xml_out.attr_esc("calcext:value", condition_to_calcext(v.condition()))?;
+                    // Relative references default to the first cell.
+                    if let Some(base_cell) = v.base_cell() {
+                        xml_out.attr_esc("calcext:base-cell-address", base_cell.to_string())?;
+                    } else if let Some(range) = cf.ranges().first() {
+                        let base_cell = CellRef::new_all(
+                            range.iri().cloned(),
+                            range.table().cloned(),
+                            false,
+                            range.row(),
+                            false,
+                            range.col(),
+                        );
+                        xml_out.attr_esc("calcext:base-cell-address", base_cell.to_string())?;
+                    }
+                }
+                ConditionalEntry::ColorScale(v) => {
+                    xml_out.elem("calcext:color-scale")?;
+                    for e in v.entries() {
+                        write_scale_entry("calcext:color-scale-entry", e, xml_out)?;
+                    }
+                    xml_out.end_elem("calcext:color-scale")?;
+                }
+                ConditionalEntry::DataBar(v) => {
+                    xml_out.elem("calcext:data-bar")?;
+                    xml_out.attr("calcext:positive-color", color_string(v.positive_color()))?;
+                    if let Some(color) = v.negative_color() {
+                        xml_out.attr("calcext:negative-color", color_string(color))?;
+                    }
+                    if let Some(color) = v.axis_color() {
+                        xml_out.attr("calcext:axis-color", color_string(color))?;
+                    }
+                    if let Some(gradient) = v.gradient() {
+                        xml_out.attr("calcext:gradient", gradient.to_string())?;
+                    }
+                    if let Some(show_value) = v.show_value() {
+                        xml_out.attr("calcext:show-value", show_value.to_string())?;
+                    }
+                    if let Some(min_length) = v.min_length() {
+                        xml_out.attr("calcext:min-length", min_length.to_string())?;
+                    }
+                    if let Some(max_length) = v.max_length() {
+                        xml_out.attr("calcext:max-length", max_length.to_string())?;
+                    }
+                    write_scale_entry("calcext:formatting-entry", v.min(), xml_out)?;
+                    write_scale_entry("calcext:formatting-entry", v.max(), xml_out)?;
+                    xml_out.end_elem("calcext:data-bar")?;
+                }
+                ConditionalEntry::IconSet(v) => {
+                    xml_out.elem("calcext:icon-set")?;
+                    xml_out.attr_esc("calcext:icon-set-type", v.icon_set_type())?;
+                    if let Some(show_value) = v.show_value() {
+                        xml_out.attr("calcext:show-value", show_value.to_string())?;
+                    }
+                    for e in v.entries() {
+                        write_scale_entry("calcext:formatting-entry", e, xml_out)?;
+                    }
+                    xml_out.end_elem("calcext:icon-set")?;
+                }
+                ConditionalEntry::Date(v) => {
+                    xml_out.empty("calcext:date-is")?;
+                    xml_out.attr("calcext:date", v.date().to_string())?;
+                    xml_out.attr_esc("calcext:style", v.style().as_str())?;
+                }
+                ConditionalEntry::Xml(v) => {
+                    write_xmltag(v, xml_out)?;
+                }
+            }
+        }
+
+        xml_out.end_elem("calcext:conditional-format")?;
+    }
+    xml_out.end_elem("calcext:conditional-formats")?;
+
+    Ok(())
+}
+
+fn write_scale_entry<W: Write>(
+    tag: &'static str,
+    entry: &ScaleEntry,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.empty(tag)?;
+    xml_out.attr_esc("calcext:value", entry.value())?;
+    xml_out.attr("calcext:type", entry.scale_type().to_string())?;
+    if let Some(color) = entry.color() {
+        xml_out.attr("calcext:color", color_string(color))?;
+    }
+    Ok(())
+}
+
+fn write_named_expressions<W: Write>(
+    book: &WorkBook,
+    scope: &NameScope,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    let ranges: Vec<_> = book
+        .named_ranges
+        .iter()
+        .filter(|v| v.scope() == scope)
+        .collect();
+    let exprs: Vec<_> = book
+        .named_expressions
+        .iter()
+        .filter(|v| v.scope() == scope)
+        .collect();
+
+    if ranges.is_empty() && exprs.is_empty() {
+        return Ok(());
+    }
+
+    xml_out.elem("table:named-expressions")?;
+    for range in ranges {
+        xml_out.empty("table:named-range")?;
+        xml_out.attr_esc("table:name", range.name())?;
+        xml_out.attr_esc(
+            "table:base-cell-address",
+            range.base_cell().to_string().as_str(),
+        )?;
+        xml_out.attr_esc(
+            "table:cell-range-address",
+            range.range().to_string().as_str(),
+        )?;
+        if let Some(usable_as) = range.usable_as() {
+            xml_out.attr_esc("table:range-usable-as", usable_as)?;
+        }
+    }
+    for expr in exprs {
+        xml_out.empty("table:named-expression")?;
+        xml_out.attr_esc("table:name", expr.name())?;
+        if let Some(base_cell) = expr.base_cell() {
+            xml_out.attr_esc("table:base-cell-address", base_cell.to_string().as_str())?;
+        }
+        xml_out.attr_esc("table:expression", expr.expression())?;
+    }
+    xml_out.end_elem("table:named-expressions")?;
+
+    Ok(())
+}
+
+fn write_sheet<W: Write>(
+    book: &WorkBook,
+    sheet: &Sheet,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    let max_cell = sheet.used_grid_size();
+
+    write_table_start(book, sheet, max_cell, xml_out)?;
 
     // list of current spans
     let mut spans = Vec::<CellRange>::new();
 
+    // header-rows and row-groups
+    let mut nesting = Nesting::rows(sheet);
+
     // table-row + table-cell
     let mut first_cell = true;
     let mut last_r: u32 = 0;
-    let mut last_r_repeat: u32 = 1;
     let mut last_c: u32 = 0;
+    // First row after the last written one.
+    let mut next_row: u32 = 0;
 
     let mut it = sheet.into_iter();
     while let Some(((cur_row, cur_col), cell)) = it.next() {
@@ -1276,29 +2230,23 @@ fn write_sheet<W: Write + Seek>(
         // After the first cell there is always an open row tag that
         // needs to be closed.
         if backward_dr > 0 && !first_cell {
-            write_end_last_row(sheet, cur_row, backward_dr, xml_out)?;
-        }
-
-        // Any empty rows before this one?
-        if backward_dr > 0 {
-            // If the last row had a repeat counter the distance is reduced.
-            // We should not add any extra empty rows.
-            if last_r_repeat - 1 < backward_dr {
-                write_empty_rows_before(
-                    sheet,
-                    cur_row,
-                    first_cell,
-                    backward_dr - last_r_repeat + 1,
-                    max_cell,
-                    xml_out,
-                )?;
-            }
+            xml_out.end_elem("table:table-row")?;
         }
 
         // Start a new row if there is a delta or we are at the start.
         // Fills in any blank cells before the current cell.
         if backward_dr > 0 || first_cell {
+            // Any empty rows before this one?
+            write_empty_rows(sheet, &mut nesting, next_row, cur_row, max_cell, xml_out)?;
+
+            nesting.enter(cur_row, xml_out)?;
             write_start_current_row(sheet, cur_row, backward_dc, xml_out)?;
+
+            next_row = if let Some(row_header) = sheet.row_header.get(&cur_row) {
+                cur_row + row_header.repeat
+            } else {
+                cur_row + 1
+            };
         }
 
         // Remove no longer usefull cell-spans.
@@ -1318,7 +2266,7 @@ fn write_sheet<W: Write + Seek>(
         // The last cell we will write? We can close the last row here,
         // where we have all the data.
         if is_last_cell {
-            write_end_current_row(sheet, cur_row, xml_out)?;
+            xml_out.end_elem("table:table-row")?;
         }
 
         // maybe span. only if visible, that nicely eliminates all
@@ -1331,29 +2279,30 @@ fn write_sheet<W: Write + Seek>(
 
         first_cell = false;
         last_r = cur_row;
-        last_r_repeat = if let Some(row_header) = sheet.row_header.get(&cur_row) {
-            row_header.repeat
-        } else {
-            1
-        };
         last_c = cur_col;
     }
 
-    xml_out.end_elem("table:table")?;
+    // Groups may extend beyond the data.
+    let end = nesting.end();
+    write_empty_rows(
+        sheet,
+        &mut nesting,
+        next_row,
+        end,
+        (max_cell.0, max(max_cell.1, 1)),
+        xml_out,
+    )?;
+    nesting.close_all(xml_out)?;
 
-    for tag in &sheet.extra {
-        if tag.name() == "table:named-expressions" || tag.name() == "calcext:conditional-formats" {
-            write_xmltag(tag, xml_out)?;
-        }
-    }
+    write_table_end(book, sheet, xml_out)?;
 
     Ok(())
 }
 
-fn write_empty_cells<W: Write + Seek>(
+fn write_empty_cells<W: Write>(
     mut forward_dc: u32,
     hidden_cols: u32,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     // split between hidden and regular cells.
     if hidden_cols >= forward_dc {
@@ -1379,19 +2328,12 @@ fn write_empty_cells<W: Write + Seek>(
     Ok(())
 }
 
-fn write_start_current_row<W: Write + Seek>(
+fn write_start_current_row<W: Write>(
     sheet: &Sheet,
     cur_row: u32,
     backward_dc: u32,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
-    // Start of headers
-    if let Some(header_rows) = &sheet.header_rows {
-        if header_rows.row() == cur_row {
-            xml_out.elem("table:table-header-rows")?;
-        }
-    }
-
     xml_out.elem("table:table-row")?;
     if let Some(row_header) = sheet.row_header.get(&cur_row) {
         if row_header.repeat > 1 {
@@ -1421,115 +2363,58 @@ fn write_start_current_row<W: Write + Seek>(
     Ok(())
 }
 
-fn write_end_last_row<W: Write + Seek>(
-    sheet: &Sheet,
-    cur_row: u32,
-    backward_dr: u32,
-    xml_out: &mut XmlOdsWriter<'_, W>,
-) -> Result<(), OdsError> {
-    xml_out.end_elem("table:table-row")?;
-
-    // This row was the end of the header.
-    if let Some(header_rows) = &sheet.header_rows {
-        let last_row = cur_row - backward_dr;
-        if header_rows.to_row() == last_row {
-            xml_out.end_elem("table:table-header-rows")?;
-        }
-    }
-
-    Ok(())
-}
-
-fn write_end_current_row<W: Write + Seek>(
-    sheet: &Sheet,
-    cur_row: u32,
-    xml_out: &mut XmlOdsWriter<'_, W>,
-) -> Result<(), OdsError> {
-    xml_out.end_elem("table:table-row")?;
-
-    // This row was the end of the header.
-    if let Some(header_rows) = &sheet.header_rows {
-        if header_rows.to_row() == cur_row {
-            xml_out.end_elem("table:table-header-rows")?;
-        }
-    }
-
-    Ok(())
-}
-
-fn write_empty_rows_before<W: Write + Seek>(
+/// Writes empty rows from..to. The rows are split up at the start and
+/// end of any group and where the row header changes.
+fn write_empty_rows<W: Write>(
     sheet: &Sheet,
-    cur_row: u32,
-    first_cell: bool,
-    mut backward_dr: u32,
+    nesting: &mut Nesting,
+    from: u32,
+    to: u32,
     max_cell: (u32, u32),
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
-    // Empty rows in between are 1 less than the delta, except at the very start.
-    #[allow(clippy::bool_to_int_with_if)]
-    let mut corr = if first_cell { 0u32 } else { 1u32 };
-
-    // Only deltas greater 1 are relevant.
-    // Or if this is the very start.
-    if backward_dr > 1 || first_cell && backward_dr > 0 {
-        // split up the empty rows, if there is some header stuff.
-        if let Some(header_rows) = &sheet.header_rows {
-            // What was the last_row? Was there a header start since?
-            let last_row = cur_row - backward_dr;
-            if header_rows.row() < cur_row && header_rows.row() > last_row {
-                write_empty_row(
-                    sheet,
-                    last_row,
-                    header_rows.row() - last_row - corr,
-                    max_cell,
-                    xml_out,
-                )?;
-                xml_out.elem("table:table-header-rows")?;
-                // Don't write the empty line for the first header-row, we can
-                // collapse it with the rest. corr suits fine for this.
-                corr = 0;
-                // We correct the empty line count.
-                backward_dr = cur_row - header_rows.row();
-            }
-
-            // What was the last row here? Was there a header end since?
-            let last_row = cur_row - backward_dr;
-            if header_rows.to_row() < cur_row && header_rows.to_row() > cur_row - backward_dr {
-                // Empty lines, including the current line that marks
-                // the end of the header.
-                write_empty_row(
-                    sheet,
-                    last_row,
-                    header_rows.to_row() - last_row - corr + 1,
-                    max_cell,
-                    xml_out,
-                )?;
-                xml_out.end_elem("table:table-header-rows")?;
-                // Correction for table start is no longer needed.
-                corr = 1;
-                // We correct the empty line count.
-                backward_dr = cur_row - header_rows.to_row();
+    let mut row = from;
+    while row < to {
+        nesting.enter(row, xml_out)?;
+
+        let mut end = to;
+        if let Some(boundary) = nesting.next_boundary(row) {
+            end = min(end, boundary);
+        }
+        if let Some((header_row, row_header)) = sheet.row_header.range(..=row).next_back() {
+            let header_end = *header_row + max(row_header.repeat, 1);
+            if row < header_end {
+                end = min(end, header_end);
             }
         }
+        if let Some((next, _)) = sheet.row_header.range(row + 1..end).next() {
+            end = *next;
+        }
 
-        // Write out the empty lines.
-        let last_row = cur_row - backward_dr;
-        write_empty_row(sheet, last_row, backward_dr - corr, max_cell, xml_out)?;
+        write_empty_row(sheet, row, end - row, max_cell, xml_out)?;
+        row = end;
     }
 
     Ok(())
 }
 
-fn write_empty_row<W: Write + Seek>(
+fn write_empty_row<W: Write>(
     sheet: &Sheet,
     cur_row: u32,
     empty_count: u32,
     max_cell: (u32, u32),
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     xml_out.elem("table:table-row")?;
     xml_out.attr("table:number-rows-repeated", &empty_count.to_string())?;
-    if let Some(row_header) = sheet.row_header.get(&cur_row) {
+    // A repeated row header may have been split at a group boundary.
+    let row_header = sheet
+        .row_header
+        .range(..=cur_row)
+        .next_back()
+        .filter(|(header_row, v)| cur_row < **header_row + max(v.repeat, 1))
+        .map(|(_, v)| v);
+    if let Some(row_header) = row_header {
         if let Some(rowstyle) = row_header.style() {
             xml_out.attr_esc("table:style-name", rowstyle.as_str())?;
         }
@@ -1554,10 +2439,7 @@ fn write_empty_row<W: Write + Seek>(
     Ok(())
 }
 
-fn write_xmltag<W: Write + Seek>(
-    x: &XmlTag,
-    xml_out: &mut XmlOdsWriter<'_, W>,
-) -> Result<(), OdsError> {
+fn write_xmltag<W: Write>(x: &XmlTag, xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
     if x.is_empty() {
         xml_out.empty(x.name())?;
     } else {
@@ -1585,19 +2467,17 @@ fn write_xmltag<W: Write + Seek>(
     Ok(())
 }
 
-fn write_table_columns<W: Write + Seek>(
+fn write_table_columns<W: Write>(
     sheet: &Sheet,
     max_cell: (u32, u32),
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
+    // header-columns and column-groups
+    let mut nesting = Nesting::cols(sheet);
+
     // table:table-column
-    for c in 0..max_cell.1 {
-        // markup header columns
-        if let Some(header_cols) = &sheet.header_cols {
-            if header_cols.col() == c {
-                xml_out.elem("table:table-header-columns")?;
-            }
-        }
+    for c in 0..max(max_cell.1, nesting.end()) {
+        nesting.enter(c, xml_out)?;
 
         xml_out.empty("table:table-column")?;
         if let Some(col_header) = sheet.col_header.get(&c) {
@@ -1614,24 +2494,166 @@ fn write_table_columns<W: Write + Seek>(
                 )?;
             }
         }
+    }
+
+    nesting.close_all(xml_out)?;
+
+    Ok(())
+}
+
+/// Tracks the nesting of header-rows and row-groups resp.
+/// header-columns and column-groups while writing rows/columns.
+#[derive(Debug, Default)]
+struct Nesting {
+    /// (from, to, tag, display) sorted with the outer elements first.
+    ranges: Vec<(u32, u32, &'static str, bool)>,
+    /// Next range that has not been opened yet.
+    next: usize,
+    /// Currently open ranges.
+    open: Vec<usize>,
+}
+
+impl Nesting {
+    fn rows(sheet: &Sheet) -> Self {
+        let mut ranges = Vec::new();
+        for v in sheet.row_groups() {
+            ranges.push((v.from(), v.to(), "table:table-row-group", v.display()));
+        }
+        if let Some(header_rows) = &sheet.header_rows {
+            ranges.push((
+                header_rows.row(),
+                header_rows.to_row(),
+                "table:table-header-rows",
+                true,
+            ));
+        }
+        Self::new(ranges)
+    }
 
-        // markup header columns
+    fn cols(sheet: &Sheet) -> Self {
+        let mut ranges = Vec::new();
+        for v in sheet.col_groups() {
+            ranges.push((v.from(), v.to(), "table:table-column-group", v.display()));
+        }
         if let Some(header_cols) = &sheet.header_cols {
-            if header_cols.to_col() == c {
-                xml_out.end_elem("table:table-header-columns")?;
+            ranges.push((
+                header_cols.col(),
+                header_cols.to_col(),
+                "table:table-header-columns",
+                true,
+            ));
+        }
+        Self::new(ranges)
+    }
+
+    fn new(mut ranges: Vec<(u32, u32, &'static str, bool)>) -> Self {
+        // stable sort, groups enclose the headers with the same range.
+        ranges.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+        Self {
+            ranges,
+            next: 0,
+            open: Vec::new(),
+        }
+    }
+
+    /// First row/column after all ranges.
+    fn end(&self) -> u32 {
+        self.ranges.iter().map(|v| v.1 + 1).max().unwrap_or(0)
+    }
+
+    /// Next position after pos where some element must be opened or
+    /// closed.
+    fn next_boundary(&self, pos: u32) -> Option<u32> {
+        let close = self.open.iter().map(|v| self.ranges[*v].1 + 1);
+        let open = self.ranges[self.next..]
+            .iter()
+            .map(|v| v.0)
+            .filter(|v| *v > pos);
+        close.chain(open).filter(|v| *v > pos).min()
+    }
+
+    /// Closes all elements that end before pos and opens the ones
+    /// that start at pos.
+    ///
+    /// Partially overlapping elements can't be nested in xml, instead
+    /// they are split in two. Groups are split, header rows/columns are
+    /// kept in one piece as there can be only one.
+    fn enter<W: Write>(&mut self, pos: u32, xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
+        // Elements on top of an ending one are closed and reopened.
+        if let Some(depth) = self.open.iter().position(|v| self.ranges[*v].1 < pos) {
+            let reopen = self.close_to(depth, pos, xml_out)?;
+            for idx in reopen.into_iter().rev() {
+                self.start(idx, xml_out)?;
+            }
+        }
+
+        while self.next < self.ranges.len() && self.ranges[self.next].0 <= pos {
+            let (_, to, tag, _) = self.ranges[self.next];
+            if to >= pos {
+                // Header rows/columns are never split. Any element that
+                // ends earlier is reopened inside.
+                let mut reopen = Vec::new();
+                if !tag.ends_with("-group") {
+                    if let Some(depth) = self.open.iter().position(|v| self.ranges[*v].1 < to) {
+                        reopen = self.close_to(depth, pos, xml_out)?;
+                    }
+                }
+                self.start(self.next, xml_out)?;
+                for idx in reopen.into_iter().rev() {
+                    self.start(idx, xml_out)?;
+                }
+            }
+            self.next += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Closes the open elements down to depth. Returns the closed
+    /// elements that continue at pos, innermost first.
+    fn close_to<W: Write>(
+        &mut self,
+        depth: usize,
+        pos: u32,
+        xml_out: &mut XmlWriter<W>,
+    ) -> Result<Vec<usize>, OdsError> {
+        let mut reopen = Vec::new();
+        while self.open.len() > depth {
+            if let Some(idx) = self.open.pop() {
+                xml_out.end_elem(self.ranges[idx].2)?;
+                if self.ranges[idx].1 >= pos {
+                    reopen.push(idx);
+                }
             }
         }
+        Ok(reopen)
     }
 
-    Ok(())
+    /// Opens the element.
+    fn start<W: Write>(&mut self, idx: usize, xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
+        let (_, _, tag, display) = self.ranges[idx];
+        xml_out.elem(tag)?;
+        if !display {
+            xml_out.attr("table:display", "false")?;
+        }
+        self.open.push(idx);
+        Ok(())
+    }
+
+    /// Closes all open elements.
+    fn close_all<W: Write>(&mut self, xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
+        while let Some(idx) = self.open.pop() {
+            xml_out.end_elem(self.ranges[idx].2)?;
+        }
+        Ok(())
+    }
 }
 
-#[allow(clippy::single_char_add_str)]
-fn write_cell<W: Write + Seek>(
+fn write_cell<W: Write>(
     book: &WorkBook,
     cell: &CellContentRef<'_>,
     is_hidden: bool,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     let tag = if is_hidden {
         "table:covered-table-cell"
@@ -1639,9 +2661,13 @@ fn write_cell<W: Write + Seek>(
         "table:table-cell"
     };
 
-    match cell.value {
-        None | Some(Value::Empty) => xml_out.empty(tag)?,
-        _ => xml_out.elem(tag)?,
+    let has_content = !matches!(cell.value, None | Some(Value::Empty))
+        || cell.annotation.is_some()
+        || !cell.images.is_empty();
+    if has_content {
+        xml_out.elem(tag)?;
+    } else {
+        xml_out.empty(tag)?;
     }
 
     if let Some(formula) = cell.formula {
@@ -1677,6 +2703,18 @@ fn write_cell<W: Write + Seek>(
             )?;
         }
     }
+    if let Some(span) = cell.matrix_span {
+        if span.row_span > 1 || span.col_span > 1 {
+            xml_out.attr_esc(
+                "table:number-matrix-columns-spanned",
+                span.col_span.to_string().as_str(),
+            )?;
+            xml_out.attr_esc(
+                "table:number-matrix-rows-spanned",
+                span.row_span.to_string().as_str(),
+            )?;
+        }
+    }
 
     // This finds the correct ValueFormat, but there is no way to use it.
     // Falls back to: Output the same string as needed for the value-attribute
@@ -1690,8 +2728,74 @@ fn write_cell<W: Write + Seek>(
 
     match cell.value {
         None | Some(Value::Empty) => {}
-        Some(Value::Text(s)) => {
+        Some(Value::Text(_)) | Some(Value::TextXml(_)) => {
+            xml_out.attr("office:value-type", "string")?;
+        }
+        Some(Value::DateTime(d)) => {
+            xml_out.attr("office:value-type", "date")?;
+            let value = d.format("%Y-%m-%dT%H:%M:%S%.f").to_string();
+            xml_out.attr("office:date-value", value.as_str())?;
+        }
+        Some(Value::Date(d)) => {
+            xml_out.attr("office:value-type", "date")?;
+            let value = d.format("%Y-%m-%d").to_string();
+            xml_out.attr("office:date-value", value.as_str())?;
+        }
+        Some(Value::TimeOfDay(t)) => {
+            xml_out.attr("office:value-type", "time")?;
+            let value = t.format("PT%HH%MM%S%.fS").to_string();
+            xml_out.attr("office:time-value", value.as_str())?;
+        }
+        Some(Value::TimeDuration(d)) => {
+            xml_out.attr("office:value-type", "time")?;
+            xml_out.attr("office:time-value", format_time_duration(d).as_str())?;
+        }
+        Some(Value::Boolean(b)) => {
+            xml_out.attr("office:value-type", "boolean")?;
+            xml_out.attr("office:boolean-value", if *b { "true" } else { "false" })?;
+        }
+        Some(Value::Currency(v, c)) => {
+            xml_out.attr("office:value-type", "currency")?;
+            xml_out.attr_esc("office:currency", c)?;
+            let value = v.to_string();
+            xml_out.attr("office:value", value.as_str())?;
+        }
+        Some(Value::Number(v)) => {
+            xml_out.attr("office:value-type", "float")?;
+            let value = v.to_string();
+            xml_out.attr("office:value", value.as_str())?;
+        }
+        Some(Value::Decimal(v)) => {
+            xml_out.attr("office:value-type", "float")?;
+            let value = v.to_string();
+            xml_out.attr("office:value", value.as_str())?;
+        }
+        Some(Value::DecimalCurrency(v, c)) => {
+            xml_out.attr("office:value-type", "currency")?;
+            xml_out.attr_esc("office:currency", c)?;
+            let value = v.to_string();
+            xml_out.attr("office:value", value.as_str())?;
+        }
+        Some(Value::Percentage(v)) => {
+            xml_out.attr("office:value-type", "percentage")?;
+            let value = v.to_string();
+            xml_out.attr("office:value", value.as_str())?;
+        }
+        Some(Value::Error(_)) => {
             xml_out.attr("office:value-type", "string")?;
+            xml_out.attr("office:string-value", "")?;
+            xml_out.attr("calcext:value-type", "error")?;
+        }
+    }
+
+    // The annotation precedes the text content.
+    if let Some(annotation) = cell.annotation {
+        write_annotation(annotation, xml_out)?;
+    }
+
+    match cell.value {
+        None | Some(Value::Empty) => {}
+        Some(Value::Text(s)) => {
             for l in s.split('\n') {
                 xml_out.elem("text:p")?;
                 xml_out.text_esc(l)?;
@@ -1699,86 +2803,239 @@ fn write_cell<W: Write + Seek>(
             }
         }
         Some(Value::TextXml(t)) => {
-            xml_out.attr("office:value-type", "string")?;
             for tt in t.iter() {
                 write_xmltag(tt, xml_out)?;
             }
         }
         Some(Value::DateTime(d)) => {
-            xml_out.attr("office:value-type", "date")?;
             let value = d.format("%Y-%m-%dT%H:%M:%S%.f").to_string();
-            xml_out.attr("office:date-value", value.as_str())?;
+            xml_out.elem("text:p")?;
+            xml_out.text_esc(value)?;
+            xml_out.end_elem("text:p")?;
+        }
+        Some(Value::Date(d)) => {
+            let value = d.format("%Y-%m-%d").to_string();
+            xml_out.elem("text:p")?;
+            xml_out.text_esc(value)?;
+            xml_out.end_elem("text:p")?;
+        }
+        Some(Value::TimeOfDay(t)) => {
+            let value = t.format("%H:%M:%S%.f").to_string();
             xml_out.elem("text:p")?;
             xml_out.text_esc(value)?;
             xml_out.end_elem("text:p")?;
         }
         Some(Value::TimeDuration(d)) => {
-            xml_out.attr("office:value-type", "time")?;
-
-            let mut value = String::from("PT");
-            value.push_str(&d.num_hours().to_string());
-            value.push_str("H");
-            value.push_str(&(d.num_minutes() % 60).to_string());
-            value.push_str("M");
-            value.push_str(&(d.num_seconds() % 60).to_string());
-            value.push_str(".");
-            value.push_str(&(d.num_milliseconds() % 1000).to_string());
-            value.push_str("S");
-
-            xml_out.attr("office:time-value", value.as_str())?;
-
             xml_out.elem("text:p")?;
-            xml_out.text(value)?;
+            xml_out.text(format_duration(d))?;
             xml_out.end_elem("text:p")?;
         }
         Some(Value::Boolean(b)) => {
-            xml_out.attr("office:value-type", "boolean")?;
-            xml_out.attr("office:boolean-value", if *b { "true" } else { "false" })?;
             xml_out.elem("text:p")?;
             xml_out.text(if *b { "true" } else { "false" })?;
             xml_out.end_elem("text:p")?;
         }
         Some(Value::Currency(v, c)) => {
-            xml_out.attr("office:value-type", "currency")?;
-            xml_out.attr_esc("office:currency", c)?;
-            let value = v.to_string();
-            xml_out.attr("office:value", value.as_str())?;
             xml_out.elem("text:p")?;
             xml_out.text(c)?;
             xml_out.text(" ")?;
-            xml_out.text(value)?;
+            xml_out.text(v.to_string())?;
+            xml_out.end_elem("text:p")?;
+        }
+        Some(Value::DecimalCurrency(v, c)) => {
+            xml_out.elem("text:p")?;
+            xml_out.text(c)?;
+            xml_out.text(" ")?;
+            xml_out.text(v.to_string())?;
             xml_out.end_elem("text:p")?;
         }
         Some(Value::Number(v)) => {
-            xml_out.attr("office:value-type", "float")?;
-            let value = v.to_string();
-            xml_out.attr("office:value", value.as_str())?;
             xml_out.elem("text:p")?;
-            xml_out.text(value)?;
+            xml_out.text(v.to_string())?;
+            xml_out.end_elem("text:p")?;
+        }
+        Some(Value::Decimal(v)) => {
+            xml_out.elem("text:p")?;
+            xml_out.text(v.to_string())?;
             xml_out.end_elem("text:p")?;
         }
         Some(Value::Percentage(v)) => {
-            xml_out.attr("office:value-type", "percentage")?;
-            let value = v.to_string();
-            xml_out.attr("office:value", value.as_str())?;
             xml_out.elem("text:p")?;
-            xml_out.text(value)?;
+            xml_out.text(v.to_string())?;
+            xml_out.end_elem("text:p")?;
+        }
+        Some(Value::Error(e)) => {
+            xml_out.elem("text:p")?;
+            xml_out.text(e.as_str())?;
             xml_out.end_elem("text:p")?;
         }
     }
 
-    match cell.value {
-        None | Some(Value::Empty) => {}
-        _ => xml_out.end_elem(tag)?,
+    for image in cell.images {
+        write_image(book, image, xml_out)?;
+    }
+
+    if has_content {
+        xml_out.end_elem(tag)?;
+    }
+
+    Ok(())
+}
+
+// Images without a style use a default graphic style. It is only
+// written as an automatic style, the book is not changed.
+fn default_image_style(book: &WorkBook) -> String {
+    let mut name = "gr_image".to_string();
+    let mut idx = 0;
+    while book.graphicstyles.contains_key(&name) {
+        idx += 1;
+        name = format!("gr_image{}", idx);
+    }
+    name
+}
+
+fn has_default_image_style(book: &WorkBook) -> bool {
+    book.sheets
+        .iter()
+        .any(|v| v.all_images().any(|i| i.style().is_none()))
+}
+
+fn write_default_image_style<W: Write>(
+    book: &WorkBook,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    let mut style = GraphicStyle::new(default_image_style(book));
+    let props = style.graphicstyle_mut();
+    props.set_attr("draw:stroke", "none".to_string());
+    props.set_attr("draw:fill", "none".to_string());
+    write_graphicstyle(&style, xml_out)
+}
+
+fn write_image<W: Write>(
+    book: &WorkBook,
+    image: &Image,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.elem("draw:frame")?;
+    if !image.name().is_empty() {
+        xml_out.attr_esc("draw:name", image.name())?;
+    }
+    if let Some(style) = image.style() {
+        xml_out.attr_esc("draw:style-name", style.as_str())?;
+    } else {
+        xml_out.attr_esc("draw:style-name", default_image_style(book))?;
+    }
+    let (width, height) = image.size();
+    let (x, y) = image.offset();
+    for (k, v) in [
+        ("svg:width", width),
+        ("svg:height", height),
+        ("svg:x", x),
+        ("svg:y", y),
+    ] {
+        if v != Length::Default {
+            xml_out.attr(k, v.to_string())?;
+        }
     }
+    for (k, v) in image.attrmap().iter() {
+        xml_out.attr_esc(k.as_ref(), v.as_str())?;
+    }
+
+    xml_out.elem("draw:image")?;
+    if !image.href().is_empty() {
+        xml_out.attr_esc("xlink:href", image.href())?;
+        xml_out.attr("xlink:type", "simple")?;
+        xml_out.attr("xlink:show", "embed")?;
+        xml_out.attr("xlink:actuate", "onLoad")?;
+    }
+    if !image.mime().is_empty() {
+        xml_out.attr_esc("loext:mime-type", image.mime())?;
+    }
+    if image.href().is_empty() {
+        xml_out.elem_text("office:binary-data", STANDARD.encode(image.data()))?;
+    }
+    xml_out.end_elem("draw:image")?;
+
+    if let Some(title) = image.title() {
+        xml_out.elem_text_esc("svg:title", title)?;
+    }
+    if let Some(desc) = image.desc() {
+        xml_out.elem_text_esc("svg:desc", desc)?;
+    }
+
+    xml_out.end_elem("draw:frame")?;
+
+    Ok(())
+}
+
+#[allow(clippy::single_char_add_str)]
+fn format_duration(d: &Duration) -> String {
+    let mut value = String::from("PT");
+    value.push_str(&d.num_hours().to_string());
+    value.push_str("H");
+    value.push_str(&(d.num_minutes() % 60).to_string());
+    value.push_str("M");
+    value.push_str(&(d.num_seconds() % 60).to_string());
+    value.push_str(".");
+    value.push_str(&(d.num_milliseconds() % 1000).to_string());
+    value.push_str("S");
+    value
+}
+
+/// Duration for an office:time-value. A negative duration gets one
+/// sign in front of the P.
+fn format_time_duration(d: &Duration) -> String {
+    let sign = if *d < Duration::zero() { "-" } else { "" };
+    let d = d.abs();
+    format!(
+        "{}P{}DT{}H{}M{}.{:03}S",
+        sign,
+        d.num_days(),
+        d.num_hours() % 24,
+        d.num_minutes() % 60,
+        d.num_seconds() % 60,
+        d.num_milliseconds() % 1000
+    )
+}
+
+fn write_annotation<W: Write>(
+    annotation: &Annotation,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.elem("office:annotation")?;
+    if annotation.display() {
+        xml_out.attr("office:display", "true")?;
+    }
+    if !annotation.name().is_empty() {
+        xml_out.attr_esc("office:name", annotation.name())?;
+    }
+    for (k, v) in annotation.attrmap().iter() {
+        xml_out.attr_esc(k.as_ref(), v.as_str())?;
+    }
+
+    if let Some(author) = annotation.author() {
+        xml_out.elem("dc:creator")?;
+        xml_out.text_esc(author)?;
+        xml_out.end_elem("dc:creator")?;
+    }
+    if let Some(date) = annotation.date() {
+        xml_out.elem("dc:date")?;
+        xml_out.text(date.format("%Y-%m-%dT%H:%M:%S%.f").to_string())?;
+        xml_out.end_elem("dc:date")?;
+    }
+    for t in annotation.text() {
+        write_xmltag(t, xml_out)?;
+    }
+
+    xml_out.end_elem("office:annotation")?;
 
     Ok(())
 }
 
-fn write_font_decl<W: Write + Seek>(
+fn write_font_decl<W: Write>(
     fonts: &HashMap<String, FontFaceDecl>,
     origin: StyleOrigin,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     for font in fonts.values().filter(|s| s.origin() == origin) {
         xml_out.empty("style:font-face")?;
@@ -1790,11 +3047,11 @@ fn write_font_decl<W: Write + Seek>(
     Ok(())
 }
 
-fn write_styles<W: Write + Seek>(
+fn write_styles<W: Write>(
     book: &WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     for style in book.tablestyles.values() {
         if style.origin() == origin && style.styleuse() == styleuse {
@@ -1844,9 +3101,9 @@ fn write_styles<W: Write + Seek>(
     Ok(())
 }
 
-fn write_tablestyle<W: Write + Seek>(
+fn write_tablestyle<W: Write>(
     style: &TableStyle,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     if style.styleuse() == StyleUse::Default {
         xml_out.elem("style:default-style")?;
@@ -1880,10 +3137,7 @@ fn write_tablestyle<W: Write + Seek>(
     Ok(())
 }
 
-fn write_rowstyle<W: Write + Seek>(
-    style: &RowStyle,
-    xml_out: &mut XmlOdsWriter<'_, W>,
-) -> Result<(), OdsError> {
+fn write_rowstyle<W: Write>(style: &RowStyle, xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
     if style.styleuse() == StyleUse::Default {
         xml_out.elem("style:default-style")?;
     } else {
@@ -1916,10 +3170,7 @@ fn write_rowstyle<W: Write + Seek>(
     Ok(())
 }
 
-fn write_colstyle<W: Write + Seek>(
-    style: &ColStyle,
-    xml_out: &mut XmlOdsWriter<'_, W>,
-) -> Result<(), OdsError> {
+fn write_colstyle<W: Write>(style: &ColStyle, xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
     if style.styleuse() == StyleUse::Default {
         xml_out.elem("style:default-style")?;
     } else {
@@ -1952,9 +3203,9 @@ fn write_colstyle<W: Write + Seek>(
     Ok(())
 }
 
-fn write_cellstyle<W: Write + Seek>(
+fn write_cellstyle<W: Write>(
     style: &CellStyle,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     if style.styleuse() == StyleUse::Default {
         xml_out.elem("style:default-style")?;
@@ -2010,9 +3261,9 @@ fn write_cellstyle<W: Write + Seek>(
     Ok(())
 }
 
-fn write_paragraphstyle<W: Write + Seek>(
+fn write_paragraphstyle<W: Write>(
     style: &ParagraphStyle,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     if style.styleuse() == StyleUse::Default {
         xml_out.elem("style:default-style")?;
@@ -2070,9 +3321,9 @@ fn write_paragraphstyle<W: Write + Seek>(
     Ok(())
 }
 
-fn write_textstyle<W: Write + Seek>(
+fn write_textstyle<W: Write>(
     style: &TextStyle,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     if style.styleuse() == StyleUse::Default {
         xml_out.elem("style:default-style")?;
@@ -2106,9 +3357,9 @@ fn write_textstyle<W: Write + Seek>(
     Ok(())
 }
 
-fn write_graphicstyle<W: Write + Seek>(
+fn write_graphicstyle<W: Write>(
     style: &GraphicStyle,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     if style.styleuse() == StyleUse::Default {
         xml_out.elem("style:default-style")?;
@@ -2143,11 +3394,11 @@ fn write_graphicstyle<W: Write + Seek>(
     Ok(())
 }
 
-fn write_valuestyles<W: Write + Seek, T: ValueFormatTrait>(
+fn write_valuestyles<W: Write, T: ValueFormatTrait>(
     value_formats: &HashMap<String, T>,
     origin: StyleOrigin,
     styleuse: StyleUse,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     for value_format in value_formats
         .values()
@@ -2162,7 +3413,9 @@ fn write_valuestyles<W: Write + Seek, T: ValueFormatTrait>(
             ValueType::TimeDuration => "number:time-style",
             ValueType::Percentage => "number:percentage-style",
             ValueType::Currency => "number:currency-style",
-            ValueType::DateTime => "number:date-style",
+            ValueType::DateTime | ValueType::Date => "number:date-style",
+            ValueType::TimeOfDay => "number:time-style",
+            ValueType::Error => unreachable!(),
         };
 
         xml_out.elem(tag)?;
@@ -2255,9 +3508,9 @@ fn write_valuestyles<W: Write + Seek, T: ValueFormatTrait>(
     Ok(())
 }
 
-fn write_pagestyles<W: Write + Seek>(
+fn write_pagestyles<W: Write>(
     styles: &HashMap<String, PageStyle>,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     for style in styles.values() {
         xml_out.elem("style:page-layout")?;
@@ -2297,9 +3550,9 @@ fn write_pagestyles<W: Write + Seek>(
     Ok(())
 }
 
-fn write_masterpage<W: Write + Seek>(
+fn write_masterpage<W: Write>(
     styles: &HashMap<String, MasterPage>,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     for style in styles.values() {
         xml_out.elem("style:master-page")?;
@@ -2358,10 +3611,7 @@ fn write_masterpage<W: Write + Seek>(
     Ok(())
 }
 
-fn write_regions<W: Write + Seek>(
-    hf: &HeaderFooter,
-    xml_out: &mut XmlOdsWriter<'_, W>,
-) -> Result<(), OdsError> {
+fn write_regions<W: Write>(hf: &HeaderFooter, xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
     for left in hf.left() {
         xml_out.elem("style:region-left")?;
         write_xmltag(left, xml_out)?;
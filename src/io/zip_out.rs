@@ -70,6 +70,17 @@ impl<W: Write + Seek> ZipOut<W> {
         })
     }
 
+    /// Starts a new file without borrowing the ZipOut. Everything written
+    /// to the ZipOut itself goes to this file.
+    pub(crate) fn start_file_direct<S: Into<String>>(
+        &mut self,
+        name: S,
+        options: FileOptions,
+    ) -> Result<(), ZipError> {
+        let options = options.compression_method(self.compression);
+        self.zip.start_file(name, options)
+    }
+
     pub(crate) fn zip(mut self) -> Result<W, ZipError> {
         self.zip.finish()
     }
@@ -84,3 +95,13 @@ impl<W: Write + Seek> Write for ZipWrite<'_, W> {
         self.write.flush()
     }
 }
+
+impl<W: Write + Seek> Write for ZipOut<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, IOError> {
+        self.zip.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), IOError> {
+        self.zip.flush()
+    }
+}
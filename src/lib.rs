@@ -67,7 +67,7 @@
 //! * Spread-sheets
 //!   * Handles all datatypes
 //!     * Uses time::Duration
-//!     * Uses chrono::NaiveDate and NaiveDateTime
+//!     * Uses chrono::NaiveDate, NaiveTime and NaiveDateTime
 //!   * Column/Row/Cell styles
 //!   * Formulas
 //!     * Only as strings, but support functions for cell/range references.
@@ -93,6 +93,8 @@
 //!
 //! * Content validation
 //!
+//! * Named ranges and named expressions
+//!
 //! * Fonts
 //!   * Preserves all font attributes.
 //!   * Basic support for setting this stuff.
@@ -189,19 +191,37 @@ pub use crate::format::{
     ValueFormatBoolean, ValueFormatCurrency, ValueFormatDateTime, ValueFormatNumber,
     ValueFormatPercentage, ValueFormatRef, ValueFormatText, ValueFormatTimeDuration,
 };
-pub use crate::io::read::{read_ods, read_ods_buf, read_ods_from};
-pub use crate::io::write::{write_ods, write_ods_buf, write_ods_buf_uncompressed, write_ods_to};
+pub use crate::io::read::{
+    read_fods, read_fods_buf, read_fods_from, read_ods, read_ods_buf, read_ods_buf_with,
+    read_ods_from, read_ods_with, OdsRowReader, OdsRows, ReadOptions, RowContent,
+};
+pub use crate::io::write::{
+    write_fods, write_fods_buf, write_fods_to, write_ods, write_ods_buf,
+    write_ods_buf_uncompressed, write_ods_to, OdsRowWriter,
+};
 pub use crate::refs::{CellRange, CellRef, ColRange, RowRange};
+#[cfg(feature = "serde")]
+pub use crate::ser::SerializeOptions;
 pub use crate::style::units::{Angle, Length};
 pub use crate::style::{CellStyle, CellStyleRef};
 
+use crate::annotation::Annotation;
+use crate::conditional::ConditionalFormat;
 use crate::config::Config;
 use crate::defaultstyles::{DefaultFormat, DefaultStyle};
+use crate::ds::cellstore::{self, CellStore};
 use crate::ds::detach::Detach;
 use crate::ds::detach::Detached;
 use crate::format::ValueFormatTrait;
+use crate::formula::rewrite::{
+    make_remote, rewrite_book, rewrite_formula, rewrite_sheet, Relocate, Rename, Shift, Translate,
+};
+use crate::formula::ErrorKind;
+use crate::image::Image;
 use crate::io::filebuf::FileBuf;
 use crate::io::read::default_settings;
+use crate::metadata::Metadata;
+use crate::named::{NameScope, NamedExpression, NamedRange};
 use crate::style::{
     ColStyle, ColStyleRef, FontFaceDecl, GraphicStyle, GraphicStyleRef, MasterPage, MasterPageRef,
     PageStyle, PageStyleRef, ParagraphStyle, ParagraphStyleRef, RowStyle, RowStyleRef, TableStyle,
@@ -214,8 +234,8 @@ use chrono::{Duration, NaiveTime};
 use chrono::{NaiveDate, NaiveDateTime};
 use icu_locid::Locale;
 #[cfg(feature = "use_decimal")]
-use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
-#[cfg(feature = "use_decimal")]
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap};
@@ -223,7 +243,9 @@ use std::convert::TryFrom;
 use std::fmt;
 use std::fmt::{Display, Formatter};
 use std::iter::FusedIterator;
+use std::mem;
 use std::ops::RangeBounds;
+use std::sync::Arc;
 
 #[macro_use]
 mod attr_macro;
@@ -242,12 +264,21 @@ mod ds;
 mod io;
 mod locale;
 
+pub mod annotation;
 pub mod condition;
+pub mod conditional;
+#[cfg(feature = "serde")]
+pub mod de;
 pub mod defaultstyles;
 pub mod error;
 pub mod format;
 pub mod formula;
+pub mod image;
+pub mod metadata;
+pub mod named;
 pub mod refs;
+#[cfg(feature = "serde")]
+pub mod ser;
 pub mod style;
 pub mod text;
 pub mod validation;
@@ -298,6 +329,13 @@ pub struct WorkBook {
     /// Validations.
     validations: HashMap<String, Validation>,
 
+    /// Named ranges and expressions. Global and sheet-local.
+    named_ranges: Vec<NamedRange>,
+    named_expressions: Vec<NamedExpression>,
+
+    /// Document metadata from meta.xml.
+    metadata: Metadata,
+
     /// Configuration data. Internal cache for all values.
     /// Mapped into WorkBookConfig, SheetConfig.
     config: Detach<Config>,
@@ -371,6 +409,13 @@ impl fmt::Debug for WorkBook {
         for s in self.validations.values() {
             writeln!(f, "{:?}", s)?;
         }
+        for s in &self.named_ranges {
+            writeln!(f, "{:?}", s)?;
+        }
+        for s in &self.named_expressions {
+            writeln!(f, "{:?}", s)?;
+        }
+        writeln!(f, "{:?}", self.metadata)?;
         for xtr in &self.extra {
             writeln!(f, "extras {:?}", xtr)?;
         }
@@ -433,6 +478,9 @@ impl WorkBook {
             pagestyles: Default::default(),
             masterpages: Default::default(),
             validations: Default::default(),
+            named_ranges: Default::default(),
+            named_expressions: Default::default(),
+            metadata: Default::default(),
             config: default_settings(),
             workbook_config: Default::default(),
             extra: vec![],
@@ -508,7 +556,9 @@ impl WorkBook {
         self.add_def_style(ValueType::Number, &DefaultStyle::number());
         self.add_def_style(ValueType::Percentage, &DefaultStyle::percent());
         self.add_def_style(ValueType::Currency, &DefaultStyle::currency());
-        self.add_def_style(ValueType::DateTime, &DefaultStyle::date());
+        self.add_def_style(ValueType::DateTime, &DefaultStyle::datetime());
+        self.add_def_style(ValueType::Date, &DefaultStyle::date());
+        self.add_def_style(ValueType::TimeOfDay, &DefaultStyle::time_of_day());
         self.add_def_style(ValueType::TimeDuration, &DefaultStyle::time_interval());
     }
 
@@ -610,6 +660,205 @@ impl WorkBook {
         self.sheets.remove(n).take()
     }
 
+    /// Inserts n empty rows before the given row of the sheet.
+    /// Same as Sheet::insert_rows(), but also adjusts the references
+    /// in all other sheets, validations, named ranges and named
+    /// expressions.
+    ///
+    /// Panics
+    ///
+    /// Panics if the sheet does not exist.
+    pub fn insert_rows(&mut self, sheet: usize, at: u32, n: u32) {
+        self.shift(sheet, true, at, n, false);
+    }
+
+    /// Deletes n rows of the sheet starting with the given row.
+    /// Same as Sheet::delete_rows(), but also adjusts the references
+    /// in all other sheets, validations, named ranges and named
+    /// expressions.
+    ///
+    /// Panics
+    ///
+    /// Panics if the sheet does not exist.
+    pub fn delete_rows(&mut self, sheet: usize, at: u32, n: u32) {
+        self.shift(sheet, true, at, n, true);
+    }
+
+    /// Inserts n empty columns before the given column of the sheet.
+    /// See insert_rows().
+    ///
+    /// Panics
+    ///
+    /// Panics if the sheet does not exist.
+    pub fn insert_cols(&mut self, sheet: usize, at: u32, n: u32) {
+        self.shift(sheet, false, at, n, false);
+    }
+
+    /// Deletes n columns of the sheet starting with the given column.
+    /// See delete_rows().
+    ///
+    /// Panics
+    ///
+    /// Panics if the sheet does not exist.
+    pub fn delete_cols(&mut self, sheet: usize, at: u32, n: u32) {
+        self.shift(sheet, false, at, n, true);
+    }
+
+    /// Copies a range of cells, possibly to another sheet.
+    /// See Sheet::copy_range().
+    ///
+    /// Panics
+    ///
+    /// Panics if one of the sheets does not exist.
+    pub fn copy_range(
+        &mut self,
+        src_sheet: usize,
+        src: CellRange,
+        dest_sheet: usize,
+        dest: CellRef,
+    ) {
+        let cells = self.sheet_mut(src_sheet).range_cells(&src, false);
+        self.sheet_mut(dest_sheet)
+            .put_range_cells(cells, &src, &dest, true);
+    }
+
+    /// Moves a range of cells, possibly to another sheet.
+    /// Same as Sheet::move_range(), but the references in all sheets,
+    /// validations, named ranges and named expressions are adjusted.
+    ///
+    /// When moving to another sheet, the references of the moved formulas
+    /// get the name of the source sheet, so they still point to the
+    /// same cells.
+    ///
+    /// Panics
+    ///
+    /// Panics if one of the sheets does not exist.
+    pub fn move_range(
+        &mut self,
+        src_sheet: usize,
+        src: CellRange,
+        dest_sheet: usize,
+        dest: CellRef,
+    ) {
+        let dest_name = self.sheet(dest_sheet).name().clone();
+        let relocate = self.sheet(src_sheet).relocate(&src, &dest, dest_name);
+
+        let mut cells = self.sheet_mut(src_sheet).range_cells(&src, true);
+        if src_sheet != dest_sheet {
+            let src_name = self.sheet(src_sheet).name().clone();
+            for (_, cell) in cells.iter_mut() {
+                if let Some(formula) = cell.formula() {
+                    let formula = rewrite_formula(formula, &src_name, &mut make_remote);
+                    cell.set_formula(Some(formula));
+                }
+            }
+        }
+        self.sheet_mut(dest_sheet)
+            .put_range_cells(cells, &src, &dest, false);
+
+        rewrite_book(self, None, &mut |sheet, r| relocate.apply(sheet, r));
+    }
+
+    fn shift(&mut self, idx: usize, rows: bool, at: u32, n: u32, delete: bool) {
+        let shift = Shift {
+            sheet: self.sheet(idx).name().clone(),
+            rows,
+            at,
+            n,
+            delete,
+        };
+        if n == 0 {
+            return;
+        }
+        self.sheet_mut(idx).shift(&shift);
+        rewrite_book(self, Some(idx), &mut |sheet, r| shift.apply(sheet, r));
+    }
+
+    /// Renames a sheet. All references to the sheet in formulas,
+    /// conditional formats, print ranges, validations, named ranges and
+    /// named expressions are changed too, as well as the scope of the
+    /// names local to the sheet, the active table and the per-sheet
+    /// settings.
+    ///
+    /// The name must not be empty, must not contain any of []*?:/\ and
+    /// must not start or end with an apostrophe. It must be unique
+    /// within the workbook, ignoring case.
+    ///
+    /// Panics
+    ///
+    /// Panics if the sheet does not exist.
+    pub fn rename_sheet<S: Into<String>>(&mut self, idx: usize, name: S) -> Result<(), OdsError> {
+        let new = name.into();
+        let old = self.sheet(idx).name().clone();
+        if new == old {
+            return Ok(());
+        }
+        if new.is_empty()
+            || new.contains(['[', ']', '*', '?', ':', '/', '\\'])
+            || new.starts_with('\'')
+            || new.ends_with('\'')
+        {
+            return Err(OdsError::Ods(format!("Invalid sheet name {}", new)));
+        }
+        let lower = new.to_lowercase();
+        if self
+            .sheets
+            .iter()
+            .enumerate()
+            .any(|(i, v)| i != idx && v.name().to_lowercase() == lower)
+        {
+            return Err(OdsError::Ods(format!("Duplicate sheet name {}", new)));
+        }
+
+        self.sheet_mut(idx).set_name(new.as_str());
+        let rename = Rename { old, new };
+        rewrite_book(self, None, &mut |sheet, r| rename.apply(sheet, r));
+
+        for named in self.named_ranges.iter_mut() {
+            if matches!(named.scope(), NameScope::Sheet(v) if *v == rename.old) {
+                named.set_scope(NameScope::Sheet(rename.new.clone()));
+            }
+        }
+        for named in self.named_expressions.iter_mut() {
+            if matches!(named.scope(), NameScope::Sheet(v) if *v == rename.old) {
+                named.set_scope(NameScope::Sheet(rename.new.clone()));
+            }
+        }
+        if self.config().active_table == rename.old {
+            self.config_mut().active_table = rename.new.clone();
+        }
+        if let Some(tables) = self
+            .config
+            .get_mut(&["ooo:view-settings", "Views", "0", "Tables"])
+        {
+            tables.rename(&rename.old, &rename.new);
+        }
+        if let Some(scripts) = self
+            .config
+            .get_mut(&["ooo:configuration-settings", "ScriptConfiguration"])
+        {
+            scripts.rename(&rename.old, &rename.new);
+        }
+
+        Ok(())
+    }
+
+    /// Recalculates all formulas and stores the results as cell values.
+    /// See [formula::calculate].
+    pub fn calculate(&mut self) {
+        formula::calculate(self);
+    }
+
+    /// Recalculates only the formulas that depend on the changed cells.
+    /// See [formula::recalculate].
+    pub fn recalculate(
+        &mut self,
+        graph: &formula::DependencyGraph,
+        changed: &[formula::CellKey],
+    ) -> Vec<formula::CellKey> {
+        formula::recalculate(self, graph, changed)
+    }
+
     /// Adds a default-style for all new values.
     /// This information is only used when writing the data to the ODS file.
     pub fn add_def_style(&mut self, value_type: ValueType, style: &CellStyleRef) {
@@ -1123,6 +1372,133 @@ impl WorkBook {
     pub fn validation_mut(&mut self, name: &str) -> Option<&mut Validation> {
         self.validations.get_mut(name)
     }
+
+    /// Adds a named range. Replaces any named range with the same
+    /// name and scope. Names ignore case.
+    pub fn add_named_range(&mut self, range: NamedRange) {
+        if let Some(v) = self
+            .named_ranges
+            .iter_mut()
+            .find(|v| named::same_name(v.name(), range.name()) && v.scope() == range.scope())
+        {
+            *v = range;
+        } else {
+            self.named_ranges.push(range);
+        }
+    }
+
+    /// Removes a named range.
+    pub fn remove_named_range(&mut self, scope: &NameScope, name: &str) -> Option<NamedRange> {
+        let idx = self
+            .named_ranges
+            .iter()
+            .position(|v| named::same_name(v.name(), name) && v.scope() == scope)?;
+        Some(self.named_ranges.remove(idx))
+    }
+
+    /// Returns the global named range.
+    pub fn named_range(&self, name: &str) -> Option<&NamedRange> {
+        self.named_ranges
+            .iter()
+            .find(|v| named::same_name(v.name(), name) && *v.scope() == NameScope::Global)
+    }
+
+    /// Returns the mutable global named range.
+    pub fn named_range_mut(&mut self, name: &str) -> Option<&mut NamedRange> {
+        self.named_ranges
+            .iter_mut()
+            .find(|v| named::same_name(v.name(), name) && *v.scope() == NameScope::Global)
+    }
+
+    /// Returns the named range as seen from within the given sheet.
+    /// A sheet-local name hides a global one.
+    pub fn local_named_range(&self, sheet: &str, name: &str) -> Option<&NamedRange> {
+        self.named_ranges
+            .iter()
+            .find(|v| {
+                named::same_name(v.name(), name)
+                    && matches!(v.scope(), NameScope::Sheet(s) if s == sheet)
+            })
+            .or_else(|| self.named_range(name))
+    }
+
+    /// All named ranges, global and sheet-local.
+    pub fn named_ranges(&self) -> &[NamedRange] {
+        &self.named_ranges
+    }
+
+    /// Adds a named expression. Replaces any named expression with the
+    /// same name and scope. Names ignore case.
+    pub fn add_named_expression(&mut self, expr: NamedExpression) {
+        if let Some(v) = self
+            .named_expressions
+            .iter_mut()
+            .find(|v| named::same_name(v.name(), expr.name()) && v.scope() == expr.scope())
+        {
+            *v = expr;
+        } else {
+            self.named_expressions.push(expr);
+        }
+    }
+
+    /// Removes a named expression.
+    pub fn remove_named_expression(
+        &mut self,
+        scope: &NameScope,
+        name: &str,
+    ) -> Option<NamedExpression> {
+        let idx = self
+            .named_expressions
+            .iter()
+            .position(|v| named::same_name(v.name(), name) && v.scope() == scope)?;
+        Some(self.named_expressions.remove(idx))
+    }
+
+    /// Returns the global named expression.
+    pub fn named_expression(&self, name: &str) -> Option<&NamedExpression> {
+        self.named_expressions
+            .iter()
+            .find(|v| named::same_name(v.name(), name) && *v.scope() == NameScope::Global)
+    }
+
+    /// Returns the mutable global named expression.
+    pub fn named_expression_mut(&mut self, name: &str) -> Option<&mut NamedExpression> {
+        self.named_expressions
+            .iter_mut()
+            .find(|v| named::same_name(v.name(), name) && *v.scope() == NameScope::Global)
+    }
+
+    /// Returns the named expression as seen from within the given sheet.
+    /// A sheet-local name hides a global one.
+    pub fn local_named_expression(&self, sheet: &str, name: &str) -> Option<&NamedExpression> {
+        self.named_expressions
+            .iter()
+            .find(|v| {
+                named::same_name(v.name(), name)
+                    && matches!(v.scope(), NameScope::Sheet(s) if s == sheet)
+            })
+            .or_else(|| self.named_expression(name))
+    }
+
+    /// All named expressions, global and sheet-local.
+    pub fn named_expressions(&self) -> &[NamedExpression] {
+        &self.named_expressions
+    }
+
+    /// Document metadata.
+    pub fn set_metadata(&mut self, metadata: Metadata) {
+        self.metadata = metadata;
+    }
+
+    /// Document metadata.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Document metadata.
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
 }
 
 /// Subset of the Workbook wide configurations.
@@ -1174,6 +1550,152 @@ impl Display for Visibility {
     }
 }
 
+/// A group of rows or columns. The group can be collapsed by
+/// setting display to false.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Grouped {
+    from: u32,
+    to: u32,
+    display: bool,
+}
+
+impl Grouped {
+    /// New group. Inclusive range.
+    ///
+    /// Panics
+    ///
+    /// If from > to.
+    pub fn new(from: u32, to: u32, display: bool) -> Self {
+        assert!(from <= to);
+        Self { from, to, display }
+    }
+
+    /// First row/column.
+    pub fn from(&self) -> u32 {
+        self.from
+    }
+
+    /// Last row/column.
+    pub fn to(&self) -> u32 {
+        self.to
+    }
+
+    /// The group is expanded.
+    pub fn display(&self) -> bool {
+        self.display
+    }
+
+    /// The group is expanded.
+    pub fn set_display(&mut self, display: bool) {
+        self.display = display;
+    }
+
+    /// Contains the row/column.
+    pub fn contains(&self, idx: u32) -> bool {
+        idx >= self.from && idx <= self.to
+    }
+
+    /// Partially overlapping groups can't be nested.
+    fn overlaps_partially(&self, other: &Grouped) -> bool {
+        (self.from < other.from && other.from <= self.to && self.to < other.to)
+            || (other.from < self.from && self.from <= other.to && other.to < self.to)
+    }
+}
+
+/// Adds a group and keeps the list sorted, outer groups first.
+fn add_group(
+    groups: &mut Vec<Grouped>,
+    from: u32,
+    to: u32,
+    display: bool,
+) -> Result<(), OdsError> {
+    if from > to {
+        return Err(OdsError::Ods(format!("Invalid group {}..{}", from, to)));
+    }
+    let group = Grouped::new(from, to, display);
+    if let Some(v) = groups
+        .iter_mut()
+        .find(|v| v.from == group.from && v.to == group.to)
+    {
+        v.display = group.display;
+        return Ok(());
+    }
+    if let Some(v) = groups.iter().find(|v| v.overlaps_partially(&group)) {
+        return Err(OdsError::Ods(format!(
+            "Group {}..{} overlaps partially with {}..{}",
+            group.from, group.to, v.from, v.to
+        )));
+    }
+    groups.push(group);
+    groups.sort_by(|a, b| a.from.cmp(&b.from).then(b.to.cmp(&a.to)));
+    Ok(())
+}
+
+/// Moves the groups after inserting/deleting rows or columns.
+fn shift_groups(groups: &mut Vec<Grouped>, shift: &Shift) {
+    let mut result: Vec<Grouped> = Vec::new();
+    for group in groups.drain(..) {
+        if let Some((from, to)) = shift.span(group.from, group.to) {
+            // deleting can make two groups identical.
+            if !result.iter().any(|v| v.from == from && v.to == to) {
+                result.push(Grouped::new(from, to, group.display));
+            }
+        }
+    }
+    *groups = result;
+}
+
+/// Moves the row headers after inserting/deleting rows. Repeated rows
+/// that contain the insert position are split, deleting shrinks them.
+fn shift_row_header(header: BTreeMap<u32, RowHeader>, shift: &Shift) -> BTreeMap<u32, RowHeader> {
+    let mut result = BTreeMap::new();
+    for (row, mut rh) in header {
+        let to_row = row.saturating_add(rh.repeat - 1);
+        if !shift.delete && row < shift.at && to_row >= shift.at {
+            let mut tail = rh.clone();
+            tail.repeat = to_row - shift.at + 1;
+            rh.repeat = shift.at - row;
+            result.insert(row, rh);
+            result.insert(shift.at.saturating_add(shift.n), tail);
+        } else if let Some((from, to)) = shift.span(row, to_row) {
+            rh.repeat = to - from + 1;
+            result.insert(from, rh);
+        }
+    }
+    result
+}
+
+/// Moves the cursor and the split positions after inserting/deleting
+/// rows or columns.
+fn shift_config(config: &mut SheetConfig, shift: &Shift) {
+    let position = |v: u32| shift.index(v).unwrap_or(shift.at);
+    // number of rows/columns before the split.
+    let boundary = |v: u32| match v {
+        0 => 0,
+        v => shift.span(0, v - 1).map(|(_, to)| to + 1).unwrap_or(0),
+    };
+    if shift.rows {
+        config.cursor_y = position(config.cursor_y);
+        config.position_top = position(config.position_top);
+        config.position_bottom = position(config.position_bottom);
+        if matches!(config.vert_split_mode, SplitMode::Heading) {
+            config.vert_split_pos = boundary(config.vert_split_pos);
+        }
+    } else {
+        config.cursor_x = position(config.cursor_x);
+        config.position_left = position(config.position_left);
+        config.position_right = position(config.position_right);
+        if matches!(config.hor_split_mode, SplitMode::Heading) {
+            config.hor_split_pos = boundary(config.hor_split_pos);
+        }
+    }
+}
+
+fn remove_group(groups: &mut Vec<Grouped>, from: u32, to: u32) -> Option<Grouped> {
+    let idx = groups.iter().position(|v| v.from == from && v.to == to)?;
+    Some(groups.remove(idx))
+}
+
 /// Row data
 #[derive(Debug, Clone, Default)]
 struct RowHeader {
@@ -1308,14 +1830,13 @@ impl ColHeader {
 /// One sheet of the spreadsheet.
 ///
 /// Contains the data and the style-references. The can also be
-/// styles on the whole sheet, columns and rows. The more complicated
-/// grouping tags are not covered.
+/// styles on the whole sheet, columns and rows.
 #[derive(Clone, Default)]
 pub struct Sheet {
     name: String,
     style: Option<String>,
 
-    data: BTreeMap<(u32, u32), CellData>,
+    data: CellStore,
 
     col_header: BTreeMap<u32, ColHeader>,
     row_header: BTreeMap<u32, RowHeader>,
@@ -1327,6 +1848,13 @@ pub struct Sheet {
     header_cols: Option<ColRange>,
     print_ranges: Option<Vec<CellRange>>,
 
+    group_rows: Vec<Grouped>,
+    group_cols: Vec<Grouped>,
+
+    conditional_formats: Vec<ConditionalFormat>,
+
+    page_images: Vec<Image>,
+
     sheet_config: SheetConfig,
 
     extra: Vec<XmlTag>,
@@ -1348,15 +1876,15 @@ impl<'a> IntoIterator for &'a Sheet {
 /// Iterator over cells.
 #[derive(Clone, Debug)]
 pub struct CellIter<'a> {
-    it_data: std::collections::btree_map::Iter<'a, (u32, u32), CellData>,
-    k_data: Option<&'a (u32, u32)>,
+    it_data: cellstore::Iter<'a>,
+    k_data: Option<(u32, u32)>,
     v_data: Option<&'a CellData>,
 }
 
 impl CellIter<'_> {
     /// Returns the (row,col) of the next cell.
     pub fn peek_cell(&mut self) -> Option<(u32, u32)> {
-        self.k_data.copied()
+        self.k_data
     }
 
     fn load_next_data(&mut self) {
@@ -1382,7 +1910,7 @@ impl<'a> Iterator for CellIter<'a> {
 
         if let Some(k_data) = self.k_data {
             if let Some(v_data) = self.v_data {
-                let r = Some((*k_data, v_data.into()));
+                let r = Some((k_data, v_data.into()));
                 self.load_next_data();
                 r
             } else {
@@ -1397,7 +1925,7 @@ impl<'a> Iterator for CellIter<'a> {
 /// Range iterator.
 #[derive(Clone, Debug)]
 pub struct Range<'a> {
-    range: std::collections::btree_map::Range<'a, (u32, u32), CellData>,
+    range: cellstore::Range<'a>,
 }
 
 impl FusedIterator for Range<'_> {}
@@ -1407,7 +1935,7 @@ impl<'a> Iterator for Range<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if let Some((k, v)) = self.range.next() {
-            Some((*k, v.into()))
+            Some((k, v.into()))
         } else {
             None
         }
@@ -1421,7 +1949,7 @@ impl<'a> Iterator for Range<'a> {
 impl DoubleEndedIterator for Range<'_> {
     fn next_back(&mut self) -> Option<Self::Item> {
         if let Some((k, v)) = self.range.next_back() {
-            Some((*k, v.into()))
+            Some((k, v.into()))
         } else {
             None
         }
@@ -1448,6 +1976,18 @@ impl fmt::Debug for Sheet {
         if let Some(header_cols) = &self.header_cols {
             writeln!(f, "header cols {:?}", header_cols)?;
         }
+        for v in &self.group_rows {
+            writeln!(f, "group rows {:?}", v)?;
+        }
+        for v in &self.group_cols {
+            writeln!(f, "group cols {:?}", v)?;
+        }
+        for v in &self.conditional_formats {
+            writeln!(f, "conditional format {:?}", v)?;
+        }
+        for v in &self.page_images {
+            writeln!(f, "page image {:?}", v)?;
+        }
         for xtr in &self.extra {
             writeln!(f, "extras {:?}", xtr)?;
         }
@@ -1474,12 +2014,16 @@ impl Sheet {
     pub fn new<S: Into<String>>(name: S) -> Self {
         Sheet {
             name: name.into(),
-            data: BTreeMap::new(),
+            data: Default::default(),
             col_header: Default::default(),
             style: None,
             header_rows: None,
             header_cols: None,
             print_ranges: None,
+            group_rows: Default::default(),
+            group_cols: Default::default(),
+            conditional_formats: Default::default(),
+            page_images: Default::default(),
             sheet_config: Default::default(),
             extra: vec![],
             row_header: Default::default(),
@@ -1501,6 +2045,10 @@ impl Sheet {
             header_rows: self.header_rows.clone(),
             header_cols: self.header_cols.clone(),
             print_ranges: self.print_ranges.clone(),
+            group_rows: self.group_rows.clone(),
+            group_cols: self.group_cols.clone(),
+            conditional_formats: self.conditional_formats.clone(),
+            page_images: self.page_images.clone(),
             sheet_config: Default::default(),
             extra: self.extra.clone(),
         }
@@ -1521,6 +2069,112 @@ impl Sheet {
         }
     }
 
+    /// Deserializes the rows into structs.
+    ///
+    /// The text in the header row names the fields, the columns are
+    /// matched by name. Tuples take the columns in order. The rows
+    /// default to everything below the header row, blank rows are
+    /// skipped.
+    ///
+    /// Empty cells can be read as Option. Numbers without fraction can
+    /// be read as integers. NaiveDate, NaiveDateTime, NaiveTime and
+    /// Decimal are supported too. A date cell can be read into a
+    /// NaiveDateTime with the deserialize_with functions in
+    /// [de](crate::de).
+    ///
+    /// A failed conversion returns OdsError::Serde with the position
+    /// of the cell. A field without a header column is reported in the
+    /// header row, behind the last header.
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use spreadsheet_ods::Sheet;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Order {
+    ///     item: String,
+    ///     amount: u32,
+    ///     note: Option<String>,
+    /// }
+    ///
+    /// let mut sheet = Sheet::new("orders");
+    /// sheet.set_value(0, 0, "item");
+    /// sheet.set_value(0, 1, "amount");
+    /// sheet.set_value(0, 2, "note");
+    /// sheet.set_value(1, 0, "pencil");
+    /// sheet.set_value(1, 1, 12);
+    ///
+    /// let orders: Vec<Order> = sheet.deserialize_rows(0, ..).unwrap();
+    /// assert_eq!(orders[0].item, "pencil");
+    /// assert_eq!(orders[0].amount, 12);
+    /// assert!(orders[0].note.is_none());
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn deserialize_rows<'a, T, R>(
+        &'a self,
+        header_row: u32,
+        rows: R,
+    ) -> Result<Vec<T>, OdsError>
+    where
+        T: serde::Deserialize<'a>,
+        R: RangeBounds<u32>,
+    {
+        de::deserialize_rows(self, header_row, rows)
+    }
+
+    /// Serializes the records into rows.
+    ///
+    /// The field names are written as header row at start, each
+    /// record as one row below. Records must be structs or maps.
+    ///
+    /// The cells get no style, when writing the workbook uses the
+    /// default style for the value type, see WorkBook::add_def_style().
+    /// Strings are always written as text, chrono values too unless
+    /// they use the serialize_with functions in [ser](crate::ser).
+    /// None is written as an empty cell.
+    ///
+    /// The options select the columns and their order, and set the
+    /// width and the style of a column. Decimals and currencies need
+    /// an option too, or one of the serialize_with functions in
+    /// [ser](crate::ser).
+    ///
+    /// A failed conversion returns OdsError::Serde with the position
+    /// of the cell. Nothing is written in that case.
+    ///
+    /// ```
+    /// use serde::Serialize;
+    /// use spreadsheet_ods::{CellRef, SerializeOptions, Sheet};
+    ///
+    /// #[derive(Serialize)]
+    /// struct Order {
+    ///     item: String,
+    ///     amount: u32,
+    ///     price: f64,
+    /// }
+    ///
+    /// let orders = [Order {
+    ///     item: "pencil".to_string(),
+    ///     amount: 12,
+    ///     price: 0.5,
+    /// }];
+    ///
+    /// let mut sheet = Sheet::new("orders");
+    /// let options = SerializeOptions::new().currency("price", "EUR");
+    /// sheet.serialize_rows(&orders, CellRef::local(0, 0), &options).unwrap();
+    /// assert_eq!(sheet.value(0, 1).as_str_or(""), "amount");
+    /// assert_eq!(sheet.value(1, 1).as_u32_or(0), 12);
+    /// assert_eq!(sheet.value(1, 2).currency(), "EUR");
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn serialize_rows<T: serde::Serialize>(
+        &mut self,
+        rows: &[T],
+        start: CellRef,
+        options: &SerializeOptions,
+    ) -> Result<(), OdsError> {
+        ser::serialize_rows(self, rows, start, options)
+    }
+
     /// Sheet name.
     pub fn set_name<V: Into<String>>(&mut self, name: V) {
         self.name = name.into();
@@ -1755,11 +2409,7 @@ impl Sheet {
 
     /// Returns a tuple of (max(row)+1, max(col)+1)
     pub fn used_grid_size(&self) -> (u32, u32) {
-        let max = self.data.keys().fold((0, 0), |mut max, (r, c)| {
-            max.0 = u32::max(max.0, *r);
-            max.1 = u32::max(max.1, *c);
-            max
-        });
+        let max = self.data.max_cell().unwrap_or((0, 0));
 
         (max.0 + 1, max.1 + 1)
     }
@@ -1801,45 +2451,19 @@ impl Sheet {
     pub fn cell(&self, row: u32, col: u32) -> Option<CellContent> {
         let value = self.data.get(&(row, col));
 
-        value.map(|value| CellContent {
-            value: value.value.clone(),
-            style: value.style.clone(),
-            formula: value.formula.clone(),
-            validation_name: value.validation_name.clone(),
-            span: value.span,
-        })
+        value.map(CellData::to_content)
     }
 
     /// Consumes the CellContent and sets the values.
     pub fn add_cell(&mut self, row: u32, col: u32, cell: CellContent) {
-        self.add_cell_data(
-            row,
-            col,
-            CellData {
-                value: cell.value,
-                formula: cell.formula,
-                style: cell.style,
-                validation_name: cell.validation_name,
-                span: cell.span,
-            },
-        );
+        self.add_cell_data(row, col, CellData::from_content(cell));
     }
 
     /// Removes the cell and returns the values as CellContent.
     pub fn remove_cell(&mut self, row: u32, col: u32) -> Option<CellContent> {
         let value = self.data.remove(&(row, col));
 
-        if let Some(value) = value {
-            Some(CellContent {
-                value: value.value,
-                style: value.style,
-                formula: value.formula,
-                validation_name: value.validation_name,
-                span: value.span,
-            })
-        } else {
-            None
-        }
+        value.map(CellData::into_content)
     }
 
     /// Add a new cell. Main use is for reading the spreadsheet.
@@ -1855,14 +2479,14 @@ impl Sheet {
         value: V,
         style: &CellStyleRef,
     ) {
-        let mut cell = self.data.entry((row, col)).or_insert_with(CellData::new);
+        let cell = self.data.get_or_default((row, col));
         cell.value = value.into();
-        cell.style = Some(style.to_string());
+        cell.style = Some(Arc::new(style.to_string()));
     }
 
     /// Sets a value for the specified cell. Creates a new cell if necessary.
     pub fn set_value<V: Into<Value>>(&mut self, row: u32, col: u32, value: V) {
-        let mut cell = self.data.entry((row, col)).or_insert_with(CellData::new);
+        let cell = self.data.get_or_default((row, col));
         cell.value = value.into();
     }
 
@@ -1877,21 +2501,92 @@ impl Sheet {
 
     /// Sets a formula for the specified cell. Creates a new cell if necessary.
     pub fn set_formula<V: Into<String>>(&mut self, row: u32, col: u32, formula: V) {
-        let mut cell = self.data.entry((row, col)).or_insert_with(CellData::new);
-        cell.formula = Some(formula.into());
+        let cell = self.data.get_or_default((row, col));
+        cell.set_formula(Some(formula.into()));
     }
 
-    /// Removes the formula.
+    /// Sets a formula in Excel A1 syntax like "=SUM(A1:B3)".
+    /// It's translated to OpenFormula with [formula::from_excel].
+    pub fn set_formula_excel(&mut self, row: u32, col: u32, formula: &str) -> Result<(), OdsError> {
+        let formula = formula::from_excel(formula)?;
+        self.set_formula(row, col, formula);
+        Ok(())
+    }
+
+    /// Removes the formula. This also ends an array formula.
     pub fn clear_formula(&mut self, row: u32, col: u32) {
         if let Some(cell) = self.data.get_mut(&(row, col)) {
-            cell.formula = None;
+            if let Some(extra) = &mut cell.extra {
+                extra.formula = None;
+                extra.matrix_span = Default::default();
+            }
+        }
+    }
+
+    /// Sets an array formula for the range. The formula is stored with
+    /// the first cell of the range, the other cells only get the results.
+    /// Formulas in the rest of the range are removed. A reversed range
+    /// is the same as the normal one.
+    ///
+    /// ```
+    /// use spreadsheet_ods::{CellRange, Sheet};
+    ///
+    /// let mut sheet = Sheet::new("Matrix");
+    /// sheet.set_array_formula(
+    ///     CellRange::local(4, 0, 5, 1),
+    ///     "of:=MMULT([.A1:.B2];[.C1:.D2])",
+    /// );
+    /// assert_eq!(sheet.matrix_row_span(4, 0), 2);
+    /// ```
+    pub fn set_array_formula<V: Into<String>>(&mut self, range: CellRange, formula: V) {
+        let (row, to_row) = (
+            range.row().min(range.to_row()),
+            range.row().max(range.to_row()),
+        );
+        let (col, to_col) = (
+            range.col().min(range.to_col()),
+            range.col().max(range.to_col()),
+        );
+
+        let cells: Vec<(u32, u32)> = self
+            .data
+            .range((row, col)..=(to_row, to_col))
+            .map(|(k, _)| k)
+            .filter(|(_, c)| *c >= col && *c <= to_col)
+            .collect();
+        for (r, c) in cells {
+            self.clear_formula(r, c);
+        }
+
+        let cell = self.data.get_or_default((row, col));
+        let extra = cell.extra_mut();
+        extra.formula = Some(formula.into());
+        extra.matrix_span.row_span = to_row - row + 1;
+        extra.matrix_span.col_span = to_col - col + 1;
+    }
+
+    /// Number of rows of the array formula.
+    pub fn matrix_row_span(&self, row: u32, col: u32) -> u32 {
+        if let Some(c) = self.data.get(&(row, col)) {
+            c.matrix_span().row_span
+        } else {
+            1
+        }
+    }
+
+    /// Number of columns of the array formula.
+    pub fn matrix_col_span(&self, row: u32, col: u32) -> u32 {
+        if let Some(c) = self.data.get(&(row, col)) {
+            c.matrix_span().col_span
+        } else {
+            1
         }
     }
 
     /// Returns a value
     pub fn formula(&self, row: u32, col: u32) -> Option<&String> {
         if let Some(c) = self.data.get(&(row, col)) {
-            c.formula.as_ref()
+            c.formula()
         } else {
             None
         }
@@ -1899,8 +2594,8 @@ impl Sheet {
 
     /// Sets the cell-style for the specified cell. Creates a new cell if necessary.
     pub fn set_cellstyle(&mut self, row: u32, col: u32, style: &CellStyleRef) {
-        let mut cell = self.data.entry((row, col)).or_insert_with(CellData::new);
-        cell.style = Some(style.to_string());
+        let cell = self.data.get_or_default((row, col));
+        cell.style = Some(Arc::new(style.to_string()));
     }
 
     /// Removes the cell-style.
@@ -1913,44 +2608,187 @@ impl Sheet {
     /// Returns a value
     pub fn cellstyle(&self, row: u32, col: u32) -> Option<&String> {
         if let Some(c) = self.data.get(&(row, col)) {
-            c.style.as_ref()
+            c.style.as_deref()
         } else {
             None
         }
     }
 
+    /// Returns the cell-style name as &str.
+    pub fn cellstyle_str(&self, row: u32, col: u32) -> Option<&str> {
+        self.cellstyle(row, col).map(|v| v.as_str())
+    }
+
     /// Sets a content-validation for this cell.
     pub fn set_validation(&mut self, row: u32, col: u32, validation: &ValidationRef) {
-        let mut cell = self.data.entry((row, col)).or_insert_with(CellData::new);
-        cell.validation_name = Some(validation.to_string());
+        let cell = self.data.get_or_default((row, col));
+        cell.extra_mut().validation_name = Some(Arc::new(validation.to_string()));
     }
 
     /// Removes the cell-style.
     pub fn clear_validation(&mut self, row: u32, col: u32) {
         if let Some(cell) = self.data.get_mut(&(row, col)) {
-            cell.validation_name = None;
+            if let Some(extra) = &mut cell.extra {
+                extra.validation_name = None;
+            }
         }
     }
 
     /// Returns a content-validation name for this cell.
     pub fn validation(&self, row: u32, col: u32) -> Option<&String> {
         if let Some(c) = self.data.get(&(row, col)) {
-            c.validation_name.as_ref()
+            c.validation_name()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the content-validation name as &str.
+    pub fn validation_str(&self, row: u32, col: u32) -> Option<&str> {
+        self.validation(row, col).map(|v| v.as_str())
+    }
+
+    /// Sets an annotation for this cell.
+    pub fn set_annotation(&mut self, row: u32, col: u32, annotation: Annotation) {
+        let cell = self.data.get_or_default((row, col));
+        cell.extra_mut().annotation = Some(annotation);
+    }
+
+    /// Removes the annotation.
+    pub fn clear_annotation(&mut self, row: u32, col: u32) -> Option<Annotation> {
+        if let Some(cell) = self.data.get_mut(&(row, col)) {
+            cell.extra.as_mut().and_then(|v| v.annotation.take())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the annotation for this cell.
+    pub fn annotation(&self, row: u32, col: u32) -> Option<&Annotation> {
+        if let Some(c) = self.data.get(&(row, col)) {
+            c.annotation()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the mutable annotation for this cell.
+    pub fn annotation_mut(&mut self, row: u32, col: u32) -> Option<&mut Annotation> {
+        if let Some(c) = self.data.get_mut(&(row, col)) {
+            c.extra.as_mut().and_then(|v| v.annotation.as_mut())
         } else {
             None
         }
     }
 
+    /// Anchors an image to the given cell. The size and the offset
+    /// relative to the top left corner of the cell are given as
+    /// (width, height) and (x, y). The data is stored under Pictures/
+    /// when writing.
+    ///
+    /// Returns the image for further settings.
+    pub fn add_image<S: Into<String>>(
+        &mut self,
+        anchor: CellRef,
+        size: (Length, Length),
+        offset: (Length, Length),
+        data: Vec<u8>,
+        mime: S,
+    ) -> &mut Image {
+        let mut image = Image::new(mime, data);
+        image.set_size(size.0, size.1);
+        image.set_offset(offset.0, offset.1);
+
+        let cell = self.data.get_or_default((anchor.row(), anchor.col()));
+        let images = &mut cell.extra_mut().images;
+        images.push(image);
+        images.last_mut().expect("image")
+    }
+
+    /// Anchors an image to the given cell.
+    pub fn push_image(&mut self, row: u32, col: u32, image: Image) {
+        let cell = self.data.get_or_default((row, col));
+        cell.extra_mut().images.push(image);
+    }
+
+    /// Returns the images anchored to this cell.
+    pub fn cell_images(&self, row: u32, col: u32) -> &[Image] {
+        if let Some(c) = self.data.get(&(row, col)) {
+            c.images()
+        } else {
+            &[]
+        }
+    }
+
+    /// Returns the images anchored to this cell.
+    pub fn cell_images_mut(&mut self, row: u32, col: u32) -> Option<&mut Vec<Image>> {
+        self.data
+            .get_mut(&(row, col))
+            .map(|c| &mut c.extra_mut().images)
+    }
+
+    /// Removes all images anchored to this cell.
+    pub fn clear_images(&mut self, row: u32, col: u32) -> Vec<Image> {
+        if let Some(c) = self.data.get_mut(&(row, col)) {
+            c.extra
+                .as_mut()
+                .map(|v| mem::take(&mut v.images))
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Iterates all images of the sheet together with their anchor cell.
+    /// Images anchored to the page are not included, see page_images().
+    pub fn images(&self) -> impl Iterator<Item = ((u32, u32), &Image)> {
+        self.data
+            .iter()
+            .flat_map(|(k, v)| v.images().iter().map(move |i| (k, i)))
+    }
+
+    /// Anchors an image to the page. The offset is relative to the
+    /// top left corner of the sheet.
+    pub fn push_page_image(&mut self, image: Image) {
+        self.page_images.push(image);
+    }
+
+    /// Images anchored to the page.
+    pub fn page_images(&self) -> &[Image] {
+        &self.page_images
+    }
+
+    /// Images anchored to the page.
+    pub fn page_images_mut(&mut self) -> &mut Vec<Image> {
+        &mut self.page_images
+    }
+
+    /// All images, anchored to a cell or to the page.
+    pub(crate) fn all_images(&self) -> impl Iterator<Item = &Image> {
+        self.data
+            .values()
+            .flat_map(|v| v.images().iter())
+            .chain(self.page_images.iter())
+    }
+
+    /// All images, anchored to a cell or to the page.
+    pub(crate) fn all_images_mut(&mut self) -> impl Iterator<Item = &mut Image> {
+        self.data
+            .values_mut()
+            .flat_map(|v| v.images_mut().iter_mut())
+            .chain(self.page_images.iter_mut())
+    }
+
     /// Sets the rowspan of the cell. Must be greater than 0.
     pub fn set_row_span(&mut self, row: u32, col: u32, span: u32) {
-        let mut cell = self.data.entry((row, col)).or_insert_with(CellData::new);
-        cell.span.row_span = span;
+        let cell = self.data.get_or_default((row, col));
+        cell.extra_mut().span.row_span = span;
     }
 
     /// Rowspan of the cell.
     pub fn row_span(&self, row: u32, col: u32) -> u32 {
         if let Some(c) = self.data.get(&(row, col)) {
-            c.span.row_span
+            c.span().row_span
         } else {
             1
         }
@@ -1959,14 +2797,14 @@ impl Sheet {
     /// Sets the colspan of the cell. Must be greater than 0.
     pub fn set_col_span(&mut self, row: u32, col: u32, span: u32) {
         assert!(span > 0);
-        let mut cell = self.data.entry((row, col)).or_insert_with(CellData::new);
-        cell.span.col_span = span;
+        let cell = self.data.get_or_default((row, col));
+        cell.extra_mut().span.col_span = span;
     }
 
     /// Colspan of the cell.
     pub fn col_span(&self, row: u32, col: u32) -> u32 {
         if let Some(c) = self.data.get(&(row, col)) {
-            c.span.col_span
+            c.span().col_span
         } else {
             1
         }
@@ -2017,6 +2855,349 @@ impl Sheet {
         self.print_ranges.as_ref()
     }
 
+    /// Adds a conditional format.
+    pub fn add_conditional_format(&mut self, cf: ConditionalFormat) {
+        self.conditional_formats.push(cf);
+    }
+
+    /// Removes the conditional format at the index.
+    pub fn remove_conditional_format(&mut self, idx: usize) -> ConditionalFormat {
+        self.conditional_formats.remove(idx)
+    }
+
+    /// Removes all conditional formats.
+    pub fn clear_conditional_formats(&mut self) {
+        self.conditional_formats.clear();
+    }
+
+    /// Returns the conditional formats.
+    pub fn conditional_formats(&self) -> &[ConditionalFormat] {
+        &self.conditional_formats
+    }
+
+    /// Returns the conditional formats.
+    pub fn conditional_formats_mut(&mut self) -> &mut Vec<ConditionalFormat> {
+        &mut self.conditional_formats
+    }
+
+    /// Returns the conditional formats that apply to the cell.
+    pub fn conditional_formats_at(
+        &self,
+        row: u32,
+        col: u32,
+    ) -> impl Iterator<Item = &ConditionalFormat> {
+        self.conditional_formats
+            .iter()
+            .filter(move |v| v.ranges().iter().any(|r| r.contains(row, col)))
+    }
+
+    /// Adds a row group. Groups can be nested, but they must not
+    /// partially overlap. Adding a group with the same range again
+    /// changes its display flag.
+    ///
+    /// Fails if from > to or the group overlaps an existing one
+    /// partially.
+    pub fn add_row_group(&mut self, from: u32, to: u32, display: bool) -> Result<(), OdsError> {
+        add_group(&mut self.group_rows, from, to, display)
+    }
+
+    /// Removes the row group with exactly this range.
+    pub fn remove_row_group(&mut self, from: u32, to: u32) -> Option<Grouped> {
+        remove_group(&mut self.group_rows, from, to)
+    }
+
+    /// Returns the row group with exactly this range.
+    pub fn row_group(&self, from: u32, to: u32) -> Option<&Grouped> {
+        self.group_rows
+            .iter()
+            .find(|v| v.from() == from && v.to() == to)
+    }
+
+    /// Iterates the row groups. Outer groups come before the groups
+    /// nested within.
+    pub fn row_groups(&self) -> impl Iterator<Item = &Grouped> {
+        self.group_rows.iter()
+    }
+
+    /// Number of row groups containing this row.
+    pub fn row_group_level(&self, row: u32) -> u32 {
+        self.group_rows.iter().filter(|v| v.contains(row)).count() as u32
+    }
+
+    /// Adds a column group. Groups can be nested, but they must not
+    /// partially overlap. Adding a group with the same range again
+    /// changes its display flag.
+    ///
+    /// Fails if from > to or the group overlaps an existing one
+    /// partially.
+    pub fn add_col_group(&mut self, from: u32, to: u32, display: bool) -> Result<(), OdsError> {
+        add_group(&mut self.group_cols, from, to, display)
+    }
+
+    /// Removes the column group with exactly this range.
+    pub fn remove_col_group(&mut self, from: u32, to: u32) -> Option<Grouped> {
+        remove_group(&mut self.group_cols, from, to)
+    }
+
+    /// Returns the column group with exactly this range.
+    pub fn col_group(&self, from: u32, to: u32) -> Option<&Grouped> {
+        self.group_cols
+            .iter()
+            .find(|v| v.from() == from && v.to() == to)
+    }
+
+    /// Iterates the column groups. Outer groups come before the groups
+    /// nested within.
+    pub fn col_groups(&self) -> impl Iterator<Item = &Grouped> {
+        self.group_cols.iter()
+    }
+
+    /// Number of column groups containing this column.
+    pub fn col_group_level(&self, col: u32) -> u32 {
+        self.group_cols.iter().filter(|v| v.contains(col)).count() as u32
+    }
+
+    /// Inserts n empty rows before the given row. All cells, row
+    /// headers, spans, groups, header rows, print ranges and conditional
+    /// formats below are moved down, as well as the cursor and a fixed
+    /// split. Repeated rows are split at the insert position. References
+    /// to the moved cells in the formulas of this sheet are adjusted.
+    ///
+    /// Use WorkBook::insert_rows() to adjust the references from other
+    /// sheets, validations and named ranges too.
+    pub fn insert_rows(&mut self, at: u32, n: u32) {
+        self.shift(&Shift {
+            sheet: self.name.clone(),
+            rows: true,
+            at,
+            n,
+            delete: false,
+        });
+    }
+
+    /// Deletes n rows starting with the given row. Everything below is
+    /// moved up. References to deleted cells become #REF!, ranges
+    /// are shrunk.
+    ///
+    /// Use WorkBook::delete_rows() to adjust the references from other
+    /// sheets, validations and named ranges too.
+    pub fn delete_rows(&mut self, at: u32, n: u32) {
+        self.shift(&Shift {
+            sheet: self.name.clone(),
+            rows: true,
+            at,
+            n,
+            delete: true,
+        });
+    }
+
+    /// Inserts n empty columns before the given column. See insert_rows().
+    pub fn insert_cols(&mut self, at: u32, n: u32) {
+        self.shift(&Shift {
+            sheet: self.name.clone(),
+            rows: false,
+            at,
+            n,
+            delete: false,
+        });
+    }
+
+    /// Deletes n columns starting with the given column. See delete_rows().
+    pub fn delete_cols(&mut self, at: u32, n: u32) {
+        self.shift(&Shift {
+            sheet: self.name.clone(),
+            rows: false,
+            at,
+            n,
+            delete: true,
+        });
+    }
+
+    /// Copies the cells of the range to the destination. Values,
+    /// formulas, styles, validations, spans, annotations and images are
+    /// copied. Cells in the destination area are replaced.
+    ///
+    /// The relative references in the copied formulas are moved by the
+    /// offset between the source and the destination, absolute references
+    /// are kept. References that would end up outside the sheet become
+    /// #REF!.
+    ///
+    /// The sheet names of src and dest are ignored.
+    pub fn copy_range(&mut self, src: CellRange, dest: CellRef) {
+        let cells = self.range_cells(&src, false);
+        self.put_range_cells(cells, &src, &dest, true);
+    }
+
+    /// Moves the cells of the range to the destination. Cells in the
+    /// destination area are replaced.
+    ///
+    /// The formulas of the moved cells are not changed, but all references
+    /// to the moved cells within this sheet now point to the new position.
+    ///
+    /// Use WorkBook::move_range() to adjust the references from other
+    /// sheets, validations and named ranges too.
+    ///
+    /// The sheet names of src and dest are ignored.
+    pub fn move_range(&mut self, src: CellRange, dest: CellRef) {
+        let relocate = self.relocate(&src, &dest, self.name.clone());
+        let cells = self.range_cells(&src, true);
+        self.put_range_cells(cells, &src, &dest, false);
+        rewrite_sheet(self, &mut |sheet, r| relocate.apply(sheet, r));
+    }
+
+    /// Clones or removes the cells in the range. The positions are
+    /// relative to the start of the range.
+    fn range_cells(&mut self, range: &CellRange, remove: bool) -> Vec<((u32, u32), CellData)> {
+        let (row, to_row) = (
+            range.row().min(range.to_row()),
+            range.row().max(range.to_row()),
+        );
+        let (col, to_col) = (
+            range.col().min(range.to_col()),
+            range.col().max(range.to_col()),
+        );
+
+        let keys: Vec<(u32, u32)> = self
+            .data
+            .range((row, col)..=(to_row, to_col))
+            .filter(|((_, c), _)| *c >= col && *c <= to_col)
+            .map(|(k, _)| k)
+            .collect();
+        keys.into_iter()
+            .map(|(r, c)| {
+                let cell = if remove {
+                    self.data.remove(&(r, c)).expect("cell")
+                } else {
+                    self.data.get(&(r, c)).expect("cell").clone()
+                };
+                ((r - row, c - col), cell)
+            })
+            .collect()
+    }
+
+    /// Replaces the destination area with the cells.
+    fn put_range_cells(
+        &mut self,
+        cells: Vec<((u32, u32), CellData)>,
+        src: &CellRange,
+        dest: &CellRef,
+        translate: bool,
+    ) {
+        let rows = src.row().abs_diff(src.to_row());
+        let cols = src.col().abs_diff(src.to_col());
+        let (row, col) = (dest.row(), dest.col());
+        let (to_row, to_col) = (row.saturating_add(rows), col.saturating_add(cols));
+
+        let keys: Vec<(u32, u32)> = self
+            .data
+            .range((row, col)..=(to_row, to_col))
+            .filter(|((_, c), _)| *c >= col && *c <= to_col)
+            .map(|(k, _)| k)
+            .collect();
+        for k in keys {
+            self.data.remove(&k);
+        }
+
+        let offset = Translate {
+            rows: row as i64 - src.row().min(src.to_row()) as i64,
+            cols: col as i64 - src.col().min(src.to_col()) as i64,
+        };
+        let translate = translate && (offset.rows != 0 || offset.cols != 0);
+        for ((r, c), mut cell) in cells {
+            if let Some(formula) = cell.formula() {
+                if translate {
+                    let formula = rewrite_formula(formula, &self.name, &mut |sheet, r| {
+                        offset.apply(sheet, r)
+                    });
+                    cell.set_formula(Some(formula));
+                }
+            }
+            if let (Some(r), Some(c)) = (row.checked_add(r), col.checked_add(c)) {
+                self.data.insert((r, c), cell);
+            }
+        }
+    }
+
+    fn relocate(&self, src: &CellRange, dest: &CellRef, dest_sheet: String) -> Relocate {
+        let (row, to_row) = (src.row().min(src.to_row()), src.row().max(src.to_row()));
+        let (col, to_col) = (src.col().min(src.to_col()), src.col().max(src.to_col()));
+        Relocate {
+            sheet: self.name.clone(),
+            row,
+            col,
+            to_row,
+            to_col,
+            dest: dest_sheet,
+            rows: dest.row() as i64 - row as i64,
+            cols: dest.col() as i64 - col as i64,
+        }
+    }
+
+    fn shift(&mut self, shift: &Shift) {
+        if shift.n == 0 {
+            return;
+        }
+
+        for ((row, col), mut cell) in self.data.drain() {
+            let pos = if shift.rows { row } else { col };
+            let idx = match shift.index(pos) {
+                Some(idx) => idx,
+                None => continue,
+            };
+            if let Some(extra) = &mut cell.extra {
+                for span in [&mut extra.span, &mut extra.matrix_span] {
+                    let len = if shift.rows {
+                        &mut span.row_span
+                    } else {
+                        &mut span.col_span
+                    };
+                    if *len > 1 {
+                        if let Some((from, to)) = shift.span(pos, pos.saturating_add(*len - 1)) {
+                            *len = to - from + 1;
+                        }
+                    }
+                }
+            }
+            if shift.rows {
+                self.data.insert((idx, col), cell);
+            } else {
+                self.data.insert((row, idx), cell);
+            }
+        }
+
+        if shift.rows {
+            self.row_header = shift_row_header(mem::take(&mut self.row_header), shift);
+            if let Some(header_rows) = &mut self.header_rows {
+                match shift.span(header_rows.row(), header_rows.to_row()) {
+                    Some((from, to)) => {
+                        header_rows.set_row(from);
+                        header_rows.set_to_row(to);
+                    }
+                    None => self.header_rows = None,
+                }
+            }
+            shift_groups(&mut self.group_rows, shift);
+        } else {
+            self.col_header = mem::take(&mut self.col_header)
+                .into_iter()
+                .filter_map(|(k, v)| shift.index(k).map(|k| (k, v)))
+                .collect();
+            if let Some(header_cols) = &mut self.header_cols {
+                match shift.span(header_cols.col(), header_cols.to_col()) {
+                    Some((from, to)) => {
+                        header_cols.set_col(from);
+                        header_cols.set_to_col(to);
+                    }
+                    None => self.header_cols = None,
+                }
+            }
+            shift_groups(&mut self.group_cols, shift);
+        }
+        shift_config(&mut self.sheet_config, shift);
+
+        rewrite_sheet(self, &mut |sheet, r| shift.apply(sheet, r));
+    }
+
     /// Split horizontally on a cell boundary. The splitting is fixed in
     /// position.
     pub fn split_col_header(&mut self, col: u32) {
@@ -2209,28 +3390,158 @@ impl CellSpan {
 }
 
 /// One Cell of the spreadsheet.
+///
+/// Kept small, everything but the value and the style lives in
+/// CellDataExt and is only allocated when needed.
 #[derive(Debug, Clone, Default)]
 struct CellData {
     value: Value,
+    // Cell style name, shared between cells.
+    style: Option<Arc<String>>,
+    // Everything else.
+    extra: Option<Box<CellDataExt>>,
+}
+
+/// The rarely used parts of a cell.
+#[derive(Debug, Clone, Default)]
+struct CellDataExt {
     // Unparsed formula string.
     formula: Option<String>,
-    // Cell style name.
-    style: Option<String>,
-    // Content validation name.
-    validation_name: Option<String>,
+    // Content validation name, shared between cells.
+    validation_name: Option<Arc<String>>,
     // Row/Column span.
     span: CellSpan,
+    // Extent of an array formula.
+    matrix_span: CellSpan,
+    // Comment.
+    annotation: Option<Annotation>,
+    // Anchored images.
+    images: Vec<Image>,
 }
 
+/// Span of a cell without a CellDataExt.
+static NO_SPAN: CellSpan = CellSpan {
+    row_span: 1,
+    col_span: 1,
+};
+
 impl CellData {
     /// New, empty.
     pub(crate) fn new() -> Self {
         CellData {
             value: Value::Empty,
-            formula: None,
             style: None,
-            validation_name: None,
-            span: Default::default(),
+            extra: None,
+        }
+    }
+
+    fn from_content(cell: CellContent) -> Self {
+        let has_extra = cell.formula.is_some()
+            || cell.validation_name.is_some()
+            || cell.span.row_span > 1
+            || cell.span.col_span > 1
+            || cell.matrix_span.row_span > 1
+            || cell.matrix_span.col_span > 1
+            || cell.annotation.is_some()
+            || !cell.images.is_empty();
+        CellData {
+            value: cell.value,
+            style: cell.style.map(Arc::new),
+            extra: if has_extra {
+                Some(Box::new(CellDataExt {
+                    formula: cell.formula,
+                    validation_name: cell.validation_name.map(Arc::new),
+                    span: cell.span,
+                    matrix_span: cell.matrix_span,
+                    annotation: cell.annotation,
+                    images: cell.images,
+                }))
+            } else {
+                None
+            },
+        }
+    }
+
+    fn to_content(&self) -> CellContent {
+        CellContent {
+            value: self.value.clone(),
+            style: self.style.as_deref().cloned(),
+            formula: self.formula().cloned(),
+            validation_name: self.validation_name().cloned(),
+            span: *self.span(),
+            matrix_span: *self.matrix_span(),
+            annotation: self.annotation().cloned(),
+            images: self.images().to_vec(),
+        }
+    }
+
+    fn into_content(self) -> CellContent {
+        match self.extra {
+            Some(extra) => CellContent {
+                value: self.value,
+                style: self.style.as_deref().cloned(),
+                formula: extra.formula,
+                validation_name: extra.validation_name.as_deref().cloned(),
+                span: extra.span,
+                matrix_span: extra.matrix_span,
+                annotation: extra.annotation,
+                images: extra.images,
+            },
+            None => CellContent {
+                value: self.value,
+                style: self.style.as_deref().cloned(),
+                ..CellContent::new()
+            },
+        }
+    }
+
+    /// Creates the CellDataExt if necessary.
+    fn extra_mut(&mut self) -> &mut CellDataExt {
+        self.extra.get_or_insert_with(Default::default)
+    }
+
+    fn formula(&self) -> Option<&String> {
+        self.extra.as_ref().and_then(|v| v.formula.as_ref())
+    }
+
+    fn set_formula(&mut self, formula: Option<String>) {
+        if formula.is_some() || self.extra.is_some() {
+            self.extra_mut().formula = formula;
+        }
+    }
+
+    fn validation_name(&self) -> Option<&String> {
+        self.extra
+            .as_ref()
+            .and_then(|v| v.validation_name.as_deref())
+    }
+
+    fn span(&self) -> &CellSpan {
+        self.extra.as_ref().map(|v| &v.span).unwrap_or(&NO_SPAN)
+    }
+
+    fn matrix_span(&self) -> &CellSpan {
+        self.extra
+            .as_ref()
+            .map(|v| &v.matrix_span)
+            .unwrap_or(&NO_SPAN)
+    }
+
+    fn annotation(&self) -> Option<&Annotation> {
+        self.extra.as_ref().and_then(|v| v.annotation.as_ref())
+    }
+
+    fn images(&self) -> &[Image] {
+        self.extra
+            .as_ref()
+            .map(|v| v.images.as_slice())
+            .unwrap_or(&[])
+    }
+
+    fn images_mut(&mut self) -> &mut [Image] {
+        match &mut self.extra {
+            Some(v) => v.images.as_mut_slice(),
+            None => &mut [],
         }
     }
 }
@@ -2249,16 +3560,25 @@ pub struct CellContentRef<'a> {
     pub validation_name: Option<&'a String>,
     /// Reference to the cellspan.
     pub span: Option<&'a CellSpan>,
+    /// Reference to the extent of an array formula.
+    matrix_span: Option<&'a CellSpan>,
+    /// Reference to the annotation.
+    annotation: Option<&'a Annotation>,
+    /// Reference to the anchored images.
+    images: &'a [Image],
 }
 
 impl<'a> From<&'a CellData> for CellContentRef<'a> {
     fn from(cd: &'a CellData) -> Self {
         CellContentRef {
             value: Some(&cd.value),
-            style: cd.style.as_ref(),
-            formula: cd.formula.as_ref(),
-            validation_name: cd.validation_name.as_ref(),
-            span: Some(&cd.span),
+            style: cd.style.as_deref(),
+            formula: cd.formula(),
+            validation_name: cd.validation_name(),
+            span: Some(cd.span()),
+            matrix_span: Some(cd.matrix_span()),
+            annotation: cd.annotation(),
+            images: cd.images(),
         }
     }
 }
@@ -2283,11 +3603,31 @@ impl<'a> CellContentRef<'a> {
         self.style
     }
 
+    /// Returns the cell style as &str.
+    pub fn style_str(&self) -> Option<&'a str> {
+        self.style.map(|v| v.as_str())
+    }
+
     /// Returns the validation name.
     pub fn validation(&self) -> Option<&'a String> {
         self.validation_name
     }
 
+    /// Returns the validation name as &str.
+    pub fn validation_str(&self) -> Option<&'a str> {
+        self.validation_name.map(|v| v.as_str())
+    }
+
+    /// Returns the annotation.
+    pub fn annotation(&self) -> Option<&'a Annotation> {
+        self.annotation
+    }
+
+    /// Returns the anchored images.
+    pub fn images(&self) -> &'a [Image] {
+        self.images
+    }
+
     /// Returns the row span.
     pub fn row_span(&self) -> u32 {
         if let Some(span) = self.span {
@@ -2305,6 +3645,24 @@ impl<'a> CellContentRef<'a> {
             1
         }
     }
+
+    /// Returns the number of rows of an array formula.
+    pub fn matrix_row_span(&self) -> u32 {
+        if let Some(span) = self.matrix_span {
+            span.row_span
+        } else {
+            1
+        }
+    }
+
+    /// Returns the number of columns of an array formula.
+    pub fn matrix_col_span(&self) -> u32 {
+        if let Some(span) = self.matrix_span {
+            span.col_span
+        } else {
+            1
+        }
+    }
 }
 
 /// A copy of the relevant data for a spreadsheet cell.
@@ -2320,6 +3678,12 @@ pub struct CellContent {
     pub validation_name: Option<String>,
     /// Cellspan.
     pub span: CellSpan,
+    /// Extent of an array formula.
+    matrix_span: CellSpan,
+    /// Annotation.
+    annotation: Option<Annotation>,
+    /// Anchored images.
+    images: Vec<Image>,
 }
 
 impl CellContent {
@@ -2331,6 +3695,9 @@ impl CellContent {
             formula: None,
             validation_name: None,
             span: Default::default(),
+            matrix_span: Default::default(),
+            annotation: None,
+            images: Default::default(),
         }
     }
 
@@ -2389,6 +3756,31 @@ impl CellContent {
         self.validation_name = None;
     }
 
+    /// Returns the annotation.
+    pub fn annotation(&self) -> Option<&Annotation> {
+        self.annotation.as_ref()
+    }
+
+    /// Sets the annotation.
+    pub fn set_annotation(&mut self, annotation: Annotation) {
+        self.annotation = Some(annotation);
+    }
+
+    /// Removes the annotation.
+    pub fn clear_annotation(&mut self) {
+        self.annotation = None;
+    }
+
+    /// Returns the anchored images.
+    pub fn images(&self) -> &[Image] {
+        &self.images
+    }
+
+    /// Anchors an image to this cell.
+    pub fn push_image(&mut self, image: Image) {
+        self.images.push(image);
+    }
+
     /// Sets the row span of this cell.
     /// Cells below with values will be lost when writing.
     pub fn set_row_span(&mut self, rows: u32) {
@@ -2412,6 +3804,28 @@ impl CellContent {
     pub fn col_span(&self) -> u32 {
         self.span.col_span
     }
+
+    /// Sets the number of rows of an array formula.
+    pub fn set_matrix_row_span(&mut self, rows: u32) {
+        assert!(rows > 0);
+        self.matrix_span.row_span = rows;
+    }
+
+    /// Returns the number of rows of an array formula.
+    pub fn matrix_row_span(&self) -> u32 {
+        self.matrix_span.row_span
+    }
+
+    /// Sets the number of columns of an array formula.
+    pub fn set_matrix_col_span(&mut self, cols: u32) {
+        assert!(cols > 0);
+        self.matrix_span.col_span = cols;
+    }
+
+    /// Returns the number of columns of an array formula.
+    pub fn matrix_col_span(&self) -> u32 {
+        self.matrix_span.col_span
+    }
 }
 
 /// Datatypes for the values. Only the discriminants of the Value enum.
@@ -2426,7 +3840,10 @@ pub enum ValueType {
     Text,
     TextXml,
     DateTime,
+    Date,
+    TimeOfDay,
     TimeDuration,
+    Error,
 }
 
 /// Content-Values
@@ -2438,10 +3855,22 @@ pub enum Value {
     Number(f64),
     Percentage(f64),
     Currency(f64, String),
+    /// Number without loss of precision. Written exactly as it is.
+    /// See ReadOptions::exact_numbers().
+    Decimal(Decimal),
+    /// Currency without loss of precision.
+    DecimalCurrency(Decimal, String),
     Text(String),
     TextXml(Vec<TextTag>),
     DateTime(NaiveDateTime),
+    /// Date without a time part. Written as office:date-value="2023-01-01".
+    Date(NaiveDate),
+    /// Time of day. Written as office:time-value="PT13H45M00S", which is
+    /// read back as a TimeDuration.
+    TimeOfDay(NaiveTime),
     TimeDuration(Duration),
+    /// Result of a formula that failed, like #DIV/0!.
+    Error(ErrorKind),
 }
 
 impl Value {
@@ -2453,10 +3882,15 @@ impl Value {
             Value::Number(_) => ValueType::Number,
             Value::Percentage(_) => ValueType::Percentage,
             Value::Currency(_, _) => ValueType::Currency,
+            Value::Decimal(_) => ValueType::Number,
+            Value::DecimalCurrency(_, _) => ValueType::Currency,
             Value::Text(_) => ValueType::Text,
             Value::TextXml(_) => ValueType::TextXml,
             Value::TimeDuration(_) => ValueType::TimeDuration,
             Value::DateTime(_) => ValueType::DateTime,
+            Value::Date(_) => ValueType::Date,
+            Value::TimeOfDay(_) => ValueType::TimeOfDay,
+            Value::Error(_) => ValueType::Error,
         }
     }
 
@@ -2475,6 +3909,7 @@ impl Value {
             Value::Number(n) => *n as i64,
             Value::Percentage(p) => *p as i64,
             Value::Currency(v, _) => *v as i64,
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64().unwrap_or(0.0) as i64,
             _ => d,
         }
     }
@@ -2486,6 +3921,7 @@ impl Value {
             Value::Number(n) => Some(*n as i64),
             Value::Percentage(p) => Some(*p as i64),
             Value::Currency(v, _) => Some(*v as i64),
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64().map(|v| v as i64),
             _ => None,
         }
     }
@@ -2497,6 +3933,7 @@ impl Value {
             Value::Number(n) => *n as u64,
             Value::Percentage(p) => *p as u64,
             Value::Currency(v, _) => *v as u64,
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64().unwrap_or(0.0) as u64,
             _ => d,
         }
     }
@@ -2508,6 +3945,7 @@ impl Value {
             Value::Number(n) => Some(*n as u64),
             Value::Percentage(p) => Some(*p as u64),
             Value::Currency(v, _) => Some(*v as u64),
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64().map(|v| v as u64),
             _ => None,
         }
     }
@@ -2519,6 +3957,7 @@ impl Value {
             Value::Number(n) => *n as i32,
             Value::Percentage(p) => *p as i32,
             Value::Currency(v, _) => *v as i32,
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64().unwrap_or(0.0) as i32,
             _ => d,
         }
     }
@@ -2530,6 +3969,7 @@ impl Value {
             Value::Number(n) => Some(*n as i32),
             Value::Percentage(p) => Some(*p as i32),
             Value::Currency(v, _) => Some(*v as i32),
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64().map(|v| v as i32),
             _ => None,
         }
     }
@@ -2541,6 +3981,7 @@ impl Value {
             Value::Number(n) => *n as u32,
             Value::Percentage(p) => *p as u32,
             Value::Currency(v, _) => *v as u32,
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64().unwrap_or(0.0) as u32,
             _ => d,
         }
     }
@@ -2552,6 +3993,7 @@ impl Value {
             Value::Number(n) => Some(*n as u32),
             Value::Percentage(p) => Some(*p as u32),
             Value::Currency(v, _) => Some(*v as u32),
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64().map(|v| v as u32),
             _ => None,
         }
     }
@@ -2563,6 +4005,7 @@ impl Value {
             Value::Number(n) => *n as i16,
             Value::Percentage(p) => *p as i16,
             Value::Currency(v, _) => *v as i16,
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64().unwrap_or(0.0) as i16,
             _ => d,
         }
     }
@@ -2574,6 +4017,7 @@ impl Value {
             Value::Number(n) => Some(*n as i16),
             Value::Percentage(p) => Some(*p as i16),
             Value::Currency(v, _) => Some(*v as i16),
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64().map(|v| v as i16),
             _ => None,
         }
     }
@@ -2585,6 +4029,7 @@ impl Value {
             Value::Number(n) => *n as u16,
             Value::Percentage(p) => *p as u16,
             Value::Currency(v, _) => *v as u16,
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64().unwrap_or(0.0) as u16,
             _ => d,
         }
     }
@@ -2596,6 +4041,7 @@ impl Value {
             Value::Number(n) => Some(*n as u16),
             Value::Percentage(p) => Some(*p as u16),
             Value::Currency(v, _) => Some(*v as u16),
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64().map(|v| v as u16),
             _ => None,
         }
     }
@@ -2607,6 +4053,7 @@ impl Value {
             Value::Number(n) => *n as i8,
             Value::Percentage(p) => *p as i8,
             Value::Currency(v, _) => *v as i8,
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64().unwrap_or(0.0) as i8,
             _ => d,
         }
     }
@@ -2618,6 +4065,7 @@ impl Value {
             Value::Number(n) => Some(*n as i8),
             Value::Percentage(p) => Some(*p as i8),
             Value::Currency(v, _) => Some(*v as i8),
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64().map(|v| v as i8),
             _ => None,
         }
     }
@@ -2629,6 +4077,7 @@ impl Value {
             Value::Number(n) => *n as u8,
             Value::Percentage(p) => *p as u8,
             Value::Currency(v, _) => *v as u8,
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64().unwrap_or(0.0) as u8,
             _ => d,
         }
     }
@@ -2640,6 +4089,7 @@ impl Value {
             Value::Number(n) => Some(*n as u8),
             Value::Percentage(p) => Some(*p as u8),
             Value::Currency(v, _) => Some(*v as u8),
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64().map(|v| v as u8),
             _ => None,
         }
     }
@@ -2652,6 +4102,7 @@ impl Value {
             Value::Number(n) => Decimal::from_f64(*n).unwrap(),
             Value::Currency(v, _) => Decimal::from_f64(*v).unwrap(),
             Value::Percentage(p) => Decimal::from_f64(*p).unwrap(),
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => *v,
             _ => d,
         }
     }
@@ -2664,6 +4115,7 @@ impl Value {
             Value::Number(n) => Some(Decimal::from_f64(*n).unwrap()),
             Value::Currency(v, _) => Some(Decimal::from_f64(*v).unwrap()),
             Value::Percentage(p) => Some(Decimal::from_f64(*p).unwrap()),
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => Some(*v),
             _ => None,
         }
     }
@@ -2675,6 +4127,7 @@ impl Value {
             Value::Number(n) => *n,
             Value::Currency(v, _) => *v,
             Value::Percentage(p) => *p,
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64().unwrap_or(d),
             _ => d,
         }
     }
@@ -2686,6 +4139,7 @@ impl Value {
             Value::Number(n) => Some(*n),
             Value::Currency(v, _) => Some(*v),
             Value::Percentage(p) => Some(*p),
+            Value::Decimal(v) | Value::DecimalCurrency(v, _) => v.to_f64(),
             _ => None,
         }
     }
@@ -2727,55 +4181,92 @@ impl Value {
     }
 
     /// Return the content as Duration if the value is a TimeDuration.
+    /// A TimeOfDay is the duration since midnight.
     /// Default otherwise.
     pub fn as_timeduration_or(&self, d: Duration) -> Duration {
         match self {
             Value::TimeDuration(td) => *td,
+            Value::TimeOfDay(t) => *t - NaiveTime::MIN,
             _ => d,
         }
     }
 
     /// Return the content as Duration if the value is a TimeDuration.
+    /// A TimeOfDay is the duration since midnight.
     /// Default otherwise.
     pub fn as_timeduration_opt(&self) -> Option<Duration> {
         match self {
             Value::TimeDuration(td) => Some(*td),
+            Value::TimeOfDay(t) => Some(*t - NaiveTime::MIN),
             _ => None,
         }
     }
 
     /// Return the content as NaiveDateTime if the value is a DateTime.
+    /// A Date is returned at midnight.
     /// Default otherwise.
     pub fn as_datetime_or(&self, d: NaiveDateTime) -> NaiveDateTime {
         match self {
             Value::DateTime(dt) => *dt,
+            Value::Date(dt) => dt.and_time(NaiveTime::MIN),
             _ => d,
         }
     }
 
     /// Return the content as an optional NaiveDateTime if the value is
-    /// a DateTime.
+    /// a DateTime. A Date is returned at midnight.
     pub fn as_datetime_opt(&self) -> Option<NaiveDateTime> {
         match self {
             Value::DateTime(dt) => Some(*dt),
+            Value::Date(dt) => Some(dt.and_time(NaiveTime::MIN)),
             _ => None,
         }
     }
 
-    /// Return the content as NaiveDate if the value is a DateTime.
-    /// Default otherwise.
+    /// Return the content as NaiveDate if the value is a Date or a
+    /// DateTime. Default otherwise.
     pub fn as_date_or(&self, d: NaiveDate) -> NaiveDate {
         match self {
             Value::DateTime(dt) => dt.date(),
+            Value::Date(dt) => *dt,
             _ => d,
         }
     }
 
-    /// Return the content as an optional NaiveDateTime if the value is
-    /// a DateTime.
+    /// Return the content as an optional NaiveDate if the value is
+    /// a Date or a DateTime.
     pub fn as_date_opt(&self) -> Option<NaiveDate> {
         match self {
             Value::DateTime(dt) => Some(dt.date()),
+            Value::Date(dt) => Some(*dt),
+            _ => None,
+        }
+    }
+
+    /// Return the content as NaiveTime if the value is a TimeOfDay or a
+    /// DateTime. Default otherwise.
+    pub fn as_time_or(&self, d: NaiveTime) -> NaiveTime {
+        match self {
+            Value::DateTime(dt) => dt.time(),
+            Value::TimeOfDay(t) => *t,
+            _ => d,
+        }
+    }
+
+    /// Return the content as an optional NaiveTime if the value is
+    /// a TimeOfDay or a DateTime.
+    pub fn as_time_opt(&self) -> Option<NaiveTime> {
+        match self {
+            Value::DateTime(dt) => Some(dt.time()),
+            Value::TimeOfDay(t) => Some(*t),
+            _ => None,
+        }
+    }
+
+    /// Return the error if the value is an Error.
+    pub fn as_error_opt(&self) -> Option<ErrorKind> {
+        match self {
+            Value::Error(e) => Some(*e),
             _ => None,
         }
     }
@@ -2784,6 +4275,7 @@ impl Value {
     pub fn currency(&self) -> &str {
         match self {
             Value::Currency(_, c) => c,
+            Value::DecimalCurrency(_, c) => c,
             _ => "",
         }
     }
@@ -2794,6 +4286,12 @@ impl Value {
         Value::Currency(value, cur.as_ref().to_string())
     }
 
+    /// Create a currency value without loss of precision.
+    #[cfg(feature = "use_decimal")]
+    pub fn new_decimal_currency<S: AsRef<str>>(cur: S, value: Decimal) -> Self {
+        Value::DecimalCurrency(value, cur.as_ref().to_string())
+    }
+
     /// Create a percentage value.
     pub fn new_percentage(value: f64) -> Self {
         Value::Percentage(value)
@@ -2891,7 +4389,7 @@ impl From<Option<String>> for Value {
 #[cfg(feature = "use_decimal")]
 impl From<Decimal> for Value {
     fn from(f: Decimal) -> Self {
-        Value::Number(f.to_f64().unwrap())
+        Value::Decimal(f)
     }
 }
 
@@ -2899,7 +4397,7 @@ impl From<Decimal> for Value {
 impl From<Option<Decimal>> for Value {
     fn from(f: Option<Decimal>) -> Self {
         if let Some(f) = f {
-            Value::Number(f.to_f64().unwrap())
+            Value::Decimal(f)
         } else {
             Value::Empty
         }
@@ -2991,14 +4489,14 @@ impl From<Option<NaiveDateTime>> for Value {
 
 impl From<NaiveDate> for Value {
     fn from(dt: NaiveDate) -> Self {
-        Value::DateTime(dt.and_hms_opt(0, 0, 0).unwrap())
+        Value::Date(dt)
     }
 }
 
 impl From<Option<NaiveDate>> for Value {
     fn from(dt: Option<NaiveDate>) -> Self {
         if let Some(dt) = dt {
-            Value::DateTime(dt.and_hms_opt(0, 0, 0).unwrap())
+            Value::Date(dt)
         } else {
             Value::Empty
         }
@@ -3007,20 +4505,14 @@ impl From<Option<NaiveDate>> for Value {
 
 impl From<NaiveTime> for Value {
     fn from(ti: NaiveTime) -> Self {
-        Value::DateTime(NaiveDateTime::new(
-            NaiveDate::from_ymd_opt(1900, 1, 1).unwrap(),
-            ti,
-        ))
+        Value::TimeOfDay(ti)
     }
 }
 
 impl From<Option<NaiveTime>> for Value {
     fn from(dt: Option<NaiveTime>) -> Self {
         if let Some(ti) = dt {
-            Value::DateTime(NaiveDateTime::new(
-                NaiveDate::from_ymd_opt(1900, 1, 1).unwrap(),
-                ti,
-            ))
+            Value::TimeOfDay(ti)
         } else {
             Value::Empty
         }
@@ -3042,3 +4534,9 @@ impl From<Option<Duration>> for Value {
         }
     }
 }
+
+impl From<ErrorKind> for Value {
+    fn from(e: ErrorKind) -> Self {
+        Value::Error(e)
+    }
+}
@@ -0,0 +1,333 @@
+//!
+//! Document metadata as stored in meta.xml.
+//!
+//! ```
+//! use spreadsheet_ods::WorkBook;
+//! use spreadsheet_ods::metadata::MetaValue;
+//!
+//! let mut book = WorkBook::new_empty();
+//! let meta = book.metadata_mut();
+//! meta.set_title(Some("Orders".to_string()));
+//! meta.set_creator(Some("export job".to_string()));
+//! meta.push_keyword("orders");
+//! meta.set_user_defined("batch", MetaValue::Float(17.0));
+//!
+//! assert!(matches!(book.metadata().user_defined("batch"), Some(MetaValue::Float(_))));
+//! ```
+
+use crate::xmltree::XmlTag;
+use chrono::{DateTime, Duration, FixedOffset, NaiveDateTime};
+
+/// Document metadata.
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    generator: String,
+    title: Option<String>,
+    description: Option<String>,
+    subject: Option<String>,
+    keywords: Vec<String>,
+    initial_creator: Option<String>,
+    creator: Option<String>,
+    printed_by: Option<String>,
+    creation_date: Option<MetaDateTime>,
+    date: Option<MetaDateTime>,
+    print_date: Option<MetaDateTime>,
+    language: Option<String>,
+    editing_cycles: u32,
+    editing_duration: Option<Duration>,
+    user_defined: Vec<MetaUserDefined>,
+    /// meta:document-statistic, meta:template, meta:auto-reload and
+    /// meta:hyperlink-behaviour are passed through.
+    extra: Vec<XmlTag>,
+}
+
+impl Default for Metadata {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metadata {
+    /// Empty metadata.
+    pub fn new() -> Self {
+        Self {
+            generator: Default::default(),
+            title: None,
+            description: None,
+            subject: None,
+            keywords: Default::default(),
+            initial_creator: None,
+            creator: None,
+            printed_by: None,
+            creation_date: None,
+            date: None,
+            print_date: None,
+            language: None,
+            editing_cycles: 1,
+            editing_duration: None,
+            user_defined: Default::default(),
+            extra: Default::default(),
+        }
+    }
+
+    /// Application that wrote the document. This is only read,
+    /// when writing spreadsheet-ods is used.
+    pub fn generator(&self) -> &str {
+        &self.generator
+    }
+
+    pub(crate) fn set_generator<S: Into<String>>(&mut self, generator: S) {
+        self.generator = generator.into();
+    }
+
+    /// Title.
+    pub fn set_title(&mut self, title: Option<String>) {
+        self.title = title;
+    }
+
+    /// Title.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Description.
+    pub fn set_description(&mut self, description: Option<String>) {
+        self.description = description;
+    }
+
+    /// Description.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Subject.
+    pub fn set_subject(&mut self, subject: Option<String>) {
+        self.subject = subject;
+    }
+
+    /// Subject.
+    pub fn subject(&self) -> Option<&str> {
+        self.subject.as_deref()
+    }
+
+    /// Appends a keyword.
+    pub fn push_keyword<S: Into<String>>(&mut self, keyword: S) {
+        self.keywords.push(keyword.into());
+    }
+
+    /// Keywords.
+    pub fn set_keywords(&mut self, keywords: Vec<String>) {
+        self.keywords = keywords;
+    }
+
+    /// Keywords.
+    pub fn keywords(&self) -> &[String] {
+        &self.keywords
+    }
+
+    /// Name of the person who created the document.
+    pub fn set_initial_creator(&mut self, initial_creator: Option<String>) {
+        self.initial_creator = initial_creator;
+    }
+
+    /// Name of the person who created the document.
+    pub fn initial_creator(&self) -> Option<&str> {
+        self.initial_creator.as_deref()
+    }
+
+    /// Name of the person who last modified the document.
+    pub fn set_creator(&mut self, creator: Option<String>) {
+        self.creator = creator;
+    }
+
+    /// Name of the person who last modified the document.
+    pub fn creator(&self) -> Option<&str> {
+        self.creator.as_deref()
+    }
+
+    /// Name of the person who last printed the document.
+    pub fn set_printed_by(&mut self, printed_by: Option<String>) {
+        self.printed_by = printed_by;
+    }
+
+    /// Name of the person who last printed the document.
+    pub fn printed_by(&self) -> Option<&str> {
+        self.printed_by.as_deref()
+    }
+
+    /// Creation date. If this is not set, the current time is used
+    /// when writing.
+    pub fn set_creation_date(&mut self, creation_date: Option<MetaDateTime>) {
+        self.creation_date = creation_date;
+    }
+
+    /// Creation date.
+    pub fn creation_date(&self) -> Option<MetaDateTime> {
+        self.creation_date
+    }
+
+    /// Date of the last modification.
+    pub fn set_date(&mut self, date: Option<MetaDateTime>) {
+        self.date = date;
+    }
+
+    /// Date of the last modification.
+    pub fn date(&self) -> Option<MetaDateTime> {
+        self.date
+    }
+
+    /// Date of the last printout.
+    pub fn set_print_date(&mut self, print_date: Option<MetaDateTime>) {
+        self.print_date = print_date;
+    }
+
+    /// Date of the last printout.
+    pub fn print_date(&self) -> Option<MetaDateTime> {
+        self.print_date
+    }
+
+    /// Default language as language tag, e.g. "de-AT".
+    pub fn set_language(&mut self, language: Option<String>) {
+        self.language = language;
+    }
+
+    /// Default language.
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Number of times the document has been edited.
+    pub fn set_editing_cycles(&mut self, editing_cycles: u32) {
+        self.editing_cycles = editing_cycles;
+    }
+
+    /// Number of times the document has been edited.
+    pub fn editing_cycles(&self) -> u32 {
+        self.editing_cycles
+    }
+
+    /// Total time spent editing the document.
+    pub fn set_editing_duration(&mut self, editing_duration: Option<Duration>) {
+        self.editing_duration = editing_duration;
+    }
+
+    /// Total time spent editing the document.
+    pub fn editing_duration(&self) -> Option<Duration> {
+        self.editing_duration
+    }
+
+    /// Sets a user-defined property. An existing property with the
+    /// same name is replaced.
+    pub fn set_user_defined<S: Into<String>>(&mut self, name: S, value: MetaValue) {
+        let name = name.into();
+        if let Some(v) = self.user_defined.iter_mut().find(|v| v.name == name) {
+            v.value = value;
+        } else {
+            self.user_defined.push(MetaUserDefined { name, value });
+        }
+    }
+
+    /// Returns a user-defined property.
+    pub fn user_defined(&self, name: &str) -> Option<&MetaValue> {
+        self.user_defined
+            .iter()
+            .find(|v| v.name == name)
+            .map(|v| &v.value)
+    }
+
+    /// Removes a user-defined property.
+    pub fn remove_user_defined(&mut self, name: &str) -> Option<MetaValue> {
+        let idx = self.user_defined.iter().position(|v| v.name == name)?;
+        Some(self.user_defined.remove(idx).value)
+    }
+
+    /// All user-defined properties.
+    pub fn user_defined_list(&self) -> &[MetaUserDefined] {
+        &self.user_defined
+    }
+
+    pub(crate) fn push_extra(&mut self, tag: XmlTag) {
+        self.extra.push(tag);
+    }
+
+    pub(crate) fn extra(&self) -> &[XmlTag] {
+        &self.extra
+    }
+}
+
+/// A date in meta.xml. The timezone is optional and kept as it was
+/// read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetaDateTime {
+    datetime: NaiveDateTime,
+    offset: Option<FixedOffset>,
+}
+
+impl MetaDateTime {
+    /// Date and time with an optional timezone.
+    pub fn new(datetime: NaiveDateTime, offset: Option<FixedOffset>) -> Self {
+        Self { datetime, offset }
+    }
+
+    /// Date and time as written, in the timezone given by offset().
+    pub fn datetime(&self) -> NaiveDateTime {
+        self.datetime
+    }
+
+    /// Timezone.
+    pub fn offset(&self) -> Option<FixedOffset> {
+        self.offset
+    }
+
+    /// Returns the date with timezone, if there is one.
+    pub fn to_fixed_offset(&self) -> Option<DateTime<FixedOffset>> {
+        self.offset
+            .and_then(|v| self.datetime.and_local_timezone(v).single())
+    }
+}
+
+impl From<NaiveDateTime> for MetaDateTime {
+    fn from(datetime: NaiveDateTime) -> Self {
+        Self::new(datetime, None)
+    }
+}
+
+impl From<DateTime<FixedOffset>> for MetaDateTime {
+    fn from(datetime: DateTime<FixedOffset>) -> Self {
+        Self::new(datetime.naive_local(), Some(*datetime.offset()))
+    }
+}
+
+/// A user-defined property.
+#[derive(Debug, Clone)]
+pub struct MetaUserDefined {
+    name: String,
+    value: MetaValue,
+}
+
+impl MetaUserDefined {
+    /// Name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Value.
+    pub fn value(&self) -> &MetaValue {
+        &self.value
+    }
+}
+
+/// Value of a user-defined property.
+#[derive(Debug, Clone)]
+pub enum MetaValue {
+    /// Boolean
+    Boolean(bool),
+    /// Date and time
+    Datetime(MetaDateTime),
+    /// Number
+    Float(f64),
+    /// Duration
+    TimeDuration(Duration),
+    /// Text
+    String(String),
+}
@@ -0,0 +1,193 @@
+//!
+//! Named ranges and named expressions.
+//!
+//! Both can be defined for the whole workbook or local to one sheet.
+//! Names ignore case, "Prices" and "prices" are the same name.
+//!
+//! ```
+//! use spreadsheet_ods::{CellRange, CellRef, WorkBook};
+//! use spreadsheet_ods::named::{NameScope, NamedRange};
+//!
+//! let mut book = WorkBook::new_empty();
+//! let range = CellRange::remote("Sheet1", 0, 0, 9, 0).absolute();
+//! book.add_named_range(NamedRange::new("prices", range));
+//!
+//! let local = CellRange::remote("Sheet1", 0, 1, 9, 1).absolute();
+//! let mut name = NamedRange::new("prices", local);
+//! name.set_scope(NameScope::Sheet("Sheet1".to_string()));
+//! book.add_named_range(name);
+//!
+//! assert_eq!(book.named_range("prices").map(|v| v.range().col()), Some(0));
+//! assert_eq!(
+//!     book.local_named_range("Sheet1", "prices").map(|v| v.range().col()),
+//!     Some(1)
+//! );
+//! ```
+
+use crate::{CellRange, CellRef};
+
+/// Names are compared ignoring case.
+pub(crate) fn same_name(a: &str, b: &str) -> bool {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .eq(b.chars().flat_map(char::to_lowercase))
+}
+
+/// Visibility of a name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameScope {
+    /// Can be used everywhere in the workbook.
+    Global,
+    /// Can only be used within the sheet with the given name.
+    Sheet(String),
+}
+
+/// A named cell range.
+#[derive(Debug, Clone)]
+pub struct NamedRange {
+    name: String,
+    base_cell: CellRef,
+    range: CellRange,
+    scope: NameScope,
+    usable_as: Option<String>,
+}
+
+impl NamedRange {
+    /// Creates a global named range. The base cell is the first cell
+    /// of the range.
+    pub fn new<S: Into<String>>(name: S, range: CellRange) -> Self {
+        let base_cell = CellRef::new_all(
+            range.iri().cloned(),
+            range.table().cloned(),
+            true,
+            range.row(),
+            true,
+            range.col(),
+        );
+        Self {
+            name: name.into(),
+            base_cell,
+            range,
+            scope: NameScope::Global,
+            usable_as: None,
+        }
+    }
+
+    /// Name.
+    pub fn set_name<S: Into<String>>(&mut self, name: S) {
+        self.name = name.into();
+    }
+
+    /// Name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Relative references in the range are relative to this cell.
+    pub fn set_base_cell(&mut self, base_cell: CellRef) {
+        self.base_cell = base_cell;
+    }
+
+    /// Relative references in the range are relative to this cell.
+    pub fn base_cell(&self) -> &CellRef {
+        &self.base_cell
+    }
+
+    /// The range.
+    pub fn set_range(&mut self, range: CellRange) {
+        self.range = range;
+    }
+
+    /// The range.
+    pub fn range(&self) -> &CellRange {
+        &self.range
+    }
+
+    /// Mutable range.
+    pub fn range_mut(&mut self) -> &mut CellRange {
+        &mut self.range
+    }
+
+    /// Scope of the name.
+    pub fn set_scope(&mut self, scope: NameScope) {
+        self.scope = scope;
+    }
+
+    /// Scope of the name.
+    pub fn scope(&self) -> &NameScope {
+        &self.scope
+    }
+
+    /// Value for table:range-usable-as. Either "none" or a list of
+    /// "print-range", "filter", "repeat-row" and "repeat-column".
+    pub fn set_usable_as(&mut self, usable_as: Option<String>) {
+        self.usable_as = usable_as;
+    }
+
+    /// Value for table:range-usable-as.
+    pub fn usable_as(&self) -> Option<&str> {
+        self.usable_as.as_deref()
+    }
+}
+
+/// A named expression.
+#[derive(Debug, Clone)]
+pub struct NamedExpression {
+    name: String,
+    base_cell: Option<CellRef>,
+    expression: String,
+    scope: NameScope,
+}
+
+impl NamedExpression {
+    /// Creates a global named expression. The expression is an
+    /// OpenFormula expression like "of:=[.A1]*2".
+    pub fn new<S: Into<String>, T: Into<String>>(name: S, expression: T) -> Self {
+        Self {
+            name: name.into(),
+            base_cell: None,
+            expression: expression.into(),
+            scope: NameScope::Global,
+        }
+    }
+
+    /// Name.
+    pub fn set_name<S: Into<String>>(&mut self, name: S) {
+        self.name = name.into();
+    }
+
+    /// Name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Relative references in the expression are relative to this cell.
+    pub fn set_base_cell(&mut self, base_cell: Option<CellRef>) {
+        self.base_cell = base_cell;
+    }
+
+    /// Relative references in the expression are relative to this cell.
+    pub fn base_cell(&self) -> Option<&CellRef> {
+        self.base_cell.as_ref()
+    }
+
+    /// The expression.
+    pub fn set_expression<S: Into<String>>(&mut self, expression: S) {
+        self.expression = expression.into();
+    }
+
+    /// The expression.
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// Scope of the name.
+    pub fn set_scope(&mut self, scope: NameScope) {
+        self.scope = scope;
+    }
+
+    /// Scope of the name.
+    pub fn scope(&self) -> &NameScope {
+        &self.scope
+    }
+}
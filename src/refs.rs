@@ -5,6 +5,9 @@
 use crate::refs::format_refs::{
     fmt_cell_range, fmt_cell_ref, fmt_col, fmt_col_range, fmt_row, fmt_row_range,
 };
+pub(crate) use crate::refs::format_refs::{
+    fmt_excel_cell_range, fmt_excel_cell_ref, fmt_excel_col_range, fmt_excel_row_range,
+};
 use crate::refs::parser::CRCode::{CRCellRange, CRCellRef, CRColRange, CRRowRange};
 use crate::refs::parser::KTokenizerError;
 use crate::OdsError;
@@ -14,6 +17,7 @@ use std::fmt::{Display, Formatter, Write};
 
 mod format;
 mod parser;
+mod r1c1;
 
 /// Basic cell reference.
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
@@ -213,6 +217,14 @@ impl CellRef {
         buf
     }
 
+    /// Returns the cell reference in R1C1 notation, e.g. R[-1]C[2].
+    /// Relative parts are offsets to the base cell.
+    pub fn to_r1c1(&self, base: &CellRef) -> String {
+        let mut buf = String::new();
+        let _ = r1c1::fmt_cell_ref(&mut buf, self, base);
+        buf
+    }
+
     /// Makes this CellReference into an absolute reference.
     pub fn absolute(mut self) -> Self {
         self.col.col_abs = true;
@@ -530,6 +542,14 @@ impl CellRange {
         buf
     }
 
+    /// Returns the range reference in R1C1 notation, e.g. R1C1:R[1]C[1].
+    /// A range of one cell is written as a single cell.
+    pub fn to_r1c1(&self, base: &CellRef) -> String {
+        let mut buf = String::new();
+        let _ = r1c1::fmt_cell_range(&mut buf, self, base);
+        buf
+    }
+
     /// Makes this CellReference into an absolute reference.
     pub fn absolute(mut self) -> Self {
         self.from_col.col_abs = true;
@@ -726,6 +746,14 @@ impl ColRange {
         buf
     }
 
+    /// Returns the column range in R1C1 notation, e.g. C[-2]:C5.
+    /// Only the column of the base cell is used.
+    pub fn to_r1c1(&self, base: &CellRef) -> String {
+        let mut buf = String::new();
+        let _ = r1c1::fmt_col_range(&mut buf, self, base);
+        buf
+    }
+
     /// Makes this CellReference into an absolute reference.
     pub fn absolute(mut self) -> Self {
         self.from_col.col_abs = true;
@@ -893,6 +921,14 @@ impl RowRange {
         buf
     }
 
+    /// Returns the row range in R1C1 notation, e.g. R2:R[1].
+    /// Only the row of the base cell is used.
+    pub fn to_r1c1(&self, base: &CellRef) -> String {
+        let mut buf = String::new();
+        let _ = r1c1::fmt_row_range(&mut buf, self, base);
+        buf
+    }
+
     /// Makes this CellReference into an absolute reference.
     pub fn absolute(mut self) -> Self {
         self.from_row.row_abs = true;
@@ -1054,6 +1090,119 @@ mod format_refs {
         Ok(())
     }
 
+    /// Appends a cell reference in Excel A1 syntax.
+    pub(crate) fn fmt_excel_cell_ref(f: &mut impl fmt::Write, cell_ref: &CellRef) -> fmt::Result {
+        fmt_excel_tables(f, cell_ref.iri(), cell_ref.table(), None)?;
+        fmt_col(f, &cell_ref.col)?;
+        fmt_row(f, &cell_ref.row)?;
+        Ok(())
+    }
+
+    /// Appends a range reference in Excel A1 syntax. A range across
+    /// sheets is written as a 3D reference.
+    pub(crate) fn fmt_excel_cell_range(
+        f: &mut impl fmt::Write,
+        cell_range: &CellRange,
+    ) -> fmt::Result {
+        fmt_excel_tables(
+            f,
+            cell_range.iri(),
+            cell_range.table(),
+            cell_range.to_table(),
+        )?;
+        fmt_col(f, &cell_range.from_col)?;
+        fmt_row(f, &cell_range.from_row)?;
+        // Sheet1:Sheet3!A1
+        let three_d =
+            cell_range.to_table().is_some() && cell_range.to_table() != cell_range.table();
+        if !three_d
+            || cell_range.from_col != cell_range.to_col
+            || cell_range.from_row != cell_range.to_row
+        {
+            write!(f, ":")?;
+            fmt_col(f, &cell_range.to_col)?;
+            fmt_row(f, &cell_range.to_row)?;
+        }
+        Ok(())
+    }
+
+    /// Appends a column range in Excel A1 syntax.
+    pub(crate) fn fmt_excel_col_range(
+        f: &mut impl fmt::Write,
+        col_range: &ColRange,
+    ) -> fmt::Result {
+        fmt_excel_tables(f, col_range.iri(), col_range.table(), col_range.to_table())?;
+        fmt_col(f, &col_range.from_col)?;
+        write!(f, ":")?;
+        fmt_col(f, &col_range.to_col)?;
+        Ok(())
+    }
+
+    /// Appends a row range in Excel A1 syntax.
+    pub(crate) fn fmt_excel_row_range(
+        f: &mut impl fmt::Write,
+        row_range: &RowRange,
+    ) -> fmt::Result {
+        fmt_excel_tables(f, row_range.iri(), row_range.table(), row_range.to_table())?;
+        fmt_row(f, &row_range.from_row)?;
+        write!(f, ":")?;
+        fmt_row(f, &row_range.to_row)?;
+        Ok(())
+    }
+
+    /// Appends the sheet prefix "Sheet1!" or "'[file]Sheet1:Sheet3'!".
+    pub(crate) fn fmt_excel_tables(
+        f: &mut impl fmt::Write,
+        iri: Option<&String>,
+        table: Option<&String>,
+        to_table: Option<&String>,
+    ) -> fmt::Result {
+        let to_table = to_table.filter(|v| Some(*v) != table);
+        if iri.is_none() && table.is_none() && to_table.is_none() {
+            return Ok(());
+        }
+
+        let quote = iri.is_some()
+            || table.map(|v| excel_quote(v)).unwrap_or(false)
+            || to_table.map(|v| excel_quote(v)).unwrap_or(false);
+        let mut buf = String::new();
+        if let Some(iri) = iri {
+            buf.push('[');
+            buf.push_str(iri);
+            buf.push(']');
+        }
+        if let Some(table) = table {
+            buf.push_str(table);
+        }
+        if let Some(to_table) = to_table {
+            buf.push(':');
+            buf.push_str(to_table);
+        }
+        if quote {
+            write!(f, "'{}'!", buf.replace('\'', "''"))?;
+        } else {
+            write!(f, "{}!", buf)?;
+        }
+        Ok(())
+    }
+
+    /// Excel needs quotes for anything but simple names. Names that look
+    /// like a cell reference are quoted too.
+    fn excel_quote(table_name: &str) -> bool {
+        let letters = table_name
+            .chars()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .count();
+        let cell_like = (1..=3).contains(&letters)
+            && table_name.len() > letters
+            && table_name[letters..].chars().all(|c| c.is_ascii_digit());
+
+        table_name.is_empty()
+            || table_name.starts_with(|c: char| c.is_ascii_digit())
+            || table_name.contains(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+            || cell_like
+    }
+
     /// Appends the table-name
     pub(crate) fn fmt_table_name(
         f: &mut impl fmt::Write,
@@ -1124,6 +1273,30 @@ pub fn parse_rowrange(buf: &str) -> Result<RowRange, OdsError> {
     }
 }
 
+/// Parse a cell reference in R1C1 notation like "R1C1", "R[-1]C[2]"
+/// or "'Sheet 1'!RC1". Offsets are relative to the base cell.
+pub fn parse_cellref_r1c1(buf: &str, base: &CellRef) -> Result<CellRef, OdsError> {
+    r1c1::parse_cell_ref(buf, base)
+}
+
+/// Parse a cell range in R1C1 notation like "R1C1:R[2]C[2]".
+/// Offsets are relative to the base cell.
+pub fn parse_cellrange_r1c1(buf: &str, base: &CellRef) -> Result<CellRange, OdsError> {
+    r1c1::parse_cell_range(buf, base)
+}
+
+/// Parse a column range in R1C1 notation like "C1:C3" or "C[-1]".
+/// Offsets are relative to the base cell.
+pub fn parse_colrange_r1c1(buf: &str, base: &CellRef) -> Result<ColRange, OdsError> {
+    r1c1::parse_col_range(buf, base)
+}
+
+/// Parse a row range in R1C1 notation like "R1:R3" or "R[-1]".
+/// Offsets are relative to the base cell.
+pub fn parse_rowrange_r1c1(buf: &str, base: &CellRef) -> Result<RowRange, OdsError> {
+    r1c1::parse_row_range(buf, base)
+}
+
 /// Parse a list of range refs
 pub fn parse_cellranges(buf: &str) -> Result<Option<Vec<CellRange>>, OdsError> {
     let trk = Track::new_tracker();
@@ -0,0 +1,286 @@
+//!
+//! Parsing and formatting of references in R1C1 notation.
+//!
+//! Absolute rows and columns are written as R5 or C3, relative ones as an
+//! offset to a base cell like R[-1] or C[2]. A zero offset is just R or C.
+//! The sheet prefix is the same as in Excel A1 references.
+//!
+
+use crate::refs::format_refs::fmt_excel_tables;
+use crate::refs::{CCol, CRow, CellRange, CellRef, ColRange, RowRange};
+use crate::OdsError;
+use std::fmt;
+
+fn fmt_index(f: &mut impl fmt::Write, c: char, abs: bool, idx: u32, base: u32) -> fmt::Result {
+    if abs {
+        write!(f, "{}{}", c, idx as u64 + 1)
+    } else if idx == base {
+        write!(f, "{}", c)
+    } else {
+        write!(f, "{}[{}]", c, idx as i64 - base as i64)
+    }
+}
+
+fn fmt_row(f: &mut impl fmt::Write, row: &CRow, base: &CellRef) -> fmt::Result {
+    fmt_index(f, 'R', row.row_abs, row.row, base.row())
+}
+
+fn fmt_col(f: &mut impl fmt::Write, col: &CCol, base: &CellRef) -> fmt::Result {
+    fmt_index(f, 'C', col.col_abs, col.col, base.col())
+}
+
+/// Appends the cell reference.
+pub(crate) fn fmt_cell_ref(
+    f: &mut impl fmt::Write,
+    cell_ref: &CellRef,
+    base: &CellRef,
+) -> fmt::Result {
+    fmt_excel_tables(f, cell_ref.iri(), cell_ref.table(), None)?;
+    fmt_row(f, &cell_ref.row, base)?;
+    fmt_col(f, &cell_ref.col, base)?;
+    Ok(())
+}
+
+/// Appends the range reference.
+pub(crate) fn fmt_cell_range(
+    f: &mut impl fmt::Write,
+    cell_range: &CellRange,
+    base: &CellRef,
+) -> fmt::Result {
+    fmt_excel_tables(
+        f,
+        cell_range.iri(),
+        cell_range.table(),
+        cell_range.to_table(),
+    )?;
+    fmt_row(f, &cell_range.from_row, base)?;
+    fmt_col(f, &cell_range.from_col, base)?;
+    if cell_range.from_row != cell_range.to_row || cell_range.from_col != cell_range.to_col {
+        write!(f, ":")?;
+        fmt_row(f, &cell_range.to_row, base)?;
+        fmt_col(f, &cell_range.to_col, base)?;
+    }
+    Ok(())
+}
+
+/// Appends the column range.
+pub(crate) fn fmt_col_range(
+    f: &mut impl fmt::Write,
+    col_range: &ColRange,
+    base: &CellRef,
+) -> fmt::Result {
+    fmt_excel_tables(f, col_range.iri(), col_range.table(), col_range.to_table())?;
+    fmt_col(f, &col_range.from_col, base)?;
+    if col_range.from_col != col_range.to_col {
+        write!(f, ":")?;
+        fmt_col(f, &col_range.to_col, base)?;
+    }
+    Ok(())
+}
+
+/// Appends the row range.
+pub(crate) fn fmt_row_range(
+    f: &mut impl fmt::Write,
+    row_range: &RowRange,
+    base: &CellRef,
+) -> fmt::Result {
+    fmt_excel_tables(f, row_range.iri(), row_range.table(), row_range.to_table())?;
+    fmt_row(f, &row_range.from_row, base)?;
+    if row_range.from_row != row_range.to_row {
+        write!(f, ":")?;
+        fmt_row(f, &row_range.to_row, base)?;
+    }
+    Ok(())
+}
+
+/// Sheet prefix: iri, table and to_table.
+type Tables = (Option<String>, Option<String>, Option<String>);
+
+struct Parser<'a> {
+    buf: &'a str,
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn new(buf: &'a str) -> Self {
+        Self {
+            buf,
+            rest: buf.trim(),
+        }
+    }
+
+    fn error(&self, msg: &'static str) -> OdsError {
+        OdsError::Parse(msg, Some(self.buf.to_string()))
+    }
+
+    fn end(&self) -> Result<(), OdsError> {
+        if self.rest.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error("unexpected trailing characters"))
+        }
+    }
+
+    fn colon(&mut self) -> bool {
+        match self.rest.strip_prefix(':') {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 'Sheet 1'!, Sheet1:Sheet3! or '[file]Sheet1'!
+    fn tables(&mut self) -> Result<Tables, OdsError> {
+        let name = if let Some(quoted) = self.rest.strip_prefix('\'') {
+            let mut name = String::new();
+            let mut chars = quoted.char_indices().peekable();
+            let mut end = None;
+            while let Some((i, c)) = chars.next() {
+                if c == '\'' {
+                    if let Some((_, '\'')) = chars.peek() {
+                        chars.next();
+                    } else {
+                        end = Some(i);
+                        break;
+                    }
+                }
+                name.push(c);
+            }
+            let rest = match end {
+                Some(i) => &quoted[i + 1..],
+                None => return Err(self.error("unterminated sheet name")),
+            };
+            match rest.strip_prefix('!') {
+                Some(rest) => self.rest = rest,
+                None => return Err(self.error("expected ! after the sheet name")),
+            }
+            name
+        } else if let Some((name, rest)) = self.rest.split_once('!') {
+            self.rest = rest;
+            name.to_string()
+        } else {
+            return Ok((None, None, None));
+        };
+
+        let (iri, name) = match name.strip_prefix('[').and_then(|v| v.split_once(']')) {
+            Some((iri, name)) => (Some(iri.to_string()), name),
+            None => (None, name.as_str()),
+        };
+        let (table, to_table) = match name.split_once(':') {
+            Some((table, to_table)) => (table, Some(to_table.to_string())),
+            None => (name, None),
+        };
+        if table.is_empty() {
+            return Err(self.error("empty sheet name"));
+        }
+        Ok((iri, Some(table.to_string()), to_table))
+    }
+
+    /// R5, R[-1] or R.
+    fn index(&mut self, marker: char, base: u32) -> Result<(bool, u32), OdsError> {
+        let rest = match self
+            .rest
+            .strip_prefix([marker, marker.to_ascii_lowercase()])
+        {
+            Some(v) => v,
+            None if marker == 'R' => return Err(self.error("expected R")),
+            None => return Err(self.error("expected C")),
+        };
+
+        if let Some(offset) = rest.strip_prefix('[') {
+            let end = match offset.find(']') {
+                Some(v) => v,
+                None => return Err(self.error("unterminated offset")),
+            };
+            let idx = match offset[..end].trim().parse::<i64>() {
+                Ok(v) => base as i64 + v,
+                Err(_) => return Err(self.error("invalid offset")),
+            };
+            if idx < 0 || idx > u32::MAX as i64 {
+                return Err(self.error("offset out of range"));
+            }
+            self.rest = &offset[end + 1..];
+            Ok((false, idx as u32))
+        } else {
+            let len = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            self.rest = &rest[len..];
+            if len == 0 {
+                Ok((false, base))
+            } else {
+                match rest[..len].parse::<u32>() {
+                    Ok(v) if v > 0 => Ok((true, v - 1)),
+                    _ => Err(self.error("invalid index")),
+                }
+            }
+        }
+    }
+}
+
+/// Parses a cell reference like R1C1, R[-1]C or Sheet1!RC[2].
+pub(crate) fn parse_cell_ref(buf: &str, base: &CellRef) -> Result<CellRef, OdsError> {
+    let mut p = Parser::new(buf);
+    let (iri, table, to_table) = p.tables()?;
+    if to_table.is_some() {
+        return Err(p.error("a cell reference can't span sheets"));
+    }
+    let (row_abs, row) = p.index('R', base.row())?;
+    let (col_abs, col) = p.index('C', base.col())?;
+    p.end()?;
+    Ok(CellRef::new_all(iri, table, row_abs, row, col_abs, col))
+}
+
+/// Parses a cell range like R1C1:R[2]C[2]. A single cell is a
+/// range too.
+pub(crate) fn parse_cell_range(buf: &str, base: &CellRef) -> Result<CellRange, OdsError> {
+    let mut p = Parser::new(buf);
+    let (iri, table, to_table) = p.tables()?;
+    let (row_abs, row) = p.index('R', base.row())?;
+    let (col_abs, col) = p.index('C', base.col())?;
+    let (to_row_abs, to_row, to_col_abs, to_col) = if p.colon() {
+        let (to_row_abs, to_row) = p.index('R', base.row())?;
+        let (to_col_abs, to_col) = p.index('C', base.col())?;
+        (to_row_abs, to_row, to_col_abs, to_col)
+    } else {
+        (row_abs, row, col_abs, col)
+    };
+    p.end()?;
+    Ok(CellRange::new_all(
+        iri, table, row_abs, row, col_abs, col, to_table, to_row_abs, to_row, to_col_abs, to_col,
+    ))
+}
+
+/// Parses a column range like C1:C3 or C[-1].
+pub(crate) fn parse_col_range(buf: &str, base: &CellRef) -> Result<ColRange, OdsError> {
+    let mut p = Parser::new(buf);
+    let (iri, table, to_table) = p.tables()?;
+    let (col_abs, col) = p.index('C', base.col())?;
+    let (to_col_abs, to_col) = if p.colon() {
+        p.index('C', base.col())?
+    } else {
+        (col_abs, col)
+    };
+    p.end()?;
+    Ok(ColRange::new_all(
+        iri, table, col_abs, col, to_table, to_col_abs, to_col,
+    ))
+}
+
+/// Parses a row range like R1:R3 or R[-1].
+pub(crate) fn parse_row_range(buf: &str, base: &CellRef) -> Result<RowRange, OdsError> {
+    let mut p = Parser::new(buf);
+    let (iri, table, to_table) = p.tables()?;
+    let (row_abs, row) = p.index('R', base.row())?;
+    let (to_row_abs, to_row) = if p.colon() {
+        p.index('R', base.row())?
+    } else {
+        (row_abs, row)
+    };
+    p.end()?;
+    Ok(RowRange::new_all(
+        iri, table, row_abs, row, to_table, to_row_abs, to_row,
+    ))
+}
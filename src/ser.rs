@@ -0,0 +1,755 @@
+//!
+//! Serializes records into the rows of a sheet with serde.
+//!
+//! The field names are written as header row, each record as one row
+//! below. Records must be structs or maps, the fields plain values.
+//!
+//! The functions decimal(), currency() and decimal_currency() can be
+//! used with `#[serde(serialize_with = "...")]` to write a field as
+//! Value::Decimal, Value::Currency or Value::DecimalCurrency. date(),
+//! datetime() and time() write chrono values as Value::Date,
+//! Value::DateTime and Value::TimeOfDay, without them they are text.
+//!
+//! ```
+//! use serde::{Serialize, Serializer};
+//! use spreadsheet_ods::{CellRef, SerializeOptions, Sheet, ValueType};
+//!
+//! fn eur<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+//!     spreadsheet_ods::ser::currency(value, "EUR", serializer)
+//! }
+//!
+//! #[derive(Serialize)]
+//! struct Line {
+//!     item: String,
+//!     #[serde(serialize_with = "eur")]
+//!     price: f64,
+//! }
+//!
+//! let lines = [Line {
+//!     item: "pencil".to_string(),
+//!     price: 0.5,
+//! }];
+//!
+//! let mut sheet = Sheet::new("lines");
+//! sheet.serialize_rows(&lines, CellRef::local(0, 0), &SerializeOptions::new()).unwrap();
+//! assert_eq!(sheet.value(1, 1).value_type(), ValueType::Currency);
+//! assert_eq!(sheet.value(1, 1).currency(), "EUR");
+//! ```
+//!
+
+use crate::refs::CellRef;
+use crate::style::units::Length;
+use crate::{CellStyleRef, OdsError, Sheet, Value};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use rust_decimal::Decimal;
+use serde::ser::{self, Impossible, Serialize, SerializeMap, SerializeStruct, Serializer};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Newtype names that mark the value of a field.
+const DECIMAL: &str = "$spreadsheet_ods::Decimal";
+const CURRENCY: &str = "$spreadsheet_ods::Currency";
+const DECIMAL_CURRENCY: &str = "$spreadsheet_ods::DecimalCurrency";
+const DATE: &str = "$spreadsheet_ods::Date";
+const DATETIME: &str = "$spreadsheet_ods::DateTime";
+const TIME: &str = "$spreadsheet_ods::Time";
+
+/// Writes the field as Value::Decimal. Other serializers get the
+/// decimal as a string.
+pub fn decimal<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_newtype_struct(DECIMAL, &value.to_string())
+}
+
+/// Writes the field as Value::Currency. It needs a small function that
+/// passes the currency for serialize_with. Other serializers get a
+/// string like "0.5 EUR".
+pub fn currency<S: Serializer>(
+    value: &f64,
+    currency: &str,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_newtype_struct(CURRENCY, &format!("{} {}", value, currency))
+}
+
+/// Writes the field as Value::DecimalCurrency. Like currency(), other
+/// serializers get a string like "0.50 EUR".
+pub fn decimal_currency<S: Serializer>(
+    value: &Decimal,
+    currency: &str,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_newtype_struct(DECIMAL_CURRENCY, &format!("{} {}", value, currency))
+}
+
+/// Writes the field as Value::Date. Other serializers get a string
+/// like "2023-01-01".
+pub fn date<S: Serializer>(value: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_newtype_struct(DATE, &value.format("%Y-%m-%d").to_string())
+}
+
+/// Writes the field as Value::DateTime. Other serializers get a string
+/// like "2023-01-01T10:30:00".
+pub fn datetime<S: Serializer>(value: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_newtype_struct(DATETIME, &value.format("%Y-%m-%dT%H:%M:%S%.f").to_string())
+}
+
+/// Writes the field as Value::TimeOfDay. Other serializers get a string
+/// like "10:30:00".
+pub fn time<S: Serializer>(value: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_newtype_struct(TIME, &value.format("%H:%M:%S%.f").to_string())
+}
+
+/// Options for Sheet::serialize_rows().
+///
+/// The settings for a column use the field name, after any serde
+/// rename.
+#[derive(Debug, Clone, Default)]
+pub struct SerializeOptions {
+    columns: Option<Vec<String>>,
+    column: HashMap<String, ColumnOptions>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ColumnOptions {
+    width: Option<Length>,
+    style: Option<CellStyleRef>,
+    decimal: bool,
+    currency: Option<String>,
+}
+
+impl SerializeOptions {
+    /// Default options. All fields are written in the order of the
+    /// struct.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Writes only these fields, in this order.
+    pub fn columns<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.columns = Some(names.into_iter().map(|v| v.into()).collect());
+        self
+    }
+
+    /// Width of the column for the field.
+    pub fn col_width<S: Into<String>>(mut self, name: S, width: Length) -> Self {
+        self.column.entry(name.into()).or_default().width = Some(width);
+        self
+    }
+
+    /// Cell style for the field. Without it the cell gets the default
+    /// style for the value type of the workbook, see
+    /// WorkBook::add_def_style().
+    pub fn col_style<S: Into<String>>(mut self, name: S, style: &CellStyleRef) -> Self {
+        self.column.entry(name.into()).or_default().style = Some(style.clone());
+        self
+    }
+
+    /// Writes the field as Value::Decimal. rust_decimal serializes as
+    /// a string, which would be written as text otherwise.
+    pub fn decimal<S: Into<String>>(mut self, name: S) -> Self {
+        self.column.entry(name.into()).or_default().decimal = true;
+        self
+    }
+
+    /// Writes the field as currency. Numbers become a Value::Currency,
+    /// decimals a Value::DecimalCurrency.
+    pub fn currency<S: Into<String>, C: Into<String>>(mut self, name: S, currency: C) -> Self {
+        self.column.entry(name.into()).or_default().currency = Some(currency.into());
+        self
+    }
+}
+
+/// Error while serializing a record. The key is set when the
+/// conversion of a field fails.
+#[derive(Debug)]
+struct SerError {
+    msg: String,
+    key: Option<String>,
+}
+
+impl SerError {
+    fn at(mut self, key: &str) -> Self {
+        if self.key.is_none() {
+            self.key = Some(key.to_string());
+        }
+        self
+    }
+}
+
+impl fmt::Display for SerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.msg)
+    }
+}
+
+impl std::error::Error for SerError {}
+
+impl ser::Error for SerError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerError {
+            msg: msg.to_string(),
+            key: None,
+        }
+    }
+}
+
+/// Fields of one record.
+type Fields = Vec<(Cow<'static, str>, Value)>;
+
+/// Serializes the records into the sheet. See Sheet::serialize_rows().
+pub(crate) fn serialize_rows<T: Serialize>(
+    sheet: &mut Sheet,
+    rows: &[T],
+    start: CellRef,
+    options: &SerializeOptions,
+) -> Result<(), OdsError> {
+    let mut columns: Vec<String> = options.columns.clone().unwrap_or_default();
+
+    // convert everything first, nothing is written if a record fails.
+    let mut records = Vec::with_capacity(rows.len());
+    for (idx, record) in rows.iter().enumerate() {
+        let mut fields = Fields::new();
+        let ser = RecordSerializer {
+            fields: &mut fields,
+            options,
+        };
+        let result = record.serialize(ser);
+        if options.columns.is_none() {
+            for (key, _) in &fields {
+                if !columns.iter().any(|v| v == key) {
+                    columns.push(key.to_string());
+                }
+            }
+        }
+        if let Err(e) = result {
+            let col = match &e.key {
+                Some(key) => columns
+                    .iter()
+                    .position(|v| v == key)
+                    .unwrap_or(columns.len()),
+                None => 0,
+            };
+            return Err(OdsError::Serde(
+                CellRef::remote(
+                    sheet.name().as_str(),
+                    start.row() + 1 + idx as u32,
+                    start.col() + col as u32,
+                ),
+                e.msg,
+            ));
+        }
+        records.push(fields);
+    }
+
+    let mut col_idx = HashMap::new();
+    for (idx, name) in columns.iter().enumerate() {
+        let col = start.col() + idx as u32;
+        col_idx.insert(name.as_str(), col);
+        sheet.set_value(start.row(), col, name.as_str());
+        if let Some(width) = options.column.get(name).and_then(|v| v.width) {
+            sheet.set_col_width(col, width);
+        }
+    }
+
+    for (idx, fields) in records.into_iter().enumerate() {
+        let row = start.row() + 1 + idx as u32;
+        for (key, value) in fields {
+            let col = match col_idx.get(key.as_ref()) {
+                Some(col) => *col,
+                None => continue,
+            };
+            match options
+                .column
+                .get(key.as_ref())
+                .and_then(|v| v.style.as_ref())
+            {
+                Some(style) => sheet.set_styled_value(row, col, value, style),
+                None => sheet.set_value(row, col, value),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Applies the column options to the value.
+fn convert(value: Value, options: Option<&ColumnOptions>) -> Result<Value, SerError> {
+    let options = match options {
+        Some(v) => v,
+        None => return Ok(value),
+    };
+    let value = match value {
+        Value::Text(v) if options.decimal || options.currency.is_some() => {
+            match Decimal::from_str(&v) {
+                Ok(v) => Value::Decimal(v),
+                Err(_) => return Err(ser::Error::custom(format!("not a decimal: {}", v))),
+            }
+        }
+        v => v,
+    };
+    match (&options.currency, value) {
+        (Some(c), Value::Number(v)) => Ok(Value::Currency(v, c.clone())),
+        (Some(c), Value::Decimal(v)) => Ok(Value::DecimalCurrency(v, c.clone())),
+        (_, value) => Ok(value),
+    }
+}
+
+/// Splits "0.5 EUR" into value and currency.
+fn split_currency(v: &str) -> Result<(&str, &str), SerError> {
+    match v.rsplit_once(' ') {
+        Some((v, c)) if !c.is_empty() => Ok((v, c)),
+        _ => Err(ser::Error::custom(format!("not a currency: {}", v))),
+    }
+}
+
+fn unsupported<T>(what: &str) -> Result<T, SerError> {
+    Err(ser::Error::custom(format!("{} are not supported", what)))
+}
+
+/// Collects the fields of one record.
+struct RecordSerializer<'f> {
+    fields: &'f mut Fields,
+    options: &'f SerializeOptions,
+}
+
+impl<'f> Serializer for RecordSerializer<'f> {
+    type Ok = ();
+    type Error = SerError;
+    type SerializeSeq = Impossible<(), SerError>;
+    type SerializeTuple = Impossible<(), SerError>;
+    type SerializeTupleStruct = Impossible<(), SerError>;
+    type SerializeTupleVariant = Impossible<(), SerError>;
+    type SerializeMap = FieldSerializer<'f>;
+    type SerializeStruct = FieldSerializer<'f>;
+    type SerializeStructVariant = Impossible<(), SerError>;
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, SerError> {
+        Ok(FieldSerializer {
+            fields: self.fields,
+            options: self.options,
+            key: None,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerError> {
+        Ok(FieldSerializer {
+            fields: self.fields,
+            options: self.options,
+            key: None,
+        })
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), SerError> {
+        value.serialize(self)
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_char(self, _v: char) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_none(self) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_unit(self) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerError> {
+        unsupported("records other than structs or maps")
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SerError> {
+        unsupported("records other than structs or maps")
+    }
+}
+
+/// Converts the fields of a struct or the entries of a map.
+struct FieldSerializer<'f> {
+    fields: &'f mut Fields,
+    options: &'f SerializeOptions,
+    key: Option<String>,
+}
+
+impl FieldSerializer<'_> {
+    fn push<T: ?Sized + Serialize>(
+        &mut self,
+        key: Cow<'static, str>,
+        value: &T,
+    ) -> Result<(), SerError> {
+        let value = value
+            .serialize(ValueSerializer)
+            .and_then(|v| convert(v, self.options.column.get(key.as_ref())))
+            .map_err(|e| e.at(&key))?;
+        self.fields.push((key, value));
+        Ok(())
+    }
+}
+
+impl SerializeStruct for FieldSerializer<'_> {
+    type Ok = ();
+    type Error = SerError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerError> {
+        self.push(Cow::Borrowed(key), value)
+    }
+
+    fn end(self) -> Result<(), SerError> {
+        Ok(())
+    }
+}
+
+impl SerializeMap for FieldSerializer<'_> {
+    type Ok = ();
+    type Error = SerError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), SerError> {
+        let key = match key.serialize(ValueSerializer)? {
+            Value::Text(v) => v,
+            Value::Number(v) => v.to_string(),
+            _ => return unsupported("map keys other than strings or numbers"),
+        };
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerError> {
+        match self.key.take() {
+            Some(key) => self.push(Cow::Owned(key), value),
+            None => Err(ser::Error::custom("map value without a key")),
+        }
+    }
+
+    fn end(self) -> Result<(), SerError> {
+        Ok(())
+    }
+}
+
+/// Converts one field into a Value.
+struct ValueSerializer;
+
+impl Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = SerError;
+    type SerializeSeq = Impossible<Value, SerError>;
+    type SerializeTuple = Impossible<Value, SerError>;
+    type SerializeTupleStruct = Impossible<Value, SerError>;
+    type SerializeTupleVariant = Impossible<Value, SerError>;
+    type SerializeMap = Impossible<Value, SerError>;
+    type SerializeStruct = Impossible<Value, SerError>;
+    type SerializeStructVariant = Impossible<Value, SerError>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, SerError> {
+        Ok(Value::Boolean(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, SerError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, SerError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, SerError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, SerError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, SerError> {
+        Ok(Value::Number(v as f64))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, SerError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, SerError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, SerError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, SerError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, SerError> {
+        Ok(Value::Number(v as f64))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, SerError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, SerError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, SerError> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, SerError> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Value, SerError> {
+        unsupported("bytes")
+    }
+
+    fn serialize_none(self) -> Result<Value, SerError> {
+        Ok(Value::Empty)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Value, SerError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, SerError> {
+        Ok(Value::Empty)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, SerError> {
+        Ok(Value::Empty)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value, SerError> {
+        Ok(Value::Text(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Value, SerError> {
+        match (name, value.serialize(self)?) {
+            (DECIMAL, Value::Text(v)) => match Decimal::from_str(&v) {
+                Ok(v) => Ok(Value::Decimal(v)),
+                Err(_) => Err(ser::Error::custom(format!("not a decimal: {}", v))),
+            },
+            (CURRENCY, Value::Text(v)) => {
+                let (n, c) = split_currency(&v)?;
+                match f64::from_str(n) {
+                    Ok(n) => Ok(Value::Currency(n, c.to_string())),
+                    Err(_) => Err(ser::Error::custom(format!("not a currency: {}", v))),
+                }
+            }
+            (DECIMAL_CURRENCY, Value::Text(v)) => {
+                let (n, c) = split_currency(&v)?;
+                match Decimal::from_str(n) {
+                    Ok(n) => Ok(Value::DecimalCurrency(n, c.to_string())),
+                    Err(_) => Err(ser::Error::custom(format!("not a currency: {}", v))),
+                }
+            }
+            (DATE, Value::Text(v)) => match NaiveDate::parse_from_str(&v, "%Y-%m-%d") {
+                Ok(v) => Ok(Value::Date(v)),
+                Err(_) => Err(ser::Error::custom(format!("not a date: {}", v))),
+            },
+            (DATETIME, Value::Text(v)) => {
+                match NaiveDateTime::parse_from_str(&v, "%Y-%m-%dT%H:%M:%S%.f") {
+                    Ok(v) => Ok(Value::DateTime(v)),
+                    Err(_) => Err(ser::Error::custom(format!("not a date and time: {}", v))),
+                }
+            }
+            (TIME, Value::Text(v)) => match NaiveTime::parse_from_str(&v, "%H:%M:%S%.f") {
+                Ok(v) => Ok(Value::TimeOfDay(v)),
+                Err(_) => Err(ser::Error::custom(format!("not a time: {}", v))),
+            },
+            (_, value) => Ok(value),
+        }
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Value, SerError> {
+        unsupported("enum variants with data")
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerError> {
+        unsupported("nested sequences")
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerError> {
+        unsupported("nested tuples")
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, SerError> {
+        unsupported("nested tuples")
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerError> {
+        unsupported("enum variants with data")
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerError> {
+        unsupported("nested maps")
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, SerError> {
+        unsupported("nested structs")
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SerError> {
+        unsupported("enum variants with data")
+    }
+}
@@ -0,0 +1,120 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use spreadsheet_ods::{read_ods, CellStyleRef, OdsError, Sheet};
+
+/// Counts the bytes currently allocated.
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATED.fetch_add(new_size, Ordering::Relaxed);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocated() -> usize {
+    ALLOCATED.load(Ordering::Relaxed)
+}
+
+fn create_sheet(rows: u32, cols: u32) -> Sheet {
+    let style = CellStyleRef::from("ce1");
+    let mut sh = Sheet::new("1");
+    for r in 0..rows {
+        for c in 0..cols {
+            if c % 2 == 0 {
+                sh.set_styled_value(r, c, r * c, &style);
+            } else {
+                sh.set_styled_value(r, c, "text", &style);
+            }
+        }
+        if r % 10 == 0 {
+            sh.set_formula(r, 0, "of:=1+1");
+        }
+    }
+    sh
+}
+
+// cargo test --release --test bench_cells -- --ignored --nocapture
+#[test]
+#[ignore]
+fn test_cell_memory() -> Result<(), OdsError> {
+    const ROWS: u32 = 1000;
+    const COLS: u32 = 100;
+    const CELLS: usize = ROWS as usize * COLS as usize;
+
+    let before = allocated();
+    let now = Instant::now();
+    let sh = create_sheet(ROWS, COLS);
+    let elapsed = now.elapsed();
+    let used = allocated() - before;
+    println!(
+        "create {} cells {:?} {}ns/cell {} bytes {} bytes/cell",
+        CELLS,
+        elapsed,
+        elapsed.as_nanos() / CELLS as u128,
+        used,
+        used / CELLS
+    );
+
+    let now = Instant::now();
+    let mut n = 0;
+    for (_, cell) in sh.iter() {
+        if cell.style.is_some() {
+            n += 1;
+        }
+    }
+    let elapsed = now.elapsed();
+    assert_eq!(n, CELLS);
+    println!(
+        "iter {} cells {:?} {}ns/cell",
+        CELLS,
+        elapsed,
+        elapsed.as_nanos() / CELLS as u128
+    );
+    drop(sh);
+
+    const READS: u32 = 50;
+    let now = Instant::now();
+    for _ in 0..READS {
+        read_ods("tests/orders.ods")?;
+    }
+    let elapsed = now.elapsed() / READS;
+
+    let before = allocated();
+    let wb = read_ods("tests/orders.ods")?;
+    let used = allocated() - before;
+    let cells: usize = (0..wb.num_sheets())
+        .map(|i| wb.sheet(i).iter().count())
+        .sum();
+    println!(
+        "read orders.ods {} cells {:?} {} bytes",
+        cells, elapsed, used
+    );
+
+    // only the sheets.
+    let mut wb = wb;
+    let sheets: Vec<Sheet> = (0..wb.num_sheets()).map(|_| wb.remove_sheet(0)).collect();
+    let before = allocated();
+    drop(sheets);
+    println!("orders.ods sheets {} bytes", before - allocated());
+
+    Ok(())
+}
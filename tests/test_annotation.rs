@@ -0,0 +1,51 @@
+use chrono::NaiveDate;
+use spreadsheet_ods::annotation::Annotation;
+use spreadsheet_ods::text::TextP;
+use spreadsheet_ods::{cm, read_ods_buf, write_ods_buf, Length, OdsError, Sheet, WorkBook};
+
+#[test]
+fn test_annotation() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("1");
+
+    sh.set_value(0, 0, 42);
+    let mut a = Annotation::new("first line\nsecond line");
+    a.set_author(Some("reviewer".to_string()));
+    let date = NaiveDate::from_ymd_opt(2023, 4, 5)
+        .expect("date")
+        .and_hms_opt(10, 11, 12)
+        .expect("time");
+    a.set_date(Some(date));
+    a.set_display(true);
+    a.set_rect(cm!(5), cm!(1), cm!(4), cm!(2));
+    sh.set_annotation(0, 0, a);
+
+    // annotation on an empty cell.
+    let mut a = Annotation::new_empty();
+    a.push_text(TextP::new().text("rich ").text("text").into_xmltag());
+    sh.set_annotation(3, 3, a);
+
+    wb.push_sheet(sh);
+
+    let buf = write_ods_buf(&mut wb, Vec::new())?;
+    let mut wi = read_ods_buf(&buf)?;
+    let si = wi.sheet_mut(0);
+
+    assert_eq!(si.value(0, 0).as_i32_or(0), 42);
+    let a = si.annotation(0, 0).expect("annotation");
+    assert_eq!(a.author(), Some("reviewer"));
+    assert_eq!(a.date(), Some(date));
+    assert!(a.display());
+    assert_eq!(a.text().len(), 2);
+    assert_eq!(a.text_str(), "first line\nsecond line");
+    assert_eq!(a.rect()?, Some((cm!(5), cm!(1), cm!(4), cm!(2))));
+
+    let a = si.annotation(3, 3).expect("annotation");
+    assert_eq!(a.text_str(), "rich text");
+    assert!(!a.display());
+
+    assert!(si.clear_annotation(3, 3).is_some());
+    assert!(si.annotation(3, 3).is_none());
+
+    Ok(())
+}
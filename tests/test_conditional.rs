@@ -0,0 +1,207 @@
+use color::Rgb;
+use spreadsheet_ods::condition::{Condition, ValueCondition};
+use spreadsheet_ods::conditional::{
+    ColorScale, ConditionEntry, ConditionalEntry, ConditionalFormat, DataBar, DateCondition,
+    DateEntry, IconSet, ScaleType,
+};
+use spreadsheet_ods::style::{StyleOrigin, StyleUse};
+use spreadsheet_ods::{
+    read_fods_buf, read_ods, read_ods_buf, write_fods_buf, write_ods, write_ods_buf, CellRange,
+    CellStyle, CellStyleRef, OdsError, Sheet, WorkBook,
+};
+
+#[test]
+fn test_conditional_read() -> Result<(), OdsError> {
+    let mut wb = read_ods("tests/orders.ods")?;
+    let sh = wb.sheet(0);
+
+    assert_eq!(sh.conditional_formats().len(), 2);
+    let cf = &sh.conditional_formats()[0];
+    assert_eq!(cf.ranges()[0].to_row(), 9);
+    match &cf.entries()[0] {
+        ConditionalEntry::Condition(v) => {
+            assert_eq!(v.style().as_str(), "Neutral");
+            assert_eq!(v.condition().to_string(), "cell-content()=\"BS\"");
+            assert_eq!(v.base_cell().map(|v| v.row()), Some(1));
+        }
+        _ => panic!(),
+    }
+    assert_eq!(sh.conditional_formats_at(5, 3).count(), 2);
+    assert_eq!(sh.conditional_formats_at(0, 3).count(), 1);
+    assert_eq!(sh.conditional_formats_at(5, 2).count(), 0);
+
+    let buf = write_ods_buf(&mut wb, Vec::new())?;
+    let wi = read_ods_buf(&buf)?;
+    let si = wi.sheet(0);
+    assert_eq!(si.conditional_formats().len(), 2);
+    assert_eq!(si.conditional_formats()[1].entries().len(), 6);
+
+    Ok(())
+}
+
+#[test]
+fn test_conditional_write() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+
+    let mut good = CellStyle::new_empty();
+    good.set_name("good");
+    good.set_origin(StyleOrigin::Styles);
+    good.set_styleuse(StyleUse::Named);
+    good.set_color(Rgb::new(0, 128, 0));
+    let good = wb.add_cellstyle(good);
+
+    let mut bad = CellStyle::new_empty();
+    bad.set_name("bad");
+    bad.set_origin(StyleOrigin::Styles);
+    bad.set_styleuse(StyleUse::Named);
+    bad.set_color(Rgb::new(255, 0, 0));
+    let bad = wb.add_cellstyle(bad);
+
+    let mut sh = Sheet::new("Dash board");
+    for r in 0..10 {
+        sh.set_value(r, 0, r as i32 - 5);
+    }
+
+    let mut cf = ConditionalFormat::new(CellRange::remote("Dash board", 0, 0, 9, 0));
+    cf.push_entry(ConditionEntry::new(Condition::content_lt(0), &bad));
+    cf.push_entry(ConditionEntry::new_value(
+        ValueCondition::value_ge(0),
+        &good,
+    ));
+    cf.push_entry(ConditionEntry::new(
+        Condition::content_is_between(1, 3),
+        &good,
+    ));
+    cf.push_entry(ConditionEntry::top_elements(2, &good));
+    cf.push_entry(ConditionEntry::contains_text("x\"y", &bad));
+    sh.add_conditional_format(cf);
+
+    let mut cf = ConditionalFormat::new(CellRange::remote("Dash board", 0, 1, 9, 1));
+    cf.push_entry(ColorScale::three_color(
+        Rgb::new(255, 0, 0),
+        Rgb::new(255, 255, 0),
+        Rgb::new(0, 255, 0),
+    ));
+    let mut bar = DataBar::new(Rgb::new(0, 0, 255));
+    bar.set_negative_color(Some(Rgb::new(255, 0, 0)));
+    bar.set_max_length(Some(80.0));
+    cf.push_entry(bar);
+    cf.push_entry(IconSet::percent("3Arrows", 3));
+    cf.push_entry(DateEntry::new(DateCondition::Last7Days, &bad));
+    sh.add_conditional_format(cf);
+
+    wb.push_sheet(sh);
+    write_ods(&mut wb, "test_out/test_conditional.ods")?;
+
+    let wi = read_ods("test_out/test_conditional.ods")?;
+    let si = wi.sheet(0);
+    assert_eq!(si.conditional_formats().len(), 2);
+
+    let cf = &si.conditional_formats()[0];
+    assert_eq!(
+        cf.ranges()[0].table().map(|v| v.as_str()),
+        Some("Dash board")
+    );
+    let conds: Vec<_> = cf
+        .entries()
+        .iter()
+        .map(|v| match v {
+            ConditionalEntry::Condition(v) => (v.condition().to_string(), v.style().to_string()),
+            _ => panic!(),
+        })
+        .collect();
+    assert_eq!(
+        conds,
+        vec![
+            ("cell-content()<0".to_string(), "bad".to_string()),
+            ("cell-content()>=0".to_string(), "good".to_string()),
+            (
+                "cell-content-is-between(1, 3)".to_string(),
+                "good".to_string()
+            ),
+            ("top-elements(2)".to_string(), "good".to_string()),
+            ("contains-text(\"x\"\"y\")".to_string(), "bad".to_string()),
+        ]
+    );
+
+    let cf = &si.conditional_formats()[1];
+    match &cf.entries()[0] {
+        ConditionalEntry::ColorScale(v) => {
+            assert_eq!(v.entries().len(), 3);
+            assert_eq!(v.entries()[1].scale_type(), ScaleType::Percentile);
+            assert_eq!(v.entries()[1].value(), "50");
+            assert_eq!(v.entries()[2].color(), Some(Rgb::new(0, 255, 0)));
+        }
+        _ => panic!(),
+    }
+    match &cf.entries()[1] {
+        ConditionalEntry::DataBar(v) => {
+            assert_eq!(v.positive_color(), Rgb::new(0, 0, 255));
+            assert_eq!(v.negative_color(), Some(Rgb::new(255, 0, 0)));
+            assert_eq!(v.max_length(), Some(80.0));
+            assert_eq!(v.min().scale_type(), ScaleType::AutoMinimum);
+        }
+        _ => panic!(),
+    }
+    match &cf.entries()[2] {
+        ConditionalEntry::IconSet(v) => {
+            assert_eq!(v.icon_set_type(), "3Arrows");
+            assert_eq!(
+                v.entries().iter().map(|v| v.value()).collect::<Vec<_>>(),
+                vec!["0", "33", "66"]
+            );
+        }
+        _ => panic!(),
+    }
+    match &cf.entries()[3] {
+        ConditionalEntry::Date(v) => {
+            assert_eq!(v.date(), DateCondition::Last7Days);
+            assert_eq!(v.style().as_str(), "bad");
+        }
+        _ => panic!(),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_conditional_unknown() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("1");
+    sh.set_value(0, 0, 1);
+    let mut cf = ConditionalFormat::new(CellRange::remote("1", 0, 0, 9, 0));
+    cf.push_entry(ConditionEntry::new(
+        Condition::content_lt(0),
+        &CellStyleRef::from("Bad"),
+    ));
+    sh.add_conditional_format(cf);
+    wb.push_sheet(sh);
+
+    let xml = String::from_utf8(write_fods_buf(&mut wb, Vec::new())?).expect("utf8");
+    let unknown = concat!(
+        r#"<calcext:condition calcext:value="cell-content()&gt;0"/>"#,
+        r#"<calcext:date-is calcext:date="today"/>"#,
+        r#"<calcext:something calcext:value="1"/>"#,
+        r#"<calcext:data-bar calcext:positive-color="blue"/>"#,
+    );
+    let xml = xml.replace(
+        "</calcext:conditional-format>",
+        &format!("{}</calcext:conditional-format>", unknown),
+    );
+
+    let mut wi = read_fods_buf(xml.as_bytes())?;
+    let cf = &wi.sheet(0).conditional_formats()[0];
+    assert_eq!(cf.entries().len(), 5);
+    assert!(matches!(cf.entries()[0], ConditionalEntry::Condition(_)));
+    for entry in &cf.entries()[1..] {
+        assert!(matches!(entry, ConditionalEntry::Xml(_)));
+    }
+
+    let xml = String::from_utf8(write_fods_buf(&mut wi, Vec::new())?).expect("utf8");
+    assert!(xml.contains(r#"<calcext:condition calcext:value="cell-content()&gt;0"/>"#));
+    assert!(xml.contains(r#"<calcext:date-is calcext:date="today"/>"#));
+    assert!(xml.contains(r#"<calcext:something calcext:value="1"/>"#));
+    assert!(xml.contains(r#"<calcext:data-bar calcext:positive-color="blue"/>"#));
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use spreadsheet_ods::named::NamedRange;
+use spreadsheet_ods::{CellRange, CellRef, CellStyleRef, OdsError, Sheet, Value, WorkBook};
+
+fn template() -> Sheet {
+    let mut sh = Sheet::new("One");
+    sh.set_value(0, 0, "Item");
+    sh.set_value(0, 1, 10);
+    sh.set_value(1, 0, "Total");
+    sh.set_formula(1, 1, "of:=[.B1]*[$.$E$1]+SUM([.B$1:.B1])+[.$A1]");
+    sh.set_cellstyle(1, 1, &CellStyleRef::from("ce1"));
+    sh.set_col_span(0, 0, 2);
+    sh.set_value(0, 4, 2);
+    sh
+}
+
+#[test]
+fn test_copy_range() -> Result<(), OdsError> {
+    let mut sh = template();
+    sh.set_value(11, 1, "gone");
+
+    sh.copy_range(CellRange::local(0, 0, 1, 1), CellRef::local(10, 1));
+
+    assert_eq!(sh.value(10, 1).as_str_or(""), "Item");
+    assert_eq!(sh.value(10, 2).as_f64_or(0.0), 10.0);
+    assert_eq!(sh.col_span(10, 1), 2);
+    assert!(matches!(sh.value(11, 1), Value::Text(v) if v == "Total"));
+    assert_eq!(sh.cellstyle_str(11, 2), Some("ce1"));
+    assert_eq!(
+        sh.formula(11, 2).unwrap(),
+        "of:=[.C11]*[$.$E$1]+SUM([.C$1:.C11])+[.$A11]"
+    );
+    // the source is unchanged.
+    assert_eq!(
+        sh.formula(1, 1).unwrap(),
+        "of:=[.B1]*[$.$E$1]+SUM([.B$1:.B1])+[.$A1]"
+    );
+
+    // off the sheet.
+    sh.copy_range(CellRange::local(1, 1, 1, 1), CellRef::local(0, 0));
+    assert_eq!(
+        sh.formula(0, 0).unwrap(),
+        "of:=[.#REF!]*[$.$E$1]+SUM([.#REF!])+[.#REF!]"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_move_range() -> Result<(), OdsError> {
+    let mut sh = template();
+    sh.set_formula(5, 5, "of:=[.B2]+SUM([.A1:.B2])+[.E1]");
+
+    sh.move_range(CellRange::local(0, 0, 1, 1), CellRef::local(2, 2));
+
+    assert!(matches!(sh.value(0, 0), Value::Empty));
+    assert_eq!(sh.value(2, 3).as_f64_or(0.0), 10.0);
+    // references to the moved cells follow them.
+    assert_eq!(
+        sh.formula(3, 3).unwrap(),
+        "of:=[.D3]*[$.$E$1]+SUM([.D$3:.D3])+[.$C3]"
+    );
+    assert_eq!(sh.formula(5, 5).unwrap(), "of:=[.D4]+SUM([.C3:.D4])+[.E1]");
+
+    Ok(())
+}
+
+#[test]
+fn test_book_range() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    wb.push_sheet(template());
+    let mut sh = Sheet::new("Two");
+    sh.set_formula(0, 0, "of:=[One.B2]");
+    wb.push_sheet(sh);
+    wb.add_named_range(NamedRange::new(
+        "Block",
+        CellRange::remote("One", 0, 0, 1, 1),
+    ));
+
+    wb.copy_range(0, CellRange::local(0, 0, 1, 1), 1, CellRef::local(5, 0));
+    assert_eq!(
+        wb.sheet(1).formula(6, 1).unwrap(),
+        "of:=[.B6]*[$.$E$1]+SUM([.B$1:.B6])+[.$A6]"
+    );
+
+    wb.move_range(0, CellRange::local(0, 0, 1, 1), 1, CellRef::local(10, 0));
+    let sh = wb.sheet(1);
+    assert_eq!(sh.value(10, 1).as_f64_or(0.0), 10.0);
+    assert_eq!(
+        sh.formula(11, 1).unwrap(),
+        "of:=[Two.B11]*[$One.$E$1]+SUM([$Two.B$11:.B11])+[$Two.$A11]"
+    );
+    assert_eq!(sh.formula(0, 0).unwrap(), "of:=[Two.B12]");
+    assert_eq!(
+        wb.named_range("Block").unwrap().range(),
+        &CellRange::remote("Two", 10, 0, 11, 1)
+    );
+    assert!(matches!(wb.sheet(0).value(0, 1), Value::Empty));
+
+    Ok(())
+}
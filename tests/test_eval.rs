@@ -0,0 +1,290 @@
+use chrono::NaiveDate;
+use spreadsheet_ods::formula::{evaluate, parse, DependencyGraph, ErrorKind};
+use spreadsheet_ods::named::NamedExpression;
+use spreadsheet_ods::{OdsError, Sheet, Value, WorkBook};
+
+#[test]
+fn test_calculate() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("One");
+    sh.set_value(0, 0, 1);
+    sh.set_value(1, 0, 2);
+    sh.set_value(2, 0, 3);
+    sh.set_value(0, 1, "apple");
+    sh.set_value(1, 1, "pear");
+    sh.set_value(2, 1, "plum");
+
+    sh.set_formula(0, 3, "of:=SUM([.A1:.A3])*2");
+    sh.set_formula(1, 3, "of:=AVERAGE([.A1:.A3])+MAX([.A1:.A3])-MIN([.A1:.A3])");
+    sh.set_formula(2, 3, "of:=[.D1]+COUNT([.A1:.B3])");
+    sh.set_formula(3, 3, "of:=IF(AND([.A1]>0;[.A2]<>2);\"yes\";\"no\")");
+    sh.set_formula(4, 3, "of:=VLOOKUP(2;[.A1:.B3];2;0)&\"s\"");
+    sh.set_formula(5, 3, "of:=INDEX([.A1:.B3];MATCH(\"PLUM\";[.B1:.B3];0);1)");
+    sh.set_formula(6, 3, "of:=[.A1]/0");
+    sh.set_formula(7, 3, "of:=IFERROR([.D7];-1)");
+    sh.set_formula(8, 3, "of:=ROUND(2/3;2)");
+    sh.set_formula(9, 3, "of:=YEAR(DATE(2020;14;1))");
+    sh.set_formula(10, 3, "of:=[.D12]");
+    sh.set_formula(11, 3, "of:=[.D11]");
+    sh.set_formula(12, 3, "of:=UNKNOWN(1)");
+    sh.set_formula(13, 3, "of:=SUM(['Two'.A1:.A2])");
+    sh.set_formula(14, 3, "of:=DATE(2020;2;29)+1");
+    sh.set_formula(15, 3, "of:=Tax*100");
+    sh.set_value(16, 3, Value::Percentage(0.0));
+    sh.set_formula(16, 3, "of:=[.A1]/4");
+    wb.push_sheet(sh);
+
+    let mut sh = Sheet::new("Two");
+    sh.set_value(0, 0, 10);
+    sh.set_value(1, 0, 20);
+    wb.push_sheet(sh);
+
+    wb.add_named_expression(NamedExpression::new("Tax", "of:=0.2"));
+
+    wb.calculate();
+
+    let sh = wb.sheet(0);
+    assert_eq!(sh.value(0, 3).as_f64_or(0.0), 12.0);
+    assert_eq!(sh.value(1, 3).as_f64_or(0.0), 4.0);
+    assert_eq!(sh.value(2, 3).as_f64_or(0.0), 15.0);
+    assert_eq!(sh.value(3, 3).as_str_or(""), "no");
+    assert_eq!(sh.value(4, 3).as_str_or(""), "pears");
+    assert_eq!(sh.value(5, 3).as_f64_or(0.0), 3.0);
+    assert_eq!(sh.value(6, 3).as_error_opt(), Some(ErrorKind::Div0));
+    assert_eq!(sh.value(7, 3).as_f64_or(0.0), -1.0);
+    assert_eq!(sh.value(8, 3).as_f64_or(0.0), 0.67);
+    assert_eq!(sh.value(9, 3).as_f64_or(0.0), 2021.0);
+    assert_eq!(sh.value(10, 3).as_error_opt(), Some(ErrorKind::Circular));
+    assert_eq!(sh.value(11, 3).as_error_opt(), Some(ErrorKind::Circular));
+    assert_eq!(sh.value(12, 3).as_error_opt(), Some(ErrorKind::Name));
+    assert_eq!(sh.value(13, 3).as_f64_or(0.0), 30.0);
+    assert_eq!(
+        sh.value(14, 3).as_datetime_opt(),
+        NaiveDate::from_ymd_opt(2020, 3, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+    );
+    assert_eq!(sh.value(15, 3).as_f64_or(0.0), 20.0);
+    assert!(matches!(sh.value(16, 3), Value::Percentage(v) if *v == 0.25));
+
+    Ok(())
+}
+
+#[test]
+fn test_evaluate() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("One");
+    sh.set_value(0, 0, 5);
+    sh.set_formula(0, 1, "of:=[.A1]*2");
+    wb.push_sheet(sh);
+
+    let v = evaluate(&wb, 0, 5, 5, &parse("[.B1]+ROW()")?);
+    assert_eq!(v.as_f64_or(0.0), 16.0);
+    let v = evaluate(&wb, 0, 0, 0, &parse("\"n=\"&[.A1]&\"; \"&(1<2)")?);
+    assert_eq!(v.as_str_or(""), "n=5; TRUE");
+    let v = evaluate(&wb, 0, 0, 0, &parse("MID(UPPER(\"abcdef\");2;3)")?);
+    assert_eq!(v.as_str_or(""), "BCD");
+    let v = evaluate(&wb, 0, 0, 0, &parse("[.A1]=\"5\"")?);
+    assert!(matches!(v, Value::Boolean(false)));
+    let v = evaluate(&wb, 0, 0, 0, &parse("SQRT(-1)")?);
+    assert_eq!(v.as_error_opt(), Some(ErrorKind::Num));
+
+    // referenced formulas are not changed.
+    assert!(matches!(wb.sheet(0).value(0, 1), Value::Empty));
+
+    Ok(())
+}
+
+#[test]
+fn test_evaluate_limits() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("One");
+    sh.set_value(0, 0, 5);
+    sh.set_value(2, 0, 7);
+    sh.set_value(0, 2, 1);
+    wb.push_sheet(sh);
+
+    for f in [
+        "DATE(2020;1;1E18)",
+        "DATE(1E300;1;1)",
+        "DATE(2020;-1E300;1)",
+        "YEAR(-1E300)",
+        "YEAR(1E300)",
+        "DATE(2020;1;1)+1E300",
+    ] {
+        let v = evaluate(&wb, 0, 0, 0, &parse(f)?);
+        assert_eq!(v.as_error_opt(), Some(ErrorKind::Num), "{}", f);
+    }
+
+    for (f, r) in [
+        ("ROUND(1.005;2)", 1.01),
+        ("ROUND(-1.005;2)", -1.01),
+        ("ROUNDDOWN(2.3;2)", 2.3),
+        ("ROUNDUP(1.1;1)", 1.1),
+        ("ROUND(1250;-2)", 1300.0),
+        ("ROUND(1.5;400)", 1.5),
+        ("ROUND(1E300;10)", 1E300),
+        ("ROUND(1;-400)", 0.0),
+    ] {
+        let v = evaluate(&wb, 0, 0, 0, &parse(f)?);
+        assert_eq!(v.as_f64_or(f64::NAN), r, "{}", f);
+    }
+
+    let v = evaluate(
+        &wb,
+        0,
+        0,
+        0,
+        &parse("COM.MICROSOFT.CONCAT([.A1:.A3];\"x\")")?,
+    );
+    assert_eq!(v.as_str_or(""), "57x");
+
+    // whole columns and rows end at the used area.
+    let v = evaluate(&wb, 0, 5, 5, &parse("ROWS([.A1:.A1048576])")?);
+    assert_eq!(v.as_f64_or(0.0), 3.0);
+    let v = evaluate(&wb, 0, 5, 5, &parse("SUM([.A1:.A1048576])+SUM([.A:.A])")?);
+    assert_eq!(v.as_f64_or(0.0), 24.0);
+    let v = evaluate(
+        &wb,
+        0,
+        5,
+        5,
+        &parse("COLUMNS([.A1:.XFD1])+COLUMNS([.1:.1])")?,
+    );
+    assert_eq!(v.as_f64_or(0.0), 6.0);
+    let v = evaluate(&wb, 0, 5, 5, &parse("ROWS([.A1:.A10])")?);
+    assert_eq!(v.as_f64_or(0.0), 10.0);
+
+    Ok(())
+}
+
+#[test]
+fn test_calculate_chain() -> Result<(), OdsError> {
+    // each formula references the one below.
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("One");
+    let n = 20_000;
+    for r in 0..n {
+        sh.set_formula(r, 0, format!("of:=[.A{}]+1", r + 2));
+    }
+    wb.push_sheet(sh);
+
+    wb.calculate();
+    assert_eq!(wb.sheet(0).value(0, 0).as_f64_or(0.0), n as f64);
+
+    Ok(())
+}
+
+#[test]
+fn test_recalculate() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("One");
+    sh.set_value(0, 0, 1);
+    sh.set_value(1, 0, 2);
+    sh.set_formula(0, 1, "of:=[.A1]*10");
+    sh.set_formula(1, 1, "of:=SUM([.A1:.A2])");
+    sh.set_formula(2, 1, "of:=[.B1]+[.B2]");
+    sh.set_formula(3, 1, "of:=[.A2]+100");
+    sh.set_formula(4, 1, "of:=[.B6]+1");
+    sh.set_formula(5, 1, "of:=[.B5]+1");
+    sh.set_formula(6, 1, "of:=[.B6]+[.A1]");
+    wb.push_sheet(sh);
+    let mut sh = Sheet::new("Two");
+    sh.set_formula(0, 0, "of:=['One'.B3]*2");
+    wb.push_sheet(sh);
+
+    let graph = DependencyGraph::new(&wb);
+    assert_eq!(graph.len(), 8);
+    assert_eq!(
+        graph.dependents(0, 0, 0),
+        vec![(0, 0, 1), (0, 1, 1), (0, 6, 1)]
+    );
+    assert_eq!(graph.circular(), vec![(0, 4, 1), (0, 5, 1), (0, 6, 1)]);
+
+    wb.sheet_mut(0).set_value(0, 0, 5);
+    // circular references are always dirty.
+    let dirty = graph.dirty(&[(0, 0, 0)]);
+    assert_eq!(dirty.len(), 7);
+    assert!(!dirty.contains(&(0, 3, 1)));
+
+    let order = wb.recalculate(&graph, &[(0, 0, 0)]);
+    let pos = |k| order.iter().position(|v| *v == k).expect("cell");
+    assert!(pos((0, 0, 1)) < pos((0, 2, 1)));
+    assert!(pos((0, 2, 1)) < pos((1, 0, 0)));
+    assert_eq!(&order[4..], &[(0, 4, 1), (0, 5, 1), (0, 6, 1)]);
+
+    let sh = wb.sheet(0);
+    assert_eq!(sh.value(0, 1).as_f64_or(0.0), 50.0);
+    assert_eq!(sh.value(1, 1).as_f64_or(0.0), 7.0);
+    assert_eq!(sh.value(2, 1).as_f64_or(0.0), 57.0);
+    // not affected, so not calculated.
+    assert!(matches!(sh.value(3, 1), Value::Empty));
+    assert_eq!(sh.value(6, 1).as_error_opt(), Some(ErrorKind::Circular));
+    assert_eq!(wb.sheet(1).value(0, 0).as_f64_or(0.0), 114.0);
+
+    // changed formula
+    let mut graph = graph;
+    wb.sheet_mut(0).set_formula(4, 1, "of:=[.A2]");
+    graph.update_cell(&wb, 0, 4, 1);
+    assert!(graph.circular().is_empty());
+    wb.recalculate(&graph, &[(0, 4, 1)]);
+    assert_eq!(wb.sheet(0).value(6, 1).as_f64_or(0.0), 8.0);
+
+    Ok(())
+}
+
+#[test]
+fn test_dependency_graph() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("One");
+    sh.set_formula(0, 30, "of:=SUM([.A1:.A1000])");
+    sh.set_formula(1, 30, "of:=SUM([.A:.A])");
+    sh.set_formula(2, 30, "of:=SUM([.5:.5])");
+    sh.set_formula(3, 30, "of:=SUM([.A1:.Z1000000])");
+    sh.set_formula(4, 30, "of:=SUM(['Two'.A1:.A2])");
+    wb.push_sheet(sh);
+
+    let mut graph = DependencyGraph::new(&wb);
+    assert_eq!(
+        graph.dependents(0, 4, 0),
+        vec![(0, 0, 30), (0, 1, 30), (0, 2, 30), (0, 3, 30)]
+    );
+    assert_eq!(graph.dependents(0, 5000, 0), vec![(0, 1, 30), (0, 3, 30)]);
+    assert_eq!(graph.dependents(0, 4, 30), vec![(0, 2, 30)]);
+    assert!(graph.dependents(0, 0, 30).is_empty());
+
+    // circular references are only checked for the changed cell.
+    wb.sheet_mut(0).set_formula(0, 0, "of:=[.AE1]");
+    graph.update_cell(&wb, 0, 0, 0);
+    assert_eq!(
+        graph.circular(),
+        vec![(0, 0, 0), (0, 0, 30), (0, 1, 30), (0, 3, 30)]
+    );
+    wb.sheet_mut(0).set_formula(6, 30, "of:=[.AE2]");
+    graph.update_cell(&wb, 0, 6, 30);
+    assert_eq!(graph.circular().len(), 5);
+    wb.sheet_mut(0).clear_formula(0, 0);
+    graph.update_cell(&wb, 0, 0, 0);
+    assert!(graph.circular().is_empty());
+
+    // the referenced sheet is inserted later.
+    assert!(graph.dependents(1, 0, 0).is_empty());
+    let mut sh = Sheet::new("Two");
+    sh.set_formula(0, 1, "of:=['One'.AE1]");
+    wb.insert_sheet(0, sh);
+    graph.insert_sheet(&wb, 0);
+    assert_eq!(graph.len(), 7);
+    assert_eq!(graph.dependents(0, 1, 0), vec![(1, 4, 30)]);
+    assert_eq!(graph.dependents(1, 0, 30), vec![(0, 0, 1)]);
+
+    wb.remove_sheet(0);
+    graph.remove_sheet(&wb, 0);
+    assert_eq!(graph.len(), 6);
+    assert_eq!(
+        graph.dependents(0, 1, 0),
+        vec![(0, 0, 30), (0, 1, 30), (0, 3, 30)]
+    );
+    assert_eq!(graph.dependents(0, 1, 30), vec![(0, 6, 30)]);
+
+    Ok(())
+}
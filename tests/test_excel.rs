@@ -0,0 +1,99 @@
+use spreadsheet_ods::formula::{from_excel, parse, to_excel};
+use spreadsheet_ods::{OdsError, Sheet};
+
+#[test]
+fn test_from_excel() -> Result<(), OdsError> {
+    let cases = [
+        ("=SUM(A1:B3)", "of:=SUM([.A1:.B3])"),
+        ("=Sheet2!A1+1", "of:=[Sheet2.A1]+1"),
+        ("='My Sheet'!$A$1", "of:=[$'My Sheet'.$A$1]"),
+        ("='It''s'!B2", "of:=['It''s'.B2]"),
+        ("=SUM(A:A)+SUM($1:$3)", "of:=SUM([.A:.A])+SUM([.$1:.$3])"),
+        ("=SUM(Sheet1:Sheet3!A1)", "of:=SUM([Sheet1.A1:Sheet3.A1])"),
+        ("=[Book1.xlsx]Data!A1", "of:=['Book1.xlsx'#$Data.A1]"),
+        ("=IF(a1>0,\"a,b\",FALSE)", "of:=IF([.A1]>0;\"a,b\";FALSE())"),
+        ("={1,2;3,4}", "of:={1;2|3;4}"),
+        ("=SUM(A1:B3 B2:C4)", "of:=SUM([.A1:.B3]![.B2:.C4])"),
+        (
+            "=_xlfn.IFS(A1>1,1.5E+3,TRUE,#N/A)",
+            "of:=COM.MICROSOFT.IFS([.A1]>1;1.5E+3;TRUE();#N/A)",
+        ),
+        ("=A1 & \" \" & B1", "of:=[.A1] & \" \" & [.B1]"),
+        ("=Total*2", "of:=Total*2"),
+        ("=(A1,B1)", "of:=([.A1]~[.B1])"),
+        ("=SUM((A1:B2,C3),1)", "of:=SUM(([.A1:.B2]~[.C3]);1)"),
+        ("=INDEX((A1,B1),1,1,2)", "of:=INDEX(([.A1]~[.B1]);1;1;2)"),
+    ];
+    for (xl, of) in cases {
+        assert_eq!(from_excel(xl)?, of, "{}", xl);
+        // the result must be valid
+        parse(of)?;
+    }
+
+    assert!(from_excel("=Sheet1!Total").is_err());
+    assert!(from_excel("=Table1[Col]").is_err());
+    assert!(from_excel("=\"abc").is_err());
+    assert!(from_excel("=$A$1:$A").is_err());
+    assert!(from_excel("=A:A1").is_err());
+    assert!(from_excel("=@A1").is_err());
+    assert!(from_excel("=1,5").is_err());
+    assert!(from_excel("=A1,B1").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_to_excel() -> Result<(), OdsError> {
+    let cases = [
+        ("of:=SUM([.A1:.B3])", "=SUM(A1:B3)"),
+        ("of:=[Sheet2.A1]+1", "=Sheet2!A1+1"),
+        ("of:=[$'My Sheet'.$A$1]", "='My Sheet'!$A$1"),
+        ("of:=['It''s'.B2]", "='It''s'!B2"),
+        ("of:=[$A1.B2]", "='A1'!B2"),
+        ("of:=SUM([.A:.A])+SUM([.$1:.$3])", "=SUM(A:A)+SUM($1:$3)"),
+        ("of:=SUM([Sheet1.A1:Sheet3.A1])", "=SUM(Sheet1:Sheet3!A1)"),
+        ("of:=['Book1.xlsx'#$Data.A1]", "='[Book1.xlsx]Data'!A1"),
+        ("of:=IF([.A1]>0;\"a;b\";FALSE())", "=IF(A1>0,\"a;b\",FALSE)"),
+        ("of:={1;2|3;4}", "={1,2;3,4}"),
+        ("of:=SUM([.A1:.B3]![.B2:.C4])", "=SUM(A1:B3 B2:C4)"),
+        (
+            "of:=COM.MICROSOFT.IFS([.A1]>1;1;TRUE( );2)",
+            "=IFS(A1>1,1,TRUE,2)",
+        ),
+        ("of:=[.#REF!]+1", "=#REF!+1"),
+        ("of:=[.A1]~[.B1]", "=(A1,B1)"),
+        (
+            "of:=SUM([.A1]~[.B1]:[.C2]~[.D4];1)*2",
+            "=SUM((A1,B1:C2,D4),1)*2",
+        ),
+        ("of:=SUM(([.A1]~[.B1]))", "=SUM((A1,B1))"),
+        (
+            "of:=INDEX([.A1]~INDEX([.B1:.C2]~[.D1];1;1);1)+1",
+            "=INDEX((A1,INDEX((B1:C2,D1),1,1)),1)+1",
+        ),
+    ];
+    for (of, xl) in cases {
+        assert_eq!(to_excel(of)?, xl, "{}", of);
+    }
+
+    // round trip
+    let f = "=SUM('Sheet 2'!$A$1:B3,C:D)*LEGACY.CHIDIST(1,2)";
+    assert_eq!(to_excel(&from_excel(f)?)?, f.replace("LEGACY.", ""));
+
+    Ok(())
+}
+
+#[test]
+fn test_set_formula_excel() -> Result<(), OdsError> {
+    let mut sh = Sheet::new("One");
+    sh.set_formula_excel(0, 0, "=SUM(A2:A5)")?;
+    assert_eq!(sh.formula(0, 0).unwrap(), "of:=SUM([.A2:.A5])");
+    assert!(sh.set_formula_excel(0, 1, "='abc").is_err());
+    assert!(sh.formula(0, 1).is_none());
+    assert!(sh.set_formula_excel(0, 2, "=@A1").is_err());
+    assert!(sh.set_formula_excel(0, 3, "=1,5").is_err());
+    assert!(sh.formula(0, 2).is_none());
+    assert!(sh.formula(0, 3).is_none());
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use spreadsheet_ods::{
+    read_fods, read_fods_buf, read_ods, write_fods, write_fods_buf, write_ods_buf, OdsError,
+    Sheet, WorkBook,
+};
+
+#[test]
+fn test_write_read_fods() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("1");
+
+    sh.set_value(0, 0, "A");
+    sh.set_value(1, 1, 42);
+    sh.set_formula(2, 2, "of:=[.B2]+1");
+
+    wb.push_sheet(sh);
+
+    write_fods(&mut wb, "test_out/test_0.fods")?;
+
+    let wi = read_fods("test_out/test_0.fods")?;
+    let si = wi.sheet(0);
+
+    assert_eq!(si.name(), "1");
+    assert_eq!(si.value(0, 0).as_str_or(""), "A");
+    assert_eq!(si.value(1, 1).as_i32_or(0), 42);
+    assert_eq!(si.formula(2, 2).map(|v| v.as_str()), Some("of:=[.B2]+1"));
+
+    Ok(())
+}
+
+#[test]
+fn test_ods_fods_ods() -> Result<(), OdsError> {
+    let mut wb = read_ods("tests/orders.ods")?;
+
+    let buf = write_fods_buf(&mut wb, Vec::new())?;
+    let mut wf = read_fods_buf(&buf)?;
+
+    assert_eq!(wb.num_sheets(), wf.num_sheets());
+    for i in 0..wb.num_sheets() {
+        let s0 = wb.sheet(i);
+        let s1 = wf.sheet(i);
+        assert_eq!(s0.name(), s1.name());
+        assert_eq!(s0.used_grid_size(), s1.used_grid_size());
+        for ((r, c), v) in s0.iter() {
+            if let Some(v) = v.value {
+                assert_eq!(format!("{:?}", v), format!("{:?}", s1.value(r, c)));
+            }
+        }
+    }
+    assert!(wf.cellstyle("Default").is_some());
+
+    let buf = write_ods_buf(&mut wf, Vec::new())?;
+    let _ = spreadsheet_ods::read_ods_buf(&buf)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_fods_binary_data() -> Result<(), OdsError> {
+    let fods = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:draw="urn:oasis:names:tc:opendocument:xmlns:drawing:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:svg="urn:oasis:names:tc:opendocument:xmlns:svg-compatible:1.0" xmlns:loext="urn:org:documentfoundation:names:experimental:office:xmlns:loext:1.0" office:version="1.3" office:mimetype="application/vnd.oasis.opendocument.spreadsheet">
+<office:body>
+<office:spreadsheet>
+<table:table table:name="Sheet1">
+<table:shapes>
+<draw:frame draw:z-index="0" draw:name="Image 1" svg:width="1cm" svg:height="1cm" svg:x="0cm" svg:y="0cm">
+<draw:image loext:mime-type="image/png"><office:binary-data>iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk
++M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==</office:binary-data>
+</draw:image>
+</draw:frame>
+</table:shapes>
+<table:table-column/>
+<table:table-row><table:table-cell office:value-type="string"><text:p>A</text:p></table:table-cell></table:table-row>
+</table:table>
+</office:spreadsheet>
+</office:body>
+</office:document>
+"#;
+
+    let mut wb = read_fods_buf(fods.as_bytes())?;
+    assert_eq!(wb.sheet(0).value(0, 0).as_str_or(""), "A");
+
+    // the picture is a separate file in the ods.
+    let buf = write_ods_buf(&mut wb, Vec::new())?;
+    let wi = spreadsheet_ods::read_ods_buf(&buf)?;
+    assert_eq!(wi.sheet(0).value(0, 0).as_str_or(""), "A");
+
+    // and embedded in the fods.
+    let buf = write_fods_buf(&mut wb, Vec::new())?;
+    let txt = String::from_utf8(buf).expect("utf8");
+    assert!(txt.contains("<office:binary-data>iVBORw0KGgo"));
+    assert!(!txt.contains("xlink:href=\"Pictures/"));
+
+    Ok(())
+}
@@ -1 +1,119 @@
+use spreadsheet_ods::formula::{parse, ErrorKind, Expr, InfixOp, PrefixOp};
+use spreadsheet_ods::{CellRange, CellRef, OdsError};
 
+#[test]
+fn test_parse() -> Result<(), OdsError> {
+    let expr = parse("of:=SUM([.A1:.B3])*2")?;
+    assert_eq!(
+        expr,
+        Expr::infix(
+            InfixOp::Mul,
+            Expr::function("SUM", vec![Expr::CellRange(CellRange::local(0, 0, 2, 1))]),
+            Expr::Number(2.0)
+        )
+    );
+
+    let expr = parse("=IF([$'Other sheet'.$B$2]>=0;\"a\"\"b\";#N/A)")?;
+    match expr {
+        Expr::Function(name, args) => {
+            assert_eq!(name, "IF");
+            assert_eq!(
+                args[0],
+                Expr::infix(
+                    InfixOp::Ge,
+                    Expr::CellRef(CellRef::remote("Other sheet", 1, 1).absolute()),
+                    Expr::Number(0.0)
+                )
+            );
+            assert_eq!(args[1], Expr::String("a\"b".to_string()));
+            assert_eq!(args[2], Expr::Error(ErrorKind::NA));
+        }
+        _ => panic!(),
+    }
+
+    assert_eq!(
+        parse("-2^2")?,
+        Expr::infix(
+            InfixOp::Pow,
+            Expr::Prefix(PrefixOp::Minus, Box::new(Expr::Number(2.0))),
+            Expr::Number(2.0)
+        )
+    );
+    assert_eq!(
+        parse("1+2*3")?,
+        Expr::infix(
+            InfixOp::Add,
+            Expr::Number(1.0),
+            Expr::infix(InfixOp::Mul, Expr::Number(2.0), Expr::Number(3.0))
+        )
+    );
+    assert!(matches!(parse("Tax_Rate")?, Expr::Name(v) if v == "Tax_Rate"));
+    assert!(matches!(parse("[.#REF!]")?, Expr::RefError(v) if v == ".#REF!"));
+    assert!(matches!(parse("[.C:.D]")?, Expr::ColRange(_)));
+    assert!(matches!(parse("[.3:.5]")?, Expr::RowRange(_)));
+    assert!(matches!(parse("IF(1;;2)")?, Expr::Function(_, v) if v[1] == Expr::Missing));
+
+    assert!(parse("SUM(1;2").is_err());
+    assert!(parse("1+").is_err());
+    assert!(parse("\"abc").is_err());
+    assert!(parse("#FOO!").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_print() -> Result<(), OdsError> {
+    for f in [
+        "of:=SUM([.A1:.B3])*2",
+        "of:=IF([$'Other sheet'.$B$2]>=0;\"a\"\"b\";#N/A)",
+        "of:=-2^2",
+        "of:=-(2^2)",
+        "of:=(1+2)*3",
+        "of:=1-(2-3)",
+        "of:=A&\"x\"&B",
+        "of:={1;2;3|4;5;6}",
+        "of:=PI()",
+        "of:=IF(1;;2)",
+        "of:=[.A1]:[.B2]!([.B1:.C3]~[.D4])",
+        "of:=5%*[.C:.D]",
+        "of:=COM.MICROSOFT.F_TEST([.A1:.A3];[.B1:.B3])",
+        "of:=[.#REF!]+1",
+    ] {
+        assert_eq!(parse(f)?.to_formula(), f);
+    }
+
+    // parentheses are added as needed.
+    let expr = Expr::infix(
+        InfixOp::Mul,
+        Expr::infix(InfixOp::Add, Expr::Number(1.0), Expr::Number(2.0)),
+        Expr::infix(InfixOp::Div, Expr::Number(6.0), Expr::Number(3.0)),
+    );
+    assert_eq!(expr.to_string(), "(1+2)*(6/3)");
+
+    // whitespace is dropped.
+    assert_eq!(parse(" = SUM( [.A1] ; 2 ) ")?.to_string(), "SUM([.A1];2)");
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_nesting() -> Result<(), OdsError> {
+    let f = format!("of:={}1{}", "(".repeat(60), ")".repeat(60));
+    assert_eq!(parse(&f)?.to_formula(), f);
+    let f = format!("of:={}1", "-".repeat(60));
+    assert_eq!(parse(&f)?.to_formula(), f);
+    let f = format!("of:={}1{}", "(".repeat(65), ")".repeat(65));
+    assert!(parse(&f).is_err());
+
+    // too deep, but no stack overflow.
+    let f = format!("of:={}1{}", "(".repeat(100_000), ")".repeat(100_000));
+    assert!(parse(&f).is_err());
+    let f = format!("of:={}1", "-".repeat(100_000));
+    assert!(parse(&f).is_err());
+    let f = format!("of:={}1{}", "ABS(".repeat(100_000), ")".repeat(100_000));
+    assert!(parse(&f).is_err());
+    let f = format!("of:={}1{}", "{".repeat(100_000), "}".repeat(100_000));
+    assert!(parse(&f).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use spreadsheet_ods::{
+    read_ods, read_ods_buf, write_ods, write_ods_buf, OdsError, OdsRowWriter, Sheet, Visibility,
+    WorkBook,
+};
+use std::io::Cursor;
+
+#[test]
+fn test_groups() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("1");
+
+    for r in 0..20 {
+        sh.set_value(r, 0, r);
+    }
+    sh.set_value(0, 5, "x");
+    sh.set_header_rows(0, 0);
+    sh.add_row_group(2, 10, true)?;
+    sh.add_row_group(4, 6, false)?;
+    sh.add_row_group(8, 9, true)?;
+    // beyond the data.
+    sh.add_row_group(25, 30, false)?;
+    sh.set_header_cols(0, 0);
+    sh.add_col_group(1, 4, true)?;
+    sh.add_col_group(2, 3, false)?;
+
+    assert_eq!(sh.row_group_level(5), 2);
+    assert_eq!(sh.row_group_level(7), 1);
+    assert_eq!(sh.row_group_level(11), 0);
+    assert_eq!(
+        sh.row_groups()
+            .map(|v| (v.from(), v.to()))
+            .collect::<Vec<_>>(),
+        vec![(2, 10), (4, 6), (8, 9), (25, 30)]
+    );
+
+    wb.push_sheet(sh);
+    write_ods(&mut wb, "test_out/test_groups.ods")?;
+
+    let wi = read_ods("test_out/test_groups.ods")?;
+    let si = wi.sheet(0);
+
+    assert_eq!(
+        si.row_groups()
+            .map(|v| (v.from(), v.to(), v.display()))
+            .collect::<Vec<_>>(),
+        vec![(2, 10, true), (4, 6, false), (8, 9, true), (25, 30, false)]
+    );
+    assert_eq!(
+        si.col_groups()
+            .map(|v| (v.from(), v.to(), v.display()))
+            .collect::<Vec<_>>(),
+        vec![(1, 4, true), (2, 3, false)]
+    );
+    assert_eq!(si.header_rows().as_ref().map(|v| v.to_row()), Some(0));
+    assert_eq!(si.header_cols().as_ref().map(|v| v.to_col()), Some(0));
+    for r in 0..20 {
+        assert_eq!(si.value(r, 0).as_u32_or(99), r);
+    }
+    assert_eq!(si.value(0, 5).as_str_or(""), "x");
+
+    Ok(())
+}
+
+#[test]
+fn test_groups_edit() -> Result<(), OdsError> {
+    let mut sh = Sheet::new("1");
+    sh.add_row_group(0, 9, true)?;
+    sh.add_row_group(0, 4, true)?;
+    sh.add_row_group(0, 9, false)?;
+    assert_eq!(sh.row_group(0, 9).map(|v| v.display()), Some(false));
+    assert_eq!(sh.row_groups().count(), 2);
+
+    assert!(sh.remove_row_group(0, 4).is_some());
+    assert!(sh.remove_row_group(0, 4).is_none());
+    assert_eq!(sh.row_groups().count(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_groups_overlap() -> Result<(), OdsError> {
+    let mut sh = Sheet::new("1");
+    sh.add_col_group(0, 5, true)?;
+    assert!(sh.add_col_group(3, 8, true).is_err());
+    assert!(sh.add_col_group(4, 2, true).is_err());
+    assert_eq!(sh.col_groups().count(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_groups_header_overlap() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("1");
+    for r in 0..12 {
+        sh.set_value(r, 0, r);
+    }
+    sh.add_row_group(0, 5, true)?;
+    sh.set_header_rows(3, 8);
+    sh.add_col_group(1, 3, false)?;
+    sh.set_header_cols(0, 1);
+    wb.push_sheet(sh);
+
+    let buf = write_ods_buf(&mut wb, Vec::new())?;
+    let wi = read_ods_buf(&buf)?;
+    let si = wi.sheet(0);
+
+    // header rows/columns are kept, the groups are split around them.
+    assert_eq!(
+        si.header_rows().as_ref().map(|v| (v.row(), v.to_row())),
+        Some((3, 8))
+    );
+    assert_eq!(
+        si.header_cols().as_ref().map(|v| (v.col(), v.to_col())),
+        Some((0, 1))
+    );
+    assert_eq!(
+        si.row_groups()
+            .map(|v| (v.from(), v.to()))
+            .collect::<Vec<_>>(),
+        vec![(0, 2), (3, 5)]
+    );
+    assert_eq!(
+        si.col_groups()
+            .map(|v| (v.from(), v.to(), v.display()))
+            .collect::<Vec<_>>(),
+        vec![(1, 1, false), (2, 3, false)]
+    );
+    for r in 0..12 {
+        assert_eq!(si.value(r, 0).as_u32_or(99), r);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_groups_row_style() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("1");
+    sh.set_value(0, 0, "x");
+    sh.set_row_visible(2, Visibility::Collapsed);
+    sh.set_row_repeat(2, 6);
+    sh.add_row_group(4, 9, true)?;
+    wb.push_sheet(sh);
+
+    let buf = write_ods_buf(&mut wb, Vec::new())?;
+    let wi = read_ods_buf(&buf)?;
+    let si = wi.sheet(0);
+
+    // split at the start of the group.
+    assert_eq!(si.row_visible(2), Visibility::Collapsed);
+    assert_eq!(si.row_repeat(2), 2);
+    assert_eq!(si.row_visible(4), Visibility::Collapsed);
+    assert_eq!(si.row_repeat(4), 4);
+    assert_eq!(si.row_visible(8), Visibility::Visible);
+
+    Ok(())
+}
+
+#[test]
+fn test_groups_rowwriter() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("1");
+    sh.add_row_group(1, 3, true)?;
+    sh.add_row_group(5, 12, false)?;
+    wb.push_sheet(sh);
+
+    let mut writer = OdsRowWriter::new(wb, Cursor::new(Vec::new()))?;
+    writer.start_sheet(0)?;
+    for r in 0..8 {
+        writer.write_row_values(r, &[r])?;
+    }
+    let buf = writer.finish()?.into_inner();
+
+    let wi = read_ods_buf(&buf)?;
+    let si = wi.sheet(0);
+    assert_eq!(
+        si.row_groups()
+            .map(|v| (v.from(), v.to(), v.display()))
+            .collect::<Vec<_>>(),
+        vec![(1, 3, true), (5, 12, false)]
+    );
+    assert_eq!(si.value(7, 0).as_u32_or(0), 7);
+
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use spreadsheet_ods::image::Image;
+use spreadsheet_ods::{
+    cm, read_fods_buf, read_ods_buf, write_fods_buf, write_ods_buf, CellContent, CellRef, Length,
+    OdsError, OdsRowWriter, Sheet, WorkBook,
+};
+use std::io::{Cursor, Read};
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\nnot really a png";
+
+#[test]
+fn test_image() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("1");
+    sh.set_value(0, 0, "logo");
+    let image = sh.add_image(
+        CellRef::local(1, 1),
+        (cm!(4), cm!(3)),
+        (cm!(0.5), cm!(0.25)),
+        PNG.to_vec(),
+        "image/png",
+    );
+    image.set_name("Logo");
+    image.set_desc(Some("Company <logo>".to_string()));
+
+    let mut svg = Image::new("image/svg+xml", b"<svg/>".to_vec());
+    svg.set_size(cm!(1), cm!(1));
+    sh.push_image(1, 1, svg);
+    wb.push_sheet(sh);
+
+    let buf = write_ods_buf(&mut wb, Vec::new())?;
+
+    let mut zip = zip::ZipArchive::new(Cursor::new(&buf)).expect("zip");
+    let mut manifest = String::new();
+    zip.by_name("META-INF/manifest.xml")
+        .expect("manifest")
+        .read_to_string(&mut manifest)?;
+    assert!(manifest
+        .contains(r#"manifest:full-path="Pictures/image1.png" manifest:media-type="image/png""#));
+    assert!(manifest.contains(r#"manifest:full-path="Pictures/image2.svg""#));
+
+    let wi = read_ods_buf(&buf)?;
+    check_images(&wi);
+
+    // written a second time the images are not duplicated.
+    let mut wi = wi;
+    let buf = write_ods_buf(&mut wi, Vec::new())?;
+    let zip = zip::ZipArchive::new(Cursor::new(&buf)).expect("zip");
+    assert_eq!(
+        zip.file_names()
+            .filter(|v| v.starts_with("Pictures/"))
+            .count(),
+        2
+    );
+    let wi = read_ods_buf(&buf)?;
+    check_images(&wi);
+
+    let buf = write_fods_buf(&mut wb, Vec::new())?;
+    let wi = read_fods_buf(&buf)?;
+    check_images(&wi);
+
+    // the default style is not added to the book.
+    assert!(wb.sheet(0).cell_images(1, 1)[0].style().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_image_page() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("1");
+    let mut image = Image::new("image/png", PNG.to_vec());
+    image.set_name("Page");
+    image.set_size(cm!(2), cm!(1));
+    image.set_offset(cm!(10), cm!(5));
+    sh.push_page_image(image);
+    wb.push_sheet(sh);
+
+    let buf = write_ods_buf(&mut wb, Vec::new())?;
+    let wi = read_ods_buf(&buf)?;
+    check_page_image(&wi);
+
+    let buf = write_fods_buf(&mut wb, Vec::new())?;
+    let wi = read_fods_buf(&buf)?;
+    check_page_image(&wi);
+
+    Ok(())
+}
+
+fn check_page_image(wb: &WorkBook) {
+    let sh = wb.sheet(0);
+    assert_eq!(sh.images().count(), 0);
+    assert_eq!(sh.page_images().len(), 1);
+    let image = &sh.page_images()[0];
+    assert_eq!(image.name(), "Page");
+    assert_eq!(image.data(), PNG);
+    assert_eq!(image.offset(), (cm!(10), cm!(5)));
+    assert!(image.style().is_some());
+}
+
+#[test]
+fn test_image_rowwriter() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("1");
+    sh.add_image(
+        CellRef::local(0, 0),
+        (cm!(1), cm!(1)),
+        (cm!(0), cm!(0)),
+        b"<svg/>".to_vec(),
+        "image/svg+xml",
+    );
+    wb.push_sheet(sh);
+    wb.push_sheet(Sheet::new("2"));
+
+    let mut writer = OdsRowWriter::new(wb, Cursor::new(Vec::new()))?;
+    writer.start_sheet(1)?;
+    let mut cell = CellContent::new();
+    cell.value = "logo".into();
+    let mut image = Image::new("image/png", PNG.to_vec());
+    image.set_size(cm!(4), cm!(3));
+    cell.push_image(image);
+    writer.write_row(3, &[CellContent::new(), cell])?;
+    let buf = writer.finish()?.into_inner();
+
+    let mut zip = zip::ZipArchive::new(Cursor::new(&buf)).expect("zip");
+    let mut manifest = String::new();
+    zip.by_name("META-INF/manifest.xml")
+        .expect("manifest")
+        .read_to_string(&mut manifest)?;
+    assert!(manifest.contains(r#"manifest:full-path="Pictures/image1.svg""#));
+    assert!(manifest
+        .contains(r#"manifest:full-path="Pictures/row1.png" manifest:media-type="image/png""#));
+
+    let wi = read_ods_buf(&buf)?;
+    assert_eq!(wi.sheet(0).cell_images(0, 0)[0].data(), b"<svg/>");
+    let images = wi.sheet(1).cell_images(3, 1);
+    assert_eq!(images.len(), 1);
+    assert_eq!(images[0].data(), PNG);
+    assert_eq!(images[0].size(), (cm!(4), cm!(3)));
+    assert!(images[0].style().is_some());
+    assert_eq!(wi.sheet(1).value(3, 1).as_str_or(""), "logo");
+
+    Ok(())
+}
+
+fn check_images(wb: &WorkBook) {
+    let sh = wb.sheet(0);
+    assert_eq!(sh.images().count(), 2);
+    assert_eq!(sh.value(0, 0).as_str_or(""), "logo");
+
+    let images = sh.cell_images(1, 1);
+    let image = &images[0];
+    assert_eq!(image.name(), "Logo");
+    assert_eq!(image.mime(), "image/png");
+    assert_eq!(image.data(), PNG);
+    assert_eq!(image.size(), (cm!(4), cm!(3)));
+    assert_eq!(image.offset(), (cm!(0.5), cm!(0.25)));
+    assert_eq!(image.desc(), Some("Company <logo>"));
+    let style = image.style().expect("style");
+    assert!(wb.graphicstyle(style.as_str()).is_some());
+
+    assert_eq!(images[1].mime(), "image/svg+xml");
+    assert_eq!(images[1].data(), b"<svg/>");
+}
@@ -0,0 +1,131 @@
+use spreadsheet_ods::formula::{calculate, ErrorKind};
+use spreadsheet_ods::{
+    read_fods_buf, read_ods_buf, write_fods_buf, write_ods_buf, CellRange, OdsError, Sheet,
+    WorkBook,
+};
+
+fn book() -> WorkBook {
+    let mut sh = Sheet::new("Matrix");
+    sh.set_value(0, 0, 1);
+    sh.set_value(0, 1, 2);
+    sh.set_value(1, 0, 3);
+    sh.set_value(1, 1, 4);
+    sh.set_value(0, 3, 5);
+    sh.set_value(0, 4, 6);
+    sh.set_value(1, 3, 7);
+    sh.set_value(1, 4, 8);
+    sh.set_array_formula(
+        CellRange::local(3, 0, 4, 1),
+        "of:=MMULT([.A1:.B2];[.D1:.E2])",
+    );
+
+    let mut wb = WorkBook::new_empty();
+    wb.push_sheet(sh);
+    wb
+}
+
+#[test]
+fn test_array_formula() -> Result<(), OdsError> {
+    let mut sh = Sheet::new("One");
+    sh.set_formula(1, 1, "of:=1");
+    sh.set_value(1, 2, "keep");
+
+    sh.set_array_formula(CellRange::local(0, 0, 1, 2), "of:=TRANSPOSE([.E1:.F3])");
+    assert_eq!(sh.formula(0, 0).unwrap(), "of:=TRANSPOSE([.E1:.F3])");
+    assert_eq!(sh.matrix_row_span(0, 0), 2);
+    assert_eq!(sh.matrix_col_span(0, 0), 3);
+    assert!(sh.formula(1, 1).is_none());
+    assert_eq!(sh.value(1, 2).as_str_or(""), "keep");
+    // not a merged cell.
+    assert_eq!(sh.row_span(0, 0), 1);
+    assert_eq!(sh.col_span(0, 0), 1);
+
+    sh.clear_formula(0, 0);
+    assert_eq!(sh.matrix_row_span(0, 0), 1);
+    assert_eq!(sh.matrix_col_span(0, 0), 1);
+
+    // reversed range.
+    let mut range = CellRange::local(5, 2, 5, 2);
+    range.set_row(6);
+    range.set_col(3);
+    sh.set_array_formula(range, "of:=[.E1:.F2]");
+    assert_eq!(sh.formula(5, 2).unwrap(), "of:=[.E1:.F2]");
+    assert_eq!(sh.matrix_row_span(5, 2), 2);
+    assert_eq!(sh.matrix_col_span(5, 2), 2);
+
+    // the span is kept when the rows above are deleted.
+    sh.delete_rows(3, 2);
+    assert_eq!(sh.matrix_row_span(3, 2), 2);
+    sh.delete_rows(4, 1);
+    assert_eq!(sh.matrix_row_span(3, 2), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_calculate_matrix() -> Result<(), OdsError> {
+    let mut wb = book();
+    // results are read through the array.
+    wb.sheet_mut(0).set_formula(6, 0, "of:=SUM([.A4:.B5])");
+    calculate(&mut wb);
+
+    let sh = wb.sheet(0);
+    assert_eq!(sh.value(3, 0).as_f64_or(0.0), 19.0);
+    assert_eq!(sh.value(3, 1).as_f64_or(0.0), 22.0);
+    assert_eq!(sh.value(4, 0).as_f64_or(0.0), 43.0);
+    assert_eq!(sh.value(4, 1).as_f64_or(0.0), 50.0);
+    assert_eq!(sh.value(6, 0).as_f64_or(0.0), 134.0);
+
+    // the range is larger than the result.
+    let mut wb = book();
+    wb.sheet_mut(0)
+        .set_array_formula(CellRange::local(8, 0, 10, 1), "of:=TRANSPOSE([.A1:.A2])");
+    calculate(&mut wb);
+    let sh = wb.sheet(0);
+    assert_eq!(sh.value(8, 0).as_f64_or(0.0), 1.0);
+    assert_eq!(sh.value(8, 1).as_f64_or(0.0), 3.0);
+    assert_eq!(sh.value(10, 1).as_f64_or(0.0), 3.0);
+    assert_eq!(sh.value(3, 0).as_f64_or(0.0), 19.0);
+
+    let mut wb = book();
+    wb.sheet_mut(0).set_array_formula(
+        CellRange::local(8, 0, 10, 1),
+        "of:=MMULT([.A1:.B2];[.A1:.A2])",
+    );
+    calculate(&mut wb);
+    let sh = wb.sheet(0);
+    assert_eq!(sh.value(8, 0).as_f64_or(0.0), 7.0);
+    // a single column is repeated.
+    assert_eq!(sh.value(9, 1).as_f64_or(0.0), 15.0);
+    assert_eq!(sh.value(10, 0).as_error_opt(), Some(ErrorKind::NA));
+
+    Ok(())
+}
+
+#[test]
+fn test_read_write_matrix() -> Result<(), OdsError> {
+    let mut wb = book();
+    calculate(&mut wb);
+
+    let buf = write_fods_buf(&mut wb, Vec::new())?;
+    let xml = String::from_utf8(buf.clone()).expect("utf8");
+    assert!(xml.contains("table:number-matrix-columns-spanned=\"2\""));
+    assert!(xml.contains("table:number-matrix-rows-spanned=\"2\""));
+    assert!(!xml.contains("table:covered-table-cell"));
+
+    let wb = read_fods_buf(&buf)?;
+    let sh = wb.sheet(0);
+    assert_eq!(sh.matrix_row_span(3, 0), 2);
+    assert_eq!(sh.matrix_col_span(3, 0), 2);
+    assert_eq!(sh.value(4, 1).as_f64_or(0.0), 50.0);
+
+    let mut wb = book();
+    let buf = write_ods_buf(&mut wb, Vec::new())?;
+    let wb = read_ods_buf(&buf)?;
+    let sh = wb.sheet(0);
+    assert_eq!(sh.formula(3, 0).unwrap(), "of:=MMULT([.A1:.B2];[.D1:.E2])");
+    assert_eq!(sh.matrix_row_span(3, 0), 2);
+    assert_eq!(sh.matrix_col_span(3, 0), 2);
+
+    Ok(())
+}
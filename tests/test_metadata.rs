@@ -0,0 +1,117 @@
+use chrono::{Duration, FixedOffset, NaiveDate};
+use spreadsheet_ods::metadata::{MetaDateTime, MetaValue};
+use spreadsheet_ods::{
+    read_fods_buf, read_ods, read_ods_buf, write_fods_buf, write_ods_buf, OdsError, Sheet, WorkBook,
+};
+
+#[test]
+fn test_metadata_read() -> Result<(), OdsError> {
+    let wb = read_ods("tests/orders.ods")?;
+    let meta = wb.metadata();
+
+    assert!(meta.generator().starts_with("LibreOffice"));
+    assert_eq!(meta.initial_creator(), Some("Thomas Scharler"));
+    assert_eq!(meta.editing_cycles(), 162);
+    assert_eq!(
+        meta.creation_date().map(|v| v.datetime().date()),
+        NaiveDate::from_ymd_opt(2018, 1, 8)
+    );
+    assert_eq!(meta.creation_date().and_then(|v| v.offset()), None);
+    assert_eq!(
+        meta.editing_duration(),
+        Some(Duration::seconds(3 * 86400 + 2 * 3600 + 10 * 60 + 34))
+    );
+
+    // passed through.
+    let mut wb = wb;
+    let xml = String::from_utf8(write_fods_buf(&mut wb, Vec::new())?).expect("utf8");
+    assert!(xml.contains("<meta:document-statistic "));
+    assert!(xml.contains(r#"meta:cell-count="99""#));
+    assert!(xml.contains(concat!(
+        "<meta:generator>spreadsheet-ods ",
+        env!("CARGO_PKG_VERSION"),
+        "</meta:generator>"
+    )));
+
+    Ok(())
+}
+
+#[test]
+fn test_metadata_write() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    wb.push_sheet(Sheet::new("1"));
+
+    let date = NaiveDate::from_ymd_opt(2023, 5, 17)
+        .and_then(|v| v.and_hms_opt(10, 11, 12))
+        .expect("date");
+
+    let meta = wb.metadata_mut();
+    meta.set_title(Some("Title & more".to_string()));
+    meta.set_subject(Some("Subject".to_string()));
+    meta.set_description(Some("Description".to_string()));
+    meta.push_keyword("one");
+    meta.push_keyword("two");
+    meta.set_initial_creator(Some("initial".to_string()));
+    meta.set_creator(Some("creator".to_string()));
+    meta.set_creation_date(Some(date.into()));
+    let offset = FixedOffset::east_opt(2 * 3600).expect("offset");
+    meta.set_date(Some(MetaDateTime::new(date, Some(offset))));
+    meta.set_print_date(Some(MetaDateTime::new(date, FixedOffset::east_opt(0))));
+    meta.set_language(Some("de-AT".to_string()));
+    meta.set_editing_cycles(3);
+    meta.set_user_defined("flag", MetaValue::Boolean(true));
+    meta.set_user_defined("batch", MetaValue::Float(17.5));
+    meta.set_user_defined(
+        "stamp",
+        MetaValue::Datetime(MetaDateTime::new(date, FixedOffset::west_opt(5 * 3600))),
+    );
+    meta.set_user_defined("source", MetaValue::String("<erp>".to_string()));
+
+    let buf = write_ods_buf(&mut wb, Vec::new())?;
+    let wi = read_ods_buf(&buf)?;
+    check_meta(&wi);
+
+    let buf = write_fods_buf(&mut wb, Vec::new())?;
+    let wi = read_fods_buf(&buf)?;
+    check_meta(&wi);
+
+    Ok(())
+}
+
+fn check_meta(wb: &WorkBook) {
+    let meta = wb.metadata();
+    assert!(meta.generator().starts_with("spreadsheet-ods"));
+    assert_eq!(meta.title(), Some("Title & more"));
+    assert_eq!(meta.subject(), Some("Subject"));
+    assert_eq!(meta.description(), Some("Description"));
+    assert_eq!(meta.keywords(), &["one".to_string(), "two".to_string()]);
+    assert_eq!(meta.initial_creator(), Some("initial"));
+    assert_eq!(meta.creator(), Some("creator"));
+    assert_eq!(
+        meta.creation_date().map(|v| v.datetime().to_string()),
+        Some("2023-05-17 10:11:12".to_string())
+    );
+    assert_eq!(
+        meta.date()
+            .and_then(|v| v.to_fixed_offset())
+            .map(|v| v.to_rfc3339()),
+        Some("2023-05-17T10:11:12+02:00".to_string())
+    );
+    assert_eq!(
+        meta.print_date().and_then(|v| v.offset()),
+        FixedOffset::east_opt(0)
+    );
+    assert_eq!(meta.language(), Some("de-AT"));
+    assert_eq!(meta.editing_cycles(), 3);
+    assert!(matches!(
+        meta.user_defined("flag"),
+        Some(MetaValue::Boolean(true))
+    ));
+    assert!(matches!(meta.user_defined("batch"), Some(MetaValue::Float(v)) if *v == 17.5));
+    assert!(matches!(
+        meta.user_defined("stamp"),
+        Some(MetaValue::Datetime(v)) if v.offset() == FixedOffset::west_opt(5 * 3600)
+    ));
+    assert!(matches!(meta.user_defined("source"), Some(MetaValue::String(v)) if v == "<erp>"));
+    assert_eq!(meta.user_defined_list().len(), 4);
+}
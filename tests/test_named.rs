@@ -0,0 +1,116 @@
+use spreadsheet_ods::condition::Condition;
+use spreadsheet_ods::conditional::{ConditionEntry, ConditionalFormat};
+use spreadsheet_ods::named::{NameScope, NamedExpression, NamedRange};
+use spreadsheet_ods::{
+    read_fods_buf, read_ods_buf, write_fods_buf, write_ods_buf, CellRange, CellRef, CellStyleRef,
+    OdsError, Sheet, WorkBook,
+};
+
+#[test]
+fn test_named_range() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("Sheet1");
+    sh.set_value(0, 0, 1);
+    wb.push_sheet(sh);
+    wb.push_sheet(Sheet::new("Sheet 2"));
+
+    let range = CellRange::remote("Sheet1", 0, 0, 9, 1).absolute();
+    let mut global = NamedRange::new("data", range);
+    global.set_usable_as(Some("print-range".to_string()));
+    wb.add_named_range(global);
+
+    let mut local = NamedRange::new("data", CellRange::remote("Sheet 2", 1, 1, 2, 2).absolute());
+    local.set_scope(NameScope::Sheet("Sheet 2".to_string()));
+    wb.add_named_range(local);
+
+    let mut expr = NamedExpression::new("double", "of:=[.$A$1]*2");
+    expr.set_base_cell(Some(CellRef::remote("Sheet1", 0, 0).absolute()));
+    wb.add_named_expression(expr);
+
+    let buf = write_ods_buf(&mut wb, Vec::new())?;
+    let wi = read_ods_buf(&buf)?;
+
+    let global = wi.named_range("data").expect("global");
+    assert_eq!(global.range().to_string(), "$Sheet1.$A$1:.$B$10");
+    assert_eq!(global.base_cell().to_string(), "$Sheet1.$A$1");
+    assert_eq!(global.usable_as(), Some("print-range"));
+
+    let local = wi.local_named_range("Sheet 2", "data").expect("local");
+    assert_eq!(local.scope(), &NameScope::Sheet("Sheet 2".to_string()));
+    assert_eq!(local.range().row(), 1);
+    // no local name, falls back to global.
+    let found = wi.local_named_range("Sheet1", "data").expect("global");
+    assert_eq!(found.scope(), &NameScope::Global);
+
+    let expr = wi.named_expression("double").expect("expr");
+    assert_eq!(expr.expression(), "of:=[.$A$1]*2");
+    assert!(wi.local_named_expression("Sheet 2", "double").is_some());
+
+    assert_eq!(wi.named_ranges().len(), 2);
+
+    // names ignore case.
+    assert!(wi.named_range("DATA").is_some());
+    assert!(wi.local_named_range("Sheet 2", "Data").is_some());
+    assert!(wi.named_expression("Double").is_some());
+
+    let mut wi = wi;
+    wi.add_named_expression(NamedExpression::new("DOUBLE", "of:=[.$A$1]*3"));
+    assert_eq!(wi.named_expressions().len(), 1);
+    assert!(wi.named_expression_mut("double").is_some());
+    assert!(wi
+        .remove_named_expression(&NameScope::Global, "Double")
+        .is_some());
+    assert!(wi.named_expressions().is_empty());
+
+    assert!(wi
+        .remove_named_range(&NameScope::Sheet("Sheet 2".to_string()), "DATA")
+        .is_some());
+    assert!(wi
+        .remove_named_range(&NameScope::Sheet("Sheet 2".to_string()), "data")
+        .is_none());
+    assert_eq!(wi.named_ranges().len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_table_trailer() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("Sheet1");
+    sh.set_value(0, 0, 1);
+    let cond = ConditionEntry::new(Condition::content_eq(1), &CellStyleRef::from("Good"));
+    let mut cf = ConditionalFormat::new(CellRange::local(0, 0, 4, 0));
+    cf.push_entry(cond);
+    sh.add_conditional_format(cf);
+    wb.push_sheet(sh);
+    let mut local = NamedRange::new("data", CellRange::remote("Sheet1", 0, 0, 4, 0).absolute());
+    local.set_scope(NameScope::Sheet("Sheet1".to_string()));
+    wb.add_named_range(local);
+
+    // both belong inside the table.
+    let xml = String::from_utf8(write_fods_buf(&mut wb, Vec::new())?).expect("utf8");
+    let end = xml.find("</table:table>").expect("table");
+    let names = xml.find("<table:named-expressions>").expect("names");
+    let formats = xml.find("<calcext:conditional-formats>").expect("formats");
+    assert!(names < end);
+    assert!(formats < end);
+
+    let wi = read_fods_buf(xml.as_bytes())?;
+    assert_eq!(wi.sheet(0).conditional_formats().len(), 1);
+    assert!(wi.local_named_range("Sheet1", "data").is_some());
+
+    // 0.16 wrote the conditional formats after the table.
+    let start = formats;
+    let stop = xml.find("</calcext:conditional-formats>").expect("formats")
+        + "</calcext:conditional-formats>".len();
+    let mut legacy = xml.clone();
+    let block = legacy[start..stop].to_string();
+    legacy.replace_range(start..stop, "");
+    let end = legacy.find("</table:table>").expect("table") + "</table:table>".len();
+    legacy.insert_str(end, &block);
+
+    let wi = read_fods_buf(legacy.as_bytes())?;
+    assert_eq!(wi.sheet(0).conditional_formats().len(), 1);
+
+    Ok(())
+}
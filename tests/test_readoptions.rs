@@ -0,0 +1,92 @@
+#[cfg(feature = "use_decimal")]
+use rust_decimal::Decimal;
+use spreadsheet_ods::{read_ods, read_ods_buf_with, read_ods_with, OdsError, ReadOptions};
+#[cfg(feature = "use_decimal")]
+use spreadsheet_ods::{
+    read_ods_buf, write_fods_buf, write_ods_buf, OdsRowReader, Sheet, Value, ValueType, WorkBook,
+};
+#[cfg(feature = "use_decimal")]
+use std::io::Cursor;
+#[cfg(feature = "use_decimal")]
+use std::str::FromStr;
+
+#[test]
+fn test_read_sheet_only() -> Result<(), OdsError> {
+    let wb = read_ods("tests/orders.ods")?;
+    let name = wb.sheet(0).name().clone();
+
+    let options = ReadOptions::new().sheet(name.as_str());
+    let wi = read_ods_with("tests/orders.ods", &options)?;
+    assert_eq!(wi.num_sheets(), 1);
+    assert_eq!(wi.sheet(0).name(), &name);
+    assert_eq!(wi.sheet(0).used_grid_size(), wb.sheet(0).used_grid_size());
+
+    let options = ReadOptions::new().sheet("does not exist");
+    let wi = read_ods_with("tests/orders.ods", &options)?;
+    assert_eq!(wi.num_sheets(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_read_values_only() -> Result<(), OdsError> {
+    let wb = read_ods("tests/orders.ods")?;
+    assert!(wb.cellstyle("Default").is_some());
+
+    let buf = std::fs::read("tests/orders.ods")?;
+    let options = ReadOptions::new().values_only();
+    let wi = read_ods_buf_with(&buf, &options)?;
+
+    assert!(wi.cellstyle("Default").is_none());
+
+    assert_eq!(wb.num_sheets(), wi.num_sheets());
+    for i in 0..wb.num_sheets() {
+        for ((r, c), v) in wb.sheet(i).iter() {
+            if let Some(v) = v.value {
+                assert_eq!(format!("{:?}", v), format!("{:?}", wi.sheet(i).value(r, c)));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "use_decimal")]
+#[test]
+fn test_read_exact_numbers() -> Result<(), OdsError> {
+    let big = Decimal::from_str("12345678901234567890.12").expect("decimal");
+    let tenth = Decimal::from_str("0.1").expect("decimal");
+
+    let mut sh = Sheet::new("1");
+    sh.set_value(0, 0, Value::Decimal(tenth));
+    sh.set_value(0, 1, Value::new_decimal_currency("EUR", big));
+    let mut wb = WorkBook::new_empty();
+    wb.push_sheet(sh);
+    let buf = write_ods_buf(&mut wb, Vec::new())?;
+
+    let wi = read_ods_buf(&buf)?;
+    assert!(matches!(wi.sheet(0).value(0, 0), Value::Number(_)));
+    assert!(matches!(wi.sheet(0).value(0, 1), Value::Currency(_, _)));
+
+    let options = ReadOptions::new().exact_numbers();
+    let mut wi = read_ods_buf_with(&buf, &options)?;
+    let sh = wi.sheet(0);
+    assert_eq!(sh.value(0, 0).as_decimal_opt(), Some(tenth));
+    assert_eq!(sh.value(0, 1).as_decimal_opt(), Some(big));
+    assert_eq!(sh.value(0, 1).currency(), "EUR");
+    assert_eq!(sh.value(0, 1).value_type(), ValueType::Currency);
+
+    // written back as it was read.
+    let xml = String::from_utf8(write_fods_buf(&mut wi, Vec::new())?).expect("utf8");
+    assert!(xml.contains(r#"office:value="0.1""#));
+    assert!(xml.contains(r#"office:value="12345678901234567890.12""#));
+
+    let mut reader = OdsRowReader::new(Cursor::new(buf))?.exact_numbers();
+    let row = reader.rows()?.next().expect("row")?;
+    assert_eq!(
+        row.cell(1).and_then(|v| v.value.as_decimal_opt()),
+        Some(big)
+    );
+
+    Ok(())
+}
@@ -1 +1,70 @@
+use spreadsheet_ods::refs::{
+    parse_cellrange_r1c1, parse_cellref_r1c1, parse_colrange_r1c1, parse_rowrange_r1c1,
+};
+use spreadsheet_ods::{CellRange, CellRef, ColRange, OdsError, RowRange};
 
+#[test]
+fn test_cellref_r1c1() -> Result<(), OdsError> {
+    let base = CellRef::local(4, 2);
+
+    let c = parse_cellref_r1c1("R[-1]C[2]", &base)?;
+    assert_eq!(c, CellRef::local(3, 4));
+    assert_eq!(c.to_r1c1(&base), "R[-1]C[2]");
+    assert_eq!(c.to_r1c1(&CellRef::local(3, 4)), "RC");
+
+    let c = parse_cellref_r1c1("R1C3", &base)?;
+    assert_eq!(c, CellRef::local(0, 2).absolute());
+    assert_eq!(c.to_r1c1(&base), "R1C3");
+
+    let c = parse_cellref_r1c1("'My Sheet'!rc1", &base)?;
+    assert_eq!(c, CellRef::remote("My Sheet", 4, 0).absolute_col());
+    assert_eq!(c.to_r1c1(&base), "'My Sheet'!RC1");
+
+    assert!(parse_cellref_r1c1("R[-5]C", &base).is_err());
+    assert!(parse_cellref_r1c1("R0C1", &base).is_err());
+    assert!(parse_cellref_r1c1("R1", &base).is_err());
+    assert!(parse_cellref_r1c1("R1C1:R2C2", &base).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_cellrange_r1c1() -> Result<(), OdsError> {
+    let base = CellRef::local(4, 2);
+
+    let r = parse_cellrange_r1c1("Sheet1!R1C1:R[1]C[1]", &base)?;
+    let mut cmp = CellRange::remote("Sheet1", 0, 0, 5, 3);
+    cmp.set_row_abs(true);
+    cmp.set_col_abs(true);
+    assert_eq!(r, cmp);
+    assert_eq!(r.to_r1c1(&base), "Sheet1!R1C1:R[1]C[1]");
+
+    let r = parse_cellrange_r1c1("Sheet1:Sheet3!RC", &base)?;
+    assert_eq!(r.table().unwrap(), "Sheet1");
+    assert_eq!(r.to_table().unwrap(), "Sheet3");
+    assert_eq!((r.row(), r.col(), r.to_row(), r.to_col()), (4, 2, 4, 2));
+    assert_eq!(r.to_r1c1(&base), "Sheet1:Sheet3!RC");
+
+    Ok(())
+}
+
+#[test]
+fn test_colrow_r1c1() -> Result<(), OdsError> {
+    let base = CellRef::local(4, 2);
+
+    let c = parse_colrange_r1c1("C[-2]:C5", &base)?;
+    let mut cmp = ColRange::new(0, 4);
+    cmp.set_to_col_abs(true);
+    assert_eq!(c, cmp);
+    assert_eq!(c.to_r1c1(&base), "C[-2]:C5");
+    assert_eq!(parse_colrange_r1c1("C", &base)?, ColRange::new(2, 2));
+
+    let r = parse_rowrange_r1c1("R2:R[1]", &base)?;
+    let mut cmp = RowRange::new(1, 5);
+    cmp.set_row_abs(true);
+    assert_eq!(r, cmp);
+    assert_eq!(r.to_r1c1(&base), "R2:R[1]");
+    assert_eq!(RowRange::new(4, 4).to_r1c1(&base), "R");
+
+    Ok(())
+}
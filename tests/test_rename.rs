@@ -0,0 +1,118 @@
+use spreadsheet_ods::condition::Condition;
+use spreadsheet_ods::named::{NameScope, NamedExpression, NamedRange};
+use spreadsheet_ods::validation::Validation;
+use spreadsheet_ods::{read_ods_buf, write_ods_buf, CellRange, CellRef, OdsError, Sheet, WorkBook};
+
+fn sample() -> WorkBook {
+    let mut wb = WorkBook::new_empty();
+
+    let mut sh = Sheet::new("One");
+    sh.set_value(0, 0, 1);
+    sh.set_formula(0, 1, "of:=[.A1]+[$One.$A$1]+['One'.A1:.A2]+\"[One.A1]\"");
+    sh.add_print_range(CellRange::remote("One", 0, 0, 1, 1));
+    sh.config_mut().zoom_value = 150;
+    wb.push_sheet(sh);
+
+    let mut sh = Sheet::new("Two");
+    sh.set_formula(0, 0, "of:=[One.A1]+[One.A1:Two.B2]+[Three.A1]");
+    wb.push_sheet(sh);
+
+    let mut v = Validation::new();
+    v.set_name("val1");
+    v.set_condition(Condition::is_true_formula("[One.A1]>0"));
+    v.set_base_cell(CellRef::remote("One", 0, 1));
+    wb.add_validation(v);
+
+    wb.add_named_range(NamedRange::new(
+        "Data",
+        CellRange::remote("One", 0, 0, 1, 0),
+    ));
+    wb.add_named_expression(NamedExpression::new("Twice", "of:=[$One.$A$1]*2"));
+    let mut local = NamedRange::new("Local", CellRange::remote("One", 1, 0, 1, 1));
+    local.set_scope(NameScope::Sheet("One".to_string()));
+    wb.add_named_range(local);
+    let mut local = NamedExpression::new("Thrice", "of:=[$One.$A$1]*3");
+    local.set_scope(NameScope::Sheet("One".to_string()));
+    wb.add_named_expression(local);
+    wb.config_mut().active_table = "One".to_string();
+
+    wb
+}
+
+#[test]
+fn test_rename_sheet() -> Result<(), OdsError> {
+    let mut wb = sample();
+    wb.rename_sheet(0, "First")?;
+
+    let sh = wb.sheet(0);
+    assert_eq!(sh.name(), "First");
+    assert_eq!(
+        sh.formula(0, 1).unwrap(),
+        "of:=[.A1]+[$First.$A$1]+[First.A1:.A2]+\"[One.A1]\""
+    );
+    assert_eq!(
+        sh.print_ranges().unwrap()[0],
+        CellRange::remote("First", 0, 0, 1, 1)
+    );
+    assert_eq!(
+        wb.sheet(1).formula(0, 0).unwrap(),
+        "of:=[First.A1]+[First.A1:Two.B2]+[Three.A1]"
+    );
+    let v = wb.validation("val1").expect("validation");
+    assert_eq!(v.base_cell(), &CellRef::remote("First", 0, 1));
+    assert_eq!(v.condition(), "is-true-formula([First.A1]>0)");
+    assert_eq!(
+        wb.named_range("Data").unwrap().range(),
+        &CellRange::remote("First", 0, 0, 1, 0)
+    );
+    assert_eq!(
+        wb.named_expression("Twice").unwrap().expression(),
+        "of:=[$First.$A$1]*2"
+    );
+    assert_eq!(wb.config().active_table, "First");
+    assert!(wb.local_named_range("First", "Local").is_some());
+    assert!(wb.local_named_expression("First", "Thrice").is_some());
+
+    // unchanged name
+    wb.rename_sheet(0, "First")?;
+
+    let buf = write_ods_buf(&mut wb, Vec::new())?;
+    let wb = read_ods_buf(&buf)?;
+    assert_eq!(wb.sheet(0).name(), "First");
+    assert_eq!(wb.sheet(0).config().zoom_value, 150);
+    assert_eq!(wb.config().active_table, "First");
+    assert!(wb.local_named_range("First", "Local").is_some());
+    assert!(wb.local_named_expression("First", "Thrice").is_some());
+
+    Ok(())
+}
+
+#[test]
+fn test_rename_invalid() -> Result<(), OdsError> {
+    let mut wb = sample();
+    assert!(wb.rename_sheet(0, "Two").is_err());
+    assert!(wb.rename_sheet(0, "TWO").is_err());
+    assert!(wb.rename_sheet(0, "").is_err());
+    assert!(wb.rename_sheet(0, "A/B").is_err());
+    assert!(wb.rename_sheet(0, "[x]").is_err());
+    assert!(wb.rename_sheet(0, "'x").is_err());
+    assert_eq!(wb.sheet(0).name(), "One");
+    assert_eq!(
+        wb.sheet(1).formula(0, 0).unwrap(),
+        "of:=[One.A1]+[One.A1:Two.B2]+[Three.A1]"
+    );
+
+    // only the case changes.
+    wb.rename_sheet(1, "TWO")?;
+    wb.rename_sheet(1, "Sheet 2")?;
+    assert_eq!(
+        wb.sheet(0).formula(0, 1).unwrap(),
+        "of:=[.A1]+[$One.$A$1]+['One'.A1:.A2]+\"[One.A1]\""
+    );
+    assert_eq!(
+        wb.sheet(1).formula(0, 0).unwrap(),
+        "of:=[One.A1]+[One.A1:'Sheet 2'.B2]+[Three.A1]"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,234 @@
+use spreadsheet_ods::condition::Condition;
+use spreadsheet_ods::conditional::{ConditionEntry, ConditionalFormat};
+use spreadsheet_ods::named::{NamedExpression, NamedRange};
+use spreadsheet_ods::validation::Validation;
+use spreadsheet_ods::{
+    cm, CellRange, CellRef, CellStyleRef, Length, OdsError, Sheet, Value, Visibility, WorkBook,
+};
+
+fn sample() -> WorkBook {
+    let mut wb = WorkBook::new_empty();
+
+    let mut sh = Sheet::new("One");
+    for r in 0..5 {
+        sh.set_value(r, 0, r as f64);
+    }
+    sh.set_formula(0, 1, "of:=SUM([.A1:.A5])");
+    sh.set_formula(1, 1, "of:=[.A3]+[$One.$A$4]+\"[.A3]\"");
+    sh.set_formula(2, 1, "of:=[.A2]*[.2:.3]");
+    sh.set_row_span(3, 2, 2);
+    sh.set_header_rows(1, 2);
+    sh.add_print_range(CellRange::local(0, 0, 4, 1));
+    sh.add_row_group(1, 3, true).expect("group");
+    sh.set_row_height(4, cm!(2));
+    sh.add_conditional_format({
+        let mut cf = ConditionalFormat::new(CellRange::remote("One", 2, 0, 4, 0));
+        cf.push_entry(ConditionEntry::new(
+            Condition::is_true_formula("[.A3]>1"),
+            &CellStyleRef::from("ce1"),
+        ));
+        cf
+    });
+    wb.push_sheet(sh);
+
+    let mut sh = Sheet::new("Two");
+    sh.set_formula(0, 0, "of:=['One'.A2:.A4]+[.A2]");
+    wb.push_sheet(sh);
+
+    let mut v = Validation::new();
+    v.set_name("val1");
+    v.set_condition(Condition::is_true_formula("[.B3]>0"));
+    v.set_base_cell(CellRef::remote("One", 2, 1));
+    wb.add_validation(v);
+
+    wb.add_named_range(NamedRange::new(
+        "Data",
+        CellRange::remote("One", 1, 0, 3, 0),
+    ));
+    let mut expr = NamedExpression::new("Half", "of:=[$One.$A$5]/2");
+    expr.set_base_cell(Some(CellRef::remote("One", 0, 0)));
+    wb.add_named_expression(expr);
+
+    wb
+}
+
+#[test]
+fn test_insert_rows() -> Result<(), OdsError> {
+    let mut wb = sample();
+    wb.insert_rows(0, 2, 2);
+
+    let sh = wb.sheet(0);
+    assert_eq!(sh.value(1, 0).as_f64_or(-1.0), 1.0);
+    assert_eq!(sh.value(4, 0).as_f64_or(-1.0), 2.0);
+    assert!(matches!(sh.value(2, 0), Value::Empty));
+    assert_eq!(sh.formula(0, 1).unwrap(), "of:=SUM([.A1:.A7])");
+    assert_eq!(sh.formula(1, 1).unwrap(), "of:=[.A5]+[$One.$A$6]+\"[.A3]\"");
+    assert_eq!(sh.formula(4, 1).unwrap(), "of:=[.A2]*[.2:.5]");
+    assert_eq!(sh.row_span(5, 2), 2);
+    assert_eq!(
+        sh.header_rows().as_ref().map(|v| (v.row(), v.to_row())),
+        Some((1, 4))
+    );
+    assert_eq!(sh.print_ranges().unwrap()[0], CellRange::local(0, 0, 6, 1));
+    assert!(sh.row_group(1, 5).is_some());
+    assert_eq!(sh.row_height(6), cm!(2));
+    let cf = &sh.conditional_formats()[0];
+    assert_eq!(cf.ranges()[0], CellRange::remote("One", 4, 0, 6, 0));
+
+    assert_eq!(wb.sheet(1).formula(0, 0).unwrap(), "of:=[One.A2:.A6]+[.A2]");
+    let v = wb.validation("val1").expect("validation");
+    assert_eq!(v.base_cell(), &CellRef::remote("One", 4, 1));
+    assert_eq!(v.condition(), "is-true-formula([.B5]>0)");
+    assert_eq!(
+        wb.named_range("Data").unwrap().range(),
+        &CellRange::remote("One", 1, 0, 5, 0)
+    );
+    assert_eq!(
+        wb.named_expression("Half").unwrap().expression(),
+        "of:=[$One.$A$7]/2"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_delete_rows() -> Result<(), OdsError> {
+    let mut wb = sample();
+    wb.delete_rows(0, 1, 2);
+
+    let sh = wb.sheet(0);
+    assert_eq!(sh.value(1, 0).as_f64_or(-1.0), 3.0);
+    assert_eq!(sh.formula(0, 1).unwrap(), "of:=SUM([.A1:.A3])");
+    // the formulas in the deleted rows are gone.
+    assert!(sh.formula(1, 1).is_none());
+    assert!(sh.header_rows().is_none());
+    assert!(sh.row_group(1, 1).is_some());
+    assert_eq!(sh.row_span(1, 2), 2);
+
+    assert_eq!(wb.sheet(1).formula(0, 0).unwrap(), "of:=[One.A2:.A2]+[.A2]");
+    // the validation's base cell can't be moved.
+    let v = wb.validation("val1").expect("validation");
+    assert_eq!(v.condition(), "is-true-formula([.#REF!]>0)");
+
+    let mut wb = sample();
+    wb.delete_rows(0, 3, 1);
+    assert_eq!(
+        wb.sheet(0).formula(1, 1).unwrap(),
+        "of:=[.A3]+[.#REF!]+\"[.A3]\""
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_cols() -> Result<(), OdsError> {
+    let mut wb = sample();
+    wb.insert_cols(0, 0, 1);
+    let sh = wb.sheet(0);
+    assert_eq!(sh.value(4, 1).as_f64_or(-1.0), 4.0);
+    assert_eq!(sh.formula(0, 2).unwrap(), "of:=SUM([.B1:.B5])");
+    assert_eq!(sh.formula(2, 2).unwrap(), "of:=[.B2]*[.2:.3]");
+    assert_eq!(wb.sheet(1).formula(0, 0).unwrap(), "of:=[One.B2:.B4]+[.A2]");
+
+    wb.delete_cols(0, 1, 1);
+    let sh = wb.sheet(0);
+    assert_eq!(sh.formula(0, 1).unwrap(), "of:=SUM([.#REF!])");
+
+    // only this sheet.
+    let mut wb = sample();
+    wb.sheet_mut(0).insert_cols(0, 1);
+    assert_eq!(wb.sheet(0).formula(0, 2).unwrap(), "of:=SUM([.B1:.B5])");
+    assert_eq!(
+        wb.sheet(1).formula(0, 0).unwrap(),
+        "of:=['One'.A2:.A4]+[.A2]"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_shift_spans() -> Result<(), OdsError> {
+    let mut sh = Sheet::new("One");
+    sh.set_value(9, 0, "x");
+    sh.set_row_span(9, 0, 3);
+    sh.set_row_span(4, 0, 4);
+    sh.delete_rows(6, 2);
+    // the span starts below the deleted rows.
+    assert_eq!(sh.row_span(7, 0), 3);
+    // the span contains the deleted rows.
+    assert_eq!(sh.row_span(4, 0), 2);
+
+    sh.insert_rows(8, 2);
+    assert_eq!(sh.row_span(7, 0), 5);
+
+    Ok(())
+}
+
+#[test]
+fn test_shift_row_repeat() -> Result<(), OdsError> {
+    let mut sh = Sheet::new("One");
+    sh.set_row_visible(3, Visibility::Collapsed);
+    sh.set_row_repeat(3, 100);
+
+    let mut sh2 = sh.clone();
+    sh2.insert_rows(10, 5);
+    assert_eq!(sh2.row_repeat(3), 7);
+    assert_eq!(sh2.row_visible(3), Visibility::Collapsed);
+    assert_eq!(sh2.row_visible(10), Visibility::Visible);
+    assert_eq!(sh2.row_repeat(15), 93);
+    assert_eq!(sh2.row_visible(15), Visibility::Collapsed);
+
+    sh.delete_rows(3, 1);
+    assert_eq!(sh.row_repeat(3), 99);
+    assert_eq!(sh.row_visible(3), Visibility::Collapsed);
+
+    sh.delete_rows(0, 50);
+    assert_eq!(sh.row_repeat(0), 52);
+
+    Ok(())
+}
+
+#[test]
+fn test_shift_config() -> Result<(), OdsError> {
+    let mut sh = Sheet::new("One");
+    sh.split_row_header(2);
+    sh.split_col_header(1);
+    sh.config_mut().cursor_y = 10;
+
+    sh.insert_rows(1, 2);
+    assert_eq!(sh.config().vert_split_pos, 5);
+    assert_eq!(sh.config().position_bottom, 5);
+    assert_eq!(sh.config().cursor_y, 12);
+
+    sh.delete_rows(0, 4);
+    assert_eq!(sh.config().vert_split_pos, 1);
+    assert_eq!(sh.config().cursor_y, 8);
+
+    sh.delete_cols(1, 5);
+    assert_eq!(sh.config().hor_split_pos, 1);
+    assert_eq!(sh.config().cursor_x, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_shift_3d() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    wb.push_sheet(Sheet::new("One"));
+    let mut sh = Sheet::new("Two");
+    sh.set_formula(5, 1, "of:=SUM([One.A1:Two.A3])+[One.A3]");
+    wb.push_sheet(sh);
+
+    wb.insert_rows(0, 0, 2);
+    assert_eq!(
+        wb.sheet(1).formula(5, 1).unwrap(),
+        "of:=SUM([One.A1:Two.A3])+[One.A5]"
+    );
+    wb.delete_rows(1, 0, 1);
+    assert_eq!(
+        wb.sheet(1).formula(4, 1).unwrap(),
+        "of:=SUM([One.A1:Two.A3])+[One.A5]"
+    );
+
+    Ok(())
+}
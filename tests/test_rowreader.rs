@@ -0,0 +1,92 @@
+use spreadsheet_ods::{read_ods, write_ods, OdsError, OdsRowReader, Sheet, ValueType, WorkBook};
+
+#[test]
+fn test_rowreader_orders() -> Result<(), OdsError> {
+    let wb = read_ods("tests/orders.ods")?;
+
+    let mut reader = OdsRowReader::open("tests/orders.ods")?;
+    let mut n_cells = 0;
+    for row in reader.rows()? {
+        let row = row?;
+        let idx = wb.sheet_idx(row.sheet_name.as_str()).expect("sheet not found");
+        let sheet = wb.sheet(idx);
+        for (col, cell) in row.cells() {
+            assert_eq!(
+                format!("{:?}", cell.value),
+                format!("{:?}", sheet.value(row.row, col))
+            );
+            if cell.value.value_type() != ValueType::Empty {
+                n_cells += 1;
+            }
+        }
+    }
+
+    let mut n_values = 0;
+    for i in 0..wb.num_sheets() {
+        for (_, v) in wb.sheet(i).iter() {
+            if let Some(v) = v.value {
+                if v.value_type() != ValueType::Empty {
+                    n_values += 1;
+                }
+            }
+        }
+    }
+    assert_eq!(n_cells, n_values);
+
+    Ok(())
+}
+
+#[test]
+fn test_rowreader_repeat() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("one");
+    sh.set_value(0, 0, "A");
+    sh.set_value(0, 5, 1);
+    sh.set_value(3, 1, "B");
+    sh.set_row_repeat(3, 4);
+    sh.set_value(10, 0, "C");
+    wb.push_sheet(sh);
+    let mut sh = Sheet::new("two");
+    sh.set_value(1, 1, true);
+    wb.push_sheet(sh);
+
+    write_ods(&mut wb, "test_out/rowreader.ods")?;
+
+    let mut reader = OdsRowReader::open("test_out/rowreader.ods")?;
+    let rows = reader
+        .rows()?
+        .filter(|v| match v {
+            Ok(v) => !v.is_empty(),
+            Err(_) => true,
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    assert_eq!(rows.len(), 4);
+    assert_eq!(rows[0].sheet_name.as_str(), "one");
+    assert_eq!(rows[0].row, 0);
+    assert_eq!(rows[0].len(), 6);
+    // the empty cells between are not expanded.
+    assert_eq!(rows[0].repeated_cells.len(), 3);
+    assert_eq!(rows[0].repeated_cells[1].1, 4);
+    assert_eq!(rows[0].cell(0).map(|v| v.value.as_str_or("")), Some("A"));
+    assert_eq!(
+        rows[0].cell(3).map(|v| v.value.value_type()),
+        Some(ValueType::Empty)
+    );
+    assert_eq!(rows[0].cell(5).map(|v| v.value.as_i32_or(0)), Some(1));
+    assert!(rows[0].cell(6).is_none());
+    assert_eq!(rows[1].row, 3);
+    assert_eq!(rows[1].repeat, 4);
+    assert_eq!(rows[1].len(), 2);
+    assert_eq!(rows[2].row, 10);
+    assert_eq!(rows[2].cell(0).map(|v| v.value.as_str_or("")), Some("C"));
+    assert_eq!(rows[3].sheet_name.as_str(), "two");
+    assert!(std::sync::Arc::ptr_eq(
+        &rows[0].sheet_name,
+        &rows[2].sheet_name
+    ));
+    assert_eq!(rows[3].row, 1);
+    assert!(rows[3].cell(1).map(|v| v.value.as_bool_or(false)) == Some(true));
+
+    Ok(())
+}
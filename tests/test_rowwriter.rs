@@ -0,0 +1,72 @@
+use spreadsheet_ods::{
+    cm, read_ods, read_ods_buf, CellContent, Length, OdsError, OdsRowWriter, Sheet, Value, WorkBook,
+};
+use std::io::Cursor;
+
+#[test]
+fn test_rowwriter() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut s0 = Sheet::new("Static");
+    s0.set_value(0, 0, "static");
+    wb.push_sheet(s0);
+    let mut s1 = Sheet::new("Stream");
+    s1.set_col_width(2, cm!(3));
+    s1.set_header_rows(0, 0);
+    wb.push_sheet(s1);
+    wb.push_sheet(Sheet::new("Last"));
+
+    let mut writer = OdsRowWriter::create(wb, "test_out/test_rowwriter.ods")?;
+    writer.start_sheet(1)?;
+    writer.write_row_values(0, &["A", "B", "C"])?;
+    for row in 1..1000 {
+        writer.write_row_values(row, &[Value::from(row), Value::from(row * 2)])?;
+    }
+    // gap and some empty cells.
+    let mut cell = CellContent::new();
+    cell.set_value("far");
+    writer.write_row(1500, &[CellContent::new(), CellContent::new(), cell])?;
+    assert!(writer.write_row(1499, &[]).is_err());
+    writer.finish()?;
+
+    let wi = read_ods("test_out/test_rowwriter.ods")?;
+    assert_eq!(wi.num_sheets(), 3);
+    assert_eq!(wi.sheet(0).value(0, 0).as_str_or(""), "static");
+
+    let si = wi.sheet(1);
+    assert_eq!(si.name(), "Stream");
+    assert_eq!(si.value(0, 2).as_str_or(""), "C");
+    assert_eq!(si.value(999, 0).as_u32_or(0), 999);
+    assert_eq!(si.value(999, 1).as_u32_or(0), 1998);
+    assert_eq!(si.value(1500, 2).as_str_or(""), "far");
+    assert_eq!(si.used_grid_size(), (1501, 3));
+    assert_eq!(si.header_rows().as_ref().map(|v| v.to_row()), Some(0));
+
+    assert_eq!(wi.sheet(2).name(), "Last");
+
+    Ok(())
+}
+
+#[test]
+fn test_rowwriter_span() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    wb.push_sheet(Sheet::new("1"));
+
+    let mut writer = OdsRowWriter::new(wb, Cursor::new(Vec::new()))?;
+    writer.start_sheet(0)?;
+    let mut cell = CellContent::new();
+    cell.set_value("span");
+    cell.set_row_span(2);
+    cell.set_col_span(2);
+    writer.write_row(0, &[cell])?;
+    writer.write_row_values(1, &["x", "y", "z"])?;
+    let buf = writer.finish()?.into_inner();
+
+    let wi = read_ods_buf(&buf)?;
+    let si = wi.sheet(0);
+    assert_eq!(si.value(0, 0).as_str_or(""), "span");
+    assert_eq!(si.row_span(0, 0), 2);
+    assert_eq!(si.col_span(0, 0), 2);
+    assert_eq!(si.value(1, 2).as_str_or(""), "z");
+
+    Ok(())
+}
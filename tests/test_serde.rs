@@ -0,0 +1,361 @@
+#![cfg(all(feature = "serde", feature = "use_decimal"))]
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use icu_locid::locale;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize, Serializer};
+use spreadsheet_ods::defaultstyles::DefaultStyle;
+use spreadsheet_ods::{
+    read_ods_buf, write_ods_buf, CellRef, CellStyleRef, Length, OdsError, SerializeOptions, Sheet,
+    Value, ValueType, WorkBook,
+};
+use std::str::FromStr;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+enum Kind {
+    Retail,
+    Wholesale,
+}
+
+#[derive(Debug, Deserialize)]
+struct Order<'a> {
+    item: String,
+    code: &'a str,
+    amount: u32,
+    price: f64,
+    total: Decimal,
+    paid: bool,
+    date: NaiveDate,
+    #[serde(deserialize_with = "spreadsheet_ods::de::datetime_opt")]
+    shipped: Option<NaiveDateTime>,
+    kind: Kind,
+    note: Option<String>,
+}
+
+fn sheet() -> Sheet {
+    let mut sh = Sheet::new("Orders");
+    for (col, name) in [
+        "item", "code", "amount", "price", "total", "paid", "date", "shipped", "kind", "note",
+        "unused",
+    ]
+    .iter()
+    .enumerate()
+    {
+        sh.set_value(0, col as u32, *name);
+    }
+
+    let date = NaiveDate::from_ymd_opt(2023, 1, 1).expect("date");
+    sh.set_value(1, 0, "pencil");
+    sh.set_value(1, 1, "P-1");
+    sh.set_value(1, 2, 12);
+    sh.set_value(1, 3, 0.5);
+    sh.set_value(
+        1,
+        4,
+        Value::Decimal(Decimal::from_str("6.10").expect("decimal")),
+    );
+    sh.set_value(1, 5, true);
+    sh.set_value(1, 6, date);
+    sh.set_value(1, 7, date.and_hms_opt(10, 30, 0).expect("time"));
+    sh.set_value(1, 8, "Retail");
+    sh.set_value(1, 10, "ignored");
+
+    // blank rows are skipped.
+    sh.set_value(3, 0, "paper");
+    sh.set_value(3, 1, "P-2");
+    sh.set_value(3, 2, 500);
+    sh.set_value(3, 3, 0.01);
+    sh.set_value(3, 4, 5);
+    sh.set_value(3, 5, false);
+    sh.set_value(3, 6, date);
+    sh.set_value(3, 8, "Wholesale");
+    sh.set_value(3, 9, "urgent");
+
+    sh
+}
+
+#[test]
+fn test_deserialize_rows() -> Result<(), OdsError> {
+    let sh = sheet();
+    let orders: Vec<Order<'_>> = sh.deserialize_rows(0, ..)?;
+    assert_eq!(orders.len(), 2);
+
+    let o = &orders[0];
+    assert_eq!(o.item, "pencil");
+    assert_eq!(o.code, "P-1");
+    assert_eq!(o.amount, 12);
+    assert_eq!(o.price, 0.5);
+    assert_eq!(o.total.to_string(), "6.10");
+    assert!(o.paid);
+    assert_eq!(o.date, NaiveDate::from_ymd_opt(2023, 1, 1).expect("date"));
+    assert_eq!(
+        o.shipped,
+        NaiveDate::from_ymd_opt(2023, 1, 1)
+            .expect("date")
+            .and_hms_opt(10, 30, 0)
+    );
+    assert_eq!(o.kind, Kind::Retail);
+    assert!(o.note.is_none());
+
+    let o = &orders[1];
+    assert_eq!(o.amount, 500);
+    assert_eq!(o.total, Decimal::from(5));
+    assert!(o.shipped.is_none());
+    assert_eq!(o.kind, Kind::Wholesale);
+    assert_eq!(o.note.as_deref(), Some("urgent"));
+
+    let orders: Vec<Order<'_>> = sh.deserialize_rows(0, 2..)?;
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].item, "paper");
+
+    let rows: Vec<(String, String, u16)> = sh.deserialize_rows(0, 1..2)?;
+    assert_eq!(rows, vec![("pencil".to_string(), "P-1".to_string(), 12)]);
+
+    // a date is midnight for a NaiveDateTime.
+    let mut sh = sheet();
+    let date = NaiveDate::from_ymd_opt(2023, 2, 1).expect("date");
+    sh.set_value(3, 7, date);
+    let orders: Vec<Order<'_>> = sh.deserialize_rows(0, ..)?;
+    assert_eq!(orders[1].shipped, date.and_hms_opt(0, 0, 0));
+
+    Ok(())
+}
+
+#[test]
+fn test_deserialize_errors() {
+    let mut sh = sheet();
+    sh.set_value(3, 2, "many");
+    match sh.deserialize_rows::<Order<'_>, _>(0, ..) {
+        Err(OdsError::Serde(cell, msg)) => {
+            assert_eq!(cell.table(), Some(&"Orders".to_string()));
+            assert_eq!((cell.row(), cell.col()), (3, 2));
+            assert!(msg.contains("many"));
+        }
+        v => panic!("{:?}", v.map(|v| v.len())),
+    }
+
+    let mut sh = sheet();
+    sh.set_value(1, 2, 1.5);
+    match sh.deserialize_rows::<Order<'_>, _>(0, ..) {
+        Err(OdsError::Serde(cell, _)) => assert_eq!((cell.row(), cell.col()), (1, 2)),
+        v => panic!("{:?}", v.map(|v| v.len())),
+    }
+
+    let mut sh = sheet();
+    sh.set_value(3, 2, 70000);
+    let r = sh.deserialize_rows::<(String, String, u16), _>(0, ..);
+    assert!(matches!(r, Err(OdsError::Serde(cell, _)) if cell.col() == 2));
+
+    // missing header.
+    let mut sh = sheet();
+    sh.set_value(0, 4, "sum");
+    match sh.deserialize_rows::<Order<'_>, _>(0, ..) {
+        Err(OdsError::Serde(cell, msg)) => {
+            assert_eq!((cell.row(), cell.col()), (0, 11));
+            assert_eq!(msg, "missing header `total`");
+        }
+        v => panic!("{:?}", v.map(|v| v.len())),
+    }
+
+    // empty cell without Option.
+    let mut sh = sheet();
+    sh.set_value(1, 6, Value::Empty);
+    match sh.deserialize_rows::<Order<'_>, _>(0, ..) {
+        Err(OdsError::Serde(cell, msg)) => {
+            assert_eq!((cell.row(), cell.col()), (1, 6));
+            assert!(msg.contains("empty cell"));
+        }
+        v => panic!("{:?}", v.map(|v| v.len())),
+    }
+}
+
+fn time_opt<S: Serializer>(value: &Option<NaiveTime>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(v) => spreadsheet_ods::ser::time(v, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Line {
+    item: String,
+    amount: u32,
+    price: Decimal,
+    #[serde(serialize_with = "spreadsheet_ods::ser::date")]
+    date: NaiveDate,
+    #[serde(serialize_with = "time_opt")]
+    time: Option<NaiveTime>,
+    kind: Kind,
+}
+
+fn lines() -> Vec<Line> {
+    let date = NaiveDate::from_ymd_opt(2023, 1, 1).expect("date");
+    vec![
+        Line {
+            item: "pencil".to_string(),
+            amount: 12,
+            price: Decimal::from_str("0.10").expect("decimal"),
+            date,
+            time: NaiveTime::from_hms_opt(10, 30, 0),
+            kind: Kind::Retail,
+        },
+        Line {
+            item: "2023-01-01".to_string(),
+            amount: 500,
+            price: Decimal::from_str("12345678901234567890.12").expect("decimal"),
+            date,
+            time: None,
+            kind: Kind::Wholesale,
+        },
+    ]
+}
+
+#[test]
+fn test_serialize_rows() -> Result<(), OdsError> {
+    let lines = lines();
+
+    let mut sh = Sheet::new("Lines");
+    sh.set_value(4, 5, "old");
+    let options = SerializeOptions::new()
+        .currency("price", "EUR")
+        .col_width("item", Length::Cm(5.0));
+    sh.serialize_rows(&lines, CellRef::local(2, 1), &options)?;
+
+    assert_eq!(sh.value(2, 1).as_str_or(""), "item");
+    assert_eq!(sh.value(2, 6).as_str_or(""), "kind");
+    assert_eq!(sh.col_width(1), Length::Cm(5.0));
+
+    assert_eq!(sh.value(3, 1).as_str_or(""), "pencil");
+    assert_eq!(sh.value(3, 2).value_type(), ValueType::Number);
+    assert_eq!(sh.cellstyle(3, 2), None);
+    assert_eq!(sh.value(3, 3).value_type(), ValueType::Currency);
+    assert_eq!(sh.value(3, 3).currency(), "EUR");
+    assert_eq!(sh.value(3, 4).value_type(), ValueType::Date);
+    assert_eq!(sh.value(3, 5).value_type(), ValueType::TimeOfDay);
+    // strings are never dates.
+    assert_eq!(sh.value(4, 1).value_type(), ValueType::Text);
+    // None clears the cell.
+    assert_eq!(sh.value(4, 5).value_type(), ValueType::Empty);
+    assert_eq!(
+        sh.value(4, 3).as_decimal_opt(),
+        Some(Decimal::from_str("12345678901234567890.12").expect("decimal"))
+    );
+
+    // and back.
+    let back: Vec<Line> = sh.deserialize_rows(2, ..)?;
+    assert_eq!(back, lines);
+
+    // the default styles of the workbook apply when writing.
+    let mut wb = WorkBook::new(locale!("en_US"));
+    wb.push_sheet(sh);
+    let wb = read_ods_buf(&write_ods_buf(&mut wb, Vec::new())?)?;
+    let sh = wb.sheet(0);
+    assert_eq!(
+        sh.cellstyle_str(3, 2),
+        Some(DefaultStyle::number().as_str())
+    );
+    assert_eq!(
+        sh.cellstyle_str(3, 3),
+        Some(DefaultStyle::currency().as_str())
+    );
+    assert_eq!(sh.cellstyle_str(3, 4), Some(DefaultStyle::date().as_str()));
+    assert_eq!(
+        sh.cellstyle_str(3, 5),
+        Some(DefaultStyle::time_of_day().as_str())
+    );
+
+    // order and format.
+    let mut sh = Sheet::new("Lines");
+    let style = CellStyleRef::from("price-style");
+    let options = SerializeOptions::new()
+        .columns(["price", "item"])
+        .decimal("price")
+        .col_style("price", &style);
+    sh.serialize_rows(&lines, CellRef::local(0, 0), &options)?;
+    assert_eq!(sh.value(0, 0).as_str_or(""), "price");
+    assert_eq!(sh.value(0, 1).as_str_or(""), "item");
+    assert_eq!(sh.value(0, 2).value_type(), ValueType::Empty);
+    assert_eq!(sh.value(1, 0).value_type(), ValueType::Number);
+    assert_eq!(sh.cellstyle_str(1, 0), Some(style.as_str()));
+
+    Ok(())
+}
+
+#[test]
+fn test_serialize_with() -> Result<(), OdsError> {
+    fn eur<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        spreadsheet_ods::ser::currency(value, "EUR", serializer)
+    }
+
+    fn chf<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+        spreadsheet_ods::ser::decimal_currency(value, "CHF", serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Price {
+        #[serde(serialize_with = "spreadsheet_ods::ser::decimal")]
+        amount: Decimal,
+        #[serde(serialize_with = "eur")]
+        eur: f64,
+        #[serde(serialize_with = "chf")]
+        chf: Decimal,
+        #[serde(serialize_with = "spreadsheet_ods::ser::datetime")]
+        valid: NaiveDateTime,
+        plain: NaiveDate,
+    }
+
+    let date = NaiveDate::from_ymd_opt(2023, 1, 1).expect("date");
+    let rows = [Price {
+        amount: Decimal::from_str("0.10").expect("decimal"),
+        eur: 0.5,
+        chf: Decimal::from_str("12345678901234567890.12").expect("decimal"),
+        valid: date.and_hms_opt(10, 30, 0).expect("time"),
+        plain: date,
+    }];
+
+    let mut sh = Sheet::new("Prices");
+    sh.serialize_rows(&rows, CellRef::local(0, 0), &SerializeOptions::new())?;
+    assert_eq!(sh.value(1, 0).value_type(), ValueType::Number);
+    assert_eq!(sh.value(1, 0).as_decimal_opt(), Some(rows[0].amount));
+    assert_eq!(sh.value(1, 1).value_type(), ValueType::Currency);
+    assert_eq!(sh.value(1, 1).as_f64_opt(), Some(0.5));
+    assert_eq!(sh.value(1, 1).currency(), "EUR");
+    assert_eq!(sh.value(1, 2).as_decimal_opt(), Some(rows[0].chf));
+    assert_eq!(sh.value(1, 2).currency(), "CHF");
+    assert_eq!(sh.value(1, 3).as_datetime_opt(), Some(rows[0].valid));
+    // chrono values without serialize_with are text.
+    assert_eq!(sh.value(1, 4).as_str_or(""), "2023-01-01");
+
+    Ok(())
+}
+
+#[test]
+fn test_serialize_errors() {
+    #[derive(Serialize)]
+    struct Nested {
+        item: String,
+        tags: Vec<String>,
+    }
+
+    let mut sh = Sheet::new("Nested");
+    let rows = [Nested {
+        item: "pencil".to_string(),
+        tags: Vec::new(),
+    }];
+    match sh.serialize_rows(&rows, CellRef::local(0, 0), &SerializeOptions::new()) {
+        Err(OdsError::Serde(cell, _)) => assert_eq!((cell.row(), cell.col()), (1, 1)),
+        v => panic!("{:?}", v),
+    }
+    // nothing written.
+    assert_eq!(sh.used_grid_size(), (1, 1));
+    assert_eq!(sh.value(0, 0).value_type(), ValueType::Empty);
+
+    let options = SerializeOptions::new().decimal("item");
+    match sh.serialize_rows(&lines(), CellRef::local(0, 0), &options) {
+        Err(OdsError::Serde(cell, msg)) => {
+            assert_eq!((cell.row(), cell.col()), (1, 0));
+            assert!(msg.contains("pencil"));
+        }
+        v => panic!("{:?}", v),
+    }
+}
@@ -272,3 +272,37 @@ fn test_iterator() {
         }
     }
 }
+
+#[test]
+fn test_range() {
+    let mut sh = Sheet::new("1");
+    // out of order.
+    for r in (0..10).rev() {
+        for c in [7, 1, 4] {
+            sh.set_styled_value(r, c, r * 10 + c, &"foo".into());
+        }
+    }
+    sh.remove_cell(5, 4);
+
+    let all: Vec<(u32, u32)> = sh.iter().map(|(k, _)| k).collect();
+    assert_eq!(all.len(), 29);
+    assert!(all.windows(2).all(|v| v[0] < v[1]));
+
+    let r: Vec<(u32, u32)> = sh.range((2, 4)..(4, 4)).map(|(k, _)| k).collect();
+    assert_eq!(r, vec![(2, 4), (2, 7), (3, 1), (3, 4), (3, 7), (4, 1)]);
+    let r: Vec<(u32, u32)> = sh.range((2, 4)..=(4, 4)).rev().map(|(k, _)| k).collect();
+    assert_eq!(
+        r,
+        vec![(4, 4), (4, 1), (3, 7), (3, 4), (3, 1), (2, 7), (2, 4)]
+    );
+    let mut it = sh.range((5, 0)..(6, 0));
+    assert_eq!(it.len(), 2);
+    assert_eq!(it.next().map(|(k, _)| k), Some((5, 1)));
+    assert_eq!(it.next_back().map(|(k, _)| k), Some((5, 7)));
+    assert!(it.next().is_none());
+
+    let (_, cell) = sh.range((9, 7)..).next().expect("cell");
+    assert_eq!(cell.value().as_i32_or(0), 97);
+    assert_eq!(cell.style_str(), Some("foo"));
+    assert_eq!(sh.used_grid_size(), (10, 8));
+}
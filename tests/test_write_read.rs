@@ -2,9 +2,13 @@ use std::fs::File;
 use std::io::{Cursor, Read, Write};
 use std::path::Path;
 
+use chrono::{Duration, NaiveDate, NaiveTime};
+use icu_locid::locale;
+use spreadsheet_ods::defaultstyles::DefaultStyle;
+use spreadsheet_ods::formula::ErrorKind;
 use spreadsheet_ods::{
-    read_ods, read_ods_buf, write_ods, write_ods_buf, write_ods_to, OdsError, Sheet, SplitMode,
-    ValueType, WorkBook,
+    read_fods_buf, read_ods, read_ods_buf, write_fods_buf, write_ods, write_ods_buf, write_ods_to,
+    OdsError, Sheet, SplitMode, ValueType, WorkBook,
 };
 use std::time::Instant;
 
@@ -164,3 +168,144 @@ fn test_write_write() -> Result<(), OdsError> {
 
     Ok(())
 }
+
+#[test]
+fn test_error_value() -> Result<(), OdsError> {
+    let fods = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:calcext="urn:org:documentfoundation:names:experimental:calc:xmlns:calcext:1.0" office:version="1.3" office:mimetype="application/vnd.oasis.opendocument.spreadsheet">
+<office:body>
+<office:spreadsheet>
+<table:table table:name="Sheet1">
+<table:table-column/>
+<table:table-row>
+<table:table-cell table:formula="of:=1/0" office:value-type="float" office:value="0" calcext:value-type="error"><text:p>#DIV/0!</text:p></table:table-cell>
+<table:table-cell table:formula="of:=X()" office:value-type="string" office:string-value="" calcext:value-type="error"><text:p>#NAME?</text:p></table:table-cell>
+<table:table-cell table:formula="of:=(" office:value-type="string" office:string-value="" calcext:value-type="error"><text:p>Err:501</text:p></table:table-cell>
+<table:table-cell office:value-type="string" calcext:value-type="string"><text:p>#N/A</text:p></table:table-cell>
+</table:table-row>
+</table:table>
+</office:spreadsheet>
+</office:body>
+</office:document>
+"#;
+
+    let mut wb = read_fods_buf(fods.as_bytes())?;
+    let sh = wb.sheet(0);
+    assert_eq!(sh.value(0, 0).value_type(), ValueType::Error);
+    assert_eq!(sh.value(0, 0).as_error_opt(), Some(ErrorKind::Div0));
+    assert_eq!(sh.value(0, 1).as_error_opt(), Some(ErrorKind::Name));
+    // unknown errors stay text.
+    assert_eq!(sh.value(0, 2).as_str_or(""), "Err:501");
+    // text that looks like an error.
+    assert_eq!(sh.value(0, 3).as_str_or(""), "#N/A");
+
+    wb.sheet_mut(0).set_value(1, 0, ErrorKind::NA);
+    let buf = write_ods_buf(&mut wb, Vec::new())?;
+    let wb = read_ods_buf(&buf)?;
+    let sh = wb.sheet(0);
+    assert_eq!(sh.value(0, 0).as_error_opt(), Some(ErrorKind::Div0));
+    assert_eq!(sh.value(0, 1).as_error_opt(), Some(ErrorKind::Name));
+    assert_eq!(sh.value(1, 0).as_error_opt(), Some(ErrorKind::NA));
+    assert_eq!(sh.value(0, 3).as_str_or(""), "#N/A");
+
+    Ok(())
+}
+
+#[test]
+fn test_date_time_values() -> Result<(), OdsError> {
+    let fods = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" office:version="1.3" office:mimetype="application/vnd.oasis.opendocument.spreadsheet">
+<office:body>
+<office:spreadsheet>
+<table:table table:name="Sheet1">
+<table:table-column/>
+<table:table-row>
+<table:table-cell office:value-type="date" office:date-value="2023-01-01"><text:p>01.01.23</text:p></table:table-cell>
+<table:table-cell office:value-type="date" office:date-value="2023-01-01T10:30:00"><text:p>01.01.23 10:30</text:p></table:table-cell>
+<table:table-cell office:value-type="time" office:time-value="PT13H45M00S"><text:p>13:45</text:p></table:table-cell>
+<table:table-cell office:value-type="time" office:time-value="PT36H00M00S"><text:p>36:00</text:p></table:table-cell>
+</table:table-row>
+</table:table>
+</office:spreadsheet>
+</office:body>
+</office:document>
+"#;
+
+    let mut wb = read_fods_buf(fods.as_bytes())?;
+    let sh = wb.sheet(0);
+    let date = NaiveDate::from_ymd_opt(2023, 1, 1).expect("date");
+    let time = NaiveTime::from_hms_opt(13, 45, 0).expect("time");
+    assert_eq!(sh.value(0, 0).value_type(), ValueType::Date);
+    assert_eq!(sh.value(0, 0).as_date_opt(), Some(date));
+    assert_eq!(sh.value(0, 1).value_type(), ValueType::DateTime);
+    assert_eq!(sh.value(0, 2).value_type(), ValueType::TimeDuration);
+    assert_eq!(
+        sh.value(0, 2).as_timeduration_opt(),
+        Some(Duration::minutes(13 * 60 + 45))
+    );
+    assert_eq!(sh.value(0, 3).value_type(), ValueType::TimeDuration);
+    assert_eq!(
+        sh.value(0, 3).as_timeduration_opt(),
+        Some(Duration::hours(36))
+    );
+
+    // written back in the same shape.
+    let xml = String::from_utf8(write_fods_buf(&mut wb, Vec::new())?).expect("utf8");
+    assert!(xml.contains(r#"office:date-value="2023-01-01""#));
+    assert!(xml.contains(r#"office:date-value="2023-01-01T10:30:00"#));
+    assert!(xml.contains(r#"office:time-value="P0DT13H45M0.000S""#));
+    assert!(xml.contains(r#"office:time-value="P1DT12H0M0.000S""#));
+
+    // default styles.
+    let mut wb = WorkBook::new(locale!("en_US"));
+    let mut sh = Sheet::new("1");
+    sh.set_value(0, 0, date);
+    sh.set_value(0, 1, time);
+    sh.set_value(0, 2, date.and_time(time));
+    sh.set_value(0, 3, Duration::hours(3));
+    sh.set_value(0, 4, Duration::milliseconds(3_723_045));
+    sh.set_value(0, 5, Duration::hours(-3));
+    sh.set_value(0, 6, Duration::milliseconds(-93_723_045));
+    wb.push_sheet(sh);
+    let buf = write_ods_buf(&mut wb, Vec::new())?;
+    let wb = read_ods_buf(&buf)?;
+    let sh = wb.sheet(0);
+    assert_eq!(sh.value(0, 0).value_type(), ValueType::Date);
+    assert_eq!(sh.cellstyle_str(0, 0), Some(DefaultStyle::date().as_str()));
+    // a time of day is read back as a duration since midnight.
+    assert_eq!(sh.value(0, 1).value_type(), ValueType::TimeDuration);
+    assert_eq!(
+        sh.value(0, 1).as_timeduration_opt(),
+        Some(Duration::minutes(13 * 60 + 45))
+    );
+    assert_eq!(
+        sh.cellstyle_str(0, 1),
+        Some(DefaultStyle::time_of_day().as_str())
+    );
+    assert_eq!(sh.value(0, 2).value_type(), ValueType::DateTime);
+    assert_eq!(
+        sh.cellstyle_str(0, 2),
+        Some(DefaultStyle::datetime().as_str())
+    );
+    // durations of less than a day stay durations.
+    assert_eq!(sh.value(0, 3).value_type(), ValueType::TimeDuration);
+    assert_eq!(
+        sh.value(0, 3).as_timeduration_opt(),
+        Some(Duration::hours(3))
+    );
+    assert_eq!(sh.value(0, 4).value_type(), ValueType::TimeDuration);
+    assert_eq!(
+        sh.value(0, 4).as_timeduration_opt(),
+        Some(Duration::milliseconds(3_723_045))
+    );
+    assert_eq!(
+        sh.value(0, 5).as_timeduration_opt(),
+        Some(Duration::hours(-3))
+    );
+    assert_eq!(
+        sh.value(0, 6).as_timeduration_opt(),
+        Some(Duration::milliseconds(-93_723_045))
+    );
+
+    Ok(())
+}